@@ -20,5 +20,10 @@ fn main() {
         x11_platform: { all(feature = "x11", free_unix, not(redox)) },
         wayland_platform: { all(feature = "wayland", free_unix, not(redox)) },
         orbital_platform: { redox },
+
+        // The input methods on D-Bus.
+        dbus_ime: { all(feature = "dbus-ime", any(x11_platform, wayland_platform)) },
+        // The settings portal on D-Bus.
+        dbus_settings: { all(feature = "dbus-settings", wayland_platform) },
     }
 }
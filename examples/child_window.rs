@@ -1,13 +1,13 @@
 #[cfg(all(
     feature = "rwh_06",
-    any(x11_platform, macos_platform, windows_platform)
+    any(x11_platform, wayland_platform, macos_platform, windows_platform)
 ))]
 #[path = "util/fill.rs"]
 mod fill;
 
 #[cfg(all(
     feature = "rwh_06",
-    any(x11_platform, macos_platform, windows_platform)
+    any(x11_platform, wayland_platform, macos_platform, windows_platform)
 ))]
 #[allow(deprecated)]
 fn main() -> Result<(), impl std::error::Error> {
@@ -54,7 +54,10 @@ fn main() -> Result<(), impl std::error::Error> {
     println!("parent window: {parent_window:?})");
 
     event_loop.run(move |event: Event<()>, elwt| {
-        if let Event::WindowEvent { event, window_id } = event {
+        if let Event::WindowEvent {
+            event, window_id, ..
+        } = event
+        {
             match event {
                 WindowEvent::CloseRequested => {
                     windows.clear();
@@ -90,8 +93,8 @@ fn main() -> Result<(), impl std::error::Error> {
 
 #[cfg(not(all(
     feature = "rwh_06",
-    any(x11_platform, macos_platform, windows_platform)
+    any(x11_platform, wayland_platform, macos_platform, windows_platform)
 )))]
 fn main() {
-    panic!("This example is supported only on x11, macOS, and Windows, with the `rwh_06` feature enabled.");
+    panic!("This example is supported only on X11, Wayland, macOS, and Windows, with the `rwh_06` feature enabled.");
 }
@@ -35,7 +35,9 @@ mod example {
             match event {
                 Event::Resumed => create_first_window = true,
 
-                Event::WindowEvent { window_id, event } => match event {
+                Event::WindowEvent {
+                    window_id, event, ..
+                } => match event {
                     WindowEvent::KeyboardInput {
                         event:
                             KeyEvent {
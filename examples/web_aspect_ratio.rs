@@ -51,6 +51,7 @@ This example demonstrates the desired future functionality which will possibly b
             Event::WindowEvent {
                 event: WindowEvent::Resized(resize),
                 window_id,
+                ..
             } if window_id == window.id() => {
                 render_circle(&canvas, resize);
             }
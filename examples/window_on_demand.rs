@@ -38,6 +38,7 @@ fn main() -> Result<(), impl std::error::Error> {
                     Event::WindowEvent {
                         event: WindowEvent::CloseRequested,
                         window_id,
+                        ..
                     } if window.id() == window_id => {
                         println!("--------------------------------------------------------- Window {idx} CloseRequested");
                         fill::cleanup_window(window);
@@ -57,6 +58,7 @@ fn main() -> Result<(), impl std::error::Error> {
                     Event::WindowEvent {
                         event: WindowEvent::Destroyed,
                         window_id,
+                        ..
                     } if id == window_id => {
                         println!("--------------------------------------------------------- Window {idx} Destroyed");
                         app.window_id = None;
@@ -35,6 +35,7 @@ fn main() -> Result<(), impl std::error::Error> {
         Event::WindowEvent {
             event: WindowEvent::CloseRequested,
             window_id,
+            ..
         } if window_id == window.id() => elwt.exit(),
         Event::WindowEvent { event, .. } => match event {
             WindowEvent::MouseInput {
@@ -42,6 +42,7 @@ fn main() -> std::process::ExitCode {
                 Event::WindowEvent {
                     event: WindowEvent::CloseRequested,
                     window_id,
+                    ..
                 } if window_id == window.id() => elwt.exit(),
                 Event::AboutToWait => {
                     window.request_redraw();
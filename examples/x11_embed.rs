@@ -37,6 +37,7 @@ mod imple {
                 Event::WindowEvent {
                     event: WindowEvent::CloseRequested,
                     window_id,
+                    ..
                 } if window_id == window.id() => elwt.exit(),
                 Event::AboutToWait => {
                     window.request_redraw();
@@ -32,13 +32,14 @@
 //!
 //! [`EventLoop::run(...)`]: crate::event_loop::EventLoop::run
 //! [`ControlFlow::WaitUntil`]: crate::event_loop::ControlFlow::WaitUntil
+use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::{Mutex, Weak};
 #[cfg(not(web_platform))]
 use std::time::Instant;
 
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smol_str::SmolStr;
 #[cfg(web_platform)]
 use web_time::Instant;
@@ -71,12 +72,20 @@ pub enum Event<T: 'static> {
     WindowEvent {
         window_id: WindowId,
         event: WindowEvent,
+        /// The time the event was generated at.
+        ///
+        /// See [`Event::timestamp`] for more details.
+        timestamp: Instant,
     },
 
     /// Emitted when the OS sends an event to a device.
     DeviceEvent {
         device_id: DeviceId,
         event: DeviceEvent,
+        /// The time the event was generated at.
+        ///
+        /// See [`Event::timestamp`] for more details.
+        timestamp: Instant,
     },
 
     /// Emitted when an event is sent from [`EventLoopProxy::send_event`](crate::event_loop::EventLoopProxy::send_event)
@@ -261,8 +270,24 @@ impl<T> Event<T> {
         use self::Event::*;
         match self {
             UserEvent(_) => Err(self),
-            WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
-            DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
+            WindowEvent {
+                window_id,
+                event,
+                timestamp,
+            } => Ok(WindowEvent {
+                window_id,
+                event,
+                timestamp,
+            }),
+            DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            } => Ok(DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            }),
             NewEvents(cause) => Ok(NewEvents(cause)),
             AboutToWait => Ok(AboutToWait),
             LoopExiting => Ok(LoopExiting),
@@ -271,6 +296,40 @@ impl<T> Event<T> {
             MemoryWarning => Ok(MemoryWarning),
         }
     }
+
+    /// The time the [`Event::WindowEvent`] or [`Event::DeviceEvent`] was generated at.
+    ///
+    /// The timestamps use the same monotonic clock as [`Instant::now`], thus they could be compared
+    /// with each other and with the current time, e.g. to measure the input latency or to
+    /// estimate the velocity of the pointer. The timestamps of the events delivered by an event
+    /// loop never go backwards from one event to the next.
+    ///
+    /// Returns `None` for the other events.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The time provided by the server is used for the keyboard, pointer, touch,
+    ///   gesture, focus, modifiers and raw device events, and for the dropped data. The other
+    ///   events are stamped with the time they were received at.
+    /// - **Wayland:** The time provided by the compositor is used for the keyboard, pointer,
+    ///   touch, gesture and tablet events. The other events, e.g. the modifiers, focus, key
+    ///   repeat, drag and drop and IME events, are stamped with the time they were received at.
+    /// - **Windows:** The time of the message, as returned by `GetMessageTime`, is used for the
+    ///   keyboard, modifiers, pointer, touch and raw device input. The other events are stamped
+    ///   with the time they were received at.
+    /// - **macOS:** The window and device events are stamped with the time of the `NSEvent`
+    ///   being handled when they were generated.
+    /// - **Web:** The `timeStamp` of the DOM event is used for the keyboard, pointer, focus and
+    ///   device events. The other events are stamped with the time they were received at.
+    /// - **Others:** The events are stamped with the time they were received at.
+    pub fn timestamp(&self) -> Option<Instant> {
+        match self {
+            Self::WindowEvent { timestamp, .. } | Self::DeviceEvent { timestamp, .. } => {
+                Some(*timestamp)
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Describes the reason the event loop is resuming.
@@ -305,6 +364,7 @@ pub enum StartCause {
 
 /// Describes an event from a [`Window`].
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WindowEvent {
     /// The activation token was delivered back and now could be used.
     ///
@@ -336,6 +396,19 @@ pub enum WindowEvent {
     /// The window has been destroyed.
     Destroyed,
 
+    /// The popup was dismissed, usually because the user clicked outside of it.
+    ///
+    /// The popup isn't shown anymore and should be dropped.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Only emitted for popups with a grab, see [`PopupAttributes::with_grab`], when
+    ///   a button is pressed outside of them.
+    /// - **Wayland:** Emitted whenever the compositor dismisses the popup, with or without a grab.
+    ///
+    /// [`PopupAttributes::with_grab`]: crate::platform::popup::PopupAttributes::with_grab
+    PopupDismissed,
+
     /// A file has been dropped into the window.
     ///
     /// When the user drops multiple files at once, this event will be emitted for each file
@@ -354,11 +427,102 @@ pub enum WindowEvent {
     /// hovered.
     HoveredFileCancelled,
 
+    /// A drag from another application, or from the application itself, entered the window.
+    ///
+    /// The data is offered in the `mime_types`, and the source allows the `actions` on it. A
+    /// [`WindowEvent::DragMoved`] at the same `position` follows right away.
+    ///
+    /// See [`EventLoopWindowTargetExtDragAndDrop`] to accept the drop and read the data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    ///
+    /// [`EventLoopWindowTargetExtDragAndDrop`]: crate::platform::drag_and_drop::EventLoopWindowTargetExtDragAndDrop
+    DragEntered {
+        position: PhysicalPosition<f64>,
+        mime_types: Vec<String>,
+        actions: Vec<DndAction>,
+    },
+
+    /// The drag moved over the window.
+    ///
+    /// The `action` is the one proposed by the source, usually depending on the modifiers held by
+    /// the user. The drop is accepted or rejected at the `position` while handling this event.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    DragMoved {
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+    },
+
+    /// The drag left the window, or was dropped on it while rejected.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    DragLeft,
+
+    /// The accepted drag was dropped on the window, with the accepted `action`.
+    ///
+    /// The data can be read while handling this event, until the event loop waits again.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    DragDropped {
+        position: PhysicalPosition<f64>,
+        action: DndAction,
+    },
+
+    /// The drop target under the cursor changed its response to the drag started by the window.
+    ///
+    /// The `action` is the one the target would perform on a drop, or `None` when it would
+    /// reject it.
+    ///
+    /// See [`WindowExtDragAndDrop::start_drag`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    ///
+    /// [`WindowExtDragAndDrop::start_drag`]: crate::platform::drag_and_drop::WindowExtDragAndDrop::start_drag
+    DragSourceAction { action: Option<DndAction> },
+
+    /// The drag started by the window has ended.
+    ///
+    /// The `action` is the one performed by the target the data was dropped on, or `None` when
+    /// the drag was cancelled or the drop rejected. After a [`DndAction::Move`], the application
+    /// is expected to delete the dragged data.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    DragSourceFinished { action: Option<DndAction> },
+
     /// The window gained or lost focus.
     ///
     /// The parameter is true if the window has gained focus, and false if it has lost focus.
     Focused(bool),
 
+    /// The compositor shortcuts were inhibited or restored for the window.
+    ///
+    /// The parameter is true when the shortcuts are sent to the window as [`KeyboardInput`],
+    /// and false when the compositor revoked the inhibition or the window lost the focus.
+    /// See [`Window::set_keyboard_shortcuts_inhibit`].
+    ///
+    /// [`KeyboardInput`]: Self::KeyboardInput
+    /// [`Window::set_keyboard_shortcuts_inhibit`]: crate::window::Window::set_keyboard_shortcuts_inhibit
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    /// - **X11:** Also false when the keyboard can't be grabbed on focus, e.g. because another
+    ///   client holds it. The grab is tried again when the window regains the focus.
+    KeyboardShortcutsInhibited(bool),
+
     /// An event from the keyboard has been received.
     ///
     /// ## Platform-specific
@@ -384,6 +548,18 @@ pub enum WindowEvent {
     /// The keyboard modifiers have changed.
     ModifiersChanged(Modifiers),
 
+    /// The active keyboard layout has changed, e.g. the user switched from English to Russian.
+    ///
+    /// The event is sent to the focused window. The configured layouts can be queried with
+    /// [`EventLoopWindowTargetExtKeyboardLayout`].
+    ///
+    /// [`EventLoopWindowTargetExtKeyboardLayout`]: crate::platform::keyboard_layout::EventLoopWindowTargetExtKeyboardLayout
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    KeyboardLayoutChanged(KeyboardLayout),
+
     /// An event from an input method.
     ///
     /// **Note:** You have to explicitly enable this event using [`Window::set_ime_allowed`].
@@ -445,14 +621,36 @@ pub enum WindowEvent {
         device_id: DeviceId,
         state: ElementState,
         button: MouseButton,
+        /// The number of consecutive clicks of the `button`, `1` for a single click, `2` for a
+        /// double click and so on.
+        ///
+        /// A press continues the sequence when it follows the previous press of the same button
+        /// within the double-click time and distance of the system. A release carries the count
+        /// of the press it ends.
+        ///
+        /// ## Platform-specific
+        ///
+        /// - **Windows:** The double-click time and the `SM_CXDOUBLECLK` rectangle of the system
+        ///   are used, queried on every click.
+        /// - **macOS:** The `clickCount` of the `NSEvent` is used.
+        /// - **X11:** The `Net/DoubleClickTime` and `Net/DoubleClickDistance` XSETTINGS are used,
+        ///   falling back to the GTK `settings.ini`. They are read again when the XSETTINGS
+        ///   change.
+        /// - **Wayland:** With the `dbus-settings` feature, the double-click time of GNOME or KDE
+        ///   is read from the settings portal, and read again when it changes. Without the portal,
+        ///   the `gtk-double-click-time` and `gtk-double-click-distance` of the GTK `settings.ini`
+        ///   are used.
+        /// - **Web / Orbital:** 500 milliseconds and 4 pixels are used.
+        click_count: u32,
     },
 
     /// Two-finger pinch gesture, often used for magnification.
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **macOS** and **iOS**.
+    /// - Only available on **macOS**, **iOS**, **X11** and **Wayland**.
     /// - On iOS, not recognized by default. It must be enabled when needed.
+    /// - On X11, requires XInput 2.4.
     PinchGesture {
         device_id: DeviceId,
         /// Positive values indicate magnification (zooming in) and  negative
@@ -490,14 +688,48 @@ pub enum WindowEvent {
     ///
     /// ## Platform-specific
     ///
-    /// - Only available on **macOS** and **iOS**.
+    /// - Only available on **macOS**, **iOS**, **X11** and **Wayland**.
     /// - On iOS, not recognized by default. It must be enabled when needed.
+    /// - On X11 and Wayland, sent with the [`WindowEvent::PinchGesture`] of the same fingers.
     RotationGesture {
         device_id: DeviceId,
         delta: f32,
         phase: TouchPhase,
     },
 
+    /// Multi-finger swipe gesture, often used for navigation.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    /// - On X11, requires XInput 2.4.
+    SwipeGesture {
+        device_id: DeviceId,
+        /// The number of fingers on the touchpad.
+        fingers: u32,
+        /// The motion of the center of the fingers since the previous event.
+        delta: PhysicalPosition<f64>,
+        phase: TouchPhase,
+    },
+
+    /// Multi-finger hold gesture, when the fingers rest on the touchpad without moving, such as
+    /// to stop a kinetic scroll.
+    ///
+    /// The hold is [`TouchPhase::Cancelled`] when the fingers start moving or another gesture
+    /// begins instead, and [`TouchPhase::Ended`] when they're lifted.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**, where it requires version 3 of
+    ///   `zwp_pointer_gestures_v1`.
+    /// - **X11:** Unsupported, since XInput 2.4 has no hold gesture.
+    HoldGesture {
+        device_id: DeviceId,
+        /// The number of fingers on the touchpad.
+        fingers: u32,
+        phase: TouchPhase,
+    },
+
     /// Touchpad pressure event.
     ///
     /// At the moment, only supported on Apple forcetouch-capable macbooks.
@@ -530,13 +762,17 @@ pub enum WindowEvent {
 
     TabletPenEnter {
         device_id: DeviceId,
+        /// The tool described by [`DeviceEvent::TabletToolAdded`].
+        tool_id: TabletToolId,
         inverted: bool,
     },
     TabletPenLeave {
         device_id: DeviceId,
+        tool_id: TabletToolId,
     },
     TabletPenMotion {
         device_id: DeviceId,
+        tool_id: TabletToolId,
         location: PhysicalPosition<f64>,
         pressure: f64,
         rotation: f64,
@@ -545,10 +781,58 @@ pub enum WindowEvent {
     },
     TabletButton {
         device_id: DeviceId,
+        /// The tool with the button, or `None` for the buttons of a tablet pad.
+        tool_id: Option<TabletToolId>,
         button: TabletButton,
         state: ElementState,
     },
 
+    /// A finger moved around a ring of a tablet pad.
+    ///
+    /// The rings of the pad are numbered across its mode groups, in the order they're announced.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**.
+    TabletPadRing {
+        device_id: DeviceId,
+        ring: u32,
+        /// The angle of the finger in degrees, clockwise from the top of the ring, or `None` when
+        /// the finger left the ring.
+        angle: Option<f64>,
+        source: TabletPadSource,
+    },
+
+    /// A finger slid along a strip of a tablet pad.
+    ///
+    /// The strips of the pad are numbered across its mode groups, in the order they're announced.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**.
+    TabletPadStrip {
+        device_id: DeviceId,
+        strip: u32,
+        /// The position of the finger, from `0.0` at the top or the left of the strip to `1.0`,
+        /// or `None` when the finger left the strip.
+        position: Option<f64>,
+        source: TabletPadSource,
+    },
+
+    /// The mode of a group of buttons, rings and strips of a tablet pad changed, usually with a
+    /// mode-switch button of the group.
+    ///
+    /// Applications bind different actions to the same controls in each mode.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**.
+    TabletPadModeChanged {
+        device_id: DeviceId,
+        group: u32,
+        mode: u32,
+    },
+
     /// The window's scale factor has changed.
     ///
     /// The following user actions can cause DPI changes:
@@ -567,6 +851,7 @@ pub enum WindowEvent {
         /// Handle to update inner size during scale changes.
         ///
         /// See [`InnerSizeWriter`] docs for more details.
+        #[cfg_attr(feature = "serde", serde(skip, default = "InnerSizeWriter::detached"))]
         inner_size_writer: InnerSizeWriter,
     },
 
@@ -644,6 +929,33 @@ impl DeviceId {
     }
 }
 
+/// The device ids are serialized as their raw value on X11 and Wayland. On the other platforms,
+/// they're deserialized as the dummy id.
+#[cfg(feature = "serde")]
+impl Serialize for DeviceId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[cfg(any(x11_platform, wayland_platform))]
+        let raw = self.0.into_raw();
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        let raw: Option<u64> = None;
+        raw.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DeviceId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Option::<u64>::deserialize(deserializer)?;
+        #[cfg(any(x11_platform, wayland_platform))]
+        return Ok(DeviceId(platform_impl::DeviceId::from_raw(raw)));
+        #[cfg(not(any(x11_platform, wayland_platform)))]
+        {
+            let _ = raw;
+            Ok(unsafe { DeviceId::dummy() })
+        }
+    }
+}
+
 /// Represents raw hardware events that are not associated with any particular window.
 ///
 /// Useful for interactions that diverge significantly from a conventional 2D GUI, such as 3D camera or first-person
@@ -653,10 +965,36 @@ impl DeviceId {
 ///
 /// Note that these events are delivered regardless of input focus.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DeviceEvent {
     Added,
     Removed,
 
+    /// A tablet was added, with its description.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **Wayland**, where it follows the [`DeviceEvent::Added`] of the tablet.
+    TabletAdded(TabletInfo),
+
+    /// A tablet tool, such as a stylus or a mouse puck, was first used or plugged in.
+    ///
+    /// The id of the tool is attached to its pen events.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    /// - **X11:** The tools present when the event loop starts are announced on its first
+    ///   iteration.
+    TabletToolAdded(TabletToolInfo),
+
+    /// A tablet tool was removed, with its id.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    TabletToolRemoved(TabletToolId),
+
     /// Change in physical position of a pointing device.
     ///
     /// This represents raw, unfiltered physical motion. Not to be confused with [`WindowEvent::CursorMoved`].
@@ -704,6 +1042,7 @@ pub struct RawKeyEvent {
 
 /// Describes a keyboard input targeting a window.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyEvent {
     /// Represents the position of a key independent of the currently active layout.
     ///
@@ -816,6 +1155,7 @@ pub struct KeyEvent {
 
 /// Describes keyboard modifiers event.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Modifiers {
     pub(crate) state: ModifiersState,
 
@@ -944,11 +1284,46 @@ pub enum Ime {
     /// The cursor position is byte-wise indexed.
     Preedit(String, Option<(usize, usize)>),
 
+    /// Notifies of the styling of the ranges of the composing text, such as to underline it and
+    /// to highlight the clause being converted.
+    ///
+    /// It's sent right after the [`Preedit`](Self::Preedit) event it applies to, and only when
+    /// the input method styles its text. The ranges are byte-wise indexed, and the text outside
+    /// of them has no particular style.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** The feedback of the XIM preedit, or the formatting of the preedit of the D-Bus
+    ///   input method, see `platform::dbus_ime`.
+    /// - **Wayland:** Only sent by the D-Bus input method, since `zwp_text_input_v3` doesn't style
+    ///   the preedit.
+    /// - **Windows:** The attributes of the IME composition.
+    /// - **Android / iOS / macOS / Orbital / Web:** Unsupported.
+    PreeditSegments(Vec<PreeditSegment>),
+
     /// Notifies when text should be inserted into the editor widget.
     ///
     /// Right before this event winit will send empty [`Self::Preedit`] event.
     Commit(String),
 
+    /// Notifies when the text around the cursor should be deleted, such as when an input method
+    /// replaces the characters before the cursor.
+    ///
+    /// The lengths are in bytes of the text set with [`Window::set_ime_surrounding_text`], before
+    /// and after the cursor, or the selection when there's one. This event is sent after the
+    /// empty [`Self::Preedit`] event and before the [`Self::Commit`] event replacing the deleted
+    /// text.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - Only available on **X11** and **Wayland**.
+    ///
+    /// [`Window::set_ime_surrounding_text`]: crate::window::Window::set_ime_surrounding_text
+    DeleteSurrounding {
+        before_bytes: usize,
+        after_bytes: usize,
+    },
+
     /// Notifies when the IME was disabled.
     ///
     /// After receiving this event you won't get any more [`Preedit`](Self::Preedit) or
@@ -958,6 +1333,41 @@ pub enum Ime {
     Disabled,
 }
 
+/// A keyboard layout of the keymap, which XKB calls a group.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyboardLayout {
+    /// The index of the layout among the configured ones.
+    pub index: usize,
+    /// The human-readable name of the layout, e.g. `English (US)`.
+    ///
+    /// It's empty when the keymap doesn't name the layout.
+    pub name: String,
+}
+
+/// The styling of a range of the composing text, see [`Ime::PreeditSegments`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreeditSegment {
+    /// The byte range of the segment in the composing text.
+    pub range: Range<usize>,
+    pub style: PreeditStyle,
+}
+
+/// The style of a [`PreeditSegment`], which may combine several attributes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PreeditStyle {
+    /// The text is underlined.
+    pub underline: bool,
+    /// The text is highlighted.
+    pub highlight: bool,
+    /// The colors of the text are reversed.
+    pub reverse: bool,
+    /// The text is the clause selected for conversion.
+    pub selected: bool,
+}
+
 /// Describes touch-screen input state.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -994,6 +1404,7 @@ pub enum TouchPhase {
 /// [`padding`]: https://developer.mozilla.org/en-US/docs/Web/CSS/padding
 /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Touch {
     pub device_id: DeviceId,
     pub phase: TouchPhase,
@@ -1014,6 +1425,7 @@ pub struct Touch {
 
 /// Describes the force of a touch event
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Force {
     /// On iOS, the force is calibrated so that the same number corresponds to
     /// roughly the same amount of pressure on the screen regardless of the
@@ -1070,15 +1482,97 @@ impl Force {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TabletButton {
     Tip,
     Eraser,
-    /// - Only available on **Wayland**.
+    /// - Only available on **X11** and **Wayland**.
     Pen(u32),
     /// - Only available on **Wayland**.
     Tablet(u32),
 }
 
+/// What's interacting with a ring or a strip of a tablet pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TabletPadSource {
+    /// The source isn't known.
+    Unknown,
+    /// A finger, which tells when it left the ring or the strip.
+    Finger,
+}
+
+/// The description of a tablet, sent with [`DeviceEvent::TabletAdded`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TabletInfo {
+    /// The name of the tablet.
+    pub name: Option<String>,
+    /// The USB vendor and product ids of the tablet.
+    pub usb_id: Option<(u32, u32)>,
+    /// The paths of the device nodes of the tablet, such as `/dev/input/event12`.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Identifier for a tablet tool, unique while the tool is present.
+///
+/// Use [`TabletToolInfo::serial`] to recognize a physical tool across sessions.
+pub type TabletToolId = u64;
+
+/// The description of a tablet tool, sent with [`DeviceEvent::TabletToolAdded`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TabletToolInfo {
+    /// The id attached to the pen events of the tool.
+    pub id: TabletToolId,
+    pub tool_type: TabletToolType,
+    /// The serial number of the tool, which is the same for a physical tool on every tablet.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Always `None`.
+    pub serial: Option<u64>,
+    /// The hardware id of the tool, which tells its model for the Wacom tablets.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **X11:** Always `None`.
+    pub hardware_id: Option<u64>,
+    pub capabilities: TabletToolCapabilities,
+}
+
+/// The kind of a tablet tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum TabletToolType {
+    Pen,
+    /// The eraser end of a pen.
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Finger,
+    /// A mouse bound to the tablet.
+    Mouse,
+    /// A mouse puck with a lens.
+    Lens,
+}
+
+/// The axes a tablet tool reports with [`WindowEvent::TabletPenMotion`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TabletToolCapabilities {
+    pub pressure: bool,
+    pub tilt: bool,
+    pub rotation: bool,
+    pub distance: bool,
+    /// A slider, like the finger wheel of an airbrush.
+    pub slider: bool,
+    /// A scroll wheel, like the one of a mouse puck.
+    pub wheel: bool,
+}
+
 /// Identifier for a specific analog axis on some device.
 pub type AxisId = u32;
 
@@ -1117,6 +1611,18 @@ pub enum MouseButton {
     Other(u16),
 }
 
+/// Describes what a drag-and-drop operation does with the data.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DndAction {
+    /// The data is copied.
+    Copy,
+    /// The data is moved, thus the source deletes it after the drop.
+    Move,
+    /// A link to the data is created.
+    Link,
+}
+
 /// Describes a difference in the mouse scroll wheel state.
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -1157,6 +1663,14 @@ impl InnerSizeWriter {
         Self { new_inner_size }
     }
 
+    /// A writer which isn't connected to any window, thus the requests are ignored.
+    #[cfg(feature = "serde")]
+    fn detached() -> Self {
+        Self {
+            new_inner_size: Weak::new(),
+        }
+    }
+
     /// Try to request inner size which will be set synchroniously on the window.
     pub fn request_inner_size(
         &mut self,
@@ -1179,6 +1693,7 @@ impl PartialEq for InnerSizeWriter {
 
 #[cfg(test)]
 mod tests {
+    use super::Instant;
     use crate::event;
     use std::collections::{BTreeSet, HashSet};
 
@@ -1207,23 +1722,61 @@ mod tests {
                     x(WindowEvent {
                         window_id: wid,
                         event: wev,
+                        timestamp: Instant::now(),
                     })
                 };
 
                 with_window_event(CloseRequested);
                 with_window_event(Destroyed);
+                with_window_event(PopupDismissed);
                 with_window_event(Focused(true));
+                with_window_event(KeyboardShortcutsInhibited(true));
                 with_window_event(Moved((0, 0).into()));
                 with_window_event(Resized((0, 0).into()));
                 with_window_event(DroppedFile("x.txt".into()));
                 with_window_event(HoveredFile("x.txt".into()));
                 with_window_event(HoveredFileCancelled);
+                with_window_event(DragEntered {
+                    position: (0, 0).into(),
+                    mime_types: vec!["text/plain".into()],
+                    actions: vec![event::DndAction::Copy],
+                });
+                with_window_event(DragMoved {
+                    position: (0, 0).into(),
+                    action: event::DndAction::Move,
+                });
+                with_window_event(DragLeft);
+                with_window_event(DragDropped {
+                    position: (0, 0).into(),
+                    action: event::DndAction::Link,
+                });
+                with_window_event(DragSourceAction {
+                    action: Some(event::DndAction::Copy),
+                });
+                with_window_event(DragSourceFinished { action: None });
                 with_window_event(Ime(Enabled));
+                with_window_event(Ime(event::Ime::PreeditSegments(vec![
+                    event::PreeditSegment {
+                        range: 0..3,
+                        style: event::PreeditStyle {
+                            underline: true,
+                            ..Default::default()
+                        },
+                    },
+                ])));
+                with_window_event(Ime(event::Ime::DeleteSurrounding {
+                    before_bytes: 3,
+                    after_bytes: 0,
+                }));
                 with_window_event(CursorMoved {
                     device_id: did,
                     position: (0, 0).into(),
                 });
                 with_window_event(ModifiersChanged(event::Modifiers::default()));
+                with_window_event(KeyboardLayoutChanged(event::KeyboardLayout {
+                    index: 0,
+                    name: "English (US)".into(),
+                }));
                 with_window_event(CursorEntered { device_id: did });
                 with_window_event(CursorLeft { device_id: did });
                 with_window_event(MouseWheel {
@@ -1235,6 +1788,7 @@ mod tests {
                     device_id: did,
                     state: event::ElementState::Pressed,
                     button: event::MouseButton::Other(0),
+                    click_count: 1,
                 });
                 with_window_event(PinchGesture {
                     device_id: did,
@@ -1247,6 +1801,17 @@ mod tests {
                     delta: 0.0,
                     phase: event::TouchPhase::Started,
                 });
+                with_window_event(SwipeGesture {
+                    device_id: did,
+                    fingers: 3,
+                    delta: (0.0, 0.0).into(),
+                    phase: event::TouchPhase::Started,
+                });
+                with_window_event(HoldGesture {
+                    device_id: did,
+                    fingers: 2,
+                    phase: event::TouchPhase::Started,
+                });
                 with_window_event(TouchpadPressure {
                     device_id: did,
                     pressure: 0.0,
@@ -1264,6 +1829,23 @@ mod tests {
                     id: 0,
                     force: Some(event::Force::Normalized(0.0)),
                 }));
+                with_window_event(TabletPadRing {
+                    device_id: did,
+                    ring: 0,
+                    angle: Some(0.0),
+                    source: event::TabletPadSource::Finger,
+                });
+                with_window_event(TabletPadStrip {
+                    device_id: did,
+                    strip: 0,
+                    position: None,
+                    source: event::TabletPadSource::Unknown,
+                });
+                with_window_event(TabletPadModeChanged {
+                    device_id: did,
+                    group: 0,
+                    mode: 1,
+                });
                 with_window_event(ThemeChanged(crate::window::Theme::Light));
                 with_window_event(Occluded(true));
             }
@@ -1276,11 +1858,28 @@ mod tests {
                     x(event::Event::DeviceEvent {
                         device_id: did,
                         event: dev_ev,
+                        timestamp: Instant::now(),
                     })
                 };
 
                 with_device_event(Added);
                 with_device_event(Removed);
+                with_device_event(TabletAdded(event::TabletInfo {
+                    name: Some("tablet".into()),
+                    usb_id: Some((0x56a, 0x374)),
+                    paths: vec!["/dev/input/event12".into()],
+                }));
+                with_device_event(TabletToolAdded(event::TabletToolInfo {
+                    id: 1,
+                    tool_type: event::TabletToolType::Pen,
+                    serial: Some(0x1234),
+                    hardware_id: None,
+                    capabilities: event::TabletToolCapabilities {
+                        pressure: true,
+                        ..Default::default()
+                    },
+                }));
+                with_device_event(TabletToolRemoved(1));
                 with_device_event(MouseMotion {
                     delta: (0.0, 0.0).into(),
                 });
@@ -19,6 +19,9 @@ use std::time::{Duration, Instant};
 #[cfg(web_platform)]
 use web_time::{Duration, Instant};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::error::EventLoopError;
 use crate::{event::Event, monitor::MonitorHandle, platform_impl};
 
@@ -510,6 +513,7 @@ pub enum DeviceEvents {
 /// Then once event is arriving the working list is being traversed and a job
 /// executed and removed from the list.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AsyncRequestSerial {
     serial: u64,
 }
@@ -1751,7 +1751,7 @@ bitflags! {
 
 #[cfg(feature = "serde")]
 mod modifiers_serde {
-    use super::ModifiersState;
+    use super::{ModifiersKeys, ModifiersState};
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
     #[derive(Default, Serialize, Deserialize)]
@@ -1798,4 +1798,21 @@ mod modifiers_serde {
             Ok(m)
         }
     }
+    impl Serialize for ModifiersKeys {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            self.bits().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ModifiersKeys {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            u8::deserialize(deserializer).map(ModifiersKeys::from_bits_retain)
+        }
+    }
 }
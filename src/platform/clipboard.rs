@@ -0,0 +1,264 @@
+//! Access to the clipboard and the primary selection of the desktop.
+//!
+//! The clipboard holds the data the user copied, offered in one or more MIME types, so the
+//! application pasting it could pick the representation it understands best. The data of the
+//! application, which set the clipboard, stays available until another application takes the
+//! clipboard over or the event loop is dropped.
+//!
+//! The primary selection works the same way, but holds the content the user selected last,
+//! usually text, which is pasted with a middle click. Applications set it whenever the selection
+//! changes, instead of on an explicit copy.
+//!
+//! Reading the clipboard owned by another application blocks until that application sends the
+//! data, for at most a few seconds.
+//!
+//! ## Platform-specific
+//!
+//! - **X11:** The `CLIPBOARD` and `PRIMARY` selections are used. The data of other applications
+//!   larger than the maximum request size is received incrementally, but the own data must fit
+//!   into a single request.
+//! - **Wayland:** Setting the clipboard requires a recent input event on the seat, e.g. a key
+//!   press, which is the case when the user asks for a copy. The primary selection requires the
+//!   `zwp_primary_selection_device_manager_v1` protocol.
+//! - **Headless:** The clipboard and the primary selection are local to the event loop.
+
+use std::sync::Arc;
+
+use crate::error::ExternalError;
+use crate::event_loop::EventLoopWindowTarget;
+use crate::platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget;
+
+/// The MIME types text is offered in, in the order of preference.
+const TEXT_MIME_TYPES: [&str; 4] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "TEXT",
+];
+
+/// The legacy X11 type for text in the Latin-1 encoding.
+const LATIN1_MIME_TYPE: &str = "STRING";
+
+/// The data put on the clipboard, in one or more MIME types.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardContents {
+    data: Vec<(String, Arc<[u8]>)>,
+}
+
+impl ClipboardContents {
+    /// Create empty contents.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Create contents holding the `text`, offered in the MIME types text is usually requested in.
+    pub fn from_text(text: &str) -> Self {
+        let data: Arc<[u8]> = Arc::from(text.as_bytes());
+        Self {
+            data: TEXT_MIME_TYPES
+                .iter()
+                .map(|mime_type| (mime_type.to_string(), data.clone()))
+                .collect(),
+        }
+    }
+
+    /// Add the `data` in the `mime_type`, replacing the data previously added in it.
+    pub fn with_data(mut self, mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        let mime_type = mime_type.into();
+        let data = Arc::from(data.into());
+        match self.data.iter_mut().find(|(ty, _)| *ty == mime_type) {
+            Some((_, old)) => *old = data,
+            None => self.data.push((mime_type, data)),
+        }
+        self
+    }
+
+    /// The MIME types of the data, in the order they were added.
+    pub fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.data.iter().map(|(mime_type, _)| mime_type.as_str())
+    }
+
+    /// The data in the `mime_type`.
+    pub fn data(&self, mime_type: &str) -> Option<&[u8]> {
+        self.data
+            .iter()
+            .find(|(ty, _)| ty == mime_type)
+            .map(|(_, data)| &**data)
+    }
+
+    /// Whether there's no data.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// The data shared with the backends, which send it from other threads.
+    pub(crate) fn shared_data(&self, mime_type: &str) -> Option<Arc<[u8]>> {
+        self.data
+            .iter()
+            .find(|(ty, _)| ty == mime_type)
+            .map(|(_, data)| data.clone())
+    }
+}
+
+/// The selection accessed by the backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SelectionKind {
+    Clipboard,
+    Primary,
+}
+
+/// Additional methods on [`EventLoopWindowTarget`] to access the clipboard and the primary
+/// selection.
+pub trait EventLoopWindowTargetExtClipboard {
+    /// Put the `contents` on the clipboard, replacing its current contents.
+    ///
+    /// Empty `contents` give up the ownership of the clipboard when the application has it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Returns an error when the compositor has no data device manager or there's
+    ///   no seat with an input event to associate the request with.
+    fn set_clipboard(&self, contents: ClipboardContents) -> Result<(), ExternalError>;
+
+    /// The MIME types the clipboard data is offered in.
+    fn clipboard_mime_types(&self) -> Result<Vec<String>, ExternalError>;
+
+    /// Read the clipboard data in the `mime_type`.
+    ///
+    /// Returns `None` when the clipboard is empty or the data isn't offered in the `mime_type`.
+    fn clipboard_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, ExternalError>;
+
+    /// Read the text on the clipboard.
+    ///
+    /// Returns `None` when the clipboard holds no text.
+    fn clipboard_text(&self) -> Result<Option<String>, ExternalError> {
+        read_text(self.clipboard_mime_types()?, |mime_type| {
+            self.clipboard_data(mime_type)
+        })
+    }
+
+    /// Put the `contents` into the primary selection, replacing its current contents.
+    ///
+    /// Empty `contents` give up the ownership of the primary selection when the application has
+    /// it, which is usually done when the selected text gets deselected.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Returns an error when the compositor has no primary selection device
+    ///   manager or there's no seat with an input event to associate the request with.
+    fn set_primary_selection(&self, contents: ClipboardContents) -> Result<(), ExternalError>;
+
+    /// The MIME types the primary selection data is offered in.
+    fn primary_selection_mime_types(&self) -> Result<Vec<String>, ExternalError>;
+
+    /// Read the primary selection data in the `mime_type`.
+    ///
+    /// Returns `None` when the primary selection is empty or the data isn't offered in the
+    /// `mime_type`.
+    fn primary_selection_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, ExternalError>;
+
+    /// Read the text in the primary selection.
+    ///
+    /// Returns `None` when the primary selection holds no text.
+    fn primary_selection_text(&self) -> Result<Option<String>, ExternalError> {
+        read_text(self.primary_selection_mime_types()?, |mime_type| {
+            self.primary_selection_data(mime_type)
+        })
+    }
+}
+
+/// Read the text in the best of the offered `mime_types` with `data`.
+fn read_text(
+    mime_types: Vec<String>,
+    data: impl Fn(&str) -> Result<Option<Vec<u8>>, ExternalError>,
+) -> Result<Option<String>, ExternalError> {
+    let offered = |mime_type: &str| mime_types.iter().any(|ty| ty == mime_type);
+
+    if let Some(mime_type) = TEXT_MIME_TYPES.into_iter().find(|ty| offered(ty)) {
+        let data = data(mime_type)?;
+        return Ok(data.map(|data| String::from_utf8_lossy(&data).into_owned()));
+    }
+
+    if offered(LATIN1_MIME_TYPE) {
+        let data = data(LATIN1_MIME_TYPE)?;
+        return Ok(data.map(|data| data.into_iter().map(char::from).collect()));
+    }
+
+    Ok(None)
+}
+
+impl EventLoopWindowTarget {
+    fn set_selection(
+        &self,
+        kind: SelectionKind,
+        contents: ClipboardContents,
+    ) -> Result<(), ExternalError> {
+        let result = match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.set_selection(kind, contents),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.set_selection(kind, contents),
+            PlatformEventLoopWindowTarget::Headless(target) => target.set_selection(kind, contents),
+        };
+
+        result.map_err(|error| ExternalError::Os(os_error!(error)))
+    }
+
+    fn selection_mime_types(&self, kind: SelectionKind) -> Result<Vec<String>, ExternalError> {
+        let result = match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.selection_mime_types(kind),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.selection_mime_types(kind),
+            PlatformEventLoopWindowTarget::Headless(target) => target.selection_mime_types(kind),
+        };
+
+        result.map_err(|error| ExternalError::Os(os_error!(error)))
+    }
+
+    fn selection_data(
+        &self,
+        kind: SelectionKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, ExternalError> {
+        let result = match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.selection_data(kind, mime_type),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => {
+                target.selection_data(kind, mime_type)
+            }
+            PlatformEventLoopWindowTarget::Headless(target) => {
+                target.selection_data(kind, mime_type)
+            }
+        };
+
+        result.map_err(|error| ExternalError::Os(os_error!(error)))
+    }
+}
+
+impl EventLoopWindowTargetExtClipboard for EventLoopWindowTarget {
+    fn set_clipboard(&self, contents: ClipboardContents) -> Result<(), ExternalError> {
+        self.set_selection(SelectionKind::Clipboard, contents)
+    }
+
+    fn clipboard_mime_types(&self) -> Result<Vec<String>, ExternalError> {
+        self.selection_mime_types(SelectionKind::Clipboard)
+    }
+
+    fn clipboard_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.selection_data(SelectionKind::Clipboard, mime_type)
+    }
+
+    fn set_primary_selection(&self, contents: ClipboardContents) -> Result<(), ExternalError> {
+        self.set_selection(SelectionKind::Primary, contents)
+    }
+
+    fn primary_selection_mime_types(&self) -> Result<Vec<String>, ExternalError> {
+        self.selection_mime_types(SelectionKind::Primary)
+    }
+
+    fn primary_selection_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.selection_data(SelectionKind::Primary, mime_type)
+    }
+}
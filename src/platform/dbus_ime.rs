@@ -0,0 +1,60 @@
+//! # D-Bus input methods
+//!
+//! By default, the IME goes through the protocol of the display server: XIM on X11, and
+//! `zwp_text_input_v3` on Wayland. XIM is slow, places the preedit poorly with many input
+//! methods, and blocks the event loop when the input method hangs, so the event loop can instead
+//! talk to IBus or Fcitx 5 directly, over their interfaces on the D-Bus session bus.
+//!
+//! The input method then sends the same [`Ime`] events, and gets the key presses before they are
+//! sent as [`WindowEvent::KeyboardInput`], which they aren't when the input method handles them.
+//! The calls to the input method are made from a separate thread, and the event loop waits for
+//! the key presses to be processed for a bounded time only.
+//!
+//! A key press the input method doesn't process within 200 ms is sent as
+//! [`WindowEvent::KeyboardInput`], and the text the input method commits for it later is dropped,
+//! so the key isn't typed twice. The event loop doesn't wait for the following keys until the
+//! input method catches up.
+//!
+//! This requires the `dbus-ime` feature.
+//!
+//! ## Platform-specific
+//!
+//! - **X11:** Used instead of XIM.
+//! - **Wayland:** Only used when the compositor doesn't support `zwp_text_input_v3`, since the
+//!   compositor routes the keys to the input method otherwise.
+//!
+//! [`Ime`]: crate::event::Ime
+//! [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+
+use crate::event_loop::EventLoopBuilder;
+
+/// The input method to talk to over D-Bus.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DBusImeBackend {
+    /// Pick the input method running on the session bus, preferring the one configured with the
+    /// `GTK_IM_MODULE`, `QT_IM_MODULE` and `XMODIFIERS` environment variables.
+    #[default]
+    Auto,
+
+    /// IBus, through the `org.freedesktop.portal.IBus` service.
+    IBus,
+
+    /// Fcitx 5, through the `org.fcitx.Fcitx5` service.
+    Fcitx5,
+}
+
+/// Additional methods on [`EventLoopBuilder`] to use the input methods on D-Bus.
+pub trait EventLoopBuilderExtDBusIme {
+    /// Talk to the input method over D-Bus instead of the IME protocol of the display server.
+    ///
+    /// When the input method isn't running, the IME protocol of the display server is used.
+    fn with_dbus_ime(&mut self, backend: DBusImeBackend) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtDBusIme for EventLoopBuilder<T> {
+    #[inline]
+    fn with_dbus_ime(&mut self, backend: DBusImeBackend) -> &mut Self {
+        self.platform_specific.dbus_ime = Some(backend);
+        self
+    }
+}
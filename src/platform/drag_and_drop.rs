@@ -0,0 +1,225 @@
+//! Dragging data between windows and other applications.
+//!
+//! A drag is started with [`WindowExtDragAndDrop::start_drag`] while a pointer button is held,
+//! usually once the cursor moved far enough after a press on the dragged item. The drag follows
+//! the pointer until the button is released, and the window receives
+//! [`WindowEvent::DragSourceAction`] whenever the target under the cursor changes its response,
+//! then a single [`WindowEvent::DragSourceFinished`] with the outcome.
+//!
+//! The data is only produced when the drop target asks for it, in the MIME type it picked.
+//!
+//! Data dragged over a window, from another application or the application itself, is announced
+//! with [`WindowEvent::DragEntered`], followed by a [`WindowEvent::DragMoved`] for each motion,
+//! and ends with either [`WindowEvent::DragLeft`] or [`WindowEvent::DragDropped`]. The drop is
+//! accepted or rejected with [`EventLoopWindowTargetExtDragAndDrop::set_drop_action`] while
+//! handling these events, and the data is read with
+//! [`EventLoopWindowTargetExtDragAndDrop::drop_data`]. Files are accepted by default, which
+//! also emits the [`WindowEvent::HoveredFile`] family of events.
+//!
+//! ## Platform-specific
+//!
+//! - **X11:** The XDND protocol is used. The icon replaces the cursor during the drag.
+//! - **Wayland:** The drag requires a button press over the window. [`DndAction::Link`] isn't
+//!   supported.
+//! - **Headless:** Unsupported.
+//!
+//! [`WindowEvent::DragSourceAction`]: crate::event::WindowEvent::DragSourceAction
+//! [`WindowEvent::DragSourceFinished`]: crate::event::WindowEvent::DragSourceFinished
+//! [`WindowEvent::DragEntered`]: crate::event::WindowEvent::DragEntered
+//! [`WindowEvent::DragMoved`]: crate::event::WindowEvent::DragMoved
+//! [`WindowEvent::DragLeft`]: crate::event::WindowEvent::DragLeft
+//! [`WindowEvent::DragDropped`]: crate::event::WindowEvent::DragDropped
+//! [`WindowEvent::HoveredFile`]: crate::event::WindowEvent::HoveredFile
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::ExternalError;
+use crate::event::DndAction;
+use crate::event_loop::EventLoopWindowTarget;
+use crate::platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget;
+use crate::window::{Icon, Window};
+
+/// Produces the dragged data in the MIME type it was added for.
+type DataProvider = Arc<dyn Fn() -> Vec<u8> + Send + Sync>;
+
+/// The data dragged out of a window, with the actions allowed on it and the icon showing it.
+#[derive(Clone)]
+pub struct DragSource {
+    data: Vec<(String, DataProvider)>,
+    actions: Vec<DndAction>,
+    icon: Option<Icon>,
+}
+
+impl fmt::Debug for DragSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DragSource")
+            .field("mime_types", &self.mime_types().collect::<Vec<_>>())
+            .field("actions", &self.actions)
+            .field("icon", &self.icon)
+            .finish()
+    }
+}
+
+impl Default for DragSource {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            actions: vec![DndAction::Copy],
+            icon: None,
+        }
+    }
+}
+
+impl DragSource {
+    /// Create a drag without data, which allows copying it.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Offer the `data` in the `mime_type`, replacing the data previously offered in it.
+    pub fn with_data(self, mime_type: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        let data: Arc<[u8]> = Arc::from(data.into());
+        self.with_provider(mime_type, move || data.to_vec())
+    }
+
+    /// Offer the data in the `mime_type`, produced by the `provider` once a target requests it.
+    ///
+    /// The `provider` runs on the event loop thread and may be called several times.
+    pub fn with_provider(
+        mut self,
+        mime_type: impl Into<String>,
+        provider: impl Fn() -> Vec<u8> + Send + Sync + 'static,
+    ) -> Self {
+        let mime_type = mime_type.into();
+        let provider: DataProvider = Arc::new(provider);
+        match self.data.iter_mut().find(|(ty, _)| *ty == mime_type) {
+            Some((_, old)) => *old = provider,
+            None => self.data.push((mime_type, provider)),
+        }
+        self
+    }
+
+    /// Set the actions allowed on the data, in the order of preference.
+    ///
+    /// Only [`DndAction::Copy`] is allowed by default, and when the list is empty.
+    pub fn with_actions(mut self, actions: &[DndAction]) -> Self {
+        self.actions = actions.to_vec();
+        self
+    }
+
+    /// Set the icon shown under the cursor during the drag, centered on it.
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// The MIME types of the data, in the order they were added.
+    pub fn mime_types(&self) -> impl Iterator<Item = &str> {
+        self.data.iter().map(|(mime_type, _)| mime_type.as_str())
+    }
+
+    /// The actions allowed on the data, in the order of preference.
+    pub fn actions(&self) -> &[DndAction] {
+        &self.actions
+    }
+
+    /// The action preferred by the application.
+    pub(crate) fn preferred_action(&self) -> DndAction {
+        self.actions.first().copied().unwrap_or(DndAction::Copy)
+    }
+
+    /// Produce the data in the `mime_type`.
+    pub(crate) fn data(&self, mime_type: &str) -> Option<Vec<u8>> {
+        self.data
+            .iter()
+            .find(|(ty, _)| ty == mime_type)
+            .map(|(_, provider)| provider())
+    }
+
+    pub(crate) fn icon(&self) -> Option<&Icon> {
+        self.icon.as_ref()
+    }
+}
+
+/// Additional methods on [`Window`] to drag data into other applications.
+pub trait WindowExtDragAndDrop {
+    /// Start dragging the `source` from the window.
+    ///
+    /// This must be called while a pointer button is held over the window, the drag ends when
+    /// it's released. Starting a new drag cancels the previous one.
+    ///
+    /// The errors are reported right away when possible, otherwise the drag finishes with no
+    /// action.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Headless:** Unsupported.
+    fn start_drag(&self, source: DragSource) -> Result<(), ExternalError>;
+}
+
+impl WindowExtDragAndDrop for Window {
+    fn start_drag(&self, source: DragSource) -> Result<(), ExternalError> {
+        self.window.start_drag(source)
+    }
+}
+
+/// Additional methods on [`EventLoopWindowTarget`] to receive the data dragged over the windows.
+pub trait EventLoopWindowTargetExtDragAndDrop {
+    /// Accept the drop of the dragged data with the `action`, or reject it with `None`.
+    ///
+    /// The response applies to the position of the latest [`WindowEvent::DragMoved`] and stays
+    /// until it's changed, thus it's usually set while handling that event. Without a response,
+    /// files are accepted with the action proposed by the source and other data is rejected.
+    ///
+    /// [`WindowEvent::DragMoved`]: crate::event::WindowEvent::DragMoved
+    fn set_drop_action(&self, action: Option<DndAction>);
+
+    /// The MIME types the data dragged over the windows is offered in.
+    ///
+    /// Returns an empty list when nothing is dragged over the windows.
+    fn drop_mime_types(&self) -> Vec<String>;
+
+    /// Read the data dragged over the windows in the `mime_type`.
+    ///
+    /// The data stays readable after [`WindowEvent::DragDropped`] until the event loop waits
+    /// again. Returns `None` when nothing is dragged over the windows or the data isn't offered
+    /// in the `mime_type`.
+    ///
+    /// [`WindowEvent::DragDropped`]: crate::event::WindowEvent::DragDropped
+    fn drop_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, ExternalError>;
+}
+
+impl EventLoopWindowTargetExtDragAndDrop for EventLoopWindowTarget {
+    fn set_drop_action(&self, action: Option<DndAction>) {
+        match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.set_drop_action(action),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.set_drop_action(action),
+            PlatformEventLoopWindowTarget::Headless(target) => target.set_drop_action(action),
+        }
+    }
+
+    fn drop_mime_types(&self) -> Vec<String> {
+        match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.drop_mime_types(),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.drop_mime_types(),
+            PlatformEventLoopWindowTarget::Headless(target) => target.drop_mime_types(),
+        }
+    }
+
+    fn drop_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, ExternalError> {
+        let result = match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.drop_data(mime_type),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.drop_data(mime_type),
+            PlatformEventLoopWindowTarget::Headless(target) => target.drop_data(mime_type),
+        };
+
+        result.map_err(|error| ExternalError::Os(os_error!(error)))
+    }
+}
@@ -0,0 +1,111 @@
+//! # Headless
+//!
+//! The headless backend doesn't connect to any display server. Windows created with it are
+//! virtual: they keep their size, position, scale factor and focus state, and emit the same
+//! [`WindowEvent`]s a real backend would when those change, which makes it possible to drive an
+//! application's event handler on machines without a display, e.g. in CI.
+//!
+//! All the windows are placed on a single virtual monitor of `1920x1080` pixels with the scale
+//! factor of `1.0`, which can be changed with
+//! [`EventLoopWindowTargetExtHeadless::set_headless_scale_factor`].
+//!
+//! Input, which is normally coming from the display server, can be injected with
+//! [`EventLoopWindowTargetExtHeadless::inject_window_event`], or with
+//! [`EventLoopWindowTargetExtSyntheticInput::inject_input`] for the keyboard input, which is
+//! translated with the default keymap.
+//!
+//! There's no native window or display to render to. The `raw-window-handle` 0.6 handles return
+//! [`HandleError::NotSupported`]. The 0.4 and 0.5 ones can't fail, thus they're web handles, the
+//! window one having the reserved `0` id, which native renderers reject instead of dereferencing.
+//!
+//! [`EventLoopWindowTargetExtSyntheticInput::inject_input`]: crate::platform::synthetic_input::EventLoopWindowTargetExtSyntheticInput::inject_input
+//! [`HandleError::NotSupported`]: https://docs.rs/raw-window-handle/0.6/raw_window_handle/enum.HandleError.html#variant.NotSupported
+
+use crate::{
+    error::{ExternalError, NotSupportedError},
+    event::WindowEvent,
+    event_loop::{EventLoopBuilder, EventLoopWindowTarget},
+    platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget,
+    window::WindowId,
+};
+
+/// Additional methods on [`EventLoopBuilder`] that are specific to the headless backend.
+pub trait EventLoopBuilderExtHeadless {
+    /// Force using the headless backend.
+    ///
+    /// The headless backend is never picked automatically. The event loop can be created off of
+    /// the main thread with `EventLoopBuilderExtX11::with_any_thread` or
+    /// `EventLoopBuilderExtWayland::with_any_thread`.
+    fn with_headless(&mut self) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtHeadless for EventLoopBuilder<T> {
+    #[inline]
+    fn with_headless(&mut self) -> &mut Self {
+        self.platform_specific.forced_backend = Some(crate::platform_impl::Backend::Headless);
+        self
+    }
+}
+
+/// Additional methods on [`EventLoopWindowTarget`] that are specific to the headless backend.
+pub trait EventLoopWindowTargetExtHeadless {
+    /// True if the [`EventLoopWindowTarget`] uses the headless backend.
+    fn is_headless(&self) -> bool;
+
+    /// Change the scale factor of the virtual monitor.
+    ///
+    /// Every window receives [`WindowEvent::ScaleFactorChanged`] during the next loop iteration,
+    /// followed by [`WindowEvent::Resized`] if its physical size has changed.
+    ///
+    /// Returns [`ExternalError::NotSupported`] when not running on the headless backend.
+    fn set_headless_scale_factor(&self, scale_factor: f64) -> Result<(), ExternalError>;
+
+    /// Queue the `event` for the window as if it was sent by the display server.
+    ///
+    /// The event is delivered during the next loop iteration, after the events which were already
+    /// queued. The window state is updated to match the event, e.g. [`WindowEvent::Resized`]
+    /// updates the [`Window::inner_size`] and [`WindowEvent::Focused`] moves the keyboard focus,
+    /// unfocusing the previously focused window.
+    ///
+    /// Returns [`ExternalError::NotSupported`] when not running on the headless backend and
+    /// [`ExternalError::Os`] when the window doesn't exist.
+    ///
+    /// [`Window::inner_size`]: crate::window::Window::inner_size
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), ExternalError>;
+}
+
+impl EventLoopWindowTargetExtHeadless for EventLoopWindowTarget {
+    #[inline]
+    fn is_headless(&self) -> bool {
+        self.p.is_headless()
+    }
+
+    fn set_headless_scale_factor(&self, scale_factor: f64) -> Result<(), ExternalError> {
+        match &self.p {
+            PlatformEventLoopWindowTarget::Headless(target) => {
+                target.shared.set_scale_factor(scale_factor);
+                Ok(())
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+
+    fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), ExternalError> {
+        match &self.p {
+            PlatformEventLoopWindowTarget::Headless(target) => target
+                .inject_window_event(window_id.0, event)
+                .map_err(|error| ExternalError::Os(os_error!(error))),
+            #[allow(unreachable_patterns)]
+            _ => Err(ExternalError::NotSupported(NotSupportedError::new())),
+        }
+    }
+}
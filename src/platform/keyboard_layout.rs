@@ -0,0 +1,53 @@
+//! Access to the keyboard layouts, which XKB calls groups.
+//!
+//! The keymap of the keyboard holds one or more layouts, e.g. English and Russian, which the user
+//! switches between. The focused window receives [`WindowEvent::KeyboardLayoutChanged`] when the
+//! active layout changes.
+//!
+//! ## Platform-specific
+//!
+//! - **X11:** The layouts of the core keyboard are reported.
+//! - **Wayland:** The layouts of the seat whose keymap or layout was updated last are reported.
+//! - **Headless:** The layouts of the default keymap, which is compiled when the first synthetic
+//!   key is injected. The active layout never changes.
+//!
+//! [`WindowEvent::KeyboardLayoutChanged`]: crate::event::WindowEvent::KeyboardLayoutChanged
+
+use crate::event::KeyboardLayout;
+use crate::event_loop::EventLoopWindowTarget;
+use crate::platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget;
+
+/// Additional methods on [`EventLoopWindowTarget`] to query the keyboard layouts.
+pub trait EventLoopWindowTargetExtKeyboardLayout {
+    /// The active keyboard layout.
+    ///
+    /// Returns `None` while the keymap of the keyboard is unknown.
+    fn keyboard_layout(&self) -> Option<KeyboardLayout>;
+
+    /// The names of the configured keyboard layouts, in the order of their indices.
+    ///
+    /// The names are empty for the layouts the keymap doesn't name.
+    fn keyboard_layouts(&self) -> Vec<String>;
+}
+
+impl EventLoopWindowTargetExtKeyboardLayout for EventLoopWindowTarget {
+    fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.keyboard_layout(),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.keyboard_layout(),
+            PlatformEventLoopWindowTarget::Headless(target) => target.keyboard_layout(),
+        }
+    }
+
+    fn keyboard_layouts(&self) -> Vec<String> {
+        match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.keyboard_layouts(),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => target.keyboard_layouts(),
+            PlatformEventLoopWindowTarget::Headless(target) => target.keyboard_layouts(),
+        }
+    }
+}
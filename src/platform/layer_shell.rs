@@ -0,0 +1,186 @@
+//! Desktop shell surfaces, such as panels, docks, wallpapers and on-screen overlays.
+//!
+//! A layer surface is placed by the compositor against the edges of a monitor, in one of the
+//! [`Layer`]s stacked below and above the regular windows. It may reserve an exclusive zone along
+//! the edge it's anchored to, which the other windows won't cover, like a panel does.
+//!
+//! The window is created as a layer surface with `WindowBuilderExtWayland::with_layer_shell` or
+//! `WindowBuilderExtX11::with_layer_shell`.
+//!
+//! ```
+//! use winit::platform::layer_shell::{Layer, LayerAnchor, LayerShellAttributes};
+//!
+//! // A panel along the top of the monitor, 32 pixels high.
+//! let panel = LayerShellAttributes::new(Layer::Top)
+//!     .with_anchor(LayerAnchor::TOP | LayerAnchor::LEFT | LayerAnchor::RIGHT)
+//!     .with_exclusive_zone(32)
+//!     .with_namespace("panel");
+//! ```
+//!
+//! ## Platform-specific
+//!
+//! - **X11:** The window is a dock placed by the application, or a desktop window on the
+//!   [`Layer::Background`]. The exclusive zone is reserved with `_NET_WM_STRUT_PARTIAL`, and isn't
+//!   updated when the window is resized. [`KeyboardInteractivity::Exclusive`] behaves like
+//!   [`KeyboardInteractivity::OnDemand`].
+//! - **Wayland:** The `zwlr_layer_shell_v1` protocol is used, which isn't available on every
+//!   compositor. The window can't be created without it. [`KeyboardInteractivity::OnDemand`]
+//!   requires version 4 of the protocol, before it the surface is never focused.
+//! - **Headless:** The layer surface is created as a regular window.
+
+use crate::monitor::MonitorHandle;
+
+/// The layer a surface is stacked in, from the bottom to the top.
+///
+/// The regular windows are stacked between the [`Layer::Bottom`] and [`Layer::Top`] layers, while
+/// the fullscreen windows usually cover the [`Layer::Top`] layer too.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// Wallpapers and desktop icons.
+    Background,
+    /// Below the regular windows.
+    Bottom,
+    /// Panels and docks, above the regular windows.
+    #[default]
+    Top,
+    /// Lock screens and on-screen overlays, above everything else.
+    Overlay,
+}
+
+bitflags::bitflags! {
+    /// The edges of the monitor a layer surface is anchored to.
+    ///
+    /// A surface anchored to two opposite edges is stretched between them, and one anchored to
+    /// neither of them is centered on that axis.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LayerAnchor: u32 {
+        const TOP = 1 << 0;
+        const BOTTOM = 1 << 1;
+        const LEFT = 1 << 2;
+        const RIGHT = 1 << 3;
+    }
+}
+
+/// Whether a layer surface receives the keyboard input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyboardInteractivity {
+    /// The surface is never focused.
+    #[default]
+    None,
+    /// The surface takes the keyboard focus from the other windows, while it's in the
+    /// [`Layer::Top`] or [`Layer::Overlay`] layers.
+    Exclusive,
+    /// The surface is focused like the regular windows, when the user clicks it.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Behaves like [`KeyboardInteractivity::None`] before version 4 of
+    ///   `zwlr_layer_shell_v1`.
+    OnDemand,
+}
+
+/// The placement of a layer surface on the monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerShellAttributes {
+    layer: Layer,
+    anchor: LayerAnchor,
+    exclusive_zone: i32,
+    margin: (i32, i32, i32, i32),
+    keyboard_interactivity: KeyboardInteractivity,
+    namespace: String,
+    monitor: Option<MonitorHandle>,
+}
+
+impl LayerShellAttributes {
+    /// Place the surface in the `layer`, centered on the monitor chosen by the compositor.
+    ///
+    /// By default the surface doesn't reserve an exclusive zone, and doesn't receive the keyboard
+    /// input.
+    pub fn new(layer: Layer) -> Self {
+        Self {
+            layer,
+            anchor: LayerAnchor::empty(),
+            exclusive_zone: 0,
+            margin: (0, 0, 0, 0),
+            keyboard_interactivity: KeyboardInteractivity::None,
+            namespace: String::new(),
+            monitor: None,
+        }
+    }
+
+    /// Set the edges of the monitor the surface is anchored to.
+    pub fn with_anchor(mut self, anchor: LayerAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Reserve the `exclusive_zone` along the anchored edge, in logical pixels.
+    ///
+    /// The zone is only reserved when the surface is anchored to a single edge, or to an edge and
+    /// both of the perpendicular ones. With `0`, the surface is moved out of the exclusive zones
+    /// of the other surfaces, and with `-1` it's placed over them.
+    pub fn with_exclusive_zone(mut self, exclusive_zone: i32) -> Self {
+        self.exclusive_zone = exclusive_zone;
+        self
+    }
+
+    /// Set the distances of the surface from the anchored edges, in logical pixels.
+    pub fn with_margin(mut self, top: i32, right: i32, bottom: i32, left: i32) -> Self {
+        self.margin = (top, right, bottom, left);
+        self
+    }
+
+    /// Set whether the surface receives the keyboard input.
+    pub fn with_keyboard_interactivity(mut self, interactivity: KeyboardInteractivity) -> Self {
+        self.keyboard_interactivity = interactivity;
+        self
+    }
+
+    /// Set the purpose of the surface, such as `"panel"` or `"wallpaper"`, which the compositor
+    /// may use to apply its own rules to it.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Place the surface on the `monitor`, instead of the one chosen by the compositor.
+    pub fn with_monitor(mut self, monitor: Option<MonitorHandle>) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    /// The layer the surface is stacked in.
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// The edges of the monitor the surface is anchored to.
+    pub fn anchor(&self) -> LayerAnchor {
+        self.anchor
+    }
+
+    /// The exclusive zone reserved along the anchored edge, in logical pixels.
+    pub fn exclusive_zone(&self) -> i32 {
+        self.exclusive_zone
+    }
+
+    /// The top, right, bottom and left margins, in logical pixels.
+    pub fn margin(&self) -> (i32, i32, i32, i32) {
+        self.margin
+    }
+
+    /// Whether the surface receives the keyboard input.
+    pub fn keyboard_interactivity(&self) -> KeyboardInteractivity {
+        self.keyboard_interactivity
+    }
+
+    /// The purpose of the surface.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// The monitor the surface is placed on.
+    pub fn monitor(&self) -> Option<&MonitorHandle> {
+        self.monitor.as_ref()
+    }
+}
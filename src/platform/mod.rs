@@ -4,14 +4,32 @@
 
 #[cfg(any(android_platform, docsrs))]
 pub mod android;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod clipboard;
+#[cfg(all(feature = "dbus-ime", any(x11_platform, wayland_platform, docsrs)))]
+pub mod dbus_ime;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod drag_and_drop;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod headless;
 #[cfg(any(ios_platform, docsrs))]
 pub mod ios;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod keyboard_layout;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod layer_shell;
 #[cfg(any(macos_platform, docsrs))]
 pub mod macos;
 #[cfg(any(orbital_platform, docsrs))]
 pub mod orbital;
 #[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod popup;
+#[cfg(all(feature = "recording", any(x11_platform, wayland_platform, docsrs)))]
+pub mod recording;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
 pub mod startup_notify;
+#[cfg(any(x11_platform, wayland_platform, docsrs))]
+pub mod synthetic_input;
 #[cfg(any(wayland_platform, docsrs))]
 pub mod wayland;
 #[cfg(any(web_platform, docsrs))]
@@ -0,0 +1,213 @@
+//! Popup windows, such as menus, tooltips and the lists of combo boxes.
+//!
+//! A popup is placed relative to a rectangle of its parent window, the anchor rectangle, rather
+//! than at an absolute position. The point of the rectangle given by the anchor, and the
+//! direction the popup extends from it given by the gravity, describe the preferred placement.
+//! When the popup wouldn't fit on the screen there, the constraint adjustments tell how it may be
+//! moved instead.
+//!
+//! A popup with a grab receives the keyboard input, and is dismissed with
+//! [`WindowEvent::PopupDismissed`] when the user clicks outside of it. The popup should then be
+//! dropped, it isn't shown anymore.
+//!
+//! ```no_run
+//! # use winit::dpi::{LogicalPosition, LogicalSize};
+//! # use winit::event_loop::EventLoop;
+//! # use winit::window::{Window, WindowBuilder};
+//! use winit::platform::popup::{PopupAnchor, PopupAttributes, WindowBuilderExtPopup};
+//! # let event_loop = EventLoop::new().unwrap();
+//! # let window = Window::new(&event_loop).unwrap();
+//! // A menu below a button of the window.
+//! let popup = PopupAttributes::new(
+//!     window.id(),
+//!     LogicalPosition::new(10, 10),
+//!     LogicalSize::new(80, 24),
+//! )
+//! .with_anchor(PopupAnchor::BottomLeft)
+//! .with_gravity(PopupAnchor::BottomRight)
+//! .with_grab(true);
+//! let menu = WindowBuilder::new()
+//!     .with_inner_size(LogicalSize::new(200, 300))
+//!     .with_popup(popup)
+//!     .build(&event_loop)
+//!     .unwrap();
+//! ```
+//!
+//! ## Platform-specific
+//!
+//! - **X11:** The popup is an override-redirect window, placed by the application. Moving the
+//!   parent window doesn't move the popup. The grab is taken whenever the popup is shown, and
+//!   released when it's hidden.
+//! - **Wayland:** The `xdg_popup` role is used, and the compositor places the popup. The grab
+//!   requires a recent button press over the parent window. Nested popups must be dropped before
+//!   their parent.
+//! - **Headless:** The popup is created as a regular window.
+//!
+//! [`WindowEvent::PopupDismissed`]: crate::event::WindowEvent::PopupDismissed
+
+use crate::dpi::{Position, Size};
+use crate::window::{WindowBuilder, WindowId};
+
+/// An edge or a corner of a rectangle.
+///
+/// As an anchor, it's the point of the anchor rectangle the popup is placed at. As a gravity,
+/// it's the direction the popup extends to from that point, [`PopupAnchor::BottomRight`] placing
+/// the top left corner of the popup at the point.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PopupAnchor {
+    /// The center of the rectangle, or centered on the point as a gravity.
+    #[default]
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+bitflags::bitflags! {
+    /// How the popup may be moved when it wouldn't fit on the screen at its preferred placement.
+    ///
+    /// The adjustments are tried in the order flipping, sliding then resizing, on each axis.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ConstraintAdjustment: u32 {
+        /// Slide the popup horizontally until it fits.
+        const SLIDE_X = 1 << 0;
+        /// Slide the popup vertically until it fits.
+        const SLIDE_Y = 1 << 1;
+        /// Flip the anchor and the gravity horizontally, to the other side of the rectangle.
+        const FLIP_X = 1 << 2;
+        /// Flip the anchor and the gravity vertically, to the other side of the rectangle.
+        const FLIP_Y = 1 << 3;
+        /// Shrink the popup horizontally until it fits.
+        const RESIZE_X = 1 << 4;
+        /// Shrink the popup vertically until it fits.
+        const RESIZE_Y = 1 << 5;
+    }
+}
+
+/// The placement of a popup relative to its parent window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PopupAttributes {
+    parent: WindowId,
+    anchor_position: Position,
+    anchor_size: Size,
+    anchor: PopupAnchor,
+    gravity: PopupAnchor,
+    constraint_adjustment: ConstraintAdjustment,
+    offset: Position,
+    grab: bool,
+}
+
+impl PopupAttributes {
+    /// Place the popup relative to the rectangle of the `parent` window, at `position` in its
+    /// surface and of `size`.
+    ///
+    /// By default the popup extends below the rectangle from its bottom left corner, flipping
+    /// above it or sliding horizontally when it doesn't fit, and doesn't grab the input.
+    pub fn new(parent: WindowId, position: impl Into<Position>, size: impl Into<Size>) -> Self {
+        Self {
+            parent,
+            anchor_position: position.into(),
+            anchor_size: size.into(),
+            anchor: PopupAnchor::BottomLeft,
+            gravity: PopupAnchor::BottomRight,
+            constraint_adjustment: ConstraintAdjustment::SLIDE_X | ConstraintAdjustment::FLIP_Y,
+            offset: Position::Physical((0, 0).into()),
+            grab: false,
+        }
+    }
+
+    /// Set the point of the anchor rectangle the popup is placed at.
+    pub fn with_anchor(mut self, anchor: PopupAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set the direction the popup extends to from its anchor.
+    pub fn with_gravity(mut self, gravity: PopupAnchor) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Set how the popup may be moved when it doesn't fit on the screen.
+    pub fn with_constraint_adjustment(mut self, adjustment: ConstraintAdjustment) -> Self {
+        self.constraint_adjustment = adjustment;
+        self
+    }
+
+    /// Move the popup from its anchor by the `offset`, before the constraints are applied.
+    pub fn with_offset(mut self, offset: impl Into<Position>) -> Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Whether the popup grabs the keyboard and pointer input, and is dismissed by clicks outside
+    /// of it.
+    ///
+    /// Menus usually grab the input, while tooltips don't.
+    pub fn with_grab(mut self, grab: bool) -> Self {
+        self.grab = grab;
+        self
+    }
+
+    /// The parent window.
+    pub fn parent(&self) -> WindowId {
+        self.parent
+    }
+
+    /// The position of the anchor rectangle in the surface of the parent window.
+    pub fn anchor_position(&self) -> Position {
+        self.anchor_position
+    }
+
+    /// The size of the anchor rectangle.
+    pub fn anchor_size(&self) -> Size {
+        self.anchor_size
+    }
+
+    /// The point of the anchor rectangle the popup is placed at.
+    pub fn anchor(&self) -> PopupAnchor {
+        self.anchor
+    }
+
+    /// The direction the popup extends to from its anchor.
+    pub fn gravity(&self) -> PopupAnchor {
+        self.gravity
+    }
+
+    /// How the popup may be moved when it doesn't fit on the screen.
+    pub fn constraint_adjustment(&self) -> ConstraintAdjustment {
+        self.constraint_adjustment
+    }
+
+    /// The offset of the popup from its anchor.
+    pub fn offset(&self) -> Position {
+        self.offset
+    }
+
+    /// Whether the popup grabs the input.
+    pub fn grab(&self) -> bool {
+        self.grab
+    }
+}
+
+/// Additional methods on [`WindowBuilder`] to build popups.
+pub trait WindowBuilderExtPopup {
+    /// Build a popup placed relative to its parent window, with the inner size of the window.
+    ///
+    /// The position, decorations, title and fullscreen state of the window are ignored for
+    /// popups.
+    fn with_popup(self, popup: PopupAttributes) -> Self;
+}
+
+impl WindowBuilderExtPopup for WindowBuilder {
+    #[inline]
+    fn with_popup(mut self, popup: PopupAttributes) -> Self {
+        self.window.platform_specific.popup = Some(popup);
+        self
+    }
+}
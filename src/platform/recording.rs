@@ -0,0 +1,440 @@
+//! Recording and replaying of the event stream.
+//!
+//! The [`Recorder`] writes every [`Event`] dispatched to the wrapped event handler, along with the
+//! time it was dispatched at, to a line-delimited JSON stream, one event per line. Such a
+//! recording could later be fed back to the event handler with
+//! [`EventLoopExtReplay::pump_replay`] to reproduce the session.
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use std::io::BufWriter;
+//!
+//! use winit::event_loop::EventLoop;
+//! use winit::platform::recording::Recorder;
+//!
+//! let event_loop = EventLoop::new().unwrap();
+//! let recorder = Recorder::new(BufWriter::new(File::create("session.jsonl").unwrap()));
+//! event_loop.run(recorder.wrap(|event, elwt| {
+//!     // Handle the event as usual.
+//! })).unwrap();
+//! ```
+//!
+//! The window and device ids in the recording are the ones from the recorded session. When
+//! replaying, the windows from the recording should be mapped onto the windows of the current
+//! session with [`Replay::map_window`].
+//!
+//! The events are stored through their `serde` implementations, thus the sizes requested with the
+//! [`InnerSizeWriter`] of the replayed [`WindowEvent::ScaleFactorChanged`] events are ignored.
+//! The timestamps of the replayed events are as old relative to their dispatch as they were when
+//! recorded.
+//!
+//! [`InnerSizeWriter`]: crate::event::InnerSizeWriter
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::event::{DeviceEvent, DeviceId, Event, StartCause, WindowEvent};
+use crate::event_loop::{EventLoop, EventLoopWindowTarget};
+use crate::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use crate::window::WindowId;
+
+/// Records the events into a line-delimited JSON stream.
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Create a recorder writing into `writer`.
+    ///
+    /// The event times are recorded relative to the moment the recorder was created.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    /// Record a single event.
+    ///
+    /// The writer is flushed after [`Event::LoopExiting`].
+    pub fn record<T: Serialize>(&mut self, event: &Event<T>) -> io::Result<()> {
+        let record = Record {
+            time: self.start.elapsed(),
+            event: RecordedEvent::from_event(event),
+        };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.writer.write_all(b"\n")?;
+
+        if matches!(event, Event::LoopExiting) {
+            self.writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Wrap the event handler, so every event passed to it is recorded first.
+    ///
+    /// When writing the recording fails, the error is logged and the recording stops, while the
+    /// events are still passed to the `event_handler`.
+    pub fn wrap<T, F>(
+        mut self,
+        mut event_handler: F,
+    ) -> impl FnMut(Event<T>, &EventLoopWindowTarget)
+    where
+        T: Serialize + 'static,
+        F: FnMut(Event<T>, &EventLoopWindowTarget),
+    {
+        let mut recording = true;
+        move |event, elwt| {
+            if recording {
+                if let Err(error) = self.record(&event) {
+                    log::error!("Failed to record the event, stopping the recording: {error}");
+                    recording = false;
+                }
+            }
+            event_handler(event, elwt);
+        }
+    }
+
+    /// Get the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// The pace at which the recording is replayed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayTiming {
+    /// Replay the events with the original delays between them.
+    Original,
+
+    /// Replay the events with the delays between them divided by the given factor, which must be
+    /// finite and positive.
+    Accelerated(f64),
+
+    /// Replay the events without any delays.
+    Immediate,
+}
+
+/// A recording being replayed with [`EventLoopExtReplay::pump_replay`].
+#[derive(Debug)]
+pub struct Replay<R: BufRead, T> {
+    reader: R,
+    timing: ReplayTiming,
+    /// The moment the replay has started at.
+    start: Option<Instant>,
+    /// The next event, which is not yet due.
+    next: Option<Record<'static, T>>,
+    /// The mapping from the recorded windows to the windows of the current session.
+    windows: HashMap<u64, WindowId>,
+    line: String,
+    finished: bool,
+}
+
+impl<R: BufRead, T: DeserializeOwned> Replay<R, T> {
+    /// Create a replay of the recording read from `reader`.
+    ///
+    /// The replay starts with the first call to [`EventLoopExtReplay::pump_replay`].
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] when the factor of
+    /// [`ReplayTiming::Accelerated`] isn't finite and positive.
+    pub fn new(reader: R, timing: ReplayTiming) -> io::Result<Self> {
+        if let ReplayTiming::Accelerated(factor) = timing {
+            if !(factor.is_finite() && factor > 0.0) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid replay acceleration factor: {factor}"),
+                ));
+            }
+        }
+
+        Ok(Self {
+            reader,
+            timing,
+            start: None,
+            next: None,
+            windows: HashMap::new(),
+            line: String::new(),
+            finished: false,
+        })
+    }
+
+    /// Dispatch the events of the window with `recorded` id from the recording as the events of
+    /// the window with the `current` id.
+    pub fn map_window(&mut self, recorded: WindowId, current: WindowId) {
+        self.windows.insert(recorded.into(), current);
+    }
+
+    /// Whether all the events from the recording were dispatched.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Read the next event from the recording.
+    fn peek(&mut self) -> io::Result<Option<&Record<'static, T>>> {
+        while self.next.is_none() && !self.finished {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                self.finished = true;
+            } else if !self.line.trim().is_empty() {
+                self.next = Some(serde_json::from_str(&self.line)?);
+            }
+        }
+
+        Ok(self.next.as_ref())
+    }
+
+    /// The moment the event recorded at `time` is due.
+    fn due_time(&mut self, time: Duration) -> Instant {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        match self.timing {
+            ReplayTiming::Original => start + time,
+            ReplayTiming::Accelerated(factor) => start + time.div_f64(factor),
+            ReplayTiming::Immediate => start,
+        }
+    }
+}
+
+/// Additional methods on [`EventLoop`] to replay recordings made with [`Recorder`].
+pub trait EventLoopExtReplay {
+    /// A type provided by the user that can be passed through [`Event::UserEvent`].
+    type UserEvent;
+
+    /// Dispatch the events from the recording, which are due, to the `event_handler`.
+    ///
+    /// This works like [`EventLoopExtPumpEvents::pump_events`]: when no recorded event is due,
+    /// it waits up to `timeout` for the next one, where `None` means to wait until it's due.
+    /// The platform event loop keeps being pumped while waiting, but the events coming from it,
+    /// including the user events sent through an [`EventLoopProxy`], are not dispatched.
+    ///
+    /// Returns [`PumpStatus::Exit`] once the recording was fully replayed or when the
+    /// `event_handler` requested the exit.
+    ///
+    /// [`EventLoopProxy`]: crate::event_loop::EventLoopProxy
+    fn pump_replay<R, F>(
+        &mut self,
+        replay: &mut Replay<R, Self::UserEvent>,
+        timeout: Option<Duration>,
+        event_handler: F,
+    ) -> io::Result<PumpStatus>
+    where
+        R: BufRead,
+        F: FnMut(Event<Self::UserEvent>, &EventLoopWindowTarget);
+}
+
+impl<T: DeserializeOwned + 'static> EventLoopExtReplay for EventLoop<T> {
+    type UserEvent = T;
+
+    fn pump_replay<R, F>(
+        &mut self,
+        replay: &mut Replay<R, T>,
+        timeout: Option<Duration>,
+        mut event_handler: F,
+    ) -> io::Result<PumpStatus>
+    where
+        R: BufRead,
+        F: FnMut(Event<T>, &EventLoopWindowTarget),
+    {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        // Keep pumping the platform loop until the next event is due, without dispatching its
+        // events.
+        loop {
+            if let Some(code) = self.event_loop.window_target().p.exit_code() {
+                return Ok(PumpStatus::Exit(code));
+            }
+
+            let time = match replay.peek()? {
+                Some(record) => record.time,
+                None => return Ok(PumpStatus::Exit(0)),
+            };
+
+            let due_time = replay.due_time(time);
+            let wake_time = deadline.map_or(due_time, |deadline| deadline.min(due_time));
+            let wait = wake_time.saturating_duration_since(Instant::now());
+            if let PumpStatus::Exit(code) = self.pump_events(Some(wait), |_, _| ()) {
+                return Ok(PumpStatus::Exit(code));
+            }
+
+            let now = Instant::now();
+            if due_time <= now {
+                break;
+            } else if wake_time <= now {
+                return Ok(PumpStatus::Continue);
+            }
+        }
+
+        let target = self.event_loop.window_target();
+        while target.p.exit_code().is_none() {
+            let time = match replay.peek()? {
+                Some(record) => record.time,
+                None => break,
+            };
+
+            if replay.due_time(time) > Instant::now() {
+                break;
+            }
+
+            let record = replay.next.take().unwrap();
+            record
+                .event
+                .dispatch(&replay.windows, target, &mut event_handler);
+        }
+
+        if let Some(code) = target.p.exit_code() {
+            Ok(PumpStatus::Exit(code))
+        } else if replay.peek()?.is_none() {
+            Ok(PumpStatus::Exit(0))
+        } else {
+            Ok(PumpStatus::Continue)
+        }
+    }
+}
+
+/// A single line of the recording.
+#[derive(Debug, Serialize, Deserialize)]
+struct Record<'a, U> {
+    /// The time since the start of the recording.
+    time: Duration,
+    event: RecordedEvent<'a, U>,
+}
+
+/// The [`Event`] with the window id and the timestamps in a form which could be replayed.
+#[derive(Debug, Serialize, Deserialize)]
+enum RecordedEvent<'a, U> {
+    NewEvents(RecordedStartCause),
+    /// The `age` is how long before being recorded the event was generated.
+    WindowEvent {
+        window_id: u64,
+        event: Cow<'a, WindowEvent>,
+        age: Duration,
+    },
+    DeviceEvent {
+        device_id: DeviceId,
+        event: Cow<'a, DeviceEvent>,
+        age: Duration,
+    },
+    UserEvent(U),
+    Suspended,
+    Resumed,
+    AboutToWait,
+    LoopExiting,
+    MemoryWarning,
+}
+
+impl<'a, T> RecordedEvent<'a, &'a T> {
+    fn from_event(event: &'a Event<T>) -> Self {
+        match event {
+            Event::NewEvents(cause) => Self::NewEvents(match cause {
+                StartCause::ResumeTimeReached { .. } => RecordedStartCause::ResumeTimeReached,
+                StartCause::WaitCancelled { .. } => RecordedStartCause::WaitCancelled,
+                StartCause::Poll => RecordedStartCause::Poll,
+                StartCause::Init => RecordedStartCause::Init,
+            }),
+            Event::WindowEvent {
+                window_id,
+                event,
+                timestamp,
+            } => Self::WindowEvent {
+                window_id: (*window_id).into(),
+                event: Cow::Borrowed(event),
+                age: timestamp.elapsed(),
+            },
+            Event::DeviceEvent {
+                device_id,
+                event,
+                timestamp,
+            } => Self::DeviceEvent {
+                device_id: *device_id,
+                event: Cow::Borrowed(event),
+                age: timestamp.elapsed(),
+            },
+            Event::UserEvent(event) => Self::UserEvent(event),
+            Event::Suspended => Self::Suspended,
+            Event::Resumed => Self::Resumed,
+            Event::AboutToWait => Self::AboutToWait,
+            Event::LoopExiting => Self::LoopExiting,
+            Event::MemoryWarning => Self::MemoryWarning,
+        }
+    }
+}
+
+impl<T: 'static> RecordedEvent<'_, T> {
+    /// Convert the event back and pass it to the `event_handler`.
+    fn dispatch<F>(
+        self,
+        windows: &HashMap<u64, WindowId>,
+        target: &EventLoopWindowTarget,
+        event_handler: &mut F,
+    ) where
+        F: FnMut(Event<T>, &EventLoopWindowTarget),
+    {
+        let event = match self {
+            Self::NewEvents(cause) => {
+                let now = Instant::now();
+                Event::NewEvents(match cause {
+                    RecordedStartCause::ResumeTimeReached => StartCause::ResumeTimeReached {
+                        start: now,
+                        requested_resume: now,
+                    },
+                    RecordedStartCause::WaitCancelled => StartCause::WaitCancelled {
+                        start: now,
+                        requested_resume: None,
+                    },
+                    RecordedStartCause::Poll => StartCause::Poll,
+                    RecordedStartCause::Init => StartCause::Init,
+                })
+            }
+            Self::WindowEvent {
+                window_id,
+                event,
+                age,
+            } => Event::WindowEvent {
+                window_id: windows
+                    .get(&window_id)
+                    .copied()
+                    .unwrap_or_else(|| window_id.into()),
+                event: event.into_owned(),
+                timestamp: timestamp(age),
+            },
+            Self::DeviceEvent {
+                device_id,
+                event,
+                age,
+            } => Event::DeviceEvent {
+                device_id,
+                event: event.into_owned(),
+                timestamp: timestamp(age),
+            },
+            Self::UserEvent(event) => Event::UserEvent(event),
+            Self::Suspended => Event::Suspended,
+            Self::Resumed => Event::Resumed,
+            Self::AboutToWait => Event::AboutToWait,
+            Self::LoopExiting => Event::LoopExiting,
+            Self::MemoryWarning => Event::MemoryWarning,
+        };
+
+        event_handler(event, target);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum RecordedStartCause {
+    ResumeTimeReached,
+    WaitCancelled,
+    Poll,
+    Init,
+}
+
+/// The timestamp of the replayed event, which preserves the `age` it had when it was recorded.
+fn timestamp(age: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(age).unwrap_or(now)
+}
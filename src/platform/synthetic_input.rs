@@ -0,0 +1,119 @@
+//! Injection of synthetic input, which is useful for automated UI tests.
+//!
+//! The injected input is dispatched to the given window during the next loop iteration through
+//! the same code path the input coming from the display server takes. This means that keys are
+//! translated with the active keymap, update the state of the modifiers, which is reported with
+//! [`WindowEvent::ModifiersChanged`].
+//!
+//! The input never reaches the display server, thus other applications and the system input
+//! method don't observe it.
+//!
+//! ## Platform-specific
+//!
+//! - **X11 / Wayland:** The synthetic keys which are still held when the window loses focus are
+//!   released with [`WindowEvent::KeyboardInput`] events marked as `is_synthetic`.
+//! - **Headless:** The keys stay held when the focus moves, since the focus only moves when the
+//!   application asks for it.
+//!
+//! [`WindowEvent::ModifiersChanged`]: crate::event::WindowEvent::ModifiersChanged
+//! [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+
+use crate::{
+    dpi::PhysicalPosition,
+    error::ExternalError,
+    event::{ElementState, Force, Ime, MouseButton, TouchPhase},
+    event_loop::EventLoopWindowTarget,
+    keyboard::PhysicalKey,
+    platform_impl::EventLoopWindowTarget as PlatformEventLoopWindowTarget,
+    window::WindowId,
+};
+
+/// Input which could be injected into a window with
+/// [`EventLoopWindowTargetExtSyntheticInput::inject_input`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntheticInput {
+    /// A key was pressed or released, results in [`WindowEvent::KeyboardInput`].
+    ///
+    /// The logical key and the text are derived from the `physical_key` using the active keymap
+    /// and the current state of the modifiers.
+    ///
+    /// [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+    KeyboardInput {
+        physical_key: PhysicalKey,
+        state: ElementState,
+        /// Whether this is a repeat of the key, which is still held.
+        ///
+        /// Repeats don't change the state of the modifiers.
+        repeat: bool,
+    },
+
+    /// The cursor has moved, results in [`WindowEvent::CursorMoved`].
+    ///
+    /// [`WindowEvent::CursorMoved`]: crate::event::WindowEvent::CursorMoved
+    CursorMoved { position: PhysicalPosition<f64> },
+
+    /// A mouse button was pressed or released, results in [`WindowEvent::MouseInput`].
+    ///
+    /// [`WindowEvent::MouseInput`]: crate::event::WindowEvent::MouseInput
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+
+    /// A touch event, results in [`WindowEvent::Touch`].
+    ///
+    /// [`WindowEvent::Touch`]: crate::event::WindowEvent::Touch
+    Touch {
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+        force: Option<Force>,
+        id: u64,
+    },
+
+    /// An input method event, results in [`WindowEvent::Ime`].
+    ///
+    /// [`WindowEvent::Ime`]: crate::event::WindowEvent::Ime
+    Ime(Ime),
+}
+
+/// Additional methods on [`EventLoopWindowTarget`] to inject synthetic input.
+pub trait EventLoopWindowTargetExtSyntheticInput {
+    /// Inject the `input` into the window, as if it was sent by the display server.
+    ///
+    /// The resulting events are delivered during the next loop iteration, after the events which
+    /// were already queued.
+    ///
+    /// Returns [`ExternalError::Os`] when the window doesn't exist or the keyboard input can't
+    /// be translated, e.g. because the `physical_key` has no scancode or there's no keymap.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Wayland:** Keyboard input requires a seat with the keyboard capability.
+    /// - **Headless:** Keys are translated with the default keymap, which could be changed with
+    ///   the `XKB_DEFAULT_*` environment variables.
+    fn inject_input(&self, window_id: WindowId, input: SyntheticInput)
+        -> Result<(), ExternalError>;
+}
+
+impl EventLoopWindowTargetExtSyntheticInput for EventLoopWindowTarget {
+    fn inject_input(
+        &self,
+        window_id: WindowId,
+        input: SyntheticInput,
+    ) -> Result<(), ExternalError> {
+        let result = match &self.p {
+            #[cfg(x11_platform)]
+            PlatformEventLoopWindowTarget::X(target) => target.inject_input(window_id.0, input),
+            #[cfg(wayland_platform)]
+            PlatformEventLoopWindowTarget::Wayland(target) => {
+                target.inject_input(window_id.0, input)
+            }
+            PlatformEventLoopWindowTarget::Headless(target) => {
+                target.inject_input(window_id.0, input)
+            }
+        };
+
+        result.map_err(|error| ExternalError::Os(os_error!(error)))
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     event_loop::{EventLoopBuilder, EventLoopWindowTarget},
     monitor::MonitorHandle,
+    platform::layer_shell::LayerShellAttributes,
     window::{Window, WindowBuilder},
 };
 
@@ -46,9 +47,35 @@ impl<T> EventLoopBuilderExtWayland for EventLoopBuilder<T> {
 }
 
 /// Additional methods on [`Window`] that are specific to Wayland.
-pub trait WindowExtWayland {}
+pub trait WindowExtWayland {
+    /// Set the window icon from the icon theme, such as `"text-editor"`, or use the one set with
+    /// [`Window::set_window_icon`] with `None`.
+    ///
+    /// The named icon is preferred to the pixels of the window icon, which are only used when
+    /// the compositor can't find it. It requires the `xdg_toplevel_icon_v1` protocol.
+    fn set_icon_name(&self, name: Option<&str>);
+
+    /// Set whether the child window, created as a `wl_subsurface` of its parent with
+    /// [`WindowBuilder::with_parent_window`], is synchronized with its parent.
+    ///
+    /// A synchronized subsurface shows what's drawn on it with the next commit of its parent,
+    /// so both surfaces are updated together. Otherwise, it's shown right away, which suits a
+    /// surface drawn by a separate renderer like a video player. The subsurfaces aren't
+    /// synchronized by default. It has no effect on the other windows.
+    fn set_subsurface_sync(&self, sync: bool);
+}
 
-impl WindowExtWayland for Window {}
+impl WindowExtWayland for Window {
+    #[inline]
+    fn set_icon_name(&self, name: Option<&str>) {
+        self.window.set_icon_name(name.map(ToOwned::to_owned))
+    }
+
+    #[inline]
+    fn set_subsurface_sync(&self, sync: bool) {
+        self.window.set_subsurface_sync(sync)
+    }
+}
 
 /// Additional methods on [`WindowBuilder`] that are specific to Wayland.
 pub trait WindowBuilderExtWayland {
@@ -60,6 +87,44 @@ pub trait WindowBuilderExtWayland {
     /// For details about application ID conventions, see the
     /// [Desktop Entry Spec](https://specifications.freedesktop.org/desktop-entry-spec/desktop-entry-spec-latest.html#desktop-file-id)
     fn with_name(self, general: impl Into<String>, instance: impl Into<String>) -> Self;
+
+    /// Build the window as a `zwlr_layer_surface_v1`, such as a panel or an overlay, placed by
+    /// the compositor against the edges of a monitor.
+    ///
+    /// The inner size of the window is ignored on the axes the surface is stretched on. The
+    /// window is closed with [`WindowEvent::CloseRequested`] when the compositor removes the
+    /// surface, such as when its monitor is disconnected. See [`layer_shell`] for the details.
+    ///
+    /// ```no_run
+    /// # use winit::dpi::LogicalSize;
+    /// # use winit::event_loop::EventLoop;
+    /// # use winit::window::WindowBuilder;
+    /// use winit::platform::layer_shell::{Layer, LayerAnchor, LayerShellAttributes};
+    /// use winit::platform::wayland::WindowBuilderExtWayland;
+    /// # let event_loop = EventLoop::new().unwrap();
+    /// let layer_shell = LayerShellAttributes::new(Layer::Top)
+    ///     .with_anchor(LayerAnchor::TOP | LayerAnchor::LEFT | LayerAnchor::RIGHT)
+    ///     .with_exclusive_zone(32);
+    /// let panel = WindowBuilder::new()
+    ///     .with_inner_size(LogicalSize::new(0, 32))
+    ///     .with_layer_shell(layer_shell)
+    ///     .build(&event_loop)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`WindowEvent::CloseRequested`]: crate::event::WindowEvent::CloseRequested
+    /// [`layer_shell`]: crate::platform::layer_shell
+    fn with_layer_shell(self, layer_shell: LayerShellAttributes) -> Self;
+
+    /// Build window with an icon from the icon theme, such as `"text-editor"`.
+    ///
+    /// See [`WindowExtWayland::set_icon_name`] for details.
+    fn with_icon_name(self, name: impl Into<String>) -> Self;
+
+    /// Build the child window as a subsurface synchronized with its parent.
+    ///
+    /// See [`WindowExtWayland::set_subsurface_sync`] for details.
+    fn with_subsurface_sync(self, sync: bool) -> Self;
 }
 
 impl WindowBuilderExtWayland for WindowBuilder {
@@ -71,6 +136,24 @@ impl WindowBuilderExtWayland for WindowBuilder {
         ));
         self
     }
+
+    #[inline]
+    fn with_layer_shell(mut self, layer_shell: LayerShellAttributes) -> Self {
+        self.window.platform_specific.layer_shell = Some(layer_shell);
+        self
+    }
+
+    #[inline]
+    fn with_icon_name(mut self, name: impl Into<String>) -> Self {
+        self.window.platform_specific.icon_name = Some(name.into());
+        self
+    }
+
+    #[inline]
+    fn with_subsurface_sync(mut self, sync: bool) -> Self {
+        self.window.platform_specific.subsurface_sync = sync;
+        self
+    }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to Wayland.
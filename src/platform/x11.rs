@@ -8,6 +8,7 @@ use crate::{
 };
 
 use crate::dpi::Size;
+use crate::platform::layer_shell::LayerShellAttributes;
 
 /// X window type. Maps directly to
 /// [`_NET_WM_WINDOW_TYPE`](https://specifications.freedesktop.org/wm-spec/wm-spec-1.5.html).
@@ -98,7 +99,7 @@ pub trait EventLoopWindowTargetExtX11 {
 impl EventLoopWindowTargetExtX11 for EventLoopWindowTarget {
     #[inline]
     fn is_x11(&self) -> bool {
-        !self.p.is_wayland()
+        self.p.is_x11()
     }
 }
 
@@ -185,6 +186,15 @@ pub trait WindowBuilderExtX11 {
     /// # Ok(()) }
     /// ```
     fn with_embed_parent_window(self, parent_window_id: XWindow) -> Self;
+
+    /// Build a dock placed against the edges of a monitor, reserving its exclusive zone with
+    /// `_NET_WM_STRUT_PARTIAL`.
+    ///
+    /// The window type is `Dock`, or `Desktop` on the background layer, unless it was set with
+    /// [`WindowBuilderExtX11::with_x11_window_type`]. See [`layer_shell`] for the details.
+    ///
+    /// [`layer_shell`]: crate::platform::layer_shell
+    fn with_layer_shell(self, layer_shell: LayerShellAttributes) -> Self;
 }
 
 impl WindowBuilderExtX11 for WindowBuilder {
@@ -232,6 +242,12 @@ impl WindowBuilderExtX11 for WindowBuilder {
         self.window.platform_specific.x11.embed_window = Some(parent_window_id);
         self
     }
+
+    #[inline]
+    fn with_layer_shell(mut self, layer_shell: LayerShellAttributes) -> Self {
+        self.window.platform_specific.layer_shell = Some(layer_shell);
+        self
+    }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to X11.
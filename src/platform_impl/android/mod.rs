@@ -27,7 +27,8 @@ use crate::{
     event_loop::{self, ControlFlow, DeviceEvents, EventLoopWindowTarget as RootELW},
     platform::pump_events::PumpStatus,
     window::{
-        self, CursorGrabMode, ImePurpose, ResizeDirection, Theme, WindowButtons, WindowLevel,
+        self, CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, WindowButtons,
+        WindowLevel,
     },
 };
 use crate::{error::EventLoopError, platform_impl::Fullscreen};
@@ -137,6 +138,7 @@ impl RedrawRequester {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyEventExtra {}
 
 pub struct EventLoop<T: 'static> {
@@ -236,6 +238,7 @@ impl<T: 'static> EventLoop<T> {
                         event::Event::WindowEvent {
                             window_id: window::WindowId(WindowId),
                             event: event::WindowEvent::Focused(true),
+                            timestamp: Instant::now(),
                         },
                         self.window_target(),
                     );
@@ -246,6 +249,7 @@ impl<T: 'static> EventLoop<T> {
                         event::Event::WindowEvent {
                             window_id: window::WindowId(WindowId),
                             event: event::WindowEvent::Focused(false),
+                            timestamp: Instant::now(),
                         },
                         self.window_target(),
                     );
@@ -266,6 +270,7 @@ impl<T: 'static> EventLoop<T> {
                                 )),
                                 scale_factor,
                             },
+                            timestamp: Instant::now(),
                         };
                         callback(event, self.window_target());
                     }
@@ -349,6 +354,7 @@ impl<T: 'static> EventLoop<T> {
                 let event = event::Event::WindowEvent {
                     window_id: window::WindowId(WindowId),
                     event: event::WindowEvent::Resized(size),
+                    timestamp: Instant::now(),
                 };
                 callback(event, self.window_target());
             }
@@ -359,6 +365,7 @@ impl<T: 'static> EventLoop<T> {
                 let event = event::Event::WindowEvent {
                     window_id: window::WindowId(WindowId),
                     event: event::WindowEvent::RedrawRequested,
+                    timestamp: Instant::now(),
                 };
                 callback(event, self.window_target());
             }
@@ -424,6 +431,7 @@ impl<T: 'static> EventLoop<T> {
                                 id: pointer.pointer_id() as u64,
                                 force: Some(Force::Normalized(pointer.pressure() as f64)),
                             }),
+                            timestamp: Instant::now(),
                         };
                         callback(event, self.window_target());
                     }
@@ -467,6 +475,7 @@ impl<T: 'static> EventLoop<T> {
                                 },
                                 is_synthetic: false,
                             },
+                            timestamp: Instant::now(),
                         };
                         callback(event, self.window_target());
                     }
@@ -873,6 +882,10 @@ impl Window {
 
     pub fn set_blur(&self, _blur: bool) {}
 
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {}
+
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {}
+
     pub fn set_visible(&self, _visibility: bool) {}
 
     pub fn is_visible(&self) -> Option<bool> {
@@ -927,6 +940,10 @@ impl Window {
 
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    pub fn set_ime_hints(&self, _hints: ImeHints) {}
+
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     pub fn focus_window(&self) {}
 
     pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
@@ -0,0 +1,172 @@
+//! Counting of the consecutive clicks for the backends, which don't get it from the system.
+
+#[cfg(not(web_platform))]
+use std::time::{Duration, Instant};
+
+#[cfg(web_platform)]
+use web_time::{Duration, Instant};
+
+use crate::dpi::PhysicalPosition;
+use crate::event::{ElementState, MouseButton};
+use crate::window::WindowId;
+
+/// The double-click interval used when the system doesn't provide one.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The double-click distance in pixels used when the system doesn't provide one.
+pub const DEFAULT_DISTANCE: f64 = 4.;
+
+#[derive(Debug, Clone, Copy)]
+struct Click {
+    window_id: WindowId,
+    button: MouseButton,
+    position: PhysicalPosition<f64>,
+    timestamp: Instant,
+    count: u32,
+}
+
+/// Assigns the click count to the mouse button presses and releases.
+#[derive(Debug)]
+pub struct ClickCounter {
+    /// The maximum time between the presses of a multi-click.
+    pub interval: Duration,
+    /// The maximum distance on either axis between the presses of a multi-click.
+    pub distance: f64,
+    last: Option<Click>,
+    /// The count of the buttons being held, reported on their release.
+    held: Vec<(WindowId, MouseButton, u32)>,
+}
+
+impl Default for ClickCounter {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTERVAL, DEFAULT_DISTANCE)
+    }
+}
+
+impl ClickCounter {
+    pub fn new(interval: Duration, distance: f64) -> Self {
+        Self {
+            interval,
+            distance,
+            last: None,
+            held: Vec::new(),
+        }
+    }
+
+    /// Register the press of the `button` and return its click count.
+    fn press(
+        &mut self,
+        window_id: WindowId,
+        button: MouseButton,
+        position: PhysicalPosition<f64>,
+        timestamp: Instant,
+    ) -> u32 {
+        let count = match self.last {
+            Some(last)
+                if last.window_id == window_id
+                    && last.button == button
+                    && timestamp.saturating_duration_since(last.timestamp) <= self.interval
+                    && (position.x - last.position.x).abs() <= self.distance
+                    && (position.y - last.position.y).abs() <= self.distance =>
+            {
+                last.count.saturating_add(1)
+            }
+            _ => 1,
+        };
+
+        self.last = Some(Click {
+            window_id,
+            button,
+            position,
+            timestamp,
+            count,
+        });
+
+        self.held
+            .retain(|&(id, held, _)| id != window_id || held != button);
+        self.held.push((window_id, button, count));
+
+        count
+    }
+
+    /// Register the release of the `button` and return the click count of the matching press.
+    fn release(&mut self, window_id: WindowId, button: MouseButton) -> u32 {
+        match self
+            .held
+            .iter()
+            .position(|&(id, held, _)| id == window_id && held == button)
+        {
+            Some(index) => self.held.swap_remove(index).2,
+            None => 1,
+        }
+    }
+
+    /// Register the press or release of the `button` and return its click count.
+    pub fn count(
+        &mut self,
+        window_id: WindowId,
+        state: ElementState,
+        button: MouseButton,
+        position: PhysicalPosition<f64>,
+        timestamp: Instant,
+    ) -> u32 {
+        match state {
+            ElementState::Pressed => self.press(window_id, button, position, timestamp),
+            ElementState::Released => self.release(window_id, button),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_id() -> WindowId {
+        WindowId::from(0)
+    }
+
+    #[test]
+    fn consecutive_clicks() {
+        let mut counter = ClickCounter::default();
+        let start = Instant::now();
+        let position = PhysicalPosition::new(10., 10.);
+
+        assert_eq!(
+            counter.press(window_id(), MouseButton::Left, position, start),
+            1
+        );
+        assert_eq!(counter.release(window_id(), MouseButton::Left), 1);
+
+        let time = start + Duration::from_millis(100);
+        assert_eq!(
+            counter.press(window_id(), MouseButton::Left, position, time),
+            2
+        );
+        assert_eq!(counter.release(window_id(), MouseButton::Left), 2);
+
+        // Small movement still counts.
+        let nearby = PhysicalPosition::new(12., 8.);
+        let time = start + Duration::from_millis(200);
+        assert_eq!(
+            counter.press(window_id(), MouseButton::Left, nearby, time),
+            3
+        );
+        assert_eq!(counter.release(window_id(), MouseButton::Left), 3);
+
+        // Another button starts a new sequence.
+        assert_eq!(
+            counter.press(window_id(), MouseButton::Right, nearby, time),
+            1
+        );
+        assert_eq!(counter.release(window_id(), MouseButton::Right), 1);
+
+        // So does the expiry of the interval and moving too far.
+        let time = time + DEFAULT_INTERVAL * 2;
+        assert_eq!(
+            counter.press(window_id(), MouseButton::Right, nearby, time),
+            1
+        );
+        let far = PhysicalPosition::new(100., 100.);
+        assert_eq!(counter.press(window_id(), MouseButton::Right, far, time), 1);
+    }
+}
@@ -0,0 +1,160 @@
+//! Conversion of the platform timestamps to `Instant`.
+
+use std::cell::Cell;
+
+#[cfg(not(web_platform))]
+use std::time::{Duration, Instant};
+
+#[cfg(web_platform)]
+use web_time::{Duration, Instant};
+
+use crate::event::Event;
+
+/// Keeps the timestamps of the events delivered by an event loop on the monotonic clock.
+///
+/// X11, Wayland and Windows timestamp the input with 32-bit millisecond counters, with an
+/// unspecified base on X11 and Wayland, which wrap around every ~49.7 days. The clock remembers a
+/// reference point, which pairs such a timestamp with the `Instant` it was received at, and
+/// converts the other timestamps relative to it. The reference point is moved whenever a timestamp
+/// would end up in the future, so the offset converges to the smallest observed delivery latency.
+///
+/// Since moving the reference point, timestamps arriving out of order, or events stamped with the
+/// time they were received at in between could otherwise yield an instant before the ones already
+/// returned, the instants are clamped to never decrease.
+#[derive(Debug, Default)]
+pub struct EventClock {
+    #[cfg(any(windows_platform, x11_platform, wayland_platform))]
+    reference: Cell<Option<(Instant, u32)>>,
+    /// The last instant returned.
+    last: Cell<Option<Instant>>,
+}
+
+impl EventClock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Convert the platform `time` in milliseconds to an `Instant`.
+    #[cfg(any(windows_platform, x11_platform, wayland_platform))]
+    pub fn instant(&self, time: u32) -> Instant {
+        self.clamp(self.convert(time))
+    }
+
+    /// Clamp the `instant` to not precede the instants already returned.
+    pub fn clamp(&self, instant: Instant) -> Instant {
+        let instant = self.last.get().map_or(instant, |last| instant.max(last));
+        self.last.set(Some(instant));
+        instant
+    }
+
+    /// Clamp the timestamp of the window or device `event` right before it's delivered, so the
+    /// timestamps never go backwards from one event to the next.
+    pub fn stamp<T>(&self, mut event: Event<T>) -> Event<T> {
+        if let Event::WindowEvent { timestamp, .. } | Event::DeviceEvent { timestamp, .. } =
+            &mut event
+        {
+            *timestamp = self.clamp(*timestamp);
+        }
+        event
+    }
+
+    #[cfg(any(windows_platform, x11_platform, wayland_platform))]
+    fn convert(&self, time: u32) -> Instant {
+        let now = Instant::now();
+        let (reference_instant, reference_time) = match self.reference.get() {
+            Some(reference) => reference,
+            None => {
+                self.reference.set(Some((now, time)));
+                return now;
+            }
+        };
+
+        // The timestamps from different devices could arrive slightly out of order.
+        let delta = time.wrapping_sub(reference_time) as i32;
+        let offset = Duration::from_millis(delta.unsigned_abs() as u64);
+        let instant = if delta >= 0 {
+            reference_instant.checked_add(offset)
+        } else {
+            reference_instant.checked_sub(offset)
+        };
+
+        match instant {
+            Some(instant) if instant <= now => instant,
+            _ => {
+                self.reference.set(Some((now, time)));
+                now
+            }
+        }
+    }
+}
+
+/// Convert the `age` of an event in seconds, as the difference between the current time and the
+/// timestamp of the event on another clock, to an `Instant`.
+#[cfg(any(macos_platform, web_platform))]
+pub fn instant_from_age(age: f64) -> Instant {
+    let now = Instant::now();
+    Duration::try_from_secs_f64(age)
+        .ok()
+        .and_then(|age| now.checked_sub(age))
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::event::WindowEvent;
+    use crate::window::WindowId;
+
+    #[test]
+    fn timestamps_are_monotonic() {
+        let clock = EventClock::new();
+        let first = clock.instant(5);
+
+        // The earlier timestamps are in the past, even when wrapping around.
+        assert_eq!(
+            first - clock.convert(u32::MAX - 10),
+            Duration::from_millis(16)
+        );
+
+        // But they don't precede the instants already returned.
+        assert_eq!(clock.instant(u32::MAX - 10), first);
+
+        // The timestamps are never in the future.
+        let later = clock.instant(1005);
+        assert!(later > first);
+        assert!(later <= Instant::now());
+
+        // The reference point has moved.
+        assert_eq!(later - clock.convert(5), Duration::from_millis(1000));
+
+        // The timestamps before the new reference point don't go backwards either.
+        assert_eq!(clock.instant(1000), later);
+        assert!(clock.instant(1010) >= later);
+    }
+
+    #[test]
+    fn received_events_are_clamped() {
+        let clock = EventClock::new();
+        let before = Instant::now();
+        let first = clock.instant(5);
+
+        // The events stamped with the time they were received at are clamped with the converted
+        // timestamps.
+        let event = |timestamp| Event::<()>::WindowEvent {
+            window_id: unsafe { WindowId::dummy() },
+            event: WindowEvent::Focused(true),
+            timestamp,
+        };
+        assert_eq!(clock.stamp(event(before)).timestamp(), Some(first));
+
+        let received = Instant::now() + Duration::from_millis(100);
+        assert_eq!(clock.stamp(event(received)).timestamp(), Some(received));
+        assert_eq!(clock.instant(6), received);
+
+        assert!(matches!(
+            clock.stamp(Event::<()>::AboutToWait),
+            Event::AboutToWait
+        ));
+    }
+}
@@ -764,6 +764,7 @@ pub fn handle_main_events_cleared(mtm: MainThreadMarker) {
             EventWrapper::StaticEvent(Event::WindowEvent {
                 window_id: RootWindowId(window.id()),
                 event: WindowEvent::RedrawRequested,
+                timestamp: Instant::now(),
             })
         })
         .collect();
@@ -798,6 +799,7 @@ fn handle_hidpi_proxy(event_handler: &mut Box<dyn EventHandler>, event: ScaleFac
             scale_factor,
             inner_size_writer: InnerSizeWriter::new(Arc::downgrade(&new_inner_size)),
         },
+        timestamp: Instant::now(),
     };
     event_handler.handle_nonuser_event(event);
     let (view, screen_frame) = get_view_and_screen_frame(&window);
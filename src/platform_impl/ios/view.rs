@@ -1,5 +1,6 @@
 #![allow(clippy::unnecessary_cast)]
 use std::cell::{Cell, RefCell};
+use std::time::Instant;
 
 use icrate::Foundation::{CGFloat, CGRect, MainThreadMarker, NSObject, NSObjectProtocol, NSSet};
 use objc2::rc::Id;
@@ -57,6 +58,7 @@ declare_class!(
                 EventWrapper::StaticEvent(Event::WindowEvent {
                     window_id: RootWindowId(window.id()),
                     event: WindowEvent::RedrawRequested,
+                    timestamp: Instant::now(),
                 }),
             );
             let _: () = unsafe { msg_send![super(self), drawRect: rect] };
@@ -92,6 +94,7 @@ declare_class!(
                 EventWrapper::StaticEvent(Event::WindowEvent {
                     window_id: RootWindowId(window.id()),
                     event: WindowEvent::Resized(size),
+                    timestamp: Instant::now(),
                 }),
             );
         }
@@ -143,6 +146,7 @@ declare_class!(
                     Event::WindowEvent {
                         window_id,
                         event: WindowEvent::Resized(size.to_physical(scale_factor)),
+                        timestamp: Instant::now(),
                     },
                 ))),
             );
@@ -191,6 +195,7 @@ declare_class!(
                     delta,
                     phase,
                 },
+                timestamp: Instant::now(),
             });
 
             let mtm = MainThreadMarker::new().unwrap();
@@ -207,6 +212,7 @@ declare_class!(
                     event: WindowEvent::DoubleTapGesture {
                         device_id: DEVICE_ID,
                     },
+                    timestamp: Instant::now(),
                 });
 
                 let mtm = MainThreadMarker::new().unwrap();
@@ -235,6 +241,7 @@ declare_class!(
                     delta: recognizer.velocity() as _,
                     phase,
                 },
+                timestamp: Instant::now(),
             });
 
             let mtm = MainThreadMarker::new().unwrap();
@@ -388,6 +395,7 @@ impl WinitView {
                     force,
                     phase,
                 }),
+                timestamp: Instant::now(),
             }));
         }
         let mtm = MainThreadMarker::new().unwrap();
@@ -585,6 +593,7 @@ declare_class!(
                 EventWrapper::StaticEvent(Event::WindowEvent {
                     window_id: RootWindowId(self.id()),
                     event: WindowEvent::Focused(true),
+                    timestamp: Instant::now(),
                 }),
             );
             let _: () = unsafe { msg_send![super(self), becomeKeyWindow] };
@@ -598,6 +607,7 @@ declare_class!(
                 EventWrapper::StaticEvent(Event::WindowEvent {
                     window_id: RootWindowId(self.id()),
                     event: WindowEvent::Focused(false),
+                    timestamp: Instant::now(),
                 }),
             );
             let _: () = unsafe { msg_send![super(self), resignKeyWindow] };
@@ -693,6 +703,7 @@ declare_class!(
                     events.push(EventWrapper::StaticEvent(Event::WindowEvent {
                         window_id: RootWindowId(window.id()),
                         event: WindowEvent::Destroyed,
+                        timestamp: Instant::now(),
                     }));
                 }
             }
@@ -723,6 +734,7 @@ impl WinitApplicationDelegate {
                 events.push(EventWrapper::StaticEvent(Event::WindowEvent {
                     window_id: RootWindowId(window.id()),
                     event: WindowEvent::Occluded(occluded),
+                    timestamp: Instant::now(),
                 }));
             }
         }
@@ -1,6 +1,7 @@
 #![allow(clippy::unnecessary_cast)]
 
 use std::collections::VecDeque;
+use std::time::Instant;
 
 use icrate::Foundation::{CGFloat, CGPoint, CGRect, CGSize, MainThreadBound, MainThreadMarker};
 use log::{debug, warn};
@@ -22,8 +23,8 @@ use crate::{
         app_state, monitor, EventLoopWindowTarget, Fullscreen, MonitorHandle,
     },
     window::{
-        CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType, WindowAttributes,
-        WindowButtons, WindowId as RootWindowId, WindowLevel,
+        CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+        WindowAttributes, WindowButtons, WindowId as RootWindowId, WindowLevel,
     },
 };
 
@@ -47,6 +48,14 @@ impl Inner {
         debug!("`Window::set_blur` is ignored on iOS")
     }
 
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {
+        debug!("`Window::set_idle_inhibit` is ignored on iOS")
+    }
+
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {
+        debug!("`Window::set_keyboard_shortcuts_inhibit` is ignored on iOS")
+    }
+
     pub fn set_visible(&self, visible: bool) {
         self.window.setHidden(!visible)
     }
@@ -298,10 +307,18 @@ impl Inner {
         warn!("`Window::set_ime_allowed` is ignored on iOS")
     }
 
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {
+        warn!("`Window::set_ime_surrounding_text` is ignored on iOS")
+    }
+
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {
         warn!("`Window::set_ime_allowed` is ignored on iOS")
     }
 
+    pub fn set_ime_hints(&self, _hints: ImeHints) {
+        warn!("`Window::set_ime_hints` is ignored on iOS")
+    }
+
     pub fn focus_window(&self) {
         warn!("`Window::set_focus` is ignored on iOS")
     }
@@ -479,6 +496,7 @@ impl Window {
                     Event::WindowEvent {
                         window_id,
                         event: WindowEvent::Resized(size.to_physical(scale_factor)),
+                        timestamp: Instant::now(),
                     },
                 ))),
             );
@@ -0,0 +1,136 @@
+//! Lookup of the double-click settings of the desktop.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::platform_impl::click_counter::{ClickCounter, DEFAULT_DISTANCE, DEFAULT_INTERVAL};
+
+/// The double-click settings, with `None` for the ones which aren't configured.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ClickSettings {
+    pub interval: Option<Duration>,
+    pub distance: Option<f64>,
+}
+
+impl ClickSettings {
+    /// Read the settings from the GTK `settings.ini` files.
+    ///
+    /// The user configuration takes precedence over the system one, and GTK 4 over GTK 3.
+    pub fn from_gtk() -> Self {
+        let mut settings = Self::default();
+
+        let user_dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+        let system_dirs = env::var("XDG_CONFIG_DIRS")
+            .ok()
+            .filter(|dirs| !dirs.is_empty())
+            .unwrap_or_else(|| String::from("/etc/xdg"));
+        let dirs = user_dir.into_iter().chain(
+            system_dirs
+                .split(':')
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute()),
+        );
+
+        for dir in dirs {
+            for version in ["gtk-4.0", "gtk-3.0"] {
+                if let Ok(contents) = fs::read_to_string(dir.join(version).join("settings.ini")) {
+                    settings = settings.or(Self::parse_gtk(&contents));
+                }
+            }
+        }
+
+        settings
+    }
+
+    fn parse_gtk(contents: &str) -> Self {
+        let mut settings = Self::default();
+        let mut in_settings = false;
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_settings = line == "[Settings]";
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if in_settings => (key.trim(), value.trim()),
+                _ => continue,
+            };
+
+            match key {
+                "gtk-double-click-time" => {
+                    settings.interval = value.parse().ok().map(Duration::from_millis)
+                }
+                "gtk-double-click-distance" => settings.distance = value.parse().ok(),
+                _ => (),
+            }
+        }
+
+        settings
+    }
+
+    /// Fill the missing settings from `other`.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            interval: self.interval.or(other.interval),
+            distance: self.distance.or(other.distance),
+        }
+    }
+
+    /// Apply the settings to the `counter`, with the defaults for the ones which aren't
+    /// configured.
+    pub fn apply(self, counter: &mut ClickCounter) {
+        counter.interval = self.interval.unwrap_or(DEFAULT_INTERVAL);
+        counter.distance = self.distance.unwrap_or(DEFAULT_DISTANCE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_gtk_settings() {
+        let contents = "\
+[Other]
+gtk-double-click-time=100
+
+[Settings]
+gtk-theme-name = Adwaita
+gtk-double-click-time = 250
+gtk-double-click-distance=8
+";
+        assert_eq!(
+            ClickSettings::parse_gtk(contents),
+            ClickSettings {
+                interval: Some(Duration::from_millis(250)),
+                distance: Some(8.),
+            }
+        );
+    }
+
+    #[test]
+    fn apply_resets_unconfigured_settings() {
+        let mut counter = ClickCounter::default();
+        ClickSettings {
+            interval: Some(Duration::from_millis(250)),
+            distance: Some(8.),
+        }
+        .apply(&mut counter);
+        assert_eq!(counter.interval, Duration::from_millis(250));
+        assert_eq!(counter.distance, 8.);
+
+        // A setting removed from the XSETTINGS goes back to the default.
+        ClickSettings {
+            interval: None,
+            distance: Some(2.),
+        }
+        .apply(&mut counter);
+        assert_eq!(counter.interval, DEFAULT_INTERVAL);
+        assert_eq!(counter.distance, 2.);
+    }
+}
@@ -0,0 +1,239 @@
+//! Fcitx 5, through its frontend on the session bus.
+
+use log::debug;
+use zbus::blocking::Connection;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{Message, MessageBuilder};
+
+use super::{push_segment, Display, KeyInput, Preedit, Signal, SurroundingText};
+use crate::event::PreeditStyle;
+use crate::window::{ImeHints, ImePurpose};
+
+pub const SERVICE: &str = "org.fcitx.Fcitx5";
+const INPUT_METHOD_PATH: &str = "/org/freedesktop/portal/inputmethod";
+const INPUT_METHOD_INTERFACE: &str = "org.fcitx.Fcitx.InputMethod1";
+const INPUT_CONTEXT_INTERFACE: &str = "org.fcitx.Fcitx.InputContext1";
+
+// The capabilities of the client, which also hold the hints about the text.
+const CAP_PREEDIT: u64 = 1 << 1;
+const CAP_PASSWORD: u64 = 1 << 3;
+const CAP_FORMATTED_PREEDIT: u64 = 1 << 4;
+const CAP_SURROUNDING_TEXT: u64 = 1 << 6;
+const CAP_EMAIL: u64 = 1 << 7;
+const CAP_DIGIT: u64 = 1 << 8;
+const CAP_UPPERCASE: u64 = 1 << 9;
+const CAP_LOWERCASE: u64 = 1 << 10;
+const CAP_URL: u64 = 1 << 12;
+const CAP_DIALABLE: u64 = 1 << 13;
+const CAP_NUMBER: u64 = 1 << 14;
+const CAP_SPELLCHECK: u64 = 1 << 16;
+const CAP_WORD_COMPLETION: u64 = 1 << 18;
+const CAP_UPPERCASE_WORDS: u64 = 1 << 19;
+const CAP_UPPERCASE_SENTENCES: u64 = 1 << 20;
+const CAP_ALPHA: u64 = 1 << 21;
+const CAP_NAME: u64 = 1 << 22;
+const CAP_RELATIVE_RECT: u64 = 1 << 24;
+const CAP_MULTILINE: u64 = 1 << 32;
+const CAP_SENSITIVE: u64 = 1 << 33;
+
+// The formats of the preedit text.
+const FORMAT_UNDERLINE: i32 = 1 << 3;
+const FORMAT_HIGHLIGHT: i32 = 1 << 4;
+
+pub struct InputContext {
+    connection: Connection,
+    path: OwnedObjectPath,
+    /// The capabilities of the client, without the hints about the text.
+    capability: u64,
+}
+
+impl InputContext {
+    pub fn new(connection: &Connection, display: Display, client_name: &str) -> zbus::Result<Self> {
+        let (display_name, capability) = match display {
+            Display::X11 => ("x11:", 0),
+            Display::Wayland => ("wayland:", CAP_RELATIVE_RECT),
+        };
+        let args = [("program", client_name), ("display", display_name)];
+        let (path, _uuid): (OwnedObjectPath, Vec<u8>) = connection
+            .call_method(
+                Some(SERVICE),
+                INPUT_METHOD_PATH,
+                Some(INPUT_METHOD_INTERFACE),
+                "CreateInputContext",
+                &(&args[..],),
+            )?
+            .body()?;
+        let mut context = Self {
+            connection: connection.clone(),
+            path,
+            capability: capability | CAP_PREEDIT | CAP_FORMATTED_PREEDIT | CAP_SURROUNDING_TEXT,
+        };
+
+        context.set_capability(0)?;
+
+        Ok(context)
+    }
+
+    fn set_capability(&mut self, hints: u64) -> zbus::Result<()> {
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "SetCapability",
+            &(self.capability | hints),
+        )?;
+        Ok(())
+    }
+}
+
+impl super::InputContext for InputContext {
+    fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    fn focus(&self, focused: bool) -> zbus::Result<()> {
+        let method = if focused { "FocusIn" } else { "FocusOut" };
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            method,
+            &(),
+        )?;
+        Ok(())
+    }
+
+    fn set_cursor_area(&self, area: [i32; 4]) -> zbus::Result<()> {
+        let [x, y, width, height] = area;
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "SetCursorRect",
+            &(x, y, width, height),
+        )?;
+        Ok(())
+    }
+
+    fn set_content_type(&mut self, purpose: ImePurpose, hints: ImeHints) -> zbus::Result<()> {
+        self.set_capability(content_capability(purpose, hints))
+    }
+
+    fn set_surrounding_text(&self, surrounding_text: &SurroundingText) -> zbus::Result<()> {
+        let (cursor, anchor) = surrounding_text.char_offsets();
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "SetSurroundingText",
+            &(surrounding_text.text.as_str(), cursor, anchor),
+        )?;
+        Ok(())
+    }
+
+    fn key_event_call(&self, key: &KeyInput) -> zbus::Result<Message> {
+        MessageBuilder::method_call(self.path.as_str(), "ProcessKeyEvent")?
+            .destination(SERVICE)?
+            .interface(INPUT_CONTEXT_INTERFACE)?
+            .build(&(key.keysym, key.keycode, key.state, !key.pressed, key.time))
+    }
+}
+
+impl Drop for InputContext {
+    fn drop(&mut self) {
+        let _ = self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "DestroyIC",
+            &(),
+        );
+    }
+}
+
+/// The capabilities holding the purpose and the hints of the text.
+fn content_capability(purpose: ImePurpose, hints: ImeHints) -> u64 {
+    let mut capability = match purpose {
+        ImePurpose::Password => CAP_PASSWORD,
+        ImePurpose::Alpha => CAP_ALPHA,
+        ImePurpose::Digits => CAP_DIGIT,
+        ImePurpose::Number => CAP_NUMBER,
+        ImePurpose::Phone => CAP_DIALABLE,
+        ImePurpose::Url => CAP_URL,
+        ImePurpose::Email => CAP_EMAIL,
+        ImePurpose::Name => CAP_NAME,
+        ImePurpose::Pin => CAP_DIGIT | CAP_PASSWORD,
+        // Fcitx has no purpose for the terminals, the dates and times.
+        ImePurpose::Normal
+        | ImePurpose::Terminal
+        | ImePurpose::Date
+        | ImePurpose::Time
+        | ImePurpose::DateTime => 0,
+    };
+
+    for (hint, hint_capability) in [
+        (ImeHints::COMPLETION, CAP_WORD_COMPLETION),
+        (ImeHints::SPELLCHECK, CAP_SPELLCHECK),
+        (ImeHints::AUTO_CAPITALIZATION, CAP_UPPERCASE_SENTENCES),
+        (ImeHints::LOWERCASE, CAP_LOWERCASE),
+        (ImeHints::UPPERCASE, CAP_UPPERCASE),
+        (ImeHints::TITLECASE, CAP_UPPERCASE_WORDS),
+        (ImeHints::HIDDEN_TEXT, CAP_PASSWORD),
+        (ImeHints::SENSITIVE_DATA, CAP_SENSITIVE),
+        (ImeHints::LATIN, CAP_ALPHA),
+        (ImeHints::MULTILINE, CAP_MULTILINE),
+    ] {
+        if hints.contains(hint) {
+            capability |= hint_capability;
+        }
+    }
+
+    capability
+}
+
+/// The preedit of its formatted strings, with the byte offset of the cursor.
+fn formatted_preedit(strings: Vec<(String, i32)>, cursor: i32) -> Preedit {
+    let mut preedit = Preedit::default();
+    for (string, format) in strings {
+        let style = PreeditStyle {
+            underline: format & FORMAT_UNDERLINE != 0,
+            highlight: format & FORMAT_HIGHLIGHT != 0,
+            selected: format & FORMAT_HIGHLIGHT != 0,
+            ..Default::default()
+        };
+        let start = preedit.text.len();
+        preedit.text.push_str(&string);
+        push_segment(&mut preedit.segments, start..preedit.text.len(), style);
+    }
+
+    // The cursor is hidden when it's negative.
+    preedit.cursor = usize::try_from(cursor)
+        .ok()
+        .filter(|&cursor| preedit.text.is_char_boundary(cursor));
+
+    preedit
+}
+
+/// Parse the signal of an input context.
+pub fn parse_signal(message: &Message) -> Option<Signal> {
+    if message.interface()?.as_str() != INPUT_CONTEXT_INTERFACE {
+        return None;
+    }
+
+    match message.member()?.as_str() {
+        "CommitString" => Some(Signal::Commit(message.body().ok()?)),
+        "UpdateFormattedPreedit" => {
+            let (strings, cursor) = message.body().ok()?;
+            Some(Signal::Preedit(formatted_preedit(strings, cursor), true))
+        }
+        "DeleteSurroundingText" => {
+            let (offset, chars): (i32, u32) = message.body().ok()?;
+            Some(Signal::DeleteSurrounding { offset, chars })
+        }
+        "ForwardKey" => {
+            debug!("The keys forwarded by Fcitx aren't supported");
+            None
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,397 @@
+//! IBus, through its portal on the session bus.
+
+use std::collections::HashMap;
+
+use log::debug;
+use zbus::blocking::Connection;
+use zbus::zvariant::{OwnedObjectPath, StructureBuilder, Value};
+use zbus::{Message, MessageBuilder};
+
+use super::{byte_offset, push_segment, Display, KeyInput, Preedit, Signal, SurroundingText};
+use crate::event::{PreeditSegment, PreeditStyle};
+use crate::window::{ImeHints, ImePurpose};
+
+pub const SERVICE: &str = "org.freedesktop.portal.IBus";
+const PORTAL_PATH: &str = "/org/freedesktop/IBus";
+const PORTAL_INTERFACE: &str = "org.freedesktop.IBus.Portal";
+const SERVICE_INTERFACE: &str = "org.freedesktop.IBus.Service";
+const INPUT_CONTEXT_INTERFACE: &str = "org.freedesktop.IBus.InputContext";
+
+// The capabilities of the client.
+const CAP_PREEDIT_TEXT: u32 = 1 << 0;
+const CAP_FOCUS: u32 = 1 << 3;
+const CAP_SURROUNDING_TEXT: u32 = 1 << 5;
+
+/// The modifier of the key releases.
+const RELEASE_MASK: u32 = 1 << 30;
+
+// The types of the text attributes.
+const ATTR_TYPE_UNDERLINE: u32 = 1;
+const ATTR_TYPE_BACKGROUND: u32 = 3;
+const ATTR_UNDERLINE_NONE: u32 = 0;
+
+// The purposes of the text.
+const PURPOSE_FREE_FORM: u32 = 0;
+const PURPOSE_ALPHA: u32 = 1;
+const PURPOSE_DIGITS: u32 = 2;
+const PURPOSE_NUMBER: u32 = 3;
+const PURPOSE_PHONE: u32 = 4;
+const PURPOSE_URL: u32 = 5;
+const PURPOSE_EMAIL: u32 = 6;
+const PURPOSE_NAME: u32 = 7;
+const PURPOSE_PASSWORD: u32 = 8;
+const PURPOSE_PIN: u32 = 9;
+const PURPOSE_TERMINAL: u32 = 10;
+
+// The hints about the text.
+const HINT_SPELLCHECK: u32 = 1 << 0;
+const HINT_WORD_COMPLETION: u32 = 1 << 2;
+const HINT_LOWERCASE: u32 = 1 << 3;
+const HINT_UPPERCASE_CHARS: u32 = 1 << 4;
+const HINT_UPPERCASE_WORDS: u32 = 1 << 5;
+const HINT_UPPERCASE_SENTENCES: u32 = 1 << 6;
+const HINT_PRIVATE: u32 = 1 << 11;
+
+pub struct InputContext {
+    connection: Connection,
+    path: OwnedObjectPath,
+    display: Display,
+}
+
+impl InputContext {
+    pub fn new(connection: &Connection, display: Display, client_name: &str) -> zbus::Result<Self> {
+        let path = connection
+            .call_method(
+                Some(SERVICE),
+                PORTAL_PATH,
+                Some(PORTAL_INTERFACE),
+                "CreateInputContext",
+                &client_name,
+            )?
+            .body()?;
+        let context = Self {
+            connection: connection.clone(),
+            path,
+            display,
+        };
+
+        context.connection.call_method(
+            Some(SERVICE),
+            context.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "SetCapabilities",
+            &(CAP_PREEDIT_TEXT | CAP_FOCUS | CAP_SURROUNDING_TEXT),
+        )?;
+
+        Ok(context)
+    }
+}
+
+impl super::InputContext for InputContext {
+    fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    fn focus(&self, focused: bool) -> zbus::Result<()> {
+        let method = if focused { "FocusIn" } else { "FocusOut" };
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            method,
+            &(),
+        )?;
+        Ok(())
+    }
+
+    fn set_cursor_area(&self, area: [i32; 4]) -> zbus::Result<()> {
+        let method = match self.display {
+            Display::X11 => "SetCursorLocation",
+            Display::Wayland => "SetCursorLocationRelative",
+        };
+        let [x, y, width, height] = area;
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            method,
+            &(x, y, width, height),
+        )?;
+        Ok(())
+    }
+
+    fn set_content_type(&mut self, purpose: ImePurpose, hints: ImeHints) -> zbus::Result<()> {
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "SetContentType",
+            &content_type(purpose, hints),
+        )?;
+        Ok(())
+    }
+
+    fn set_surrounding_text(&self, surrounding_text: &SurroundingText) -> zbus::Result<()> {
+        let (cursor, anchor) = surrounding_text.char_offsets();
+        self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(INPUT_CONTEXT_INTERFACE),
+            "SetSurroundingText",
+            &(text_value(&surrounding_text.text), cursor, anchor),
+        )?;
+        Ok(())
+    }
+
+    fn key_event_call(&self, key: &KeyInput) -> zbus::Result<Message> {
+        let state = if key.pressed {
+            key.state
+        } else {
+            key.state | RELEASE_MASK
+        };
+        MessageBuilder::method_call(self.path.as_str(), "ProcessKeyEvent")?
+            .destination(SERVICE)?
+            .interface(INPUT_CONTEXT_INTERFACE)?
+            .build(&(key.keysym, key.keycode.saturating_sub(8), state))
+    }
+}
+
+impl Drop for InputContext {
+    fn drop(&mut self) {
+        let _ = self.connection.call_method(
+            Some(SERVICE),
+            self.path.as_str(),
+            Some(SERVICE_INTERFACE),
+            "Destroy",
+            &(),
+        );
+    }
+}
+
+/// The IBus purpose and hints of the text.
+fn content_type(purpose: ImePurpose, hints: ImeHints) -> (u32, u32) {
+    let purpose = match purpose {
+        ImePurpose::Normal => PURPOSE_FREE_FORM,
+        ImePurpose::Password => PURPOSE_PASSWORD,
+        ImePurpose::Terminal => PURPOSE_TERMINAL,
+        ImePurpose::Alpha => PURPOSE_ALPHA,
+        ImePurpose::Digits => PURPOSE_DIGITS,
+        ImePurpose::Number => PURPOSE_NUMBER,
+        ImePurpose::Phone => PURPOSE_PHONE,
+        ImePurpose::Url => PURPOSE_URL,
+        ImePurpose::Email => PURPOSE_EMAIL,
+        ImePurpose::Name => PURPOSE_NAME,
+        ImePurpose::Pin => PURPOSE_PIN,
+        // IBus has no purpose for the dates and times.
+        ImePurpose::Date | ImePurpose::Time | ImePurpose::DateTime => PURPOSE_FREE_FORM,
+    };
+
+    let mut content_hints = 0;
+    for (hint, content_hint) in [
+        (ImeHints::SPELLCHECK, HINT_SPELLCHECK),
+        (ImeHints::COMPLETION, HINT_WORD_COMPLETION),
+        (ImeHints::LOWERCASE, HINT_LOWERCASE),
+        (ImeHints::UPPERCASE, HINT_UPPERCASE_CHARS),
+        (ImeHints::TITLECASE, HINT_UPPERCASE_WORDS),
+        (ImeHints::AUTO_CAPITALIZATION, HINT_UPPERCASE_SENTENCES),
+        (ImeHints::SENSITIVE_DATA, HINT_PRIVATE),
+    ] {
+        if hints.contains(hint) {
+            content_hints |= content_hint;
+        }
+    }
+
+    (purpose, content_hints)
+}
+
+/// The `IBusText` of the `text`, without attributes.
+fn text_value(text: &str) -> Value<'_> {
+    let attributes = StructureBuilder::new()
+        .add_field("IBusAttrList")
+        .add_field(HashMap::<&str, Value<'_>>::new())
+        .add_field(Vec::<Value<'_>>::new())
+        .build();
+    StructureBuilder::new()
+        .add_field("IBusText")
+        .add_field(HashMap::<&str, Value<'_>>::new())
+        .add_field(text)
+        .append_field(Value::Value(Box::new(attributes.into())))
+        .build()
+        .into()
+}
+
+/// The value inside of the variants.
+fn unwrap_variant<'a>(mut value: &'a Value<'a>) -> &'a Value<'a> {
+    while let Value::Value(inner) = value {
+        value = inner;
+    }
+    value
+}
+
+/// Parse the `IBusText`, with the styling of its attributes.
+fn parse_text(value: &Value<'_>) -> Option<(String, Vec<PreeditSegment>)> {
+    let fields = match unwrap_variant(value) {
+        Value::Structure(text) => text.fields(),
+        _ => return None,
+    };
+    let text = match fields.get(2)? {
+        Value::Str(text) => text.as_str().to_owned(),
+        _ => return None,
+    };
+
+    let attributes = match fields.get(3).map(unwrap_variant) {
+        Some(Value::Structure(attributes)) => match attributes.fields().get(2) {
+            Some(Value::Array(attributes)) => attributes.get(),
+            _ => &[],
+        },
+        _ => &[],
+    };
+
+    // The attributes may overlap, so the styles of the characters are combined first.
+    let mut styles = vec![PreeditStyle::default(); text.chars().count()];
+    for attribute in attributes {
+        let (kind, value, start, end) = match unwrap_variant(attribute) {
+            Value::Structure(attribute) => match attribute.fields() {
+                [_, _, Value::U32(kind), Value::U32(value), Value::U32(start), Value::U32(end)] => {
+                    (*kind, *value, *start as usize, *end as usize)
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        let end = end.min(styles.len());
+        for style in styles.get_mut(start..end).into_iter().flatten() {
+            match kind {
+                ATTR_TYPE_UNDERLINE if value != ATTR_UNDERLINE_NONE => style.underline = true,
+                // The background marks the clause being converted.
+                ATTR_TYPE_BACKGROUND => {
+                    style.highlight = true;
+                    style.selected = true;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut segments = Vec::new();
+    for ((offset, chr), style) in text.char_indices().zip(styles) {
+        push_segment(&mut segments, offset..offset + chr.len_utf8(), style);
+    }
+
+    Some((text, segments))
+}
+
+/// Parse the signal of an input context.
+pub fn parse_signal(message: &Message) -> Option<Signal> {
+    if message.interface()?.as_str() != INPUT_CONTEXT_INTERFACE {
+        return None;
+    }
+
+    match message.member()?.as_str() {
+        "CommitText" => {
+            let text: Value<'_> = message.body().ok()?;
+            let (text, _) = parse_text(&text)?;
+            Some(Signal::Commit(text))
+        }
+        member @ ("UpdatePreeditText" | "UpdatePreeditTextWithMode") => {
+            let (text, cursor, visible): (Value<'_>, u32, bool) = if member == "UpdatePreeditText" {
+                message.body().ok()?
+            } else {
+                let (text, cursor, visible, _mode): (Value<'_>, u32, bool, u32) =
+                    message.body().ok()?;
+                (text, cursor, visible)
+            };
+            let (text, segments) = parse_text(&text)?;
+            let cursor = Some(byte_offset(&text, cursor as usize));
+            Some(Signal::Preedit(
+                Preedit {
+                    text,
+                    cursor,
+                    segments,
+                },
+                visible,
+            ))
+        }
+        "ShowPreeditText" => Some(Signal::PreeditVisible(true)),
+        "HidePreeditText" => Some(Signal::PreeditVisible(false)),
+        "DeleteSurroundingText" => {
+            let (offset, chars): (i32, u32) = message.body().ok()?;
+            Some(Signal::DeleteSurrounding { offset, chars })
+        }
+        "ForwardKeyEvent" => {
+            debug!("The keys forwarded by IBus aren't supported");
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_attributes_are_parsed() {
+        assert_eq!(
+            parse_text(&text_value("変換")),
+            Some((String::from("変換"), Vec::new()))
+        );
+
+        let attribute = |kind: u32, value: u32, start: u32, end: u32| {
+            Value::Value(Box::new(
+                StructureBuilder::new()
+                    .add_field("IBusAttribute")
+                    .add_field(HashMap::<&str, Value<'_>>::new())
+                    .add_field(kind)
+                    .add_field(value)
+                    .add_field(start)
+                    .add_field(end)
+                    .build()
+                    .into(),
+            ))
+        };
+        let attributes = StructureBuilder::new()
+            .add_field("IBusAttrList")
+            .add_field(HashMap::<&str, Value<'_>>::new())
+            .add_field(vec![
+                attribute(ATTR_TYPE_UNDERLINE, 1, 0, 3),
+                attribute(ATTR_TYPE_BACKGROUND, 0xffffff, 1, 3),
+            ])
+            .build();
+        let text: Value<'_> = StructureBuilder::new()
+            .add_field("IBusText")
+            .add_field(HashMap::<&str, Value<'_>>::new())
+            .add_field("へんかん")
+            .append_field(Value::Value(Box::new(attributes.into())))
+            .build()
+            .into();
+
+        let underline = PreeditStyle {
+            underline: true,
+            ..Default::default()
+        };
+        let selected = PreeditStyle {
+            underline: true,
+            highlight: true,
+            selected: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_text(&text),
+            Some((
+                String::from("へんかん"),
+                vec![
+                    PreeditSegment {
+                        range: 0..3,
+                        style: underline,
+                    },
+                    PreeditSegment {
+                        range: 3..9,
+                        style: selected,
+                    },
+                ]
+            ))
+        );
+    }
+}
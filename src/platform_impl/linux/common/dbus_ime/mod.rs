@@ -0,0 +1,1020 @@
+//! The input methods on the D-Bus session bus, IBus and Fcitx 5.
+//!
+//! The calls to the input method are made from a worker thread, so a hung input method doesn't
+//! block the event loop, and the signals of the input contexts are read on a listener thread.
+//! The key events are the only calls the event loop waits for, up to [`KEY_EVENT_TIMEOUT`].
+//! Once a key timed out, it's reported as not handled, so the text the input method commits or
+//! deletes for it afterwards is dropped, as the listener sees it before the reply to the key.
+
+mod fcitx;
+mod ibus;
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::names::BusName;
+use zbus::{Message, MessageBuilder, MessageType};
+
+use crate::event::{Ime, PreeditSegment, PreeditStyle};
+use crate::platform::dbus_ime::DBusImeBackend;
+use crate::platform_impl::common::xkb_state::ModifiersState;
+use crate::platform_impl::WindowId;
+use crate::window::{ImeHints, ImePurpose};
+
+/// How long the event loop waits for the input method to process a key.
+const KEY_EVENT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The display server of the windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Display {
+    /// The cursor areas are in the coordinates of the root window.
+    X11,
+    /// The cursor areas are relative to the window.
+    #[cfg_attr(not(wayland_platform), allow(dead_code))]
+    Wayland,
+}
+
+/// A key event, as sent to the input method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyInput {
+    pub keysym: u32,
+    /// The X11 keycode, which is the evdev one offset by 8.
+    pub keycode: u32,
+    /// The X11 modifier mask.
+    pub state: u32,
+    pub pressed: bool,
+    /// The timestamp of the event, in milliseconds.
+    pub time: u32,
+}
+
+impl KeyInput {
+    pub fn new(
+        keysym: u32,
+        keycode: u32,
+        modifiers: ModifiersState,
+        pressed: bool,
+        time: u32,
+    ) -> Self {
+        Self {
+            keysym,
+            keycode,
+            state: modifier_mask(modifiers),
+            pressed,
+            time,
+        }
+    }
+}
+
+/// The X11 modifier mask of the `modifiers`, which both input methods use.
+fn modifier_mask(modifiers: ModifiersState) -> u32 {
+    let mut mask = 0;
+    for (pressed, bit) in [
+        (modifiers.shift, 0),
+        (modifiers.caps_lock, 1),
+        (modifiers.ctrl, 2),
+        (modifiers.alt, 3),
+        (modifiers.num_lock, 4),
+        (modifiers.logo, 6),
+    ] {
+        if pressed {
+            mask |= 1 << bit;
+        }
+    }
+    mask
+}
+
+/// The text around the cursor, with the byte offsets of the cursor and the selection anchor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SurroundingText {
+    text: String,
+    cursor: usize,
+    anchor: usize,
+}
+
+impl SurroundingText {
+    /// The character offsets of the cursor and the anchor, which the input methods use.
+    fn char_offsets(&self) -> (u32, u32) {
+        let chars = |offset: usize| self.text[..offset].chars().count() as u32;
+        (chars(self.cursor), chars(self.anchor))
+    }
+}
+
+/// The composing text, with the byte offset of its cursor.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Preedit {
+    text: String,
+    cursor: Option<usize>,
+    segments: Vec<PreeditSegment>,
+}
+
+/// The signals of the input contexts, common to the input methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Signal {
+    Preedit(Preedit, bool),
+    /// Show or hide the preedit, without changing it.
+    PreeditVisible(bool),
+    Commit(String),
+    /// Delete the characters, relative to the cursor.
+    DeleteSurrounding {
+        offset: i32,
+        chars: u32,
+    },
+}
+
+/// The input context of a window, in one of the input methods.
+trait InputContext: Send {
+    /// The object path of the context, on which it sends its signals.
+    fn path(&self) -> &str;
+
+    fn focus(&self, focused: bool) -> zbus::Result<()>;
+
+    fn set_cursor_area(&self, area: [i32; 4]) -> zbus::Result<()>;
+
+    fn set_content_type(&mut self, purpose: ImePurpose, hints: ImeHints) -> zbus::Result<()>;
+
+    fn set_surrounding_text(&self, surrounding_text: &SurroundingText) -> zbus::Result<()>;
+
+    /// The call processing the key, which replies `true` when the input method handled the key.
+    fn key_event_call(&self, key: &KeyInput) -> zbus::Result<Message>;
+}
+
+/// The input method service the event loop talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    IBus,
+    Fcitx5,
+}
+
+impl Service {
+    fn name(self) -> &'static str {
+        match self {
+            Service::IBus => ibus::SERVICE,
+            Service::Fcitx5 => fcitx::SERVICE,
+        }
+    }
+
+    /// Pick the input method for the `backend` which is running, or can be started, on the bus.
+    fn pick(connection: &Connection, backend: DBusImeBackend) -> zbus::Result<Self> {
+        let candidates: &[Service] = match backend {
+            DBusImeBackend::IBus => &[Service::IBus],
+            DBusImeBackend::Fcitx5 => &[Service::Fcitx5],
+            DBusImeBackend::Auto if prefers_fcitx() => &[Service::Fcitx5, Service::IBus],
+            DBusImeBackend::Auto => &[Service::IBus, Service::Fcitx5],
+        };
+
+        let dbus = DBusProxy::new(connection)?;
+        let activatable = dbus.list_activatable_names()?;
+        for &service in candidates {
+            let name = BusName::try_from(service.name())?;
+            if activatable.iter().any(|activatable| *activatable == name)
+                || dbus.name_has_owner(name)?
+            {
+                return Ok(service);
+            }
+        }
+
+        Err(zbus::Error::Failure(String::from(
+            "no input method is running on the session bus",
+        )))
+    }
+
+    fn create_context(
+        self,
+        connection: &Connection,
+        display: Display,
+    ) -> zbus::Result<Box<dyn InputContext>> {
+        let client_name = client_name();
+        Ok(match self {
+            Service::IBus => Box::new(ibus::InputContext::new(connection, display, &client_name)?),
+            Service::Fcitx5 => {
+                Box::new(fcitx::InputContext::new(connection, display, &client_name)?)
+            }
+        })
+    }
+
+    fn parse_signal(self, message: &Message) -> Option<Signal> {
+        match self {
+            Service::IBus => ibus::parse_signal(message),
+            Service::Fcitx5 => fcitx::parse_signal(message),
+        }
+    }
+}
+
+/// Whether the environment configures Fcitx as the input method.
+fn prefers_fcitx() -> bool {
+    ["GTK_IM_MODULE", "QT_IM_MODULE", "XMODIFIERS"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .any(|value| value.to_lowercase().contains("fcitx"))
+}
+
+/// The name of the application, as shown by the input method.
+fn client_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| String::from("winit"))
+}
+
+/// The state shared with the listener thread.
+#[derive(Debug, Default)]
+struct Shared {
+    /// The windows of the input contexts, by their object paths.
+    windows: Mutex<HashMap<String, WindowId>>,
+    /// The text around the cursor, to convert the characters the input method deletes.
+    surrounding_texts: Mutex<HashMap<WindowId, SurroundingText>>,
+    /// Whether the event loop dropped the input method.
+    stopped: AtomicBool,
+    /// The serials of the keys sent to the input method and of their calls, until the listener
+    /// sees the replies to the calls.
+    keys_in_flight: Mutex<Vec<(u64, u32)>>,
+    /// The serial of the last key the event loop stopped waiting for.
+    key_timed_out: AtomicU64,
+}
+
+impl Shared {
+    /// Whether the input method is processing the key the event loop stopped waiting for.
+    ///
+    /// The replies to the earlier keys were seen, so the signals are sent for the first key.
+    fn processing_timed_out_key(&self) -> bool {
+        let key_timed_out = self.key_timed_out.load(Ordering::Relaxed);
+        self.keys_in_flight
+            .lock()
+            .unwrap()
+            .first()
+            .is_some_and(|&(key, _)| key == key_timed_out)
+    }
+}
+
+/// The requests to the worker thread.
+enum Request {
+    /// Focus the input context of the window, which is created on the first focus, or unfocus it.
+    Focus(WindowId, bool),
+    CursorArea(WindowId, [i32; 4]),
+    ContentType(WindowId, ImePurpose, ImeHints),
+    SurroundingText(WindowId, SurroundingText),
+    /// Process the key, replying with its serial.
+    Key(WindowId, u64, KeyInput),
+    Remove(WindowId),
+}
+
+/// The IME state of a window, as seen by the event loop.
+#[derive(Debug, Default, Clone, Copy)]
+struct WindowState {
+    allowed: bool,
+    focused: bool,
+}
+
+impl WindowState {
+    fn active(self) -> bool {
+        self.allowed && self.focused
+    }
+}
+
+/// The client of the input method on D-Bus.
+#[derive(Debug)]
+pub struct DBusIme {
+    requests: Sender<Request>,
+    key_replies: Receiver<(u64, bool)>,
+    /// The serial of the last key sent to the input method.
+    key_serial: u64,
+    /// Whether the input method didn't process the last key in time.
+    key_pending: bool,
+    windows: HashMap<WindowId, WindowState>,
+    shared: Arc<Shared>,
+}
+
+impl DBusIme {
+    /// Connect to the input method on the session bus.
+    ///
+    /// The `sink` gets the [`Ime`] events of the windows from the listener thread, except for
+    /// [`Ime::Enabled`] and [`Ime::Disabled`].
+    pub fn new<F>(backend: DBusImeBackend, display: Display, sink: F) -> zbus::Result<Self>
+    where
+        F: Fn(WindowId, Ime) + Send + 'static,
+    {
+        let connection = Connection::session()?;
+        let service = Service::pick(&connection, backend)?;
+        Self::with_connection(connection, service, display, sink)
+    }
+
+    fn with_connection<F>(
+        connection: Connection,
+        service: Service,
+        display: Display,
+        sink: F,
+    ) -> zbus::Result<Self>
+    where
+        F: Fn(WindowId, Ime) + Send + 'static,
+    {
+        let shared = Arc::new(Shared::default());
+
+        // The iterator must exist before the first call, to not miss any signal.
+        let messages = MessageIterator::from(&connection);
+        let listener_shared = Arc::clone(&shared);
+        thread::Builder::new()
+            .name(String::from("winit dbus-ime listener"))
+            .spawn(move || listen(messages, service, &listener_shared, sink))?;
+
+        let (requests, requests_receiver) = mpsc::channel();
+        let (key_replies_sender, key_replies) = mpsc::channel();
+        let mut worker = Worker {
+            connection,
+            service,
+            display,
+            contexts: HashMap::new(),
+            shared: Arc::clone(&shared),
+            key_replies: key_replies_sender,
+        };
+        thread::Builder::new()
+            .name(String::from("winit dbus-ime worker"))
+            .spawn(move || worker.run(requests_receiver))?;
+
+        Ok(Self {
+            requests,
+            key_replies,
+            key_serial: 0,
+            key_pending: false,
+            windows: HashMap::new(),
+            shared,
+        })
+    }
+
+    /// Allow the IME input for the window, returning `false` when it already was.
+    pub fn set_allowed(&mut self, window: WindowId, allowed: bool) -> bool {
+        let mut changed = false;
+        self.update_window(window, |state| {
+            changed = state.allowed != allowed;
+            state.allowed = allowed;
+        });
+        changed
+    }
+
+    /// Mark that the window has the keyboard focus.
+    pub fn set_focus(&mut self, window: WindowId, focused: bool) {
+        self.update_window(window, |state| state.focused = focused);
+    }
+
+    fn update_window(&mut self, window: WindowId, update: impl FnOnce(&mut WindowState)) {
+        let state = self.windows.entry(window).or_default();
+        let was_active = state.active();
+        update(state);
+        if state.active() != was_active {
+            let _ = self.requests.send(Request::Focus(window, state.active()));
+        }
+    }
+
+    /// Set the area of the cursor, as `[x, y, width, height]`.
+    pub fn set_cursor_area(&self, window: WindowId, area: [i32; 4]) {
+        let _ = self.requests.send(Request::CursorArea(window, area));
+    }
+
+    pub fn set_content_type(&self, window: WindowId, purpose: ImePurpose, hints: ImeHints) {
+        let _ = self
+            .requests
+            .send(Request::ContentType(window, purpose, hints));
+    }
+
+    /// Set the text around the cursor, with the byte offsets of the cursor and the anchor.
+    pub fn set_surrounding_text(
+        &self,
+        window: WindowId,
+        text: String,
+        cursor: usize,
+        anchor: usize,
+    ) {
+        let surrounding_text = SurroundingText {
+            text,
+            cursor,
+            anchor,
+        };
+        self.shared
+            .surrounding_texts
+            .lock()
+            .unwrap()
+            .insert(window, surrounding_text.clone());
+        let _ = self
+            .requests
+            .send(Request::SurroundingText(window, surrounding_text));
+    }
+
+    /// Destroy the input context of the window.
+    pub fn remove_window(&mut self, window: WindowId) {
+        self.windows.remove(&window);
+        self.shared
+            .surrounding_texts
+            .lock()
+            .unwrap()
+            .remove(&window);
+        let _ = self.requests.send(Request::Remove(window));
+    }
+
+    /// Send the key to the input method, returning `true` when it handled the key.
+    ///
+    /// Only the keys of the windows which are focused and allow the IME are sent.
+    pub fn process_key(&mut self, window: WindowId, key: KeyInput) -> bool {
+        if !self
+            .windows
+            .get(&window)
+            .is_some_and(|state| state.active())
+        {
+            return false;
+        }
+
+        // Don't wait for the input method again until it replies to the key which timed out.
+        if self.key_pending {
+            while let Ok((serial, _)) = self.key_replies.try_recv() {
+                self.key_pending &= serial != self.key_serial;
+            }
+            if self.key_pending {
+                return false;
+            }
+        }
+
+        self.key_serial += 1;
+        if self
+            .requests
+            .send(Request::Key(window, self.key_serial, key))
+            .is_err()
+        {
+            return false;
+        }
+
+        loop {
+            match self.key_replies.recv_timeout(KEY_EVENT_TIMEOUT) {
+                Ok((serial, handled)) if serial == self.key_serial => return handled,
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => {
+                    warn!("The input method didn't process the key in time");
+                    self.key_pending = true;
+                    self.shared
+                        .key_timed_out
+                        .store(self.key_serial, Ordering::Relaxed);
+                    return false;
+                }
+                Err(RecvTimeoutError::Disconnected) => return false,
+            }
+        }
+    }
+}
+
+/// The IME state of a window, as seen by the worker.
+struct WorkerContext {
+    /// The input context, created when the window is focused for the first time.
+    context: Option<Box<dyn InputContext>>,
+    cursor_area: Option<[i32; 4]>,
+    content_type: (ImePurpose, ImeHints),
+    surrounding_text: Option<SurroundingText>,
+}
+
+impl Default for WorkerContext {
+    fn default() -> Self {
+        Self {
+            context: None,
+            cursor_area: None,
+            content_type: (ImePurpose::Normal, ImeHints::empty()),
+            surrounding_text: None,
+        }
+    }
+}
+
+/// The thread making the calls to the input method.
+struct Worker {
+    connection: Connection,
+    service: Service,
+    display: Display,
+    contexts: HashMap<WindowId, WorkerContext>,
+    shared: Arc<Shared>,
+    key_replies: Sender<(u64, bool)>,
+}
+
+impl Worker {
+    fn run(&mut self, requests: Receiver<Request>) {
+        for request in requests {
+            if let Err(err) = self.handle(request) {
+                warn!("Failed to talk to the input method: {err}");
+            }
+        }
+
+        // The event loop is gone, destroy the input contexts and stop the listener.
+        self.shared.stopped.store(true, Ordering::Relaxed);
+        self.contexts.clear();
+        self.wake_listener();
+    }
+
+    fn handle(&mut self, request: Request) -> zbus::Result<()> {
+        match request {
+            Request::Focus(window, focused) => {
+                let state = self.contexts.entry(window).or_default();
+                let context = match (&mut state.context, focused) {
+                    (Some(context), _) => context,
+                    (None, false) => return Ok(()),
+                    (context @ None, true) => {
+                        let created = self
+                            .service
+                            .create_context(&self.connection, self.display)?;
+                        self.shared
+                            .windows
+                            .lock()
+                            .unwrap()
+                            .insert(created.path().to_owned(), window);
+                        context.insert(created)
+                    }
+                };
+
+                context.focus(focused)?;
+                if focused {
+                    let (purpose, hints) = state.content_type;
+                    context.set_content_type(purpose, hints)?;
+                    if let Some(surrounding_text) = state.surrounding_text.as_ref() {
+                        context.set_surrounding_text(surrounding_text)?;
+                    }
+                    if let Some(area) = state.cursor_area {
+                        context.set_cursor_area(area)?;
+                    }
+                }
+            }
+            Request::CursorArea(window, area) => {
+                let state = self.contexts.entry(window).or_default();
+                state.cursor_area = Some(area);
+                if let Some(context) = state.context.as_ref() {
+                    context.set_cursor_area(area)?;
+                }
+            }
+            Request::ContentType(window, purpose, hints) => {
+                let state = self.contexts.entry(window).or_default();
+                state.content_type = (purpose, hints);
+                if let Some(context) = state.context.as_mut() {
+                    context.set_content_type(purpose, hints)?;
+                }
+            }
+            Request::SurroundingText(window, surrounding_text) => {
+                let state = self.contexts.entry(window).or_default();
+                if let Some(context) = state.context.as_ref() {
+                    context.set_surrounding_text(&surrounding_text)?;
+                }
+                state.surrounding_text = Some(surrounding_text);
+            }
+            Request::Key(window, serial, key) => {
+                let handled = match self.contexts.get(&window).and_then(|s| s.context.as_ref()) {
+                    Some(context) => context
+                        .key_event_call(&key)
+                        .and_then(|call| self.call_key_event(serial, call)),
+                    None => Ok(false),
+                };
+                let _ = self
+                    .key_replies
+                    .send((serial, *handled.as_ref().unwrap_or(&false)));
+                handled?;
+            }
+            Request::Remove(window) => {
+                if let Some(context) = self.contexts.remove(&window).and_then(|s| s.context) {
+                    self.shared.windows.lock().unwrap().remove(context.path());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Make the `call` processing the key with the `serial`, returning whether it was handled.
+    ///
+    /// The key is in flight from before the call is sent, so the listener can't miss its reply.
+    fn call_key_event(&self, serial: u64, mut call: Message) -> zbus::Result<bool> {
+        let replies = MessageIterator::from(&self.connection);
+        let call_serial = self.connection.inner().assign_serial_num(&mut call)?;
+        let keys_in_flight = &self.shared.keys_in_flight;
+        keys_in_flight.lock().unwrap().push((serial, call_serial));
+        if let Err(err) = self.connection.send_message(call) {
+            keys_in_flight
+                .lock()
+                .unwrap()
+                .retain(|&(_, call)| call != call_serial);
+            return Err(err);
+        }
+
+        for reply in replies {
+            let reply = reply?;
+            if reply.reply_serial() != Some(call_serial) {
+                continue;
+            }
+            return match reply.message_type() {
+                MessageType::Error => Err(reply.into()),
+                _ => reply.body(),
+            };
+        }
+        Err(zbus::Error::Failure(String::from(
+            "the connection closed before the reply to the key",
+        )))
+    }
+
+    /// Make the peer reply to a ping, so the listener gets a message and sees that it's stopped.
+    fn wake_listener(&self) {
+        let mut builder = match MessageBuilder::method_call("/org/freedesktop/DBus", "Ping") {
+            Ok(builder) => builder,
+            Err(_) => return,
+        };
+        if self.connection.is_bus() {
+            builder = match builder.destination("org.freedesktop.DBus") {
+                Ok(builder) => builder,
+                Err(_) => return,
+            };
+        }
+        if let Ok(message) = builder
+            .interface("org.freedesktop.DBus.Peer")
+            .and_then(|builder| builder.build(&()))
+        {
+            let _ = self.connection.send_message(message);
+        }
+    }
+}
+
+/// Read the signals of the input contexts, sending the IME events of their windows to the `sink`.
+fn listen<F>(messages: MessageIterator, service: Service, shared: &Shared, sink: F)
+where
+    F: Fn(WindowId, Ime),
+{
+    // The last preedit of the windows, and whether it's visible.
+    let mut preedits: HashMap<WindowId, (Preedit, bool)> = HashMap::new();
+
+    for message in messages {
+        if shared.stopped.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let message = match message {
+            Ok(message) => message,
+            Err(_) => break,
+        };
+        match message.message_type() {
+            // The signals after the reply to a key are sent for the next one.
+            MessageType::MethodReturn | MessageType::Error => {
+                if let Some(reply_serial) = message.reply_serial() {
+                    shared
+                        .keys_in_flight
+                        .lock()
+                        .unwrap()
+                        .retain(|&(_, call)| call != reply_serial);
+                }
+                continue;
+            }
+            MessageType::Signal => (),
+            _ => continue,
+        }
+
+        let window = match message
+            .path()
+            .and_then(|path| shared.windows.lock().unwrap().get(path.as_str()).copied())
+        {
+            Some(window) => window,
+            None => continue,
+        };
+
+        let (preedit, visible) = preedits.entry(window).or_default();
+        match service.parse_signal(&message) {
+            Some(Signal::Commit(_) | Signal::DeleteSurrounding { .. })
+                if shared.processing_timed_out_key() =>
+            {
+                warn!("Dropped the text the input method changed for a key which timed out");
+            }
+            Some(Signal::Preedit(new_preedit, new_visible)) => {
+                *preedit = new_preedit;
+                *visible = new_visible;
+                send_preedit(&sink, window, preedit, *visible);
+            }
+            Some(Signal::PreeditVisible(new_visible)) if *visible != new_visible => {
+                *visible = new_visible;
+                send_preedit(&sink, window, preedit, *visible);
+            }
+            Some(Signal::Commit(text)) => {
+                *preedit = Preedit::default();
+                sink(window, Ime::Preedit(String::new(), None));
+                sink(window, Ime::Commit(text));
+            }
+            Some(Signal::DeleteSurrounding { offset, chars }) => {
+                let deleted = shared
+                    .surrounding_texts
+                    .lock()
+                    .unwrap()
+                    .get(&window)
+                    .and_then(|surrounding_text| {
+                        delete_surrounding_bytes(surrounding_text, offset, chars)
+                    });
+                match deleted {
+                    Some((before_bytes, after_bytes)) => sink(
+                        window,
+                        Ime::DeleteSurrounding {
+                            before_bytes,
+                            after_bytes,
+                        },
+                    ),
+                    None => warn!("The input method deleted text outside of the surrounding text"),
+                }
+            }
+            Some(Signal::PreeditVisible(_)) | None => (),
+        }
+    }
+}
+
+fn send_preedit<F>(sink: &F, window: WindowId, preedit: &Preedit, visible: bool)
+where
+    F: Fn(WindowId, Ime),
+{
+    if !visible || preedit.text.is_empty() {
+        sink(window, Ime::Preedit(String::new(), None));
+        return;
+    }
+
+    let cursor = preedit.cursor.map(|cursor| (cursor, cursor));
+    sink(window, Ime::Preedit(preedit.text.clone(), cursor));
+    if !preedit.segments.is_empty() {
+        sink(window, Ime::PreeditSegments(preedit.segments.clone()));
+    }
+}
+
+/// The bytes to delete before and after the cursor, for the `chars` characters starting at
+/// `offset` characters from the cursor.
+///
+/// Returns `None` when the deleted characters don't include the cursor.
+fn delete_surrounding_bytes(
+    surrounding_text: &SurroundingText,
+    offset: i32,
+    chars: u32,
+) -> Option<(usize, usize)> {
+    let start = offset as i64;
+    let end = start + chars as i64;
+    if start > 0 || end < 0 {
+        return None;
+    }
+
+    let (before, after) = surrounding_text.text.split_at(surrounding_text.cursor);
+    let before_bytes = before
+        .chars()
+        .rev()
+        .take(-start as usize)
+        .map(char::len_utf8)
+        .sum();
+    let after_bytes = after.chars().take(end as usize).map(char::len_utf8).sum();
+    Some((before_bytes, after_bytes))
+}
+
+/// The byte offset of the character at `chars` in the `text`, clamped to its end.
+fn byte_offset(text: &str, chars: usize) -> usize {
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Add the styled `range` to the `segments`, merging it with the last one when they're adjacent
+/// and styled the same.
+fn push_segment(
+    segments: &mut Vec<PreeditSegment>,
+    range: std::ops::Range<usize>,
+    style: PreeditStyle,
+) {
+    if range.is_empty() || style == PreeditStyle::default() {
+        return;
+    }
+
+    match segments.last_mut() {
+        Some(last) if last.range.end == range.start && last.style == style => {
+            last.range.end = range.end;
+        }
+        _ => segments.push(PreeditSegment { range, style }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use zbus::blocking::ConnectionBuilder;
+    use zbus::zvariant::OwnedObjectPath;
+    use zbus::{dbus_interface, Guid, ObjectServer, SignalContext};
+
+    use super::*;
+
+    #[test]
+    fn deleted_characters_are_converted_to_bytes() {
+        let surrounding_text = SurroundingText {
+            text: String::from("añb€c"),
+            cursor: 3,
+            anchor: 3,
+        };
+
+        assert_eq!(
+            delete_surrounding_bytes(&surrounding_text, -1, 1),
+            Some((2, 0))
+        );
+        assert_eq!(
+            delete_surrounding_bytes(&surrounding_text, -2, 4),
+            Some((3, 4))
+        );
+        assert_eq!(
+            delete_surrounding_bytes(&surrounding_text, -9, 99),
+            Some((3, 5))
+        );
+        assert_eq!(delete_surrounding_bytes(&surrounding_text, 1, 1), None);
+        assert_eq!(delete_surrounding_bytes(&surrounding_text, -3, 1), None);
+    }
+
+    const XK_A: u32 = 0x61;
+    const XK_RETURN: u32 = 0xff0d;
+    const XK_BACKSPACE: u32 = 0xff08;
+    /// A key the mock input method commits late, once the client stopped waiting for it.
+    const XK_TAB: u32 = 0xff09;
+
+    struct MockInputMethod {
+        /// Processes the `XK_TAB` key once the test stopped waiting for it.
+        late_key: Arc<Mutex<Receiver<()>>>,
+    }
+
+    #[dbus_interface(name = "org.fcitx.Fcitx.InputMethod1")]
+    impl MockInputMethod {
+        async fn create_input_context(
+            &self,
+            #[zbus(object_server)] server: &ObjectServer,
+            _args: Vec<(String, String)>,
+        ) -> (OwnedObjectPath, Vec<u8>) {
+            let path = "/org/freedesktop/portal/inputcontext/1";
+            let context = MockInputContext {
+                late_key: self.late_key.clone(),
+            };
+            server.at(path, context).await.unwrap();
+            (OwnedObjectPath::try_from(path).unwrap(), vec![0; 16])
+        }
+    }
+
+    struct MockInputContext {
+        late_key: Arc<Mutex<Receiver<()>>>,
+    }
+
+    #[dbus_interface(name = "org.fcitx.Fcitx.InputContext1")]
+    impl MockInputContext {
+        fn focus_in(&self) {}
+
+        fn focus_out(&self) {}
+
+        fn set_capability(&self, _capability: u64) {}
+
+        fn set_cursor_rect(&self, _x: i32, _y: i32, _width: i32, _height: i32) {}
+
+        fn set_surrounding_text(&self, _text: &str, _cursor: u32, _anchor: u32) {}
+
+        #[dbus_interface(name = "DestroyIC")]
+        fn destroy_ic(&self) {}
+
+        async fn process_key_event(
+            &self,
+            #[zbus(signal_context)] context: SignalContext<'_>,
+            keysym: u32,
+            _keycode: u32,
+            _state: u32,
+            is_release: bool,
+            _time: u32,
+        ) -> bool {
+            if is_release {
+                return false;
+            }
+
+            match keysym {
+                XK_A => Self::update_formatted_preedit(&context, vec![("あ", 1 << 3)], 3)
+                    .await
+                    .unwrap(),
+                XK_RETURN => Self::commit_string(&context, "あ").await.unwrap(),
+                XK_BACKSPACE => Self::delete_surrounding_text(&context, -1, 1)
+                    .await
+                    .unwrap(),
+                XK_TAB => {
+                    self.late_key.lock().unwrap().recv().unwrap();
+                    Self::commit_string(&context, "\t").await.unwrap();
+                }
+                _ => return false,
+            }
+            true
+        }
+
+        #[dbus_interface(signal)]
+        async fn update_formatted_preedit(
+            context: &SignalContext<'_>,
+            preedit: Vec<(&str, i32)>,
+            cursor: i32,
+        ) -> zbus::Result<()>;
+
+        #[dbus_interface(signal)]
+        async fn commit_string(context: &SignalContext<'_>, text: &str) -> zbus::Result<()>;
+
+        #[dbus_interface(signal)]
+        async fn delete_surrounding_text(
+            context: &SignalContext<'_>,
+            offset: i32,
+            chars: u32,
+        ) -> zbus::Result<()>;
+    }
+
+    #[test]
+    fn fcitx_input_context_sends_ime_events() {
+        let (server, client) = UnixStream::pair().unwrap();
+        let (process_late_key, late_key) = mpsc::channel();
+        let input_method = MockInputMethod {
+            late_key: Arc::new(Mutex::new(late_key)),
+        };
+        let server = thread::spawn(move || {
+            ConnectionBuilder::unix_stream(server)
+                .server(&Guid::generate())
+                .p2p()
+                .serve_at("/org/freedesktop/portal/inputmethod", input_method)
+                .unwrap()
+                .build()
+                .unwrap()
+        });
+        let connection = ConnectionBuilder::unix_stream(client)
+            .p2p()
+            .build()
+            .unwrap();
+        let _server = server.join().unwrap();
+
+        let (sender, events) = mpsc::channel();
+        let mut ime = DBusIme::with_connection(
+            connection,
+            Service::Fcitx5,
+            Display::X11,
+            move |window, event| sender.send((window, event)).unwrap(),
+        )
+        .unwrap();
+
+        let window = WindowId::from(1);
+        let key = |keysym| KeyInput {
+            keysym,
+            keycode: 0,
+            state: 0,
+            pressed: true,
+            time: 0,
+        };
+        let next_event = || events.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // The keys aren't sent until the IME is allowed on the focused window.
+        ime.set_focus(window, true);
+        assert!(!ime.process_key(window, key(XK_A)));
+        ime.set_allowed(window, true);
+
+        assert!(ime.process_key(window, key(XK_A)));
+        assert_eq!(
+            next_event(),
+            (window, Ime::Preedit(String::from("あ"), Some((3, 3))))
+        );
+        assert_eq!(
+            next_event(),
+            (
+                window,
+                Ime::PreeditSegments(vec![PreeditSegment {
+                    range: 0..3,
+                    style: PreeditStyle {
+                        underline: true,
+                        ..Default::default()
+                    },
+                }])
+            )
+        );
+
+        assert!(ime.process_key(window, key(XK_RETURN)));
+        assert_eq!(next_event(), (window, Ime::Preedit(String::new(), None)));
+        assert_eq!(next_event(), (window, Ime::Commit(String::from("あ"))));
+
+        ime.set_surrounding_text(window, String::from("aあ"), 4, 4);
+        assert!(ime.process_key(window, key(XK_BACKSPACE)));
+        assert_eq!(
+            next_event(),
+            (
+                window,
+                Ime::DeleteSurrounding {
+                    before_bytes: 3,
+                    after_bytes: 0,
+                }
+            )
+        );
+
+        assert!(!ime.process_key(window, key(0x62)));
+        let mut released = key(XK_A);
+        released.pressed = false;
+        assert!(!ime.process_key(window, released));
+
+        // The text of a key which timed out isn't committed, since the key was reported.
+        assert!(!ime.process_key(window, key(XK_TAB)));
+        process_late_key.send(()).unwrap();
+        // The keys aren't sent until the input method replied to the one which timed out.
+        while !ime.process_key(window, key(XK_RETURN)) {
+            thread::yield_now();
+        }
+        assert_eq!(next_event(), (window, Ime::Preedit(String::new(), None)));
+        assert_eq!(next_event(), (window, Ime::Commit(String::from("あ"))));
+    }
+}
@@ -0,0 +1,92 @@
+//! The files dropped on the windows, offered as a `text/uri-list`.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    str::Utf8Error,
+};
+
+use percent_encoding::percent_decode;
+
+/// The MIME type of the dropped files.
+pub const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+#[derive(Debug)]
+pub enum DndDataParseError {
+    EmptyData,
+    InvalidUtf8(Utf8Error),
+    HostnameSpecified(String),
+    UnexpectedProtocol(String),
+    UnresolvablePath(io::Error),
+}
+
+impl From<Utf8Error> for DndDataParseError {
+    fn from(e: Utf8Error) -> Self {
+        DndDataParseError::InvalidUtf8(e)
+    }
+}
+
+impl From<io::Error> for DndDataParseError {
+    fn from(e: io::Error) -> Self {
+        DndDataParseError::UnresolvablePath(e)
+    }
+}
+
+/// Parse the paths of the files in the `text/uri-list` data.
+pub fn parse_uri_list(data: &[u8]) -> Result<Vec<PathBuf>, DndDataParseError> {
+    if !data.is_empty() {
+        let mut path_list = Vec::new();
+        let decoded = percent_decode(data).decode_utf8()?.into_owned();
+        for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
+            // The format is specified as protocol://host/path
+            // However, it's typically simply protocol:///path
+            let path_str = if uri.starts_with("file://") {
+                let path_str = uri.replace("file://", "");
+                if !path_str.starts_with('/') {
+                    // A hostname is specified
+                    // Supporting this case is beyond the scope of my mental health
+                    return Err(DndDataParseError::HostnameSpecified(path_str));
+                }
+                path_str
+            } else {
+                // Only the file protocol is supported
+                return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
+            };
+
+            let path = Path::new(&path_str).canonicalize()?;
+            path_list.push(path);
+        }
+        Ok(path_list)
+    } else {
+        Err(DndDataParseError::EmptyData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uri_list_is_parsed() {
+        let root = Path::new("/").canonicalize().unwrap();
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let data = format!(
+            "file:///\r\nfile://{}\r\n",
+            dir.display().to_string().replace(' ', "%20")
+        );
+        assert_eq!(parse_uri_list(data.as_bytes()).unwrap(), [root, dir]);
+
+        assert!(matches!(
+            parse_uri_list(b""),
+            Err(DndDataParseError::EmptyData)
+        ));
+        assert!(matches!(
+            parse_uri_list(b"file://host/path\r\n"),
+            Err(DndDataParseError::HostnameSpecified(_))
+        ));
+        assert!(matches!(
+            parse_uri_list(b"https://example.com/\r\n"),
+            Err(DndDataParseError::UnexpectedProtocol(_))
+        ));
+    }
+}
@@ -1,2 +1,8 @@
+pub mod click_settings;
+#[cfg(dbus_ime)]
+pub mod dbus_ime;
+pub mod dnd;
 pub mod keymap;
+#[cfg(dbus_settings)]
+pub mod settings_portal;
 pub mod xkb_state;
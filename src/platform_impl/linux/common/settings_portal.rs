@@ -0,0 +1,138 @@
+//! The double-click settings of the desktop, read from the settings portal on D-Bus.
+//!
+//! GNOME and KDE don't write the GTK `settings.ini`, but expose their settings through
+//! `org.freedesktop.portal.Settings`, which also signals when they change.
+
+use std::thread;
+use std::time::Duration;
+
+use calloop::channel::Sender;
+use log::warn;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::{MatchRule, MessageType};
+
+use super::click_settings::ClickSettings;
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+const INTERFACE: &str = "org.freedesktop.portal.Settings";
+
+/// The settings holding the double-click time in milliseconds, by order of preference.
+const DOUBLE_CLICK_TIME: [(&str, &str); 2] = [
+    ("org.gnome.desktop.peripherals.mouse", "double-click"),
+    ("org.kde.kdeglobals.KDE", "DoubleClickInterval"),
+];
+
+/// Read the double-click settings from the portal, sending them to the `sender` from a thread
+/// whenever they change.
+///
+/// Returns `None` when the portal doesn't have the settings.
+pub fn watch_click_settings(sender: Sender<ClickSettings>) -> zbus::Result<Option<ClickSettings>> {
+    let connection = Connection::session()?;
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(INTERFACE)?
+        .member("SettingChanged")?
+        .build();
+    // Listen before reading, so no change is missed in between.
+    let signals = MessageIterator::for_match_rule(rule, &connection, None)?;
+
+    let mut settings = ClickSettings::default();
+    for (namespace, key) in DOUBLE_CLICK_TIME {
+        if let Some(value) = read(&connection, namespace, key)? {
+            settings.interval = interval(&value);
+            break;
+        }
+    }
+    if settings == ClickSettings::default() {
+        return Ok(None);
+    }
+
+    thread::Builder::new()
+        .name(String::from("winit settings portal"))
+        .spawn(move || {
+            for message in signals {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(_) => break,
+                };
+                let (namespace, key, value) = match message.body::<(String, String, OwnedValue)>() {
+                    Ok(body) => body,
+                    Err(_) => continue,
+                };
+                if !DOUBLE_CLICK_TIME.contains(&(namespace.as_str(), key.as_str())) {
+                    continue;
+                }
+
+                settings.interval = interval(&value);
+                // The event loop is gone.
+                if sender.send(settings).is_err() {
+                    break;
+                }
+            }
+        })
+        .map_err(|err| zbus::Error::InputOutput(err.into()))?;
+
+    Ok(Some(settings))
+}
+
+/// Read the setting, `None` when the portal doesn't have it.
+fn read(connection: &Connection, namespace: &str, key: &str) -> zbus::Result<Option<OwnedValue>> {
+    match connection.call_method(
+        Some(DESTINATION),
+        PATH,
+        Some(INTERFACE),
+        "Read",
+        &(namespace, key),
+    ) {
+        Ok(reply) => reply.body().map(Some),
+        Err(zbus::Error::MethodError(name, _, _))
+            if name.as_str() == "org.freedesktop.portal.Error.NotFound" =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The double-click time in the setting `value`, which KDE stores as a string.
+fn interval(value: &Value<'_>) -> Option<Duration> {
+    let millis = match value {
+        // `Read` wraps the value in another variant.
+        Value::Value(value) => return interval(value),
+        Value::I32(millis) => u64::try_from(*millis).ok(),
+        Value::U32(millis) => Some(u64::from(*millis)),
+        Value::Str(millis) => millis.parse().ok(),
+        _ => None,
+    };
+    match millis {
+        Some(millis) if millis > 0 => Some(Duration::from_millis(millis)),
+        _ => {
+            warn!("Invalid double-click time in the settings portal: {value:?}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_click_time_is_parsed() {
+        let ms = Duration::from_millis;
+        assert_eq!(interval(&Value::from(400i32)), Some(ms(400)));
+        assert_eq!(interval(&Value::from(250u32)), Some(ms(250)));
+        assert_eq!(interval(&Value::from("300")), Some(ms(300)));
+        assert_eq!(
+            interval(&Value::Value(Box::new(Value::from(400i32)))),
+            Some(ms(400))
+        );
+
+        assert_eq!(interval(&Value::from(-1i32)), None);
+        assert_eq!(interval(&Value::from(0u32)), None);
+        assert_eq!(interval(&Value::from("fast")), None);
+        assert_eq!(interval(&Value::from(true)), None);
+    }
+}
@@ -1,7 +1,7 @@
 use std::convert::TryInto;
 use std::env;
-use std::ffi::CString;
-use std::os::raw::c_char;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
 use std::os::unix::ffi::OsStringExt;
 use std::ptr;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -17,7 +17,7 @@ use {memmap2::MmapOptions, std::os::unix::io::OwnedFd};
 #[cfg(feature = "x11")]
 use {x11_dl::xlib_xcb::xcb_connection_t, xkbcommon_dl::x11::xkbcommon_x11_handle};
 
-use crate::event::KeyEvent;
+use crate::event::{KeyEvent, KeyboardLayout};
 use crate::platform_impl::common::keymap;
 use crate::platform_impl::KeyEventExtra;
 use crate::{
@@ -38,6 +38,35 @@ static XKBCH: Lazy<&'static XkbCommonCompose> = Lazy::new(xkbcommon_compose_hand
 #[cfg(feature = "x11")]
 static XKBXH: Lazy<&'static ffi::x11::XkbCommonX11> = Lazy::new(xkbcommon_x11_handle);
 
+type XkbKeymapLayoutGetName =
+    unsafe extern "C" fn(*mut ffi::xkb_keymap, ffi::xkb_layout_index_t) -> *const c_char;
+
+/// `xkb_keymap_layout_get_name`, which `xkbcommon-dl` doesn't bind, looked up in the library it
+/// has loaded.
+static XKB_KEYMAP_LAYOUT_GET_NAME: Lazy<Option<XkbKeymapLayoutGetName>> = Lazy::new(|| {
+    Lazy::force(&XKBH);
+    ["libxkbcommon.so.0\0", "libxkbcommon.so\0"]
+        .into_iter()
+        .find_map(|name| unsafe {
+            // Only find the library `xkbcommon-dl` has loaded, which it never closes, so the
+            // symbol stays valid once this handle is closed.
+            let library = libc::dlopen(
+                name.as_ptr() as *const c_char,
+                libc::RTLD_LAZY | libc::RTLD_NOLOAD,
+            );
+            if library.is_null() {
+                return None;
+            }
+            let symbol = libc::dlsym(
+                library,
+                b"xkb_keymap_layout_get_name\0".as_ptr() as *const c_char,
+            );
+            libc::dlclose(library);
+            (!symbol.is_null())
+                .then(|| std::mem::transmute::<*mut c_void, XkbKeymapLayoutGetName>(symbol))
+        })
+});
+
 #[derive(Debug)]
 pub struct KbdState {
     #[cfg(feature = "x11")]
@@ -49,6 +78,8 @@ pub struct KbdState {
     xkb_compose_state: *mut ffi::xkb_compose_state,
     xkb_compose_state_2: *mut ffi::xkb_compose_state,
     mods_state: ModifiersState,
+    /// The names of the layouts in the keymap, in the order of their indices.
+    layout_names: Vec<String>,
     #[cfg(feature = "x11")]
     pub core_keyboard_id: i32,
     scratch_buffer: Vec<u8>,
@@ -206,6 +237,7 @@ impl KbdState {
             xkb_compose_state: ptr::null_mut(),
             xkb_compose_state_2: ptr::null_mut(),
             mods_state: ModifiersState::new(),
+            layout_names: Vec::new(),
             #[cfg(feature = "x11")]
             core_keyboard_id: 0,
             scratch_buffer: Vec::new(),
@@ -299,6 +331,22 @@ impl KbdState {
         self.xkb_keymap = keymap;
         self.xkb_state = state;
         self.mods_state.update_with(state);
+
+        let num_layouts = unsafe { (XKBH.xkb_keymap_num_layouts)(keymap) };
+        self.layout_names = (0..num_layouts)
+            .map(|index| {
+                let name = match *XKB_KEYMAP_LAYOUT_GET_NAME {
+                    Some(layout_get_name) => unsafe { layout_get_name(keymap, index) },
+                    None => ptr::null(),
+                };
+                if name.is_null() {
+                    return String::new();
+                }
+                unsafe { CStr::from_ptr(name) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
     }
 
     unsafe fn de_init(&mut self) {
@@ -369,6 +417,31 @@ impl KbdState {
         unsafe { self.post_init(state, keymap) };
     }
 
+    /// Initialize the keymap from the default rules, model, and layout, which could be overridden
+    /// with the `XKB_DEFAULT_*` environment variables.
+    pub fn init_with_default_keymap(&mut self) -> Result<(), Error> {
+        if !self.xkb_keymap.is_null() {
+            unsafe { self.de_init() };
+        }
+
+        let keymap = unsafe {
+            (XKBH.xkb_keymap_new_from_names)(
+                self.xkb_context,
+                ptr::null(),
+                ffi::xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS,
+            )
+        };
+
+        if keymap.is_null() {
+            return Err(Error::KeymapNotFound);
+        }
+
+        let state = unsafe { (XKBH.xkb_state_new)(keymap) };
+        unsafe { self.post_init(state, keymap) };
+
+        Ok(())
+    }
+
     pub fn key_repeats(&mut self, keycode: ffi::xkb_keycode_t) -> bool {
         unsafe { (XKBH.xkb_keymap_key_repeats)(self.xkb_keymap, keycode) == 1 }
     }
@@ -383,6 +456,57 @@ impl KbdState {
         self.mods_state
     }
 
+    /// The names of the layouts in the keymap, in the order of their indices.
+    #[inline]
+    pub fn layout_names(&self) -> &[String] {
+        &self.layout_names
+    }
+
+    /// The index of the effective layout, combining the depressed, latched and locked ones.
+    pub fn active_layout_index(&self) -> Option<usize> {
+        if !self.ready() {
+            return None;
+        }
+        let index = unsafe {
+            (XKBH.xkb_state_serialize_layout)(
+                self.xkb_state,
+                xkb_state_component::XKB_STATE_LAYOUT_EFFECTIVE,
+            )
+        } as usize;
+        (index < self.layout_names.len()).then_some(index)
+    }
+
+    /// The effective layout.
+    pub fn active_layout(&self) -> Option<KeyboardLayout> {
+        let index = self.active_layout_index()?;
+        Some(KeyboardLayout {
+            index,
+            name: self.layout_names[index].clone(),
+        })
+    }
+
+    /// Update the state as if the key was pressed or released on the keyboard, returning whether
+    /// the effective modifiers have changed.
+    ///
+    /// This is only needed for the keys which aren't reported by the server, since for the
+    /// reported ones the server also sends the updated modifiers.
+    pub fn update_key(&mut self, keycode: u32, state: ElementState) -> bool {
+        if !self.ready() {
+            return false;
+        }
+        let direction = match state {
+            ElementState::Pressed => ffi::xkb_key_direction::XKB_KEY_DOWN,
+            ElementState::Released => ffi::xkb_key_direction::XKB_KEY_UP,
+        };
+        let mask = unsafe { (XKBH.xkb_state_update_key)(self.xkb_state, keycode, direction) };
+        if mask.contains(xkb_state_component::XKB_STATE_MODS_EFFECTIVE) {
+            self.mods_state.update_with(self.xkb_state);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn process_key_event(
         &mut self,
         keycode: u32,
@@ -663,10 +787,93 @@ impl From<ModifiersState> for crate::keyboard::ModifiersState {
     }
 }
 
+/// The layouts last reported to the application, to tell when the active one changes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Layouts {
+    pub names: Vec<String>,
+    pub active: Option<KeyboardLayout>,
+}
+
+impl Layouts {
+    /// Update the layouts with the ones of the `kb_state`, returning the active layout when it has
+    /// changed.
+    pub fn update(&mut self, kb_state: &KbdState) -> Option<KeyboardLayout> {
+        self.update_with(kb_state.layout_names(), kb_state.active_layout_index())
+    }
+
+    /// Update the layouts with the `names` and the `index` of the active one.
+    fn update_with(&mut self, names: &[String], index: Option<usize>) -> Option<KeyboardLayout> {
+        let names_changed = self.names != names;
+        if names_changed {
+            self.names = names.to_vec();
+        }
+
+        if !names_changed && self.active.as_ref().map(|layout| layout.index) == index {
+            return None;
+        }
+
+        let active = index.map(|index| KeyboardLayout {
+            index,
+            name: self.names[index].clone(),
+        });
+        if active == self.active {
+            return None;
+        }
+        self.active = active.clone();
+        active
+    }
+}
+
+/// The synthetic keys which are currently pressed.
+///
+/// The server doesn't know about them, so they update the keyboard state and are released when
+/// the window loses focus here.
+#[derive(Debug, Default)]
+pub struct SyntheticKeys {
+    pressed: Vec<u32>,
+}
+
+impl SyntheticKeys {
+    /// Process the synthetic key, returning its event and whether the effective modifiers have
+    /// changed.
+    pub fn process(
+        &mut self,
+        kb_state: &mut KbdState,
+        keycode: u32,
+        state: ElementState,
+        repeat: bool,
+    ) -> (KeyEvent, bool) {
+        let event = kb_state.process_key_event(keycode, state, repeat);
+        if repeat {
+            return (event, false);
+        }
+
+        self.pressed.retain(|key| *key != keycode);
+        if state == ElementState::Pressed {
+            self.pressed.push(keycode);
+        }
+        (event, kb_state.update_key(keycode, state))
+    }
+
+    /// Release the keys which are still pressed, returning their events.
+    pub fn release_all(&mut self, kb_state: &mut KbdState) -> Vec<KeyEvent> {
+        std::mem::take(&mut self.pressed)
+            .into_iter()
+            .map(|keycode| {
+                let event = kb_state.process_key_event(keycode, ElementState::Released, false);
+                kb_state.update_key(keycode, ElementState::Released);
+                event
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub enum Error {
     /// libxkbcommon is not available
     XKBNotFound,
+    /// The keymap couldn't be compiled
+    KeymapNotFound,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -689,3 +896,90 @@ fn byte_slice_to_smol_str(bytes: &[u8]) -> Option<SmolStr> {
         })
         .ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::{KeyCode, NamedKey};
+
+    const KEY_A: u32 = 38;
+    const KEY_LEFT_SHIFT: u32 = 50;
+
+    #[test]
+    fn held_synthetic_keys_are_released() {
+        let mut kb_state = KbdState::new().unwrap();
+        kb_state.init_with_default_keymap().unwrap();
+        let mut keys = SyntheticKeys::default();
+
+        let (_, changed) =
+            keys.process(&mut kb_state, KEY_LEFT_SHIFT, ElementState::Pressed, false);
+        assert!(changed);
+        assert!(kb_state.mods_state().shift);
+        keys.process(&mut kb_state, KEY_A, ElementState::Pressed, false);
+        keys.process(&mut kb_state, KEY_A, ElementState::Pressed, true);
+        keys.process(&mut kb_state, KEY_A, ElementState::Released, false);
+        keys.process(&mut kb_state, KEY_A, ElementState::Pressed, false);
+
+        // Only the keys still held are released, once each.
+        let released = keys.release_all(&mut kb_state);
+        let released: Vec<_> = released
+            .iter()
+            .map(|event| (event.physical_key, event.state))
+            .collect();
+        assert_eq!(
+            released,
+            [
+                (
+                    PhysicalKey::Code(KeyCode::ShiftLeft),
+                    ElementState::Released
+                ),
+                (PhysicalKey::Code(KeyCode::KeyA), ElementState::Released),
+            ]
+        );
+        assert!(!kb_state.mods_state().shift);
+        assert!(keys.release_all(&mut kb_state).is_empty());
+
+        let (event, _) = keys.process(&mut kb_state, KEY_LEFT_SHIFT, ElementState::Pressed, false);
+        assert_eq!(event.logical_key, Key::Named(NamedKey::Shift));
+    }
+
+    #[test]
+    fn layout_changes() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|&name| name.to_owned())
+                .collect::<Vec<_>>()
+        };
+        let layout = |index, name: &str| KeyboardLayout {
+            index,
+            name: name.to_owned(),
+        };
+        let mut layouts = Layouts::default();
+
+        let us_de = names(&["English (US)", "German"]);
+        assert_eq!(
+            layouts.update_with(&us_de, Some(0)),
+            Some(layout(0, "English (US)"))
+        );
+        assert_eq!(layouts.update_with(&us_de, Some(0)), None);
+        assert_eq!(
+            layouts.update_with(&us_de, Some(1)),
+            Some(layout(1, "German"))
+        );
+
+        // The active layout is replaced, at the same index.
+        let us_fr = names(&["English (US)", "French"]);
+        assert_eq!(
+            layouts.update_with(&us_fr, Some(1)),
+            Some(layout(1, "French"))
+        );
+        assert_eq!(layouts.names, us_fr);
+
+        // Another layout is replaced, the active one stays the same.
+        let es_fr = names(&["Spanish", "French"]);
+        assert_eq!(layouts.update_with(&es_fr, Some(1)), None);
+        assert_eq!(layouts.names, es_fr);
+        assert_eq!(layouts.active, Some(layout(1, "French")));
+    }
+}
@@ -0,0 +1,803 @@
+//! The event-loop routines.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use calloop::channel::{self, Sender};
+use calloop::ping::{self, Ping};
+
+use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use crate::error::EventLoopError;
+use crate::event::{
+    DndAction, Event, InnerSizeWriter, KeyboardLayout, StartCause, Touch, WindowEvent,
+};
+use crate::event_loop::{
+    ControlFlow, DeviceEvents, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget,
+};
+use crate::keyboard::ModifiersState;
+use crate::platform::clipboard::{ClipboardContents, SelectionKind};
+use crate::platform::pump_events::PumpStatus;
+use crate::platform::synthetic_input::SyntheticInput;
+use crate::platform_impl::click_counter::ClickCounter;
+use crate::platform_impl::common::{keymap, xkb_state::KbdState};
+use crate::platform_impl::platform::min_timeout;
+use crate::platform_impl::{EventLoopWindowTarget as PlatformEventLoopWindowTarget, OsError};
+
+use super::window::WindowState;
+use super::WindowId;
+
+/// An event produced by the headless "display server".
+#[derive(Debug)]
+pub(crate) enum HeadlessEvent {
+    /// An event which is forwarded to the application as is.
+    Window {
+        window_id: WindowId,
+        event: WindowEvent,
+        /// The time the event was queued at.
+        timestamp: Instant,
+    },
+
+    /// The scale factor of the window has changed, the new size is negotiated with the
+    /// application when dispatching.
+    ScaleFactorChanged {
+        window_id: WindowId,
+        scale_factor: f64,
+    },
+}
+
+/// State shared between the event loop and the windows, which could live on other threads.
+pub(crate) struct Shared {
+    /// Events waiting to be dispatched.
+    events: Mutex<VecDeque<HeadlessEvent>>,
+
+    /// The state of the alive windows.
+    windows: Mutex<HashMap<WindowId, Weak<Mutex<WindowState>>>>,
+
+    /// Windows which requested a redraw.
+    redraw_requests: Mutex<HashSet<WindowId>>,
+
+    /// The window which has the keyboard focus.
+    focused_window: Mutex<Option<WindowId>>,
+
+    /// The scale factor of the virtual monitor.
+    scale_factor: Mutex<f64>,
+
+    /// Wake up the event loop when new events were queued.
+    waker: Ping,
+}
+
+impl Shared {
+    pub(crate) fn push_event(&self, event: HeadlessEvent) {
+        self.events.lock().unwrap().push_back(event);
+        self.waker.ping();
+    }
+
+    pub(crate) fn push_window_event(&self, window_id: WindowId, event: WindowEvent) {
+        self.push_event(HeadlessEvent::Window {
+            window_id,
+            event,
+            timestamp: Instant::now(),
+        });
+    }
+
+    pub(crate) fn request_redraw(&self, window_id: WindowId) {
+        self.redraw_requests.lock().unwrap().insert(window_id);
+        self.waker.ping();
+    }
+
+    pub(crate) fn scale_factor(&self) -> f64 {
+        *self.scale_factor.lock().unwrap()
+    }
+
+    pub(crate) fn register_window(&self, window_id: WindowId, state: &Arc<Mutex<WindowState>>) {
+        self.windows
+            .lock()
+            .unwrap()
+            .insert(window_id, Arc::downgrade(state));
+    }
+
+    pub(crate) fn unregister_window(&self, window_id: WindowId) {
+        self.windows.lock().unwrap().remove(&window_id);
+        self.redraw_requests.lock().unwrap().remove(&window_id);
+
+        let mut focused_window = self.focused_window.lock().unwrap();
+        if *focused_window == Some(window_id) {
+            *focused_window = None;
+        }
+        drop(focused_window);
+
+        self.push_window_event(window_id, WindowEvent::Destroyed);
+    }
+
+    pub(crate) fn window_state(&self, window_id: WindowId) -> Option<Arc<Mutex<WindowState>>> {
+        self.windows
+            .lock()
+            .unwrap()
+            .get(&window_id)
+            .and_then(Weak::upgrade)
+    }
+
+    pub(crate) fn has_focus(&self, window_id: WindowId) -> bool {
+        *self.focused_window.lock().unwrap() == Some(window_id)
+    }
+
+    /// Move the keyboard focus, sending `Focused` to the involved windows.
+    pub(crate) fn set_focus(&self, window_id: Option<WindowId>) {
+        let mut focused_window = self.focused_window.lock().unwrap();
+        if *focused_window == window_id {
+            return;
+        }
+
+        let old_focus = std::mem::replace(&mut *focused_window, window_id);
+        drop(focused_window);
+
+        if let Some(old_focus) = old_focus {
+            self.push_window_event(old_focus, WindowEvent::Focused(false));
+        }
+
+        if let Some(window_id) = window_id {
+            self.push_window_event(window_id, WindowEvent::Focused(true));
+        }
+    }
+
+    /// Update the scale factor of the virtual monitor and thus of every window.
+    pub(crate) fn set_scale_factor(&self, scale_factor: f64) {
+        let mut current = self.scale_factor.lock().unwrap();
+        if *current == scale_factor {
+            return;
+        }
+        *current = scale_factor;
+        drop(current);
+
+        let window_ids: Vec<WindowId> = self.windows.lock().unwrap().keys().copied().collect();
+        for window_id in window_ids {
+            self.push_event(HeadlessEvent::ScaleFactorChanged {
+                window_id,
+                scale_factor,
+            });
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.events.lock().unwrap().is_empty() || !self.redraw_requests.lock().unwrap().is_empty()
+    }
+}
+
+/// The headless event loop.
+pub struct EventLoop<T: 'static> {
+    /// Has `run` or `run_on_demand` been called or a call to `pump_events` that starts the loop
+    loop_running: bool,
+
+    /// Sender of user events.
+    user_events_sender: Sender<T>,
+
+    /// Pending events from the user.
+    pending_user_events: Rc<RefCell<Vec<T>>>,
+
+    /// Event loop window target.
+    window_target: RootEventLoopWindowTarget,
+
+    /// Calloop's event loop.
+    event_loop: calloop::EventLoop<'static, ()>,
+}
+
+impl<T: 'static> EventLoop<T> {
+    pub fn new() -> Result<EventLoop<T>, EventLoopError> {
+        macro_rules! map_err {
+            ($e:expr) => {
+                $e.map_err(|_| os_error!(OsError::Misc("failed to setup the headless event loop")))
+            };
+        }
+
+        let event_loop = map_err!(calloop::EventLoop::<()>::try_new())?;
+
+        // Setup the user proxy.
+        let pending_user_events = Rc::new(RefCell::new(Vec::new()));
+        let pending_user_events_clone = pending_user_events.clone();
+        let (user_events_sender, user_events_channel) = channel::channel();
+        map_err!(event_loop
+            .handle()
+            .insert_source(user_events_channel, move |event, _, _| {
+                if let channel::Event::Msg(msg) = event {
+                    pending_user_events_clone.borrow_mut().push(msg);
+                }
+            }))?;
+
+        // An event's loop awakener to wake up for events from winit's windows.
+        let (waker, waker_source) = map_err!(ping::make_ping())?;
+        map_err!(event_loop.handle().insert_source(waker_source, |_, _, _| {
+            // No extra handling is required, we just need to wake-up.
+        }))?;
+
+        let shared = Arc::new(Shared {
+            events: Default::default(),
+            windows: Default::default(),
+            redraw_requests: Default::default(),
+            focused_window: Default::default(),
+            scale_factor: Mutex::new(1.),
+            waker,
+        });
+
+        let window_target = EventLoopWindowTarget {
+            shared,
+            control_flow: Cell::new(ControlFlow::default()),
+            exit: Cell::new(None),
+            keyboard: Default::default(),
+            cursor_position: Default::default(),
+            click_counter: Default::default(),
+            clipboard: Default::default(),
+            primary_selection: Default::default(),
+        };
+
+        Ok(Self {
+            loop_running: false,
+            user_events_sender,
+            pending_user_events,
+            window_target: RootEventLoopWindowTarget {
+                p: PlatformEventLoopWindowTarget::Headless(window_target),
+                _marker: PhantomData,
+            },
+            event_loop,
+        })
+    }
+
+    pub fn run_on_demand<F>(&mut self, mut event_handler: F) -> Result<(), EventLoopError>
+    where
+        F: FnMut(Event<T>, &RootEventLoopWindowTarget),
+    {
+        if self.loop_running {
+            return Err(EventLoopError::AlreadyRunning);
+        }
+
+        loop {
+            match self.pump_events(None, &mut event_handler) {
+                PumpStatus::Exit(0) => {
+                    break Ok(());
+                }
+                PumpStatus::Exit(code) => {
+                    break Err(EventLoopError::ExitFailure(code));
+                }
+                _ => {
+                    continue;
+                }
+            }
+        }
+    }
+
+    pub fn pump_events<F>(&mut self, timeout: Option<Duration>, mut callback: F) -> PumpStatus
+    where
+        F: FnMut(Event<T>, &RootEventLoopWindowTarget),
+    {
+        if !self.loop_running {
+            self.loop_running = true;
+
+            // Run the initial loop iteration.
+            self.single_iteration(&mut callback, StartCause::Init);
+        }
+
+        // Consider the possibility that the `StartCause::Init` iteration could
+        // request to Exit.
+        if !self.exiting() {
+            self.poll_events_with_timeout(timeout, &mut callback);
+        }
+        if let Some(code) = self.exit_code() {
+            self.loop_running = false;
+
+            callback(Event::LoopExiting, self.window_target());
+
+            PumpStatus::Exit(code)
+        } else {
+            PumpStatus::Continue
+        }
+    }
+
+    pub fn poll_events_with_timeout<F>(&mut self, mut timeout: Option<Duration>, mut callback: F)
+    where
+        F: FnMut(Event<T>, &RootEventLoopWindowTarget),
+    {
+        let start = Instant::now();
+
+        timeout = if self.has_pending() {
+            // If we already have work to do then we don't want to block on the next poll.
+            Some(Duration::ZERO)
+        } else {
+            let control_flow_timeout = match self.control_flow() {
+                ControlFlow::Wait => None,
+                ControlFlow::Poll => Some(Duration::ZERO),
+                ControlFlow::WaitUntil(wait_deadline) => {
+                    Some(wait_deadline.saturating_duration_since(start))
+                }
+            };
+
+            min_timeout(control_flow_timeout, timeout)
+        };
+
+        if let Err(error) = self
+            .event_loop
+            .dispatch(timeout, &mut ())
+            .map_err(std::io::Error::from)
+        {
+            log::error!("Failed to poll for events: {error:?}");
+            let exit_code = error.raw_os_error().unwrap_or(1);
+            self.set_exit_code(exit_code);
+            return;
+        }
+
+        // NB: `StartCause::Init` is handled as a special case and doesn't need
+        // to be considered here
+        let cause = match self.control_flow() {
+            ControlFlow::Poll => StartCause::Poll,
+            ControlFlow::Wait => StartCause::WaitCancelled {
+                start,
+                requested_resume: None,
+            },
+            ControlFlow::WaitUntil(deadline) => {
+                if Instant::now() < deadline {
+                    StartCause::WaitCancelled {
+                        start,
+                        requested_resume: Some(deadline),
+                    }
+                } else {
+                    StartCause::ResumeTimeReached {
+                        start,
+                        requested_resume: deadline,
+                    }
+                }
+            }
+        };
+
+        // Reduce spurious wake-ups.
+        if !self.has_pending()
+            && !matches!(
+                &cause,
+                StartCause::ResumeTimeReached { .. } | StartCause::Poll
+            )
+        {
+            return;
+        }
+
+        self.single_iteration(&mut callback, cause);
+    }
+
+    fn single_iteration<F>(&mut self, callback: &mut F, cause: StartCause)
+    where
+        F: FnMut(Event<T>, &RootEventLoopWindowTarget),
+    {
+        callback(Event::NewEvents(cause), &self.window_target);
+
+        // NB: For consistency all platforms must emit a 'resumed' event even though headless
+        // applications don't themselves have a formal suspend/resume lifecycle.
+        if cause == StartCause::Init {
+            callback(Event::Resumed, &self.window_target);
+        }
+
+        // Handle pending user events.
+        let user_events = std::mem::take(&mut *self.pending_user_events.borrow_mut());
+        for user_event in user_events {
+            callback(Event::UserEvent(user_event), &self.window_target);
+        }
+
+        // Only dispatch the events queued so far, the ones queued by the callback will wake up
+        // the loop for the next iteration.
+        let events = std::mem::take(&mut *self.shared().events.lock().unwrap());
+        for event in events {
+            match event {
+                HeadlessEvent::Window {
+                    window_id,
+                    event,
+                    timestamp,
+                } => callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event,
+                        timestamp,
+                    },
+                    &self.window_target,
+                ),
+                HeadlessEvent::ScaleFactorChanged {
+                    window_id,
+                    scale_factor,
+                } => self.dispatch_scale_factor_changed(window_id, scale_factor, callback),
+            }
+        }
+
+        // Empty the redraw requests.
+        let redraw_requests = std::mem::take(&mut *self.shared().redraw_requests.lock().unwrap());
+        for window_id in redraw_requests {
+            callback(
+                Event::WindowEvent {
+                    window_id: crate::window::WindowId(window_id),
+                    event: WindowEvent::RedrawRequested,
+                    timestamp: Instant::now(),
+                },
+                &self.window_target,
+            );
+        }
+
+        // This is always the last event we dispatch before poll again
+        callback(Event::AboutToWait, &self.window_target);
+    }
+
+    fn dispatch_scale_factor_changed<F>(
+        &self,
+        window_id: WindowId,
+        scale_factor: f64,
+        callback: &mut F,
+    ) where
+        F: FnMut(Event<T>, &RootEventLoopWindowTarget),
+    {
+        let state = match self.shared().window_state(window_id) {
+            Some(state) => state,
+            None => return,
+        };
+
+        let (old_scale_factor, old_physical_size) = {
+            let state = state.lock().unwrap();
+            (state.scale_factor, state.inner_size)
+        };
+
+        let logical_size: LogicalSize<f64> = old_physical_size.to_logical(old_scale_factor);
+        let suggested_size: PhysicalSize<u32> = logical_size.to_physical(scale_factor);
+        state.lock().unwrap().scale_factor = scale_factor;
+
+        let new_inner_size = Arc::new(Mutex::new(suggested_size));
+        callback(
+            Event::WindowEvent {
+                window_id: crate::window::WindowId(window_id),
+                event: WindowEvent::ScaleFactorChanged {
+                    scale_factor,
+                    inner_size_writer: InnerSizeWriter::new(Arc::downgrade(&new_inner_size)),
+                },
+                timestamp: Instant::now(),
+            },
+            &self.window_target,
+        );
+
+        let physical_size = *new_inner_size.lock().unwrap();
+        drop(new_inner_size);
+
+        if physical_size != old_physical_size {
+            state.lock().unwrap().inner_size = physical_size;
+            callback(
+                Event::WindowEvent {
+                    window_id: crate::window::WindowId(window_id),
+                    event: WindowEvent::Resized(physical_size),
+                    timestamp: Instant::now(),
+                },
+                &self.window_target,
+            );
+        }
+    }
+
+    #[inline]
+    pub fn create_proxy(&self) -> EventLoopProxy<T> {
+        EventLoopProxy {
+            user_events_sender: self.user_events_sender.clone(),
+        }
+    }
+
+    #[inline]
+    pub fn window_target(&self) -> &RootEventLoopWindowTarget {
+        &self.window_target
+    }
+
+    fn shared(&self) -> &Arc<Shared> {
+        match &self.window_target.p {
+            PlatformEventLoopWindowTarget::Headless(window_target) => &window_target.shared,
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        self.shared().has_pending() || !self.pending_user_events.borrow().is_empty()
+    }
+
+    fn control_flow(&self) -> ControlFlow {
+        self.window_target.p.control_flow()
+    }
+
+    fn exiting(&self) -> bool {
+        self.window_target.p.exiting()
+    }
+
+    fn set_exit_code(&self, code: i32) {
+        self.window_target.p.set_exit_code(code)
+    }
+
+    fn exit_code(&self) -> Option<i32> {
+        self.window_target.p.exit_code()
+    }
+}
+
+impl<T> AsFd for EventLoop<T> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.event_loop.as_fd()
+    }
+}
+
+impl<T> AsRawFd for EventLoop<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.event_loop.as_raw_fd()
+    }
+}
+
+/// A handle that can be sent across the threads and used to wake up the `EventLoop`.
+pub struct EventLoopProxy<T: 'static> {
+    user_events_sender: Sender<T>,
+}
+
+impl<T: 'static> Clone for EventLoopProxy<T> {
+    fn clone(&self) -> Self {
+        EventLoopProxy {
+            user_events_sender: self.user_events_sender.clone(),
+        }
+    }
+}
+
+impl<T: 'static> EventLoopProxy<T> {
+    pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
+        self.user_events_sender
+            .send(event)
+            .map_err(|std::sync::mpsc::SendError(error)| EventLoopClosed(error))
+    }
+}
+
+pub struct EventLoopWindowTarget {
+    /// State shared with the windows.
+    pub(crate) shared: Arc<Shared>,
+
+    /// The application's latest control_flow state
+    pub(crate) control_flow: Cell<ControlFlow>,
+
+    /// The application's exit state.
+    pub(crate) exit: Cell<Option<i32>>,
+
+    /// The keyboard state used to translate the synthetic keys, created on the first use.
+    keyboard: RefCell<Option<KbdState>>,
+
+    /// The position of the synthetic cursor, used to tell apart consecutive clicks.
+    cursor_position: Cell<PhysicalPosition<f64>>,
+
+    /// The counter of the synthetic clicks.
+    click_counter: RefCell<ClickCounter>,
+
+    /// The clipboard, which is local to the event loop.
+    clipboard: RefCell<ClipboardContents>,
+
+    /// The primary selection, which is local to the event loop.
+    primary_selection: RefCell<ClipboardContents>,
+}
+
+impl EventLoopWindowTarget {
+    pub(crate) fn set_control_flow(&self, control_flow: ControlFlow) {
+        self.control_flow.set(control_flow)
+    }
+
+    pub(crate) fn control_flow(&self) -> ControlFlow {
+        self.control_flow.get()
+    }
+
+    pub(crate) fn exit(&self) {
+        self.exit.set(Some(0))
+    }
+
+    pub(crate) fn clear_exit(&self) {
+        self.exit.set(None)
+    }
+
+    pub(crate) fn exiting(&self) -> bool {
+        self.exit.get().is_some()
+    }
+
+    pub(crate) fn set_exit_code(&self, code: i32) {
+        self.exit.set(Some(code))
+    }
+
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        self.exit.get()
+    }
+
+    #[inline]
+    pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
+
+    #[cfg(feature = "rwh_05")]
+    #[inline]
+    pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
+        // There's no display, hand out the web one, which holds no data.
+        rwh_05::WebDisplayHandle::empty().into()
+    }
+
+    #[cfg(feature = "rwh_06")]
+    #[inline]
+    pub fn raw_display_handle_rwh_06(
+        &self,
+    ) -> Result<rwh_06::RawDisplayHandle, rwh_06::HandleError> {
+        Err(rwh_06::HandleError::NotSupported)
+    }
+
+    /// Queue an event for the given window as if it was sent by the display server.
+    pub(crate) fn inject_window_event(
+        &self,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) -> Result<(), OsError> {
+        let state = self
+            .shared
+            .window_state(window_id)
+            .ok_or(OsError::Misc("no such headless window"))?;
+
+        // Keep the window state in sync with what the application observes.
+        match event {
+            // Focus changes are routed through the focus tracking to unfocus the previous window.
+            WindowEvent::Focused(focused) => {
+                if focused {
+                    self.shared.set_focus(Some(window_id));
+                } else if self.shared.has_focus(window_id) {
+                    self.shared.set_focus(None);
+                }
+                return Ok(());
+            }
+            WindowEvent::Resized(size) => state.lock().unwrap().inner_size = size,
+            WindowEvent::Moved(position) => state.lock().unwrap().position = position,
+            WindowEvent::ThemeChanged(theme) => state.lock().unwrap().theme = Some(theme),
+            WindowEvent::Occluded(occluded) => state.lock().unwrap().occluded = occluded,
+            _ => (),
+        }
+
+        self.shared.push_window_event(window_id, event);
+        Ok(())
+    }
+
+    /// Queue the events for the synthetic input.
+    pub(crate) fn inject_input(
+        &self,
+        window_id: WindowId,
+        input: SyntheticInput,
+    ) -> Result<(), OsError> {
+        if self.shared.window_state(window_id).is_none() {
+            return Err(OsError::Misc("no such headless window"));
+        }
+
+        // SAFETY: There are no real devices, thus the dummy one can't clash with them.
+        let device_id = crate::event::DeviceId(unsafe { crate::platform_impl::DeviceId::dummy() });
+        let event = match input {
+            SyntheticInput::KeyboardInput {
+                physical_key,
+                state,
+                repeat,
+            } => {
+                let keycode = keymap::physicalkey_to_scancode(physical_key)
+                    .ok_or(OsError::Misc("the key has no scancode"))?
+                    + 8;
+
+                let mut keyboard = self.keyboard.borrow_mut();
+                let keyboard = match &mut *keyboard {
+                    Some(keyboard) => keyboard,
+                    keyboard @ None => {
+                        let mut kb_state =
+                            KbdState::new().map_err(|_| OsError::Misc("xkbcommon is missing"))?;
+                        kb_state
+                            .init_with_default_keymap()
+                            .map_err(|_| OsError::Misc("failed to compile the default keymap"))?;
+                        keyboard.insert(kb_state)
+                    }
+                };
+
+                let event = keyboard.process_key_event(keycode, state, repeat);
+                self.shared.push_window_event(
+                    window_id,
+                    WindowEvent::KeyboardInput {
+                        device_id,
+                        event,
+                        is_synthetic: false,
+                    },
+                );
+
+                if !repeat && keyboard.update_key(keycode, state) {
+                    let modifiers: ModifiersState = keyboard.mods_state().into();
+                    self.shared.push_window_event(
+                        window_id,
+                        WindowEvent::ModifiersChanged(modifiers.into()),
+                    );
+                }
+
+                return Ok(());
+            }
+            SyntheticInput::CursorMoved { position } => {
+                self.cursor_position.set(position);
+                WindowEvent::CursorMoved {
+                    device_id,
+                    position,
+                }
+            }
+            SyntheticInput::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                click_count: self.click_counter.borrow_mut().count(
+                    crate::window::WindowId(window_id),
+                    state,
+                    button,
+                    self.cursor_position.get(),
+                    Instant::now(),
+                ),
+            },
+            SyntheticInput::Touch {
+                phase,
+                location,
+                force,
+                id,
+            } => WindowEvent::Touch(Touch {
+                device_id,
+                phase,
+                location,
+                force,
+                id,
+            }),
+            SyntheticInput::Ime(ime) => WindowEvent::Ime(ime),
+        };
+
+        self.shared.push_window_event(window_id, event);
+        Ok(())
+    }
+
+    fn selection(&self, kind: SelectionKind) -> &RefCell<ClipboardContents> {
+        match kind {
+            SelectionKind::Clipboard => &self.clipboard,
+            SelectionKind::Primary => &self.primary_selection,
+        }
+    }
+
+    pub(crate) fn set_selection(
+        &self,
+        kind: SelectionKind,
+        contents: ClipboardContents,
+    ) -> Result<(), OsError> {
+        *self.selection(kind).borrow_mut() = contents;
+        Ok(())
+    }
+
+    pub(crate) fn selection_mime_types(&self, kind: SelectionKind) -> Result<Vec<String>, OsError> {
+        Ok(self
+            .selection(kind)
+            .borrow()
+            .mime_types()
+            .map(String::from)
+            .collect())
+    }
+
+    pub(crate) fn selection_data(
+        &self,
+        kind: SelectionKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, OsError> {
+        Ok(self.selection(kind).borrow().data(mime_type).map(Vec::from))
+    }
+
+    pub(crate) fn set_drop_action(&self, _action: Option<DndAction>) {}
+
+    pub(crate) fn drop_mime_types(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    pub(crate) fn drop_data(&self, _mime_type: &str) -> Result<Option<Vec<u8>>, OsError> {
+        Ok(None)
+    }
+
+    pub(crate) fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.keyboard.borrow().as_ref()?.active_layout()
+    }
+
+    pub(crate) fn keyboard_layouts(&self) -> Vec<String> {
+        match &*self.keyboard.borrow() {
+            Some(keyboard) => keyboard.layout_names().to_vec(),
+            None => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Winit's headless backend.
+//!
+//! The backend doesn't talk to any display server, instead it keeps the state of virtual windows
+//! placed on a single virtual monitor, which makes it suitable for testing on machines without a
+//! display.
+
+pub use crate::platform_impl::platform::WindowId;
+pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use monitor::{MonitorHandle, VideoModeHandle};
+pub use window::Window;
+
+mod event_loop;
+mod monitor;
+mod window;
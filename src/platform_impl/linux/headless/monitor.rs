@@ -0,0 +1,138 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::platform_impl::platform::VideoModeHandle as PlatformVideoModeHandle;
+
+use super::event_loop::{EventLoopWindowTarget, Shared};
+
+/// Size of the only virtual monitor.
+pub(crate) const MONITOR_SIZE: PhysicalSize<u32> = PhysicalSize::new(1920, 1080);
+
+/// Refresh rate of the only virtual monitor.
+const MONITOR_REFRESH_RATE_MILLIHERTZ: u32 = 60_000;
+
+impl EventLoopWindowTarget {
+    #[inline]
+    pub fn available_monitors(&self) -> impl Iterator<Item = MonitorHandle> {
+        std::iter::once(MonitorHandle::new(self.shared.clone()))
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        Some(MonitorHandle::new(self.shared.clone()))
+    }
+}
+
+/// The virtual monitor every headless window is placed on.
+///
+/// There's always exactly one monitor, thus all the handles compare equal.
+#[derive(Clone)]
+pub struct MonitorHandle {
+    shared: Arc<Shared>,
+}
+
+impl MonitorHandle {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    #[inline]
+    pub fn name(&self) -> Option<String> {
+        Some(String::from("headless"))
+    }
+
+    #[inline]
+    pub fn native_identifier(&self) -> u32 {
+        0
+    }
+
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        MONITOR_SIZE
+    }
+
+    #[inline]
+    pub fn position(&self) -> PhysicalPosition<i32> {
+        PhysicalPosition::new(0, 0)
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+        Some(MONITOR_REFRESH_RATE_MILLIHERTZ)
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.shared.scale_factor()
+    }
+
+    #[inline]
+    pub fn video_modes(&self) -> impl Iterator<Item = PlatformVideoModeHandle> {
+        std::iter::once(PlatformVideoModeHandle::Headless(VideoModeHandle {
+            monitor: self.clone(),
+        }))
+    }
+}
+
+impl std::fmt::Debug for MonitorHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MonitorHandle")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for MonitorHandle {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for MonitorHandle {}
+
+impl PartialOrd for MonitorHandle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MonitorHandle {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Hash for MonitorHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.native_identifier().hash(state);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VideoModeHandle {
+    monitor: MonitorHandle,
+}
+
+impl VideoModeHandle {
+    #[inline]
+    pub fn size(&self) -> PhysicalSize<u32> {
+        MONITOR_SIZE
+    }
+
+    #[inline]
+    pub fn bit_depth(&self) -> u16 {
+        32
+    }
+
+    #[inline]
+    pub fn refresh_rate_millihertz(&self) -> u32 {
+        MONITOR_REFRESH_RATE_MILLIHERTZ
+    }
+
+    #[inline]
+    pub fn monitor(&self) -> MonitorHandle {
+        self.monitor.clone()
+    }
+}
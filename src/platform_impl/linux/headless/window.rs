@@ -0,0 +1,565 @@
+//! The headless window.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
+use crate::event::{Ime, WindowEvent};
+use crate::event_loop::AsyncRequestSerial;
+use crate::platform::drag_and_drop::DragSource;
+use crate::platform_impl::{Fullscreen, PlatformIcon};
+use crate::window::{
+    Cursor, CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+    WindowAttributes, WindowButtons, WindowLevel,
+};
+
+use super::event_loop::{EventLoopWindowTarget, Shared};
+use super::monitor::{MonitorHandle, MONITOR_SIZE};
+use super::WindowId;
+
+/// The default size of the window, matching the other Linux backends.
+const DEFAULT_INNER_SIZE: PhysicalSize<u32> = PhysicalSize::new(800, 600);
+
+/// Source of the window ids, `0` is reserved for the dummy id.
+static NEXT_WINDOW_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The state of a virtual window.
+#[derive(Debug)]
+pub struct WindowState {
+    pub title: String,
+    pub scale_factor: f64,
+    pub inner_size: PhysicalSize<u32>,
+    pub position: PhysicalPosition<i32>,
+    pub min_inner_size: Option<Size>,
+    pub max_inner_size: Option<Size>,
+    pub resize_increments: Option<Size>,
+    pub visible: bool,
+    pub occluded: bool,
+    pub resizable: bool,
+    pub decorated: bool,
+    pub minimized: bool,
+    pub maximized: bool,
+    pub fullscreen: Option<Fullscreen>,
+    pub enabled_buttons: WindowButtons,
+    pub theme: Option<Theme>,
+    pub cursor_grab: CursorGrabMode,
+    pub ime_allowed: bool,
+    pub ime_purpose: ImePurpose,
+    pub ime_hints: ImeHints,
+
+    /// The geometry to restore after leaving the maximized or fullscreen state.
+    restore_geometry: Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>,
+}
+
+impl WindowState {
+    /// Clamp the size to the size constraints of the window.
+    fn constrain_size(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let min_size: PhysicalSize<u32> = self
+            .min_inner_size
+            .map(|size| size.to_physical(self.scale_factor))
+            .unwrap_or_else(|| PhysicalSize::new(1, 1));
+        let max_size: PhysicalSize<u32> = self
+            .max_inner_size
+            .map(|size| size.to_physical(self.scale_factor))
+            .unwrap_or_else(|| PhysicalSize::new(u32::MAX, u32::MAX));
+
+        PhysicalSize::new(
+            size.width.min(max_size.width).max(min_size.width),
+            size.height.min(max_size.height).max(min_size.height),
+        )
+    }
+}
+
+/// The headless window.
+pub struct Window {
+    /// Window id.
+    window_id: WindowId,
+
+    /// The state of the window.
+    state: Arc<Mutex<WindowState>>,
+
+    /// State shared with the event loop.
+    shared: Arc<Shared>,
+}
+
+impl Window {
+    pub(crate) fn new(
+        event_loop_window_target: &EventLoopWindowTarget,
+        attributes: WindowAttributes,
+    ) -> Result<Self, RootOsError> {
+        let shared = event_loop_window_target.shared.clone();
+        let window_id = WindowId(NEXT_WINDOW_ID.fetch_add(1, Ordering::Relaxed));
+        let scale_factor = shared.scale_factor();
+
+        let mut state = WindowState {
+            title: attributes.title,
+            scale_factor,
+            inner_size: attributes
+                .inner_size
+                .map(|size| size.to_physical(scale_factor))
+                .unwrap_or(DEFAULT_INNER_SIZE),
+            position: attributes
+                .position
+                .map(|position| position.to_physical(scale_factor))
+                .unwrap_or_default(),
+            min_inner_size: attributes.min_inner_size,
+            max_inner_size: attributes.max_inner_size,
+            resize_increments: attributes.resize_increments,
+            visible: attributes.visible,
+            occluded: false,
+            resizable: attributes.resizable,
+            decorated: attributes.decorations,
+            minimized: false,
+            maximized: false,
+            fullscreen: None,
+            enabled_buttons: attributes.enabled_buttons,
+            theme: attributes.preferred_theme,
+            cursor_grab: CursorGrabMode::None,
+            ime_allowed: false,
+            ime_purpose: ImePurpose::Normal,
+            ime_hints: ImeHints::empty(),
+            restore_geometry: None,
+        };
+        state.inner_size = state.constrain_size(state.inner_size);
+
+        let state = Arc::new(Mutex::new(state));
+        shared.register_window(window_id, &state);
+
+        let window = Self {
+            window_id,
+            state,
+            shared,
+        };
+
+        match attributes.fullscreen.map(Into::into) {
+            Some(fullscreen) => window.set_fullscreen(Some(fullscreen)),
+            None if attributes.maximized => window.set_maximized(true),
+            None => (),
+        }
+
+        // Like a compositor would do, the new window gets the focus and its first frame.
+        if attributes.active && attributes.visible {
+            window.shared.set_focus(Some(window_id));
+        }
+        window.request_redraw();
+
+        Ok(window)
+    }
+
+    #[inline]
+    pub fn id(&self) -> WindowId {
+        self.window_id
+    }
+
+    #[inline]
+    pub fn set_title(&self, title: &str) {
+        self.state.lock().unwrap().title = title.to_owned();
+    }
+
+    #[inline]
+    pub fn title(&self) -> String {
+        self.state.lock().unwrap().title.clone()
+    }
+
+    #[inline]
+    pub fn set_transparent(&self, _transparent: bool) {}
+
+    #[inline]
+    pub fn set_blur(&self, _blur: bool) {}
+
+    #[inline]
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {}
+
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {}
+
+    #[inline]
+    pub fn set_visible(&self, visible: bool) {
+        self.state.lock().unwrap().visible = visible;
+        if !visible && self.shared.has_focus(self.window_id) {
+            self.shared.set_focus(None);
+        }
+    }
+
+    #[inline]
+    pub fn is_visible(&self) -> Option<bool> {
+        Some(self.state.lock().unwrap().visible)
+    }
+
+    #[inline]
+    pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        Ok(self.state.lock().unwrap().position)
+    }
+
+    #[inline]
+    pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+        // There are no decorations to account for.
+        self.outer_position()
+    }
+
+    #[inline]
+    pub fn set_outer_position(&self, position: Position) {
+        let mut state = self.state.lock().unwrap();
+        let position = position.to_physical(state.scale_factor);
+        if state.position != position {
+            state.position = position;
+            drop(state);
+            self.shared
+                .push_window_event(self.window_id, WindowEvent::Moved(position));
+        }
+    }
+
+    #[inline]
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.state.lock().unwrap().inner_size
+    }
+
+    #[inline]
+    pub fn outer_size(&self) -> PhysicalSize<u32> {
+        self.inner_size()
+    }
+
+    #[inline]
+    pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+        let state = self.state.lock().unwrap();
+        let size = state.constrain_size(size.to_physical(state.scale_factor));
+        drop(state);
+        self.resize(size);
+        Some(size)
+    }
+
+    #[inline]
+    pub(crate) fn request_activation_token(&self) -> Result<AsyncRequestSerial, NotSupportedError> {
+        Err(NotSupportedError::new())
+    }
+
+    #[inline]
+    pub fn set_min_inner_size(&self, min_size: Option<Size>) {
+        let mut state = self.state.lock().unwrap();
+        state.min_inner_size = min_size;
+        let size = state.constrain_size(state.inner_size);
+        drop(state);
+        self.resize(size);
+    }
+
+    #[inline]
+    pub fn set_max_inner_size(&self, max_size: Option<Size>) {
+        let mut state = self.state.lock().unwrap();
+        state.max_inner_size = max_size;
+        let size = state.constrain_size(state.inner_size);
+        drop(state);
+        self.resize(size);
+    }
+
+    #[inline]
+    pub fn resize_increments(&self) -> Option<PhysicalSize<u32>> {
+        let state = self.state.lock().unwrap();
+        state
+            .resize_increments
+            .map(|increments| increments.to_physical(state.scale_factor))
+    }
+
+    #[inline]
+    pub fn set_resize_increments(&self, increments: Option<Size>) {
+        self.state.lock().unwrap().resize_increments = increments;
+    }
+
+    #[inline]
+    pub fn set_resizable(&self, resizable: bool) {
+        self.state.lock().unwrap().resizable = resizable;
+    }
+
+    #[inline]
+    pub fn is_resizable(&self) -> bool {
+        self.state.lock().unwrap().resizable
+    }
+
+    #[inline]
+    pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+        self.state.lock().unwrap().enabled_buttons = buttons;
+    }
+
+    #[inline]
+    pub fn enabled_buttons(&self) -> WindowButtons {
+        self.state.lock().unwrap().enabled_buttons
+    }
+
+    #[inline]
+    pub fn set_cursor(&self, _cursor: Cursor) {}
+
+    #[inline]
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+        self.state.lock().unwrap().cursor_grab = mode;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_visible(&self, _visible: bool) {}
+
+    #[inline]
+    pub fn drag_window(&self) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn start_drag(&self, _source: DragSource) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn show_window_menu(&self, _position: Position) {}
+
+    #[inline]
+    pub fn set_cursor_hittest(&self, _hittest: bool) -> Result<(), ExternalError> {
+        Ok(())
+    }
+
+    #[inline]
+    pub fn scale_factor(&self) -> f64 {
+        self.state.lock().unwrap().scale_factor
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&self, _position: Position) -> Result<(), ExternalError> {
+        Err(ExternalError::NotSupported(NotSupportedError::new()))
+    }
+
+    #[inline]
+    pub fn set_maximized(&self, maximized: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.maximized == maximized {
+            return;
+        }
+        state.maximized = maximized;
+
+        if state.fullscreen.is_none() {
+            self.update_geometry(state, maximized);
+        }
+    }
+
+    #[inline]
+    pub fn is_maximized(&self) -> bool {
+        self.state.lock().unwrap().maximized
+    }
+
+    #[inline]
+    pub fn set_minimized(&self, minimized: bool) {
+        self.state.lock().unwrap().minimized = minimized;
+        if minimized && self.shared.has_focus(self.window_id) {
+            self.shared.set_focus(None);
+        }
+    }
+
+    #[inline]
+    pub fn is_minimized(&self) -> Option<bool> {
+        Some(self.state.lock().unwrap().minimized)
+    }
+
+    #[inline]
+    pub(crate) fn fullscreen(&self) -> Option<Fullscreen> {
+        self.state.lock().unwrap().fullscreen.clone()
+    }
+
+    #[inline]
+    pub(crate) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let mut state = self.state.lock().unwrap();
+        let was_fullscreen = state.fullscreen.is_some();
+        let is_fullscreen = fullscreen.is_some();
+        state.fullscreen = fullscreen;
+
+        if was_fullscreen != is_fullscreen && !state.maximized {
+            self.update_geometry(state, is_fullscreen);
+        }
+    }
+
+    #[inline]
+    pub fn set_decorations(&self, decorate: bool) {
+        self.state.lock().unwrap().decorated = decorate;
+    }
+
+    #[inline]
+    pub fn is_decorated(&self) -> bool {
+        self.state.lock().unwrap().decorated
+    }
+
+    #[inline]
+    pub fn set_window_level(&self, _level: WindowLevel) {}
+
+    #[inline]
+    pub(crate) fn set_window_icon(&self, _window_icon: Option<PlatformIcon>) {}
+
+    #[inline]
+    pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+    #[inline]
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        let mut state = self.state.lock().unwrap();
+        if state.ime_allowed == allowed {
+            return;
+        }
+        state.ime_allowed = allowed;
+        drop(state);
+
+        let event = WindowEvent::Ime(if allowed { Ime::Enabled } else { Ime::Disabled });
+        self.shared.push_window_event(self.window_id, event);
+    }
+
+    #[inline]
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        self.state.lock().unwrap().ime_purpose = purpose;
+    }
+
+    #[inline]
+    pub fn set_ime_hints(&self, hints: ImeHints) {
+        self.state.lock().unwrap().ime_hints = hints;
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
+    #[inline]
+    pub fn focus_window(&self) {
+        let state = self.state.lock().unwrap();
+        if state.visible && !state.minimized {
+            drop(state);
+            self.shared.set_focus(Some(self.window_id));
+        }
+    }
+
+    #[inline]
+    pub fn has_focus(&self) -> bool {
+        self.shared.has_focus(self.window_id)
+    }
+
+    #[inline]
+    pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {}
+
+    #[inline]
+    pub fn request_redraw(&self) {
+        self.shared.request_redraw(self.window_id);
+    }
+
+    #[inline]
+    pub fn pre_present_notify(&self) {}
+
+    #[inline]
+    pub fn current_monitor(&self) -> Option<MonitorHandle> {
+        Some(MonitorHandle::new(self.shared.clone()))
+    }
+
+    #[inline]
+    pub fn available_monitors(&self) -> Vec<MonitorHandle> {
+        vec![MonitorHandle::new(self.shared.clone())]
+    }
+
+    #[inline]
+    pub fn primary_monitor(&self) -> Option<MonitorHandle> {
+        Some(MonitorHandle::new(self.shared.clone()))
+    }
+
+    #[cfg(feature = "rwh_04")]
+    #[inline]
+    pub fn raw_window_handle_rwh_04(&self) -> rwh_04::RawWindowHandle {
+        // There's no native window, hand out the invalid web handle, which no native renderer
+        // dereferences.
+        rwh_04::RawWindowHandle::Web(rwh_04::WebHandle::empty())
+    }
+
+    #[cfg(feature = "rwh_05")]
+    #[inline]
+    pub fn raw_window_handle_rwh_05(&self) -> rwh_05::RawWindowHandle {
+        // There's no native window, hand out the invalid web handle, which no native renderer
+        // dereferences.
+        rwh_05::RawWindowHandle::Web(rwh_05::WebWindowHandle::empty())
+    }
+
+    #[cfg(feature = "rwh_05")]
+    #[inline]
+    pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
+        rwh_05::RawDisplayHandle::Web(rwh_05::WebDisplayHandle::empty())
+    }
+
+    #[cfg(feature = "rwh_06")]
+    #[inline]
+    pub fn raw_window_handle_rwh_06(&self) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+        Err(rwh_06::HandleError::NotSupported)
+    }
+
+    #[cfg(feature = "rwh_06")]
+    #[inline]
+    pub fn raw_display_handle_rwh_06(
+        &self,
+    ) -> Result<rwh_06::RawDisplayHandle, rwh_06::HandleError> {
+        Err(rwh_06::HandleError::NotSupported)
+    }
+
+    #[inline]
+    pub fn set_theme(&self, theme: Option<Theme>) {
+        let mut state = self.state.lock().unwrap();
+        let old_theme = std::mem::replace(&mut state.theme, theme);
+        drop(state);
+
+        if let Some(theme) = theme.filter(|theme| Some(*theme) != old_theme) {
+            self.shared
+                .push_window_event(self.window_id, WindowEvent::ThemeChanged(theme));
+        }
+    }
+
+    #[inline]
+    pub fn theme(&self) -> Option<Theme> {
+        self.state.lock().unwrap().theme
+    }
+
+    #[inline]
+    pub fn set_content_protected(&self, _protected: bool) {}
+
+    /// Apply the new size, sending `Resized` when it has changed.
+    fn resize(&self, size: PhysicalSize<u32>) {
+        let mut state = self.state.lock().unwrap();
+        if state.inner_size != size {
+            state.inner_size = size;
+            drop(state);
+            self.shared
+                .push_window_event(self.window_id, WindowEvent::Resized(size));
+        }
+    }
+
+    /// Cover the whole monitor or restore the previous geometry.
+    fn update_geometry(
+        &self,
+        mut state: std::sync::MutexGuard<'_, WindowState>,
+        cover_monitor: bool,
+    ) {
+        let (position, size) = if cover_monitor {
+            state.restore_geometry = Some((state.position, state.inner_size));
+            (PhysicalPosition::new(0, 0), MONITOR_SIZE)
+        } else {
+            match state.restore_geometry.take() {
+                Some(geometry) => geometry,
+                None => return,
+            }
+        };
+
+        let moved = state.position != position;
+        state.position = position;
+        drop(state);
+
+        if moved {
+            self.shared
+                .push_window_event(self.window_id, WindowEvent::Moved(position));
+        }
+        self.resize(size);
+    }
+}
+
+impl Drop for Window {
+    fn drop(&mut self) {
+        self.shared.unregister_window(self.window_id);
+    }
+}
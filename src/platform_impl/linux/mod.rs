@@ -27,9 +27,12 @@ use crate::{
     },
     icon::Icon,
     keyboard::Key,
-    platform::pump_events::PumpStatus,
+    platform::{
+        drag_and_drop::DragSource, layer_shell::LayerShellAttributes, popup::PopupAttributes,
+        pump_events::PumpStatus,
+    },
     window::{
-        ActivationToken, Cursor, CursorGrabMode, ImePurpose, ResizeDirection, Theme,
+        ActivationToken, Cursor, CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme,
         UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
     },
 };
@@ -40,6 +43,7 @@ pub(crate) use crate::icon::RgbaIcon as PlatformIcon;
 pub(crate) use crate::platform_impl::Fullscreen;
 
 pub(crate) mod common;
+pub(crate) mod headless;
 #[cfg(wayland_platform)]
 pub(crate) mod wayland;
 #[cfg(x11_platform)]
@@ -51,12 +55,15 @@ pub(crate) enum Backend {
     X,
     #[cfg(wayland_platform)]
     Wayland,
+    Headless,
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct PlatformSpecificEventLoopAttributes {
     pub(crate) forced_backend: Option<Backend>,
     pub(crate) any_thread: bool,
+    #[cfg(dbus_ime)]
+    pub(crate) dbus_ime: Option<crate::platform::dbus_ime::DBusImeBackend>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -75,6 +82,12 @@ impl ApplicationName {
 pub struct PlatformSpecificWindowBuilderAttributes {
     pub name: Option<ApplicationName>,
     pub activation_token: Option<ActivationToken>,
+    pub popup: Option<PopupAttributes>,
+    pub layer_shell: Option<LayerShellAttributes>,
+    #[cfg(wayland_platform)]
+    pub icon_name: Option<String>,
+    #[cfg(wayland_platform)]
+    pub subsurface_sync: bool,
     #[cfg(x11_platform)]
     pub x11: X11WindowBuilderAttributes,
 }
@@ -97,6 +110,12 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
         Self {
             name: None,
             activation_token: None,
+            popup: None,
+            layer_shell: None,
+            #[cfg(wayland_platform)]
+            icon_name: None,
+            #[cfg(wayland_platform)]
+            subsurface_sync: false,
             #[cfg(x11_platform)]
             x11: X11WindowBuilderAttributes {
                 visual_id: None,
@@ -139,7 +158,8 @@ pub(crate) enum Window {
     #[cfg(x11_platform)]
     X(x11::Window),
     #[cfg(wayland_platform)]
-    Wayland(wayland::Window),
+    Wayland(Box<wayland::Window>),
+    Headless(headless::Window),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -178,6 +198,32 @@ impl DeviceId {
         #[cfg(all(not(wayland_platform), x11_platform))]
         return DeviceId::X(unsafe { x11::DeviceId::dummy() });
     }
+
+    /// Create the id from the raw value obtained with [`DeviceId::into_raw`].
+    ///
+    /// Only X11 has distinct device ids, thus `None` stands for the Wayland one.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: Option<u64>) -> Self {
+        match raw {
+            #[cfg(x11_platform)]
+            Some(raw) => DeviceId::X(x11::DeviceId::from_raw(raw as _)),
+            #[cfg(wayland_platform)]
+            None => DeviceId::Wayland(wayland::DeviceId),
+            // The recording was made with a backend which isn't available.
+            #[allow(unreachable_patterns)]
+            _ => unsafe { DeviceId::dummy() },
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> Option<u64> {
+        match self {
+            #[cfg(x11_platform)]
+            DeviceId::X(device_id) => Some(device_id.into_raw() as u64),
+            #[cfg(wayland_platform)]
+            DeviceId::Wayland(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -186,6 +232,7 @@ pub enum MonitorHandle {
     X(x11::MonitorHandle),
     #[cfg(wayland_platform)]
     Wayland(wayland::MonitorHandle),
+    Headless(headless::MonitorHandle),
 }
 
 /// `x11_or_wayland!(match expr; Enum(foo) => foo.something())`
@@ -194,6 +241,7 @@ pub enum MonitorHandle {
 /// match self {
 ///    Enum::X(foo) => foo.something(),
 ///    Enum::Wayland(foo) => foo.something(),
+///    Enum::Headless(foo) => foo.something(),
 /// }
 /// ```
 /// The result can be converted to another enum by adding `; as AnotherEnum`
@@ -204,6 +252,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $enum2::X($x),
             #[cfg(wayland_platform)]
             $enum::Wayland($($c1)*) => $enum2::Wayland($x),
+            $enum::Headless($($c1)*) => $enum2::Headless($x),
         }
     };
     (match $what:expr; $enum:ident ( $($c1:tt)* ) => $x:expr) => {
@@ -212,6 +261,7 @@ macro_rules! x11_or_wayland {
             $enum::X($($c1)*) => $x,
             #[cfg(wayland_platform)]
             $enum::Wayland($($c1)*) => $x,
+            $enum::Headless($($c1)*) => $x,
         }
     };
 }
@@ -259,6 +309,7 @@ pub enum VideoModeHandle {
     X(x11::VideoModeHandle),
     #[cfg(wayland_platform)]
     Wayland(wayland::VideoModeHandle),
+    Headless(headless::VideoModeHandle),
 }
 
 impl VideoModeHandle {
@@ -292,12 +343,16 @@ impl Window {
         match *window_target {
             #[cfg(wayland_platform)]
             EventLoopWindowTarget::Wayland(ref window_target) => {
-                wayland::Window::new(window_target, attribs).map(Window::Wayland)
+                wayland::Window::new(window_target, attribs)
+                    .map(|window| Window::Wayland(Box::new(window)))
             }
             #[cfg(x11_platform)]
             EventLoopWindowTarget::X(ref window_target) => {
                 x11::Window::new(window_target, attribs).map(Window::X)
             }
+            EventLoopWindowTarget::Headless(ref window_target) => {
+                headless::Window::new(window_target, attribs).map(Window::Headless)
+            }
         }
     }
 
@@ -329,6 +384,16 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_blur(blur));
     }
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) {
+        x11_or_wayland!(match self; Window(w) => w.set_idle_inhibit(inhibit));
+    }
+
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, inhibit: bool) {
+        x11_or_wayland!(match self; Window(w) => w.set_keyboard_shortcuts_inhibit(inhibit));
+    }
+
     #[inline]
     pub fn set_visible(&self, visible: bool) {
         x11_or_wayland!(match self; Window(w) => w.set_visible(visible))
@@ -434,6 +499,11 @@ impl Window {
         x11_or_wayland!(match self; Window(window) => window.drag_window())
     }
 
+    #[inline]
+    pub fn start_drag(&self, source: DragSource) -> Result<(), ExternalError> {
+        x11_or_wayland!(match self; Window(window) => window.start_drag(source))
+    }
+
     #[inline]
     pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
         x11_or_wayland!(match self; Window(window) => window.drag_resize_window(direction))
@@ -509,6 +579,22 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_window_icon(window_icon.map(|icon| icon.inner)))
     }
 
+    #[cfg(wayland_platform)]
+    #[inline]
+    pub fn set_icon_name(&self, name: Option<String>) {
+        if let Self::Wayland(window) = self {
+            window.set_icon_name(name)
+        }
+    }
+
+    #[cfg(wayland_platform)]
+    #[inline]
+    pub fn set_subsurface_sync(&self, sync: bool) {
+        if let Self::Wayland(window) = self {
+            window.set_subsurface_sync(sync)
+        }
+    }
+
     #[inline]
     pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
         x11_or_wayland!(match self; Window(w) => w.set_ime_cursor_area(position, size))
@@ -529,6 +615,16 @@ impl Window {
         x11_or_wayland!(match self; Window(w) => w.set_ime_purpose(purpose))
     }
 
+    #[inline]
+    pub fn set_ime_hints(&self, hints: ImeHints) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_hints(hints))
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        x11_or_wayland!(match self; Window(w) => w.set_ime_surrounding_text(text, cursor, anchor))
+    }
+
     #[inline]
     pub fn focus_window(&self) {
         x11_or_wayland!(match self; Window(w) => w.focus_window())
@@ -567,6 +663,11 @@ impl Window {
                 .into_iter()
                 .map(MonitorHandle::Wayland)
                 .collect(),
+            Window::Headless(ref window) => window
+                .available_monitors()
+                .into_iter()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -632,6 +733,7 @@ impl Window {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeyEventExtra {
     pub text_with_all_modifiers: Option<SmolStr>,
     pub key_without_modifiers: Key,
@@ -643,6 +745,7 @@ pub(crate) enum PlatformCustomCursor {
     Wayland(wayland::CustomCursor),
     #[cfg(x11_platform)]
     X(x11::CustomCursor),
+    Headless(crate::cursor::OnlyCursorImage),
 }
 impl PlatformCustomCursor {
     pub(crate) fn build(
@@ -656,6 +759,9 @@ impl PlatformCustomCursor {
             }
             #[cfg(x11_platform)]
             EventLoopWindowTarget::X(p) => Self::X(x11::CustomCursor::build(builder, p)),
+            EventLoopWindowTarget::Headless(_) => {
+                Self::Headless(crate::cursor::OnlyCursorImage::build(builder, p))
+            }
         }
     }
 }
@@ -717,6 +823,7 @@ pub enum EventLoop<T: 'static> {
     Wayland(Box<wayland::EventLoop<T>>),
     #[cfg(x11_platform)]
     X(x11::EventLoop<T>),
+    Headless(Box<headless::EventLoop<T>>),
 }
 
 pub enum EventLoopProxy<T: 'static> {
@@ -724,6 +831,7 @@ pub enum EventLoopProxy<T: 'static> {
     X(x11::EventLoopProxy<T>),
     #[cfg(wayland_platform)]
     Wayland(wayland::EventLoopProxy<T>),
+    Headless(headless::EventLoopProxy<T>),
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
@@ -775,25 +883,34 @@ impl<T: 'static> EventLoop<T> {
         // Create the display based on the backend.
         match backend {
             #[cfg(wayland_platform)]
-            Backend::Wayland => EventLoop::new_wayland_any_thread().map_err(Into::into),
+            Backend::Wayland => EventLoop::new_wayland_any_thread(attributes),
             #[cfg(x11_platform)]
-            Backend::X => EventLoop::new_x11_any_thread().map_err(Into::into),
+            Backend::X => EventLoop::new_x11_any_thread(attributes),
+            Backend::Headless => EventLoop::new_headless_any_thread(),
         }
     }
 
     #[cfg(wayland_platform)]
-    fn new_wayland_any_thread() -> Result<EventLoop<T>, EventLoopError> {
-        wayland::EventLoop::new().map(|evlp| EventLoop::Wayland(Box::new(evlp)))
+    fn new_wayland_any_thread(
+        attributes: &PlatformSpecificEventLoopAttributes,
+    ) -> Result<EventLoop<T>, EventLoopError> {
+        wayland::EventLoop::new(attributes).map(|evlp| EventLoop::Wayland(Box::new(evlp)))
     }
 
     #[cfg(x11_platform)]
-    fn new_x11_any_thread() -> Result<EventLoop<T>, EventLoopError> {
+    fn new_x11_any_thread(
+        attributes: &PlatformSpecificEventLoopAttributes,
+    ) -> Result<EventLoop<T>, EventLoopError> {
         let xconn = match X11_BACKEND.lock().unwrap().as_ref() {
             Ok(xconn) => xconn.clone(),
             Err(_) => return Err(EventLoopError::NotSupported(NotSupportedError::new())),
         };
 
-        Ok(EventLoop::X(x11::EventLoop::new(xconn)))
+        Ok(EventLoop::X(x11::EventLoop::new(xconn, attributes)))
+    }
+
+    fn new_headless_any_thread() -> Result<EventLoop<T>, EventLoopError> {
+        headless::EventLoop::new().map(|event_loop| EventLoop::Headless(Box::new(event_loop)))
     }
 
     pub fn create_proxy(&self) -> EventLoopProxy<T> {
@@ -849,6 +966,7 @@ pub enum EventLoopWindowTarget {
     Wayland(wayland::EventLoopWindowTarget),
     #[cfg(x11_platform)]
     X(x11::EventLoopWindowTarget),
+    Headless(headless::EventLoopWindowTarget),
 }
 
 impl EventLoopWindowTarget {
@@ -857,11 +975,24 @@ impl EventLoopWindowTarget {
         match *self {
             #[cfg(wayland_platform)]
             EventLoopWindowTarget::Wayland(_) => true,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    pub fn is_x11(&self) -> bool {
+        match *self {
             #[cfg(x11_platform)]
+            EventLoopWindowTarget::X(_) => true,
             _ => false,
         }
     }
 
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        matches!(*self, EventLoopWindowTarget::Headless(_))
+    }
+
     #[inline]
     pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
         match *self {
@@ -874,6 +1005,10 @@ impl EventLoopWindowTarget {
             EventLoopWindowTarget::X(ref evlp) => {
                 evlp.available_monitors().map(MonitorHandle::X).collect()
             }
+            EventLoopWindowTarget::Headless(ref evlp) => evlp
+                .available_monitors()
+                .map(MonitorHandle::Headless)
+                .collect(),
         }
     }
 
@@ -929,6 +1064,7 @@ impl EventLoopWindowTarget {
             Self::X(conn) => OwnedDisplayHandle::X(conn.x_connection().clone()),
             #[cfg(wayland_platform)]
             Self::Wayland(conn) => OwnedDisplayHandle::Wayland(conn.connection.clone()),
+            Self::Headless(_) => OwnedDisplayHandle::Headless,
         }
     }
 
@@ -936,7 +1072,7 @@ impl EventLoopWindowTarget {
         x11_or_wayland!(match self; Self(evlp) => evlp.set_exit_code(code))
     }
 
-    fn exit_code(&self) -> Option<i32> {
+    pub(crate) fn exit_code(&self) -> Option<i32> {
         x11_or_wayland!(match self; Self(evlp) => evlp.exit_code())
     }
 }
@@ -948,6 +1084,7 @@ pub(crate) enum OwnedDisplayHandle {
     X(Arc<XConnection>),
     #[cfg(wayland_platform)]
     Wayland(wayland_client::Connection),
+    Headless,
 }
 
 impl OwnedDisplayHandle {
@@ -971,6 +1108,9 @@ impl OwnedDisplayHandle {
                 wayland_handle.display = conn.display().id().as_ptr() as *mut _;
                 wayland_handle.into()
             }
+
+            // There's no display, hand out the web one, like the headless windows do.
+            Self::Headless => rwh_05::WebDisplayHandle::empty().into(),
         }
     }
 
@@ -998,6 +1138,8 @@ impl OwnedDisplayHandle {
                 )
                 .into())
             }
+
+            Self::Headless => Err(rwh_06::HandleError::NotSupported),
         }
     }
 }
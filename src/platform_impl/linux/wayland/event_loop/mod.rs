@@ -23,8 +23,16 @@ use crate::event_loop::{
     ControlFlow, DeviceEvents, EventLoopWindowTarget as RootEventLoopWindowTarget,
 };
 use crate::platform::pump_events::PumpStatus;
+use crate::platform::synthetic_input::SyntheticInput;
+#[cfg(dbus_ime)]
+use crate::platform_impl::common::dbus_ime::{self, DBusIme};
+#[cfg(dbus_settings)]
+use crate::platform_impl::common::settings_portal;
 use crate::platform_impl::platform::min_timeout;
-use crate::platform_impl::{EventLoopWindowTarget as PlatformEventLoopWindowTarget, OsError};
+use crate::platform_impl::{
+    EventLoopWindowTarget as PlatformEventLoopWindowTarget, OsError,
+    PlatformSpecificEventLoopAttributes,
+};
 
 mod proxy;
 pub mod sink;
@@ -71,7 +79,10 @@ pub struct EventLoop<T: 'static> {
 }
 
 impl<T: 'static> EventLoop<T> {
-    pub fn new() -> Result<EventLoop<T>, EventLoopError> {
+    #[cfg_attr(not(dbus_ime), allow(unused_variables))]
+    pub fn new(
+        attributes: &PlatformSpecificEventLoopAttributes,
+    ) -> Result<EventLoop<T>, EventLoopError> {
         macro_rules! map_err {
             ($e:expr, $err:expr) => {
                 $e.map_err(|error| os_error!($err(error).into()))
@@ -94,6 +105,61 @@ impl<T: 'static> EventLoop<T> {
         let mut winit_state = WinitState::new(&globals, &queue_handle, event_loop.handle())
             .map_err(|error| os_error!(error))?;
 
+        // Talk to the input method on D-Bus when the compositor can't route the keys to it. This
+        // must happen before the keyboards are bound, in the roundtrip below.
+        #[cfg(dbus_ime)]
+        if let Some(backend) = attributes
+            .dbus_ime
+            .filter(|_| winit_state.text_input_state.is_none())
+        {
+            let (sender, channel) = calloop::channel::channel();
+            let sink = move |window_id, event| {
+                let _ = sender.send((window_id, event));
+            };
+            match DBusIme::new(backend, dbus_ime::Display::Wayland, sink) {
+                Ok(ime) => {
+                    let result = event_loop
+                        .handle()
+                        .insert_source(channel, |event, _, winit_state: &mut WinitState| {
+                            if let calloop::channel::Event::Msg((window_id, event)) = event {
+                                winit_state
+                                    .events_sink
+                                    .push_window_event(WindowEvent::Ime(event), window_id);
+                                winit_state.dispatched_events = true;
+                            }
+                        })
+                        .map_err(|error| error.error);
+                    map_err!(result, WaylandError::Calloop)?;
+                    winit_state.dbus_ime = Some(Arc::new(Mutex::new(ime)));
+                }
+                Err(err) => {
+                    log::warn!("Failed to connect to the input method on D-Bus: {err}");
+                }
+            }
+        }
+
+        // Follow the double-click settings of the desktop, when the settings portal has them.
+        #[cfg(dbus_settings)]
+        {
+            let (sender, channel) = calloop::channel::channel();
+            match settings_portal::watch_click_settings(sender) {
+                Ok(Some(settings)) => {
+                    settings.apply(&mut winit_state.click_counter);
+                    let result = event_loop
+                        .handle()
+                        .insert_source(channel, |event, _, winit_state: &mut WinitState| {
+                            if let calloop::channel::Event::Msg(settings) = event {
+                                settings.apply(&mut winit_state.click_counter);
+                            }
+                        })
+                        .map_err(|error| error.error);
+                    map_err!(result, WaylandError::Calloop)?;
+                }
+                Ok(None) => (),
+                Err(err) => log::warn!("Failed to read the settings portal: {err}"),
+            }
+        }
+
         // NOTE: do a roundtrip after binding the globals to prevent potential
         // races with the server.
         map_err!(
@@ -336,6 +402,13 @@ impl<T: 'static> EventLoop<T> {
         let mut buffer_sink = std::mem::take(&mut self.buffer_sink);
         let mut window_ids = std::mem::take(&mut self.window_ids);
 
+        // The events stamped with the time they were received at interleave with the ones
+        // stamped with the compositor time, so clamp them all on the way out.
+        let event_clock = self.with_state(|state| state.event_clock.clone());
+        let callback = &mut |event: Event<T>, target: &RootEventLoopWindowTarget| {
+            callback(event_clock.stamp(event), target)
+        };
+
         callback(Event::NewEvents(cause), &self.window_target);
 
         // NB: For consistency all platforms must emit a 'resumed' event even though Wayland
@@ -377,6 +450,7 @@ impl<T: 'static> EventLoop<T> {
                                 &new_inner_size,
                             )),
                         },
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -426,6 +500,7 @@ impl<T: 'static> EventLoop<T> {
                     Event::WindowEvent {
                         window_id: crate::window::WindowId(window_id),
                         event: WindowEvent::Resized(physical_size),
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -436,6 +511,7 @@ impl<T: 'static> EventLoop<T> {
                     Event::WindowEvent {
                         window_id: crate::window::WindowId(window_id),
                         event: WindowEvent::CloseRequested,
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -451,6 +527,19 @@ impl<T: 'static> EventLoop<T> {
             callback(event, &self.window_target);
         }
 
+        // Start the requested drags.
+        self.with_state(|state| {
+            let drags = state
+                .window_requests
+                .get_mut()
+                .iter()
+                .filter_map(|(window_id, requests)| Some((*window_id, requests.take_drag()?)))
+                .collect::<Vec<_>>();
+            for (window_id, source) in drags {
+                state.start_drag(window_id, source);
+            }
+        });
+
         // Handle non-synthetic events.
         self.with_state(|state| {
             buffer_sink.append(&mut state.events_sink);
@@ -504,6 +593,7 @@ impl<T: 'static> EventLoop<T> {
                     Event::WindowEvent {
                         window_id: crate::window::WindowId(window_id),
                         event,
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -518,6 +608,9 @@ impl<T: 'static> EventLoop<T> {
         // This is always the last event we dispatch before poll again
         callback(Event::AboutToWait, &self.window_target);
 
+        // The dropped data can't be read anymore.
+        self.with_state(|state| state.finish_drop());
+
         std::mem::swap(&mut self.compositor_updates, &mut compositor_updates);
         std::mem::swap(&mut self.buffer_sink, &mut buffer_sink);
         std::mem::swap(&mut self.window_ids, &mut window_ids);
@@ -536,7 +629,6 @@ impl<T: 'static> EventLoop<T> {
     fn with_state<'a, U: 'a, F: FnOnce(&'a mut WinitState) -> U>(&'a mut self, callback: F) -> U {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(x11_platform)]
             _ => unreachable!(),
         };
 
@@ -546,7 +638,6 @@ impl<T: 'static> EventLoop<T> {
     fn loop_dispatch<D: Into<Option<std::time::Duration>>>(&mut self, timeout: D) -> IOResult<()> {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
@@ -559,7 +650,6 @@ impl<T: 'static> EventLoop<T> {
     fn roundtrip(&mut self) -> Result<usize, RootOsError> {
         let state = match &mut self.window_target.p {
             PlatformEventLoopWindowTarget::Wayland(window_target) => window_target.state.get_mut(),
-            #[cfg(feature = "x11")]
             _ => unreachable!(),
         };
 
@@ -657,6 +747,17 @@ impl EventLoopWindowTarget {
     #[inline]
     pub fn listen_device_events(&self, _allowed: DeviceEvents) {}
 
+    /// Dispatch the synthetic input, the events are delivered during the next loop iteration.
+    pub(crate) fn inject_input(
+        &self,
+        window_id: WindowId,
+        input: SyntheticInput,
+    ) -> Result<(), OsError> {
+        self.state.borrow_mut().inject_input(window_id, input)?;
+        self.event_loop_awakener.ping();
+        Ok(())
+    }
+
     #[cfg(feature = "rwh_05")]
     #[inline]
     pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
@@ -1,5 +1,6 @@
 //! An event loop's sink to deliver events from the Wayland event callbacks.
 
+use std::time::Instant;
 use std::vec::Drain;
 
 use crate::event::{DeviceEvent, DeviceId as RootDeviceId, Event, WindowEvent};
@@ -26,21 +27,49 @@ impl EventSink {
         self.window_events.is_empty()
     }
 
-    /// Add new device event to a queue.
+    /// Add new device event, which was received now, to a queue.
+    ///
+    /// The timestamp is clamped with the compositor timestamps of the other events on delivery.
     #[inline]
     pub fn push_device_event(&mut self, event: DeviceEvent, device_id: DeviceId) {
+        self.push_timed_device_event(event, device_id, Instant::now());
+    }
+
+    /// Add new device event, which was generated at `timestamp`, to a queue.
+    #[inline]
+    pub fn push_timed_device_event(
+        &mut self,
+        event: DeviceEvent,
+        device_id: DeviceId,
+        timestamp: Instant,
+    ) {
         self.window_events.push(Event::DeviceEvent {
             event,
             device_id: RootDeviceId(PlatformDeviceId::Wayland(device_id)),
+            timestamp,
         });
     }
 
-    /// Add new window event to a queue.
+    /// Add new window event, which was received now, to a queue.
+    ///
+    /// The timestamp is clamped with the compositor timestamps of the other events on delivery.
     #[inline]
     pub fn push_window_event(&mut self, event: WindowEvent, window_id: WindowId) {
+        self.push_timed_window_event(event, window_id, Instant::now());
+    }
+
+    /// Add new window event, which was generated at `timestamp`, to a queue.
+    #[inline]
+    pub fn push_timed_window_event(
+        &mut self,
+        event: WindowEvent,
+        window_id: WindowId,
+        timestamp: Instant,
+    ) {
         self.window_events.push(Event::WindowEvent {
             event,
             window_id: RootWindowId(window_id),
+            timestamp,
         });
     }
 
@@ -0,0 +1,143 @@
+//! The data device, used for the clipboard and the drags from and over the windows.
+
+use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction;
+use sctk::reexports::client::protocol::wl_data_source::WlDataSource;
+use sctk::reexports::client::{Connection, QueueHandle};
+
+use sctk::data_device_manager::data_device::DataDeviceHandler;
+use sctk::data_device_manager::data_offer::{DataOfferHandler, DragOffer};
+use sctk::data_device_manager::data_source::DataSourceHandler;
+use sctk::data_device_manager::WritePipe;
+
+use crate::event::WindowEvent;
+use crate::platform_impl::wayland::state::WinitState;
+
+use super::drag_source::dnd_action_from_wl;
+
+impl DataDeviceHandler for WinitState {
+    fn enter(&mut self, _: &Connection, _: &QueueHandle<Self>, data_device: &WlDataDevice) {
+        self.enter_drop(data_device);
+    }
+
+    fn leave(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataDevice) {
+        self.handle_drop_leave();
+    }
+
+    fn motion(&mut self, _: &Connection, _: &QueueHandle<Self>, data_device: &WlDataDevice) {
+        self.handle_drop_motion(data_device);
+    }
+
+    fn selection(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataDevice) {
+        // The offer is kept by the data device until it's read.
+    }
+
+    fn drop_performed(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        data_device: &WlDataDevice,
+    ) {
+        self.handle_drop(data_device);
+    }
+}
+
+impl DataOfferHandler for WinitState {
+    fn source_actions(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &mut DragOffer,
+        _: DndAction,
+    ) {
+    }
+
+    fn selected_action(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &mut DragOffer,
+        _: DndAction,
+    ) {
+    }
+}
+
+impl DataSourceHandler for WinitState {
+    fn accept_mime(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &WlDataSource,
+        _: Option<String>,
+    ) {
+    }
+
+    fn send_request(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &WlDataSource,
+        mime_type: String,
+        pipe: WritePipe,
+    ) {
+        match (&self.selection, &self.drag) {
+            (Some(selection), _) if selection.source.inner() == source => {
+                selection.send(&mime_type, pipe)
+            }
+            (_, Some(drag)) if drag.source.inner() == source => drag.send(&mime_type, pipe),
+            _ => (),
+        }
+    }
+
+    fn cancelled(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        // Another client took the selection over.
+        if matches!(&self.selection, Some(selection) if selection.source.inner() == source) {
+            self.selection = None;
+        }
+
+        // The drop was rejected, or the drag was cancelled.
+        if matches!(&self.drag, Some(drag) if drag.source.inner() == source) {
+            let drag = self.drag.take().unwrap();
+            self.events_sink.push_window_event(
+                WindowEvent::DragSourceFinished { action: None },
+                drag.window_id,
+            );
+        }
+    }
+
+    fn dnd_dropped(&mut self, _: &Connection, _: &QueueHandle<Self>, _: &WlDataSource) {}
+
+    fn dnd_finished(&mut self, _: &Connection, _: &QueueHandle<Self>, source: &WlDataSource) {
+        if matches!(&self.drag, Some(drag) if drag.source.inner() == source) {
+            let drag = self.drag.take().unwrap();
+            self.events_sink.push_window_event(
+                WindowEvent::DragSourceFinished {
+                    action: drag.action,
+                },
+                drag.window_id,
+            );
+        }
+    }
+
+    fn action(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &WlDataSource,
+        action: DndAction,
+    ) {
+        let drag = match &mut self.drag {
+            Some(drag) if drag.source.inner() == source => drag,
+            _ => return,
+        };
+
+        let action = dnd_action_from_wl(action);
+        if drag.action != action {
+            drag.action = action;
+            self.events_sink
+                .push_window_event(WindowEvent::DragSourceAction { action }, drag.window_id);
+        }
+    }
+}
+
+sctk::delegate_data_device!(WinitState);
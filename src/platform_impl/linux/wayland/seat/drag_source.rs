@@ -0,0 +1,194 @@
+//! Dragging data out of the windows.
+
+use std::io::Write;
+
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction as WlDndAction;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Proxy;
+
+use sctk::data_device_manager::data_source::DragSource as WlDragSource;
+use sctk::data_device_manager::WritePipe;
+
+use crate::cursor::CursorImage;
+use crate::event::{DndAction, WindowEvent};
+use crate::platform::drag_and_drop::DragSource;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::types::cursor::CustomCursor;
+use crate::platform_impl::wayland::WindowId;
+
+/// The drag in progress.
+#[derive(Debug)]
+pub struct Drag {
+    /// The window the drag started from.
+    pub window_id: WindowId,
+    pub source: WlDragSource,
+    pub data: DragSource,
+    /// The action picked by the compositor, last reported to the window.
+    pub action: Option<DndAction>,
+    /// The surface showing the icon, with its buffer.
+    icon: Option<(WlSurface, CustomCursor)>,
+}
+
+impl Drag {
+    /// Send the data in the `mime_type` into the `pipe` of the target.
+    pub fn send(&self, mime_type: &str, mut pipe: WritePipe) {
+        // Write on a separate thread, since the target could be slow or never read at all.
+        if let Some(data) = self.data.data(mime_type) {
+            std::thread::spawn(move || {
+                if let Err(err) = pipe.write_all(&data) {
+                    log::warn!("Failed to send the dragged data: {err}");
+                }
+            });
+        }
+    }
+}
+
+impl Drop for Drag {
+    fn drop(&mut self) {
+        if let Some((surface, _)) = self.icon.take() {
+            surface.destroy();
+        }
+    }
+}
+
+impl WinitState {
+    /// Start dragging the `data` from the window, with the latest button press over it.
+    ///
+    /// The previous drag is cancelled.
+    pub fn start_drag(&mut self, window_id: WindowId, data: DragSource) {
+        if let Some(drag) = self.drag.take() {
+            self.events_sink.push_window_event(
+                WindowEvent::DragSourceFinished { action: None },
+                drag.window_id,
+            );
+        }
+
+        match self.create_drag(window_id, data) {
+            Ok(drag) => self.drag = Some(drag),
+            Err(err) => {
+                log::warn!("Failed to start the drag: {err}");
+                self.events_sink
+                    .push_window_event(WindowEvent::DragSourceFinished { action: None }, window_id);
+            }
+        }
+    }
+
+    fn create_drag(&self, window_id: WindowId, data: DragSource) -> Result<Drag, &'static str> {
+        let manager = self
+            .data_device_manager
+            .as_ref()
+            .ok_or("data device manager is not available")?;
+        let window = self
+            .windows
+            .borrow()
+            .get(&window_id)
+            .cloned()
+            .ok_or("the window is closed")?;
+        let window = window.lock().unwrap();
+        let (seat, serial) = window
+            .latest_button_press()
+            .ok_or("no button was pressed over the window")?;
+        let device = self
+            .seats
+            .get(&seat.id())
+            .and_then(|seat_state| seat_state.data_device.as_ref())
+            .ok_or("the seat has no data device")?;
+
+        let actions = match data.actions() {
+            [] => dnd_action_to_wl(data.preferred_action()),
+            actions => actions
+                .iter()
+                .fold(WlDndAction::empty(), |actions, &action| {
+                    actions | dnd_action_to_wl(action)
+                }),
+        };
+        let source =
+            manager.create_drag_and_drop_source(&window.queue_handle, data.mime_types(), actions);
+
+        let icon = data.icon().and_then(|icon| {
+            let icon = &icon.inner;
+            let image = CursorImage::from_rgba(
+                icon.rgba.clone(),
+                u16::try_from(icon.width).ok()?,
+                u16::try_from(icon.height).ok()?,
+                0,
+                0,
+            )
+            .map_err(|err| log::warn!("The drag icon can't be used: {err}"))
+            .ok()?;
+            let buffer = CustomCursor::new(&mut self.custom_cursor_pool.lock().unwrap(), &image);
+
+            // The icon is centered on the pointer, its origin being the hotspot.
+            let surface = self.compositor_state.create_surface(&window.queue_handle);
+            let (x, y) = (-buffer.w / 2, -buffer.h / 2);
+            if surface.version() >= 5 {
+                surface.attach(Some(buffer.buffer.wl_buffer()), 0, 0);
+                surface.offset(x, y);
+            } else {
+                surface.attach(Some(buffer.buffer.wl_buffer()), x, y);
+            }
+            if surface.version() >= 4 {
+                surface.damage_buffer(0, 0, buffer.w, buffer.h);
+            } else {
+                surface.damage(0, 0, buffer.w, buffer.h);
+            }
+            surface.commit();
+            Some((surface, buffer))
+        });
+
+        source.start_drag(
+            device,
+            window.window.wl_surface(),
+            icon.as_ref().map(|(surface, _)| surface),
+            serial,
+        );
+
+        Ok(Drag {
+            window_id,
+            source,
+            data,
+            action: None,
+            icon,
+        })
+    }
+}
+
+pub fn dnd_action_to_wl(action: DndAction) -> WlDndAction {
+    match action {
+        DndAction::Copy => WlDndAction::Copy,
+        DndAction::Move => WlDndAction::Move,
+        // There's no such action on Wayland.
+        DndAction::Link => WlDndAction::empty(),
+    }
+}
+
+/// The action picked by the compositor, `None` when the drop would be rejected.
+pub fn dnd_action_from_wl(action: WlDndAction) -> Option<DndAction> {
+    if action.contains(WlDndAction::Copy) {
+        Some(DndAction::Copy)
+    } else if action.contains(WlDndAction::Move) {
+        Some(DndAction::Move)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dnd_actions() {
+        for action in [DndAction::Copy, DndAction::Move] {
+            assert_eq!(dnd_action_from_wl(dnd_action_to_wl(action)), Some(action));
+        }
+        assert_eq!(dnd_action_from_wl(dnd_action_to_wl(DndAction::Link)), None);
+
+        // The compositor picks a single action, but the copy is preferred among several.
+        assert_eq!(
+            dnd_action_from_wl(WlDndAction::Copy | WlDndAction::Move),
+            Some(DndAction::Copy)
+        );
+        assert_eq!(dnd_action_from_wl(WlDndAction::Ask), None);
+    }
+}
@@ -0,0 +1,491 @@
+//! Dropping data on the windows.
+
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::PathBuf;
+
+use calloop::{PostAction, RegistrationToken};
+
+use sctk::reexports::client::protocol::wl_data_device::WlDataDevice;
+use sctk::reexports::client::protocol::wl_data_device_manager::DndAction as WlDndAction;
+use sctk::reexports::client::Proxy;
+
+use sctk::compositor::SurfaceData;
+use sctk::data_device_manager::data_device::DataDeviceData;
+use sctk::data_device_manager::data_offer::DragOffer;
+
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::{DndAction, WindowEvent};
+use crate::platform_impl::platform::common::dnd::{parse_uri_list, URI_LIST_MIME_TYPE};
+use crate::platform_impl::wayland::event_loop::EventLoopWindowTarget;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, WaylandError, WindowId};
+use crate::platform_impl::OsError;
+
+use super::drag_source::{dnd_action_from_wl, dnd_action_to_wl};
+use super::selection::read_pipe;
+
+/// The drag over one of the windows.
+#[derive(Debug)]
+pub struct DropOffer {
+    /// The window the drag is over.
+    window_id: WindowId,
+    offer: DragOffer,
+    /// The latest position of the drag.
+    position: PhysicalPosition<f64>,
+    /// The response of the application, when it set one.
+    response: Option<Option<DndAction>>,
+    state: DropState,
+    /// The source reading the dragged files.
+    files_token: Option<RegistrationToken>,
+}
+
+/// The progress of the drop, whose files are read without blocking.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct DropState {
+    /// Whether the data was dropped, and the drop waits to be finished.
+    dropped: bool,
+    /// Whether the dragged files are still being read.
+    reading_files: bool,
+    /// The dragged files, once they're read.
+    files: Option<Vec<PathBuf>>,
+}
+
+impl DropState {
+    /// Mark the data as dropped, returning the files to report as dropped.
+    ///
+    /// The files still being read are reported once they're read.
+    fn set_dropped(&mut self) -> &[PathBuf] {
+        self.dropped = true;
+        self.files.as_deref().unwrap_or_default()
+    }
+
+    /// Store the read files, returning whether they were already dropped.
+    fn set_files(&mut self, files: Vec<PathBuf>) -> bool {
+        self.reading_files = false;
+        self.files = Some(files);
+        self.dropped
+    }
+
+    /// Whether the drop can be finished, which waits for the files still being read.
+    fn can_finish(&self) -> bool {
+        self.dropped && !self.reading_files
+    }
+}
+
+impl DropOffer {
+    /// The action proposed by the source, the compositor only telling the allowed ones.
+    fn proposed_action(&self) -> DndAction {
+        dnd_action_from_wl(self.offer.source_actions).unwrap_or(DndAction::Copy)
+    }
+
+    /// The action performed on a drop, `None` when it's rejected.
+    ///
+    /// Files are accepted with the proposed action, unless the application responded otherwise.
+    fn action(&self) -> Option<DndAction> {
+        self.response.unwrap_or_else(|| {
+            self.offer
+                .with_mime_types(|types| types.iter().any(|ty| ty == URI_LIST_MIME_TYPE))
+                .then(|| self.proposed_action())
+        })
+    }
+
+    /// Tell the compositor whether the drop is accepted, and with which action.
+    fn respond(&self) {
+        match self.action() {
+            Some(action) => {
+                let mime_type = self.offer.with_mime_types(|types| types.first().cloned());
+                self.offer.accept_mime_type(self.offer.serial, mime_type);
+                let action = dnd_action_to_wl(action);
+                self.offer.set_actions(action, action);
+            }
+            None => {
+                self.offer.accept_mime_type(self.offer.serial, None);
+                self.offer
+                    .set_actions(WlDndAction::empty(), WlDndAction::empty());
+            }
+        }
+    }
+}
+
+impl WinitState {
+    /// Start the drag over the window, with the offer of the `data_device`.
+    pub fn enter_drop(&mut self, data_device: &WlDataDevice) {
+        self.finish_dropped();
+
+        let offer = match data_device
+            .data::<DataDeviceData>()
+            .and_then(|data| data.drag_offer())
+        {
+            Some(offer) => offer,
+            None => return,
+        };
+        // The drag could be over the decorations, which are subsurfaces of the window.
+        let surface = match offer.surface.data::<SurfaceData>() {
+            Some(data) => data.parent_surface().unwrap_or(&offer.surface),
+            None => return,
+        };
+        let window_id = wayland::make_wid(surface);
+        let position = match self.drop_position(window_id, &offer) {
+            Some(position) => position,
+            None => return,
+        };
+
+        let drop_offer = DropOffer {
+            window_id,
+            offer,
+            position,
+            response: None,
+            state: DropState::default(),
+            files_token: None,
+        };
+        drop_offer.respond();
+
+        self.events_sink.push_window_event(
+            WindowEvent::DragEntered {
+                position,
+                mime_types: drop_offer.offer.with_mime_types(<[String]>::to_vec),
+                actions: [DndAction::Copy, DndAction::Move]
+                    .into_iter()
+                    .filter(|&action| {
+                        drop_offer
+                            .offer
+                            .source_actions
+                            .contains(dnd_action_to_wl(action))
+                    })
+                    .collect(),
+            },
+            window_id,
+        );
+        self.events_sink.push_window_event(
+            WindowEvent::DragMoved {
+                position,
+                action: drop_offer.proposed_action(),
+            },
+            window_id,
+        );
+        self.drop_offer = Some(drop_offer);
+
+        self.read_drop_files();
+    }
+
+    /// Move the drag over the window, with the offer of the `data_device`.
+    pub fn handle_drop_motion(&mut self, data_device: &WlDataDevice) {
+        let offer = match data_device
+            .data::<DataDeviceData>()
+            .and_then(|data| data.drag_offer())
+        {
+            Some(offer) => offer,
+            None => return,
+        };
+        let window_id = match &self.drop_offer {
+            Some(drop_offer) if drop_offer.offer.inner() == offer.inner() => drop_offer.window_id,
+            _ => return,
+        };
+        let position = match self.drop_position(window_id, &offer) {
+            Some(position) => position,
+            None => return,
+        };
+
+        let drop_offer = self.drop_offer.as_mut().unwrap();
+        drop_offer.offer = offer;
+        drop_offer.position = position;
+        self.events_sink.push_window_event(
+            WindowEvent::DragMoved {
+                position,
+                action: drop_offer.proposed_action(),
+            },
+            window_id,
+        );
+    }
+
+    /// Drop the data on the window, with the offer of the `data_device`.
+    ///
+    /// An accepted drop finishes with [`Self::finish_drop`], once the application read the data
+    /// and the dragged files were read.
+    pub fn handle_drop(&mut self, data_device: &WlDataDevice) {
+        let offer = data_device
+            .data::<DataDeviceData>()
+            .and_then(|data| data.drag_offer());
+        let drop_offer = match (&mut self.drop_offer, offer) {
+            (Some(drop_offer), Some(offer)) if drop_offer.offer.inner() == offer.inner() => {
+                // The offer can only be read after the drop once it knows about it.
+                drop_offer.offer = offer;
+                drop_offer
+            }
+            _ => return,
+        };
+
+        match drop_offer.action() {
+            Some(action) => {
+                for path in drop_offer.state.set_dropped() {
+                    self.events_sink.push_window_event(
+                        WindowEvent::DroppedFile(path.clone()),
+                        drop_offer.window_id,
+                    );
+                }
+                self.events_sink.push_window_event(
+                    WindowEvent::DragDropped {
+                        position: drop_offer.position,
+                        action,
+                    },
+                    drop_offer.window_id,
+                );
+            }
+            None => {
+                let drop_offer = self.take_drop_offer().unwrap();
+                drop_offer.offer.destroy();
+                self.cancel_drop(&drop_offer);
+            }
+        }
+    }
+
+    /// Cancel the drag over the window.
+    ///
+    /// The compositor also sends it after a drop, which is kept until it's finished.
+    pub fn handle_drop_leave(&mut self) {
+        if matches!(&self.drop_offer, Some(drop_offer) if !drop_offer.state.dropped) {
+            // The offer is destroyed along with the data device's one.
+            let drop_offer = self.take_drop_offer().unwrap();
+            self.cancel_drop(&drop_offer);
+        }
+    }
+
+    /// Tell the source the dropped data was handled, once the application could read it.
+    ///
+    /// The drop waits for the dragged files still being read, to report them as dropped.
+    pub fn finish_drop(&mut self) {
+        if matches!(&self.drop_offer, Some(drop_offer) if drop_offer.state.can_finish()) {
+            self.finish_dropped();
+        }
+    }
+
+    /// Tell the source the dropped data was handled, even though the files are still read.
+    fn finish_dropped(&mut self) {
+        if matches!(&self.drop_offer, Some(drop_offer) if drop_offer.state.dropped) {
+            let drop_offer = self.take_drop_offer().unwrap();
+            drop_offer.offer.finish();
+            drop_offer.offer.destroy();
+        }
+    }
+
+    /// Tell the window the drag left it without a drop.
+    fn cancel_drop(&mut self, drop_offer: &DropOffer) {
+        self.events_sink
+            .push_window_event(WindowEvent::DragLeft, drop_offer.window_id);
+        if drop_offer.state.files.is_some() {
+            self.events_sink
+                .push_window_event(WindowEvent::HoveredFileCancelled, drop_offer.window_id);
+        }
+    }
+
+    /// Forget the drag, stopping the read of its files.
+    fn take_drop_offer(&mut self) -> Option<DropOffer> {
+        let drop_offer = self.drop_offer.take()?;
+        if let Some(token) = drop_offer.files_token {
+            self.loop_handle.remove(token);
+        }
+        Some(drop_offer)
+    }
+
+    /// Read the files offered by the drag, for `HoveredFile` and `DroppedFile`.
+    ///
+    /// They're read without blocking, since the source could be slow to send them.
+    fn read_drop_files(&mut self) {
+        let drop_offer = match &mut self.drop_offer {
+            Some(drop_offer) => drop_offer,
+            None => return,
+        };
+        if !drop_offer
+            .offer
+            .with_mime_types(|types| types.iter().any(|ty| ty == URI_LIST_MIME_TYPE))
+        {
+            return;
+        }
+
+        // The data dragged from one of the windows is only sent by the event loop.
+        if let Some(drag) = &self.drag {
+            let data = drag.data.data(URI_LIST_MIME_TYPE).unwrap_or_default();
+            self.hover_files(&data);
+            return;
+        }
+
+        let pipe = match drop_offer.offer.receive(URI_LIST_MIME_TYPE.to_owned()) {
+            Ok(pipe) => pipe,
+            Err(err) => {
+                log::warn!("Failed to read the dragged files: {err}");
+                return;
+            }
+        };
+
+        let data_offer = drop_offer.offer.inner().clone();
+        let mut data = Vec::new();
+        let result = self.loop_handle.insert_source(pipe, move |_, file, state| {
+            let mut buffer = [0; 4096];
+            let mut file: &File = file;
+            match file.read(&mut buffer) {
+                Ok(0) => (),
+                Ok(len) => {
+                    data.extend_from_slice(&buffer[..len]);
+                    return PostAction::Continue;
+                }
+                Err(err)
+                    if matches!(err.kind(), ErrorKind::Interrupted | ErrorKind::WouldBlock) =>
+                {
+                    return PostAction::Continue;
+                }
+                Err(err) => {
+                    log::warn!("Failed to read the dragged files: {err}");
+                    data.clear();
+                }
+            }
+
+            if let Some(drop_offer) = state
+                .drop_offer
+                .as_mut()
+                .filter(|drop_offer| *drop_offer.offer.inner() == data_offer)
+            {
+                drop_offer.files_token = None;
+                state.hover_files(&data);
+                state.dispatched_events = true;
+            }
+            PostAction::Remove
+        });
+
+        match result {
+            Ok(token) => {
+                drop_offer.files_token = Some(token);
+                drop_offer.state.reading_files = true;
+            }
+            Err(err) => log::warn!("Failed to read the dragged files: {}", err.error),
+        }
+    }
+
+    /// Tell the window about the dragged files in the `text/uri-list` data.
+    fn hover_files(&mut self, data: &[u8]) {
+        let drop_offer = match &mut self.drop_offer {
+            Some(drop_offer) => drop_offer,
+            None => return,
+        };
+        let files = match parse_uri_list(data) {
+            Ok(files) => files,
+            Err(_) => {
+                drop_offer.state.reading_files = false;
+                return;
+            }
+        };
+
+        for path in &files {
+            self.events_sink
+                .push_window_event(WindowEvent::HoveredFile(path.clone()), drop_offer.window_id);
+        }
+        // The files were dropped before they could be read.
+        if drop_offer.state.set_files(files.clone()) {
+            for path in files {
+                self.events_sink
+                    .push_window_event(WindowEvent::DroppedFile(path), drop_offer.window_id);
+            }
+        }
+    }
+
+    /// The position of the `offer` in the window.
+    fn drop_position(
+        &self,
+        window_id: WindowId,
+        offer: &DragOffer,
+    ) -> Option<PhysicalPosition<f64>> {
+        let windows = self.windows.borrow();
+        let scale_factor = windows.get(&window_id)?.lock().unwrap().scale_factor();
+        Some(LogicalPosition::new(offer.x, offer.y).to_physical(scale_factor))
+    }
+}
+
+impl EventLoopWindowTarget {
+    pub(crate) fn set_drop_action(&self, action: Option<DndAction>) {
+        let mut state = self.state.borrow_mut();
+        if let Some(drop_offer) = state
+            .drop_offer
+            .as_mut()
+            .filter(|offer| !offer.state.dropped)
+        {
+            drop_offer.response = Some(action);
+            drop_offer.respond();
+        }
+    }
+
+    pub(crate) fn drop_mime_types(&self) -> Vec<String> {
+        match &self.state.borrow().drop_offer {
+            Some(drop_offer) => drop_offer.offer.with_mime_types(<[String]>::to_vec),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn drop_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, OsError> {
+        let pipe = {
+            let state = self.state.borrow();
+            let drop_offer = match &state.drop_offer {
+                Some(drop_offer) => drop_offer,
+                None => return Ok(None),
+            };
+            if !drop_offer
+                .offer
+                .with_mime_types(|types| types.iter().any(|ty| ty == mime_type))
+            {
+                return Ok(None);
+            }
+
+            // The data dragged from one of the windows is only sent by the event loop, thus it
+            // can't be waited for.
+            if let Some(drag) = &state.drag {
+                return Ok(drag.data.data(mime_type));
+            }
+
+            drop_offer
+                .offer
+                .receive(mime_type.to_owned())
+                .map_err(|_| OsError::Misc("failed to create the pipe for the dropped data"))?
+        };
+
+        // Send the request before waiting for the data.
+        self.connection
+            .flush()
+            .map_err(|err| OsError::from(WaylandError::Wire(err)))?;
+
+        read_pipe(pipe).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_before_files_are_read() {
+        let files = vec![PathBuf::from("/tmp/a.txt")];
+        let mut state = DropState {
+            reading_files: true,
+            ..Default::default()
+        };
+
+        // The drop waits for the files, which are reported as dropped once they're read.
+        assert!(state.set_dropped().is_empty());
+        assert!(!state.can_finish());
+        assert!(state.set_files(files.clone()));
+        assert!(state.can_finish());
+        assert_eq!(state.files, Some(files));
+    }
+
+    #[test]
+    fn drop_after_files_are_read() {
+        let files = vec![PathBuf::from("/tmp/a.txt")];
+        let mut state = DropState {
+            reading_files: true,
+            ..Default::default()
+        };
+
+        assert!(!state.set_files(files.clone()));
+        assert!(!state.can_finish());
+        assert_eq!(state.set_dropped(), files);
+        assert!(state.can_finish());
+    }
+}
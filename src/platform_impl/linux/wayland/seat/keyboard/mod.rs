@@ -1,7 +1,9 @@
 //! The keyboard input handling.
 
+#[cfg(dbus_ime)]
+use std::sync::Arc;
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use calloop::timer::{TimeoutAction, Timer};
 use calloop::{LoopHandle, RegistrationToken};
@@ -14,14 +16,18 @@ use sctk::reexports::client::protocol::wl_keyboard::{
 use sctk::reexports::client::protocol::wl_seat::WlSeat;
 use sctk::reexports::client::{Connection, Dispatch, Proxy, QueueHandle, WEnum};
 
-use crate::event::{ElementState, WindowEvent};
+use crate::event::{ElementState, KeyboardLayout, WindowEvent};
 use crate::keyboard::ModifiersState;
 
-use crate::platform_impl::common::xkb_state::KbdState;
+#[cfg(dbus_ime)]
+use crate::platform_impl::common::dbus_ime::{DBusIme, KeyInput};
+use crate::platform_impl::common::xkb_state::{KbdState, SyntheticKeys};
 use crate::platform_impl::wayland::event_loop::sink::EventSink;
+use crate::platform_impl::wayland::event_loop::EventLoopWindowTarget;
 use crate::platform_impl::wayland::seat::WinitSeatState;
 use crate::platform_impl::wayland::state::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId, WindowId};
+use crate::platform_impl::OsError;
 
 impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
     fn event(
@@ -43,26 +49,39 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     WlKeymapFormat::NoKeymap => {
                         warn!("non-xkb compatible keymap")
                     }
-                    WlKeymapFormat::XkbV1 => unsafe {
-                        seat_state
-                            .keyboard_state
-                            .as_mut()
-                            .unwrap()
-                            .xkb_state
-                            .init_with_fd(fd, size as usize);
-                    },
+                    WlKeymapFormat::XkbV1 => {
+                        let xkb_state = &mut seat_state.keyboard_state.as_mut().unwrap().xkb_state;
+                        unsafe { xkb_state.init_with_fd(fd, size as usize) };
+
+                        let layout = state.keyboard_layouts.update(xkb_state);
+                        if let (Some(layout), Some(window_id)) =
+                            (layout, *data.window_id.lock().unwrap())
+                        {
+                            state.events_sink.push_window_event(
+                                WindowEvent::KeyboardLayoutChanged(layout),
+                                window_id,
+                            );
+                        }
+                    }
                     _ => unreachable!(),
                 },
                 WEnum::Unknown(value) => {
                     warn!("unknown keymap format 0x{:x}", value)
                 }
             },
-            WlKeyboardEvent::Enter { surface, .. } => {
+            WlKeyboardEvent::Enter {
+                surface, serial, ..
+            } => {
+                seat_state.input_serial = Some(serial);
                 let window_id = wayland::make_wid(&surface);
 
                 // Mark the window as focused.
                 match state.windows.get_mut().get(&window_id) {
-                    Some(window) => window.lock().unwrap().set_has_focus(true),
+                    Some(window) => {
+                        let mut window = window.lock().unwrap();
+                        window.set_has_focus(true);
+                        window.keyboard_entered(&data.seat);
+                    }
                     None => return,
                 };
 
@@ -102,10 +121,32 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 // NOTE: The check whether the window exists is essential as we might get a
                 // nil surface, regardless of what protocol says.
                 match state.windows.get_mut().get(&window_id) {
-                    Some(window) => window.lock().unwrap().set_has_focus(false),
+                    Some(window) => {
+                        let mut window = window.lock().unwrap();
+                        window.set_has_focus(false);
+                        window.keyboard_left(&data.seat);
+                    }
                     None => return,
                 };
 
+                // Release the synthetic keys, since the compositor doesn't know about them.
+                let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
+                let device_id =
+                    crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+                for event in keyboard_state
+                    .synthetic_keys
+                    .release_all(&mut keyboard_state.xkb_state)
+                {
+                    state.events_sink.push_window_event(
+                        WindowEvent::KeyboardInput {
+                            device_id,
+                            event,
+                            is_synthetic: true,
+                        },
+                        window_id,
+                    );
+                }
+
                 // Notify that no modifiers are being pressed.
                 state.events_sink.push_window_event(
                     WindowEvent::ModifiersChanged(ModifiersState::empty().into()),
@@ -123,8 +164,11 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
             WlKeyboardEvent::Key {
                 key,
                 state: WEnum::Value(WlKeyState::Pressed),
+                time,
+                serial,
                 ..
             } => {
+                seat_state.input_serial = Some(serial);
                 let key = key + 8;
 
                 key_input(
@@ -134,6 +178,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     key,
                     ElementState::Pressed,
                     false,
+                    state.event_clock.instant(time),
                 );
 
                 let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
@@ -180,6 +225,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                             repeat_keycode,
                             ElementState::Pressed,
                             true,
+                            Instant::now(),
                         );
 
                         // NOTE: the gap could change dynamically while repeat is going.
@@ -193,8 +239,11 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
             WlKeyboardEvent::Key {
                 key,
                 state: WEnum::Value(WlKeyState::Released),
+                time,
+                serial,
                 ..
             } => {
+                seat_state.input_serial = Some(serial);
                 let key = key + 8;
 
                 key_input(
@@ -204,6 +253,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     key,
                     ElementState::Released,
                     false,
+                    state.event_clock.instant(time),
                 );
 
                 let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
@@ -227,6 +277,7 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                 let xkb_state = &mut seat_state.keyboard_state.as_mut().unwrap().xkb_state;
                 xkb_state.update_modifiers(mods_depressed, mods_latched, mods_locked, 0, 0, group);
                 seat_state.modifiers = xkb_state.mods_state().into();
+                let layout = state.keyboard_layouts.update(xkb_state);
 
                 // HACK: part of the workaround from `WlKeyboardEvent::Enter`.
                 let window_id = match *data.window_id.lock().unwrap() {
@@ -241,6 +292,12 @@ impl Dispatch<WlKeyboard, KeyboardData, WinitState> for WinitState {
                     WindowEvent::ModifiersChanged(seat_state.modifiers.into()),
                     window_id,
                 );
+
+                if let Some(layout) = layout {
+                    state
+                        .events_sink
+                        .push_window_event(WindowEvent::KeyboardLayoutChanged(layout), window_id);
+                }
             }
             WlKeyboardEvent::RepeatInfo { rate, delay } => {
                 let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
@@ -282,6 +339,13 @@ pub struct KeyboardState {
 
     /// The current repeat raw key.
     pub current_repeat: Option<u32>,
+
+    /// The synthetic keys which are currently pressed, released when the window loses focus.
+    pub synthetic_keys: SyntheticKeys,
+
+    /// The input method on D-Bus, which gets the keys first.
+    #[cfg(dbus_ime)]
+    pub dbus_ime: Option<Arc<Mutex<DBusIme>>>,
 }
 
 impl KeyboardState {
@@ -293,6 +357,9 @@ impl KeyboardState {
             repeat_info: RepeatInfo::default(),
             repeat_token: None,
             current_repeat: None,
+            synthetic_keys: SyntheticKeys::default(),
+            #[cfg(dbus_ime)]
+            dbus_ime: None,
         }
     }
 }
@@ -363,6 +430,7 @@ fn key_input(
     keycode: u32,
     state: ElementState,
     repeat: bool,
+    timestamp: Instant,
 ) {
     let window_id = match *data.window_id.lock().unwrap() {
         Some(window_id) => window_id,
@@ -371,11 +439,60 @@ fn key_input(
 
     let keyboard_state = seat_state.keyboard_state.as_mut().unwrap();
 
+    // The input method consumes the keys it handles. It doesn't need their times, which the
+    // repeated keys don't have.
+    #[cfg(dbus_ime)]
+    if let Some(dbus_ime) = keyboard_state.dbus_ime.as_ref() {
+        let key = KeyInput::new(
+            keyboard_state.xkb_state.get_one_sym_raw(keycode),
+            keycode,
+            keyboard_state.xkb_state.mods_state(),
+            state == ElementState::Pressed,
+            0,
+        );
+        if dbus_ime.lock().unwrap().process_key(window_id, key) {
+            return;
+        }
+    }
+
     let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
     let event = keyboard_state
         .xkb_state
         .process_key_event(keycode, state, repeat);
 
+    event_sink.push_timed_window_event(
+        WindowEvent::KeyboardInput {
+            device_id,
+            event,
+            is_synthetic: false,
+        },
+        window_id,
+        timestamp,
+    );
+}
+
+/// Process the key injected by the application.
+pub(super) fn synthetic_key_input(
+    seat_state: &mut WinitSeatState,
+    event_sink: &mut EventSink,
+    window_id: WindowId,
+    keycode: u32,
+    state: ElementState,
+    repeat: bool,
+) -> Result<(), OsError> {
+    let keyboard_state = match seat_state.keyboard_state.as_mut() {
+        Some(keyboard_state) if keyboard_state.xkb_state.ready() => keyboard_state,
+        _ => return Err(OsError::Misc("no keymap available")),
+    };
+
+    let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+    let (event, modifiers_changed) = keyboard_state.synthetic_keys.process(
+        &mut keyboard_state.xkb_state,
+        keycode,
+        state,
+        repeat,
+    );
+
     event_sink.push_window_event(
         WindowEvent::KeyboardInput {
             device_id,
@@ -384,4 +501,25 @@ fn key_input(
         },
         window_id,
     );
+
+    // The compositor doesn't know about the key, so update the modifiers ourselves.
+    if modifiers_changed {
+        seat_state.modifiers = keyboard_state.xkb_state.mods_state().into();
+        event_sink.push_window_event(
+            WindowEvent::ModifiersChanged(seat_state.modifiers.into()),
+            window_id,
+        );
+    }
+
+    Ok(())
+}
+
+impl EventLoopWindowTarget {
+    pub(crate) fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.state.borrow().keyboard_layouts.active.clone()
+    }
+
+    pub(crate) fn keyboard_layouts(&self) -> Vec<String> {
+        self.state.borrow().keyboard_layouts.names.clone()
+    }
 }
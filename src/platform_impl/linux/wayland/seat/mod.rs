@@ -1,6 +1,7 @@
 //! Seat handling.
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use ahash::AHashMap;
 
@@ -10,24 +11,41 @@ use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 use sctk::reexports::protocols::wp::relative_pointer::zv1::client::zwp_relative_pointer_v1::ZwpRelativePointerV1;
 use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 
+use sctk::data_device_manager::data_device::DataDevice;
+use sctk::primary_selection::device::PrimarySelectionDevice;
 use sctk::seat::pointer::ThemeSpec;
 use sctk::seat::{Capability as SeatCapability, SeatHandler, SeatState};
 
+use crate::event::{Touch, WindowEvent};
 use crate::keyboard::ModifiersState;
+use crate::platform::synthetic_input::SyntheticInput;
+use crate::platform_impl::common::keymap;
 use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{DeviceId, WindowId};
+use crate::platform_impl::OsError;
 
+mod data_device;
+mod drag_source;
+mod drop_target;
 mod keyboard;
 mod pointer;
+mod primary_selection;
+mod selection;
 mod tablet;
 mod text_input;
 mod touch;
 
+pub use drag_source::Drag;
+pub use drop_target::DropOffer;
+pub use pointer::pointer_gestures::PointerGesturesState;
 pub use pointer::relative_pointer::RelativePointerState;
 pub use pointer::{PointerConstraintsState, WinitPointerData, WinitPointerDataExt};
+pub use selection::OwnedSelection;
 pub use tablet::{TabletPointer, TabletState};
-pub use text_input::{TextInputState, ZwpTextInputV3Ext};
+pub use text_input::{SurroundingText, TextInputState, ZwpTextInputV3Ext};
 
 use keyboard::{KeyboardData, KeyboardState};
+use pointer::pointer_gestures::PointerGestures;
 use text_input::TextInputData;
 use touch::TouchPoint;
 
@@ -48,6 +66,9 @@ pub struct WinitSeatState {
     /// The relative pointer bound on the seat.
     relative_pointer: Option<ZwpRelativePointerV1>,
 
+    /// The swipe, pinch and hold gestures bound on the seat.
+    pointer_gestures: Option<PointerGestures>,
+
     /// The keyboard bound on the seat.
     keyboard_state: Option<KeyboardState>,
 
@@ -56,11 +77,27 @@ pub struct WinitSeatState {
 
     /// Wether we have pending modifiers.
     modifiers_pending: bool,
+
+    /// The data device bound on the seat.
+    data_device: Option<DataDevice>,
+
+    /// The primary selection device bound on the seat.
+    primary_selection_device: Option<PrimarySelectionDevice>,
+
+    /// The serial of the latest input event on the seat, which the selection requests refer to.
+    input_serial: Option<u32>,
 }
 
 impl WinitSeatState {
-    pub fn new() -> Self {
-        Default::default()
+    pub fn new(
+        data_device: Option<DataDevice>,
+        primary_selection_device: Option<PrimarySelectionDevice>,
+    ) -> Self {
+        Self {
+            data_device,
+            primary_selection_device,
+            ..Default::default()
+        }
     }
 }
 
@@ -84,8 +121,13 @@ impl SeatHandler for WinitState {
             }
             SeatCapability::Keyboard if seat_state.keyboard_state.is_none() => {
                 let keyboard = seat.get_keyboard(queue_handle, KeyboardData::new(seat.clone()));
-                seat_state.keyboard_state =
-                    Some(KeyboardState::new(keyboard, self.loop_handle.clone()));
+                #[cfg_attr(not(dbus_ime), allow(unused_mut))]
+                let mut keyboard_state = KeyboardState::new(keyboard, self.loop_handle.clone());
+                #[cfg(dbus_ime)]
+                {
+                    keyboard_state.dbus_ime = self.dbus_ime.clone();
+                }
+                seat_state.keyboard_state = Some(keyboard_state);
             }
             SeatCapability::Pointer if seat_state.pointer.is_none() => {
                 let surface = self.compositor_state.create_surface(queue_handle);
@@ -111,6 +153,11 @@ impl SeatHandler for WinitState {
                     )
                 });
 
+                seat_state.pointer_gestures = self
+                    .pointer_gestures
+                    .as_ref()
+                    .map(|manager| manager.get_gestures(themed_pointer.pointer(), queue_handle));
+
                 let themed_pointer = Arc::new(super::GenericPointer::Default(themed_pointer));
 
                 // Register cursor surface.
@@ -158,6 +205,10 @@ impl SeatHandler for WinitState {
                     relative_pointer.destroy();
                 }
 
+                if let Some(pointer_gestures) = seat_state.pointer_gestures.take() {
+                    pointer_gestures.destroy();
+                }
+
                 if let Some(pointer @ super::GenericPointer::Default(themed)) =
                     seat_state.pointer.take().as_deref()
                 {
@@ -195,7 +246,18 @@ impl SeatHandler for WinitState {
         queue_handle: &QueueHandle<Self>,
         seat: WlSeat,
     ) {
-        self.seats.insert(seat.id(), WinitSeatState::new());
+        let data_device = self
+            .data_device_manager
+            .as_ref()
+            .map(|manager| manager.get_data_device(queue_handle, &seat));
+        let primary_selection_device = self
+            .primary_selection_manager
+            .as_ref()
+            .map(|manager| manager.get_selection_device(queue_handle, &seat));
+        self.seats.insert(
+            seat.id(),
+            WinitSeatState::new(data_device, primary_selection_device),
+        );
         if let Some(tablet) = &mut self.tablet {
             tablet.new_seat(queue_handle, seat);
         }
@@ -211,4 +273,81 @@ impl SeatHandler for WinitState {
     }
 }
 
+impl WinitState {
+    /// Dispatch the input injected by the application to the window.
+    pub fn inject_input(
+        &mut self,
+        window_id: WindowId,
+        input: SyntheticInput,
+    ) -> Result<(), OsError> {
+        if !self.windows.get_mut().contains_key(&window_id) {
+            return Err(OsError::Misc("no such window"));
+        }
+
+        let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+        let event = match input {
+            SyntheticInput::KeyboardInput {
+                physical_key,
+                state,
+                repeat,
+            } => {
+                let keycode = keymap::physicalkey_to_scancode(physical_key)
+                    .ok_or(OsError::Misc("the key has no scancode"))?
+                    + 8;
+
+                // Use the first seat with a keyboard.
+                let seat_state = self
+                    .seats
+                    .values_mut()
+                    .find(|seat_state| seat_state.keyboard_state.is_some())
+                    .ok_or(OsError::Misc("no keyboard available"))?;
+
+                return keyboard::synthetic_key_input(
+                    seat_state,
+                    &mut self.events_sink,
+                    window_id,
+                    keycode,
+                    state,
+                    repeat,
+                );
+            }
+            SyntheticInput::CursorMoved { position } => {
+                self.synthetic_cursor_position = position;
+                WindowEvent::CursorMoved {
+                    device_id,
+                    position,
+                }
+            }
+            SyntheticInput::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                click_count: self.click_counter.count(
+                    crate::window::WindowId(window_id),
+                    state,
+                    button,
+                    self.synthetic_cursor_position,
+                    Instant::now(),
+                ),
+            },
+            SyntheticInput::Touch {
+                phase,
+                location,
+                force,
+                id,
+            } => WindowEvent::Touch(Touch {
+                device_id,
+                phase,
+                location,
+                force,
+                id,
+            }),
+            SyntheticInput::Ime(ime) => WindowEvent::Ime(ime),
+        };
+
+        self.events_sink.push_window_event(event, window_id);
+        Ok(())
+    }
+}
+
 sctk::delegate_seat!(WinitState);
@@ -29,6 +29,7 @@ use crate::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase, Wind
 use crate::platform_impl::wayland::state::WinitState;
 use crate::platform_impl::wayland::{self, DeviceId, WindowId};
 
+pub mod pointer_gestures;
 pub mod relative_pointer;
 
 impl PointerHandler for WinitState {
@@ -44,6 +45,7 @@ impl PointerHandler for WinitState {
 
         let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
 
+        let mut input_serial = None;
         for event in events {
             let surface = &event.surface;
 
@@ -144,17 +146,27 @@ impl PointerHandler for WinitState {
                     self.events_sink
                         .push_window_event(WindowEvent::CursorLeft { device_id }, window_id);
                 }
-                PointerEventKind::Motion { .. } => {
-                    self.events_sink.push_window_event(
+                PointerEventKind::Motion { time } => {
+                    let timestamp = self.event_clock.instant(time);
+                    self.events_sink.push_timed_window_event(
                         WindowEvent::CursorMoved {
                             device_id,
                             position,
                         },
                         window_id,
+                        timestamp,
                     );
                 }
-                ref kind @ PointerEventKind::Press { button, serial, .. }
-                | ref kind @ PointerEventKind::Release { button, serial, .. } => {
+                ref kind @ PointerEventKind::Press {
+                    button,
+                    serial,
+                    time,
+                }
+                | ref kind @ PointerEventKind::Release {
+                    button,
+                    serial,
+                    time,
+                } => {
                     // Update the last button serial.
                     pointer
                         .winit_data()
@@ -162,6 +174,7 @@ impl PointerHandler for WinitState {
                         .lock()
                         .unwrap()
                         .latest_button_serial = serial;
+                    input_serial = Some(serial);
 
                     let button = wayland_button_to_winit(button);
                     let state = if matches!(kind, PointerEventKind::Press { .. }) {
@@ -169,16 +182,27 @@ impl PointerHandler for WinitState {
                     } else {
                         ElementState::Released
                     };
-                    self.events_sink.push_window_event(
+                    let timestamp = self.event_clock.instant(time);
+                    let click_count = self.click_counter.count(
+                        crate::window::WindowId(window_id),
+                        state,
+                        button,
+                        position,
+                        timestamp,
+                    );
+                    self.events_sink.push_timed_window_event(
                         WindowEvent::MouseInput {
                             device_id,
                             state,
                             button,
+                            click_count,
                         },
                         window_id,
+                        timestamp,
                     );
                 }
                 PointerEventKind::Axis {
+                    time,
                     horizontal,
                     vertical,
                     ..
@@ -222,17 +246,23 @@ impl PointerHandler for WinitState {
                         )
                     };
 
-                    self.events_sink.push_window_event(
+                    let timestamp = self.event_clock.instant(time);
+                    self.events_sink.push_timed_window_event(
                         WindowEvent::MouseWheel {
                             device_id,
                             delta,
                             phase,
                         },
                         window_id,
+                        timestamp,
                     )
                 }
             }
         }
+
+        if let Some(serial) = input_serial {
+            self.seats.get_mut(&seat.id()).unwrap().input_serial = Some(serial);
+        }
     }
 }
 
@@ -0,0 +1,334 @@
+//! Pointer gestures.
+
+use std::ops::Deref;
+use std::sync::Mutex;
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_pointer::WlPointer;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{delegate_dispatch, Dispatch};
+use sctk::reexports::client::{Connection, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::pointer_gestures::zv1::{
+    client::zwp_pointer_gesture_hold_v1::{self, ZwpPointerGestureHoldV1},
+    client::zwp_pointer_gesture_pinch_v1::{self, ZwpPointerGesturePinchV1},
+    client::zwp_pointer_gesture_swipe_v1::{self, ZwpPointerGestureSwipeV1},
+    client::zwp_pointer_gestures_v1::ZwpPointerGesturesV1,
+};
+
+use sctk::compositor::SurfaceData;
+use sctk::globals::GlobalData;
+
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::{TouchPhase, WindowEvent};
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::{self, DeviceId, WindowId};
+
+/// Wrapper around the pointer gestures.
+pub struct PointerGesturesState {
+    manager: ZwpPointerGesturesV1,
+}
+
+impl PointerGesturesState {
+    /// Create new pointer gestures manager.
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=3, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Get the gestures of the `pointer`.
+    pub fn get_gestures(
+        &self,
+        pointer: &WlPointer,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> PointerGestures {
+        PointerGestures {
+            swipe: self.get_swipe_gesture(pointer, queue_handle, Default::default()),
+            pinch: self.get_pinch_gesture(pointer, queue_handle, Default::default()),
+            // The hold gesture was added in version 3.
+            hold: (self.version() >= 3)
+                .then(|| self.get_hold_gesture(pointer, queue_handle, Default::default())),
+        }
+    }
+}
+
+impl Deref for PointerGesturesState {
+    type Target = ZwpPointerGesturesV1;
+
+    fn deref(&self) -> &Self::Target {
+        &self.manager
+    }
+}
+
+/// The gestures bound on a pointer.
+#[derive(Debug)]
+pub struct PointerGestures {
+    swipe: ZwpPointerGestureSwipeV1,
+    pinch: ZwpPointerGesturePinchV1,
+    hold: Option<ZwpPointerGestureHoldV1>,
+}
+
+impl PointerGestures {
+    pub fn destroy(self) {
+        self.swipe.destroy();
+        self.pinch.destroy();
+        if let Some(hold) = self.hold {
+            hold.destroy();
+        }
+    }
+}
+
+/// The state of the gesture in progress, since only its begin event tells the surface.
+#[derive(Debug, Default)]
+pub struct GestureData {
+    inner: Mutex<GestureDataInner>,
+}
+
+#[derive(Debug, Default)]
+struct GestureDataInner {
+    window_id: Option<WindowId>,
+    fingers: u32,
+    /// The scale of the pinch at the previous event.
+    scale: f64,
+}
+
+impl GestureData {
+    /// Start the gesture of the `fingers` on the window of the `surface`.
+    fn begin(&self, surface: &WlSurface, fingers: u32) -> WindowId {
+        // The gestures on the decorations belong to the window.
+        let surface = surface
+            .data::<SurfaceData>()
+            .and_then(|data| data.parent_surface())
+            .unwrap_or(surface);
+        let window_id = wayland::make_wid(surface);
+        *self.inner.lock().unwrap() = GestureDataInner {
+            window_id: Some(window_id),
+            fingers,
+            scale: 1.0,
+        };
+        window_id
+    }
+}
+
+impl GestureDataInner {
+    /// Update the `scale` of the pinch, returning the magnification since the previous event.
+    fn pinch(&mut self, scale: f64) -> f64 {
+        // The scale is relative to the beginning of the gesture, while the delta is applied to
+        // the magnification of the previous event.
+        let magnification = scale / self.scale - 1.0;
+        self.scale = scale;
+        magnification
+    }
+}
+
+/// The phase of the gesture ending, with `cancelled` when it wasn't completed.
+fn end_phase(cancelled: i32) -> TouchPhase {
+    match cancelled {
+        0 => TouchPhase::Ended,
+        _ => TouchPhase::Cancelled,
+    }
+}
+
+impl Dispatch<ZwpPointerGesturesV1, GlobalData, WinitState> for PointerGesturesState {
+    fn event(
+        _state: &mut WinitState,
+        _proxy: &ZwpPointerGesturesV1,
+        _event: <ZwpPointerGesturesV1 as wayland_client::Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwpPointerGestureSwipeV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGestureSwipeV1,
+        event: <ZwpPointerGestureSwipeV1 as wayland_client::Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let (window_id, time, delta, phase) = match event {
+            zwp_pointer_gesture_swipe_v1::Event::Begin {
+                time,
+                surface,
+                fingers,
+                ..
+            } => {
+                let window_id = data.begin(&surface, fingers);
+                (window_id, time, (0.0, 0.0), TouchPhase::Started)
+            }
+            zwp_pointer_gesture_swipe_v1::Event::Update { time, dx, dy } => {
+                match data.inner.lock().unwrap().window_id {
+                    Some(window_id) => (window_id, time, (dx, dy), TouchPhase::Moved),
+                    None => return,
+                }
+            }
+            zwp_pointer_gesture_swipe_v1::Event::End {
+                time, cancelled, ..
+            } => match data.inner.lock().unwrap().window_id.take() {
+                Some(window_id) => (window_id, time, (0.0, 0.0), end_phase(cancelled)),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let scale_factor = match state.windows.get_mut().get(&window_id) {
+            Some(window) => window.lock().unwrap().scale_factor(),
+            None => return,
+        };
+        let delta: PhysicalPosition<f64> =
+            LogicalPosition::new(delta.0, delta.1).to_physical(scale_factor);
+
+        let timestamp = state.event_clock.instant(time);
+        state.events_sink.push_timed_window_event(
+            WindowEvent::SwipeGesture {
+                device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                    DeviceId,
+                )),
+                fingers: data.inner.lock().unwrap().fingers,
+                delta,
+                phase,
+            },
+            window_id,
+            timestamp,
+        );
+    }
+}
+
+impl Dispatch<ZwpPointerGesturePinchV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGesturePinchV1,
+        event: <ZwpPointerGesturePinchV1 as wayland_client::Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let (window_id, time, magnification, rotation, phase) = match event {
+            zwp_pointer_gesture_pinch_v1::Event::Begin {
+                time,
+                surface,
+                fingers,
+                ..
+            } => {
+                let window_id = data.begin(&surface, fingers);
+                (window_id, time, 0.0, 0.0, TouchPhase::Started)
+            }
+            zwp_pointer_gesture_pinch_v1::Event::Update {
+                time,
+                scale,
+                rotation,
+                ..
+            } => {
+                let mut inner = data.inner.lock().unwrap();
+                let Some(window_id) = inner.window_id else {
+                    return;
+                };
+                let magnification = inner.pinch(scale);
+                // The rotation is clockwise.
+                (window_id, time, magnification, -rotation, TouchPhase::Moved)
+            }
+            zwp_pointer_gesture_pinch_v1::Event::End {
+                time, cancelled, ..
+            } => match data.inner.lock().unwrap().window_id.take() {
+                Some(window_id) => (window_id, time, 0.0, 0.0, end_phase(cancelled)),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let timestamp = state.event_clock.instant(time);
+        let device_id = crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(DeviceId));
+        state.events_sink.push_timed_window_event(
+            WindowEvent::PinchGesture {
+                device_id,
+                delta: magnification,
+                phase,
+            },
+            window_id,
+            timestamp,
+        );
+        state.events_sink.push_timed_window_event(
+            WindowEvent::RotationGesture {
+                device_id,
+                delta: rotation as f32,
+                phase,
+            },
+            window_id,
+            timestamp,
+        );
+    }
+}
+
+impl Dispatch<ZwpPointerGestureHoldV1, GestureData, WinitState> for PointerGesturesState {
+    fn event(
+        state: &mut WinitState,
+        _proxy: &ZwpPointerGestureHoldV1,
+        event: <ZwpPointerGestureHoldV1 as wayland_client::Proxy>::Event,
+        data: &GestureData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let (window_id, time, phase) = match event {
+            zwp_pointer_gesture_hold_v1::Event::Begin {
+                time,
+                surface,
+                fingers,
+                ..
+            } => (data.begin(&surface, fingers), time, TouchPhase::Started),
+            zwp_pointer_gesture_hold_v1::Event::End {
+                time, cancelled, ..
+            } => match data.inner.lock().unwrap().window_id.take() {
+                Some(window_id) => (window_id, time, end_phase(cancelled)),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let timestamp = state.event_clock.instant(time);
+        state.events_sink.push_timed_window_event(
+            WindowEvent::HoldGesture {
+                device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                    DeviceId,
+                )),
+                fingers: data.inner.lock().unwrap().fingers,
+                phase,
+            },
+            window_id,
+            timestamp,
+        );
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpPointerGesturesV1: GlobalData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGestureSwipeV1: GestureData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGesturePinchV1: GestureData] => PointerGesturesState);
+delegate_dispatch!(WinitState: [ZwpPointerGestureHoldV1: GestureData] => PointerGesturesState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinch_magnification_is_relative_to_the_previous_event() {
+        let mut inner = GestureDataInner {
+            scale: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(inner.pinch(1.5), 0.5);
+        assert_eq!(inner.pinch(3.0), 1.0);
+        assert_eq!(inner.pinch(1.5), -0.5);
+        assert_eq!(inner.pinch(1.5), 0.0);
+    }
+
+    #[test]
+    fn cancelled_gestures_end_with_the_cancelled_phase() {
+        assert_eq!(end_phase(0), TouchPhase::Ended);
+        assert_eq!(end_phase(1), TouchPhase::Cancelled);
+    }
+}
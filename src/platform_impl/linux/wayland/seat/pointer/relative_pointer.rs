@@ -61,16 +61,22 @@ impl Dispatch<ZwpRelativePointerV1, GlobalData, WinitState> for RelativePointerS
         _qhandle: &QueueHandle<WinitState>,
     ) {
         if let zwp_relative_pointer_v1::Event::RelativeMotion {
+            utime_hi,
+            utime_lo,
             dx_unaccel,
             dy_unaccel,
             ..
         } = event
         {
-            state.events_sink.push_device_event(
+            // The timestamp has microsecond granularity, while the clock works with milliseconds.
+            let time = ((utime_hi as u64) << 32 | utime_lo as u64) / 1000;
+            let timestamp = state.event_clock.instant(time as u32);
+            state.events_sink.push_timed_device_event(
                 DeviceEvent::MouseMotion {
                     delta: (dx_unaccel, dy_unaccel),
                 },
                 super::DeviceId,
+                timestamp,
             );
         }
     }
@@ -0,0 +1,55 @@
+//! The primary selection device.
+
+use sctk::reexports::client::{Connection, QueueHandle};
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_device_v1::ZwpPrimarySelectionDeviceV1;
+use sctk::reexports::protocols::wp::primary_selection::zv1::client::zwp_primary_selection_source_v1::ZwpPrimarySelectionSourceV1;
+
+use sctk::data_device_manager::WritePipe;
+use sctk::primary_selection::device::PrimarySelectionDeviceHandler;
+use sctk::primary_selection::selection::PrimarySelectionSourceHandler;
+
+use crate::platform_impl::wayland::state::WinitState;
+
+impl PrimarySelectionDeviceHandler for WinitState {
+    fn selection(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        _: &ZwpPrimarySelectionDeviceV1,
+    ) {
+        // The offer is kept by the device until it's read.
+    }
+}
+
+impl PrimarySelectionSourceHandler for WinitState {
+    fn send_request(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &ZwpPrimarySelectionSourceV1,
+        mime_type: String,
+        pipe: WritePipe,
+    ) {
+        match &self.primary_selection {
+            Some(selection) if selection.source.inner() == source => {
+                selection.send(&mime_type, pipe)
+            }
+            _ => (),
+        }
+    }
+
+    fn cancelled(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        source: &ZwpPrimarySelectionSourceV1,
+    ) {
+        // Another client took the selection over.
+        if matches!(&self.primary_selection, Some(selection) if selection.source.inner() == source)
+        {
+            self.primary_selection = None;
+        }
+    }
+}
+
+sctk::delegate_primary_selection!(WinitState);
@@ -0,0 +1,242 @@
+//! The clipboard and the primary selection.
+
+use std::io::{Read, Write};
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::time::{Duration, Instant};
+
+use sctk::data_device_manager::{ReadPipe, WritePipe};
+
+use crate::platform::clipboard::{ClipboardContents, SelectionKind};
+use crate::platform_impl::wayland::event_loop::EventLoopWindowTarget;
+use crate::platform_impl::wayland::WaylandError;
+use crate::platform_impl::OsError;
+
+/// How long to wait for the owner of the selection to send the data.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The selection while it's owned by the application.
+#[derive(Debug)]
+pub struct OwnedSelection<S> {
+    pub source: S,
+    pub contents: ClipboardContents,
+}
+
+impl<S> OwnedSelection<S> {
+    /// Send the data in the `mime_type` into the `pipe` of the requestor.
+    pub fn send(&self, mime_type: &str, mut pipe: WritePipe) {
+        // Write on a separate thread, since the reader could be slow or never read at all.
+        if let Some(data) = self.contents.shared_data(mime_type) {
+            std::thread::spawn(move || {
+                if let Err(err) = pipe.write_all(&data) {
+                    log::warn!("Failed to send the selection data: {err}");
+                }
+            });
+        }
+    }
+}
+
+impl EventLoopWindowTarget {
+    pub(crate) fn set_selection(
+        &self,
+        kind: SelectionKind,
+        contents: ClipboardContents,
+    ) -> Result<(), OsError> {
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+
+        // The selection is set on behalf of the seat the user interacted with.
+        match kind {
+            SelectionKind::Clipboard => {
+                let manager = state
+                    .data_device_manager
+                    .as_ref()
+                    .ok_or(OsError::Misc("data device manager is not available"))?;
+                let (device, serial) = state
+                    .seats
+                    .values()
+                    .find_map(|seat_state| {
+                        Some((seat_state.data_device.as_ref()?, seat_state.input_serial?))
+                    })
+                    .ok_or(OsError::Misc("no seat with an input event available"))?;
+
+                if contents.is_empty() {
+                    // Only give up the selection when it's ours, not to clear the one of another
+                    // client.
+                    if state.selection.take().is_some() {
+                        device.unset_selection(serial);
+                    }
+                    return Ok(());
+                }
+
+                let source =
+                    manager.create_copy_paste_source(&self.queue_handle, contents.mime_types());
+                source.set_selection(device, serial);
+                state.selection = Some(OwnedSelection { source, contents });
+            }
+            SelectionKind::Primary => {
+                let manager = state
+                    .primary_selection_manager
+                    .as_ref()
+                    .ok_or(OsError::Misc("primary selection manager is not available"))?;
+                let (device, serial) = state
+                    .seats
+                    .values()
+                    .find_map(|seat_state| {
+                        Some((
+                            seat_state.primary_selection_device.as_ref()?,
+                            seat_state.input_serial?,
+                        ))
+                    })
+                    .ok_or(OsError::Misc("no seat with an input event available"))?;
+
+                if contents.is_empty() {
+                    if state.primary_selection.take().is_some() {
+                        device.unset_selection(serial);
+                    }
+                    return Ok(());
+                }
+
+                let source =
+                    manager.create_selection_source(&self.queue_handle, contents.mime_types());
+                source.set_selection(device, serial);
+                state.primary_selection = Some(OwnedSelection { source, contents });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn selection_mime_types(&self, kind: SelectionKind) -> Result<Vec<String>, OsError> {
+        let state = self.state.borrow();
+        let mime_types = match kind {
+            SelectionKind::Clipboard => {
+                if let Some(selection) = &state.selection {
+                    return Ok(selection.contents.mime_types().map(String::from).collect());
+                }
+
+                state
+                    .seats
+                    .values()
+                    .filter_map(|seat_state| {
+                        seat_state.data_device.as_ref()?.data().selection_offer()
+                    })
+                    .map(|offer| offer.with_mime_types(<[String]>::to_vec))
+                    .next()
+            }
+            SelectionKind::Primary => {
+                if let Some(selection) = &state.primary_selection {
+                    return Ok(selection.contents.mime_types().map(String::from).collect());
+                }
+
+                state
+                    .seats
+                    .values()
+                    .filter_map(|seat_state| {
+                        seat_state
+                            .primary_selection_device
+                            .as_ref()?
+                            .data()
+                            .selection_offer()
+                    })
+                    .map(|offer| offer.with_mime_types(<[String]>::to_vec))
+                    .next()
+            }
+        };
+
+        Ok(mime_types.unwrap_or_default())
+    }
+
+    pub(crate) fn selection_data(
+        &self,
+        kind: SelectionKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, OsError> {
+        let offers_mime_type = |types: &[String]| types.iter().any(|ty| ty == mime_type);
+
+        let pipe = {
+            let state = self.state.borrow();
+            let pipe = match kind {
+                SelectionKind::Clipboard => {
+                    if let Some(selection) = &state.selection {
+                        return Ok(selection.contents.data(mime_type).map(Vec::from));
+                    }
+
+                    state
+                        .seats
+                        .values()
+                        .filter_map(|seat_state| {
+                            seat_state.data_device.as_ref()?.data().selection_offer()
+                        })
+                        .find(|offer| offer.with_mime_types(offers_mime_type))
+                        .map(|offer| offer.receive(mime_type.to_owned()).map_err(|_| ()))
+                }
+                SelectionKind::Primary => {
+                    if let Some(selection) = &state.primary_selection {
+                        return Ok(selection.contents.data(mime_type).map(Vec::from));
+                    }
+
+                    state
+                        .seats
+                        .values()
+                        .filter_map(|seat_state| {
+                            seat_state
+                                .primary_selection_device
+                                .as_ref()?
+                                .data()
+                                .selection_offer()
+                        })
+                        .find(|offer| offer.with_mime_types(offers_mime_type))
+                        .map(|offer| offer.receive(mime_type.to_owned()).map_err(|_| ()))
+                }
+            };
+
+            match pipe {
+                Some(pipe) => pipe.map_err(|_| {
+                    OsError::Misc("failed to create the pipe for the selection data")
+                })?,
+                None => return Ok(None),
+            }
+        };
+
+        // Send the request before waiting for the data.
+        self.connection
+            .flush()
+            .map_err(|err| OsError::from(WaylandError::Wire(err)))?;
+
+        read_pipe(pipe).map(Some)
+    }
+}
+
+/// Read the data from the `pipe` until the owner of the selection closes it.
+pub fn read_pipe(mut pipe: ReadPipe) -> Result<Vec<u8>, OsError> {
+    let deadline = Instant::now() + TIMEOUT;
+    let mut data = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let mut fd = libc::pollfd {
+            fd: pipe.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as c_int) };
+        if ready < 0 {
+            if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(OsError::Misc("failed to wait for the selection data"));
+        } else if ready == 0 {
+            return Err(OsError::Misc(
+                "the selection owner didn't send the data in time",
+            ));
+        }
+
+        match pipe.read(&mut buffer) {
+            Ok(0) => return Ok(data),
+            Ok(len) => data.extend_from_slice(&buffer[..len]),
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(_) => return Err(OsError::Misc("failed to read the selection data")),
+        }
+    }
+}
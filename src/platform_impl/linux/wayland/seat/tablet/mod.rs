@@ -10,7 +10,15 @@ use sctk::reexports::client::Proxy;
 use sctk::reexports::client::{delegate_dispatch, Dispatch};
 use sctk::reexports::client::{Connection, QueueHandle};
 use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_manager_v2::ZwpTabletManagerV2;
-use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_group_v2::ZwpTabletPadGroupV2;
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_group_v2::{
+    self, ZwpTabletPadGroupV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_ring_v2::{
+    self, ZwpTabletPadRingV2,
+};
+use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_strip_v2::{
+    self, ZwpTabletPadStripV2,
+};
 use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_pad_v2::{
     self, ZwpTabletPadV2,
 };
@@ -22,8 +30,14 @@ use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_tool_v2::{
 };
 use sctk::reexports::protocols::wp::tablet::zv2::client::zwp_tablet_v2::{self, ZwpTabletV2};
 
+use std::time::Instant;
+
 use crate::dpi::PhysicalPosition;
-use crate::event::{DeviceEvent, WindowEvent};
+use crate::event::{
+    DeviceEvent, TabletInfo, TabletPadSource, TabletToolCapabilities, TabletToolInfo,
+    TabletToolType, WindowEvent,
+};
+use crate::platform_impl::wayland::event_loop::sink::EventSink;
 use crate::platform_impl::wayland::state::WinitState;
 use crate::platform_impl::wayland::{make_wid, DeviceId};
 
@@ -31,8 +45,11 @@ use crate::platform_impl::wayland::{make_wid, DeviceId};
 pub struct TabletState {
     pub manager: ZwpTabletManagerV2,
     pub seats: Vec<(ZwpTabletSeatV2, WlSeat)>,
-    pub tablets: Vec<ZwpTabletV2>,
+    pub tablets: ahash::AHashMap<ObjectId, TabletInfo>,
     pub pads: ahash::AHashMap<ObjectId, PadData>,
+    pub pad_groups: ahash::AHashMap<ObjectId, PadGroupData>,
+    pub pad_rings: ahash::AHashMap<ObjectId, PadControlData>,
+    pub pad_strips: ahash::AHashMap<ObjectId, PadControlData>,
     pub tools: ahash::AHashMap<ObjectId, ToolData>,
 }
 impl TabletState {
@@ -46,6 +63,9 @@ impl TabletState {
             seats: Default::default(),
             tablets: Default::default(),
             pads: Default::default(),
+            pad_groups: Default::default(),
+            pad_rings: Default::default(),
+            pad_strips: Default::default(),
             tools: Default::default(),
         })
     }
@@ -55,17 +75,102 @@ impl TabletState {
             .get_tablet_seat(&seat, queue_handle, GlobalData);
         self.seats.push((tablet_seat, seat));
     }
+
+    /// Send the `event` of the `pad` to the surfaces it's focused on.
+    fn push_pad_event(
+        &self,
+        events_sink: &mut EventSink,
+        pad: &ObjectId,
+        event: WindowEvent,
+        timestamp: Instant,
+    ) {
+        let Some(pad) = self.pads.get(pad) else {
+            return;
+        };
+        for surface in &pad.surfaces {
+            events_sink.push_timed_window_event(event.clone(), make_wid(surface), timestamp);
+        }
+    }
+
+    /// Forget the `pad` with its groups, rings and strips, and destroy their proxies.
+    fn remove_pad(&mut self, proxy: &ZwpTabletPadV2) {
+        let pad = &proxy.id();
+        if let Some(data) = self.pads.remove(pad) {
+            data.rings.iter().for_each(ZwpTabletPadRingV2::destroy);
+            data.strips.iter().for_each(ZwpTabletPadStripV2::destroy);
+            data.groups.iter().for_each(ZwpTabletPadGroupV2::destroy);
+        }
+        proxy.destroy();
+        self.pad_groups.retain(|_, group| &group.pad != pad);
+        self.pad_rings.retain(|_, ring| &ring.pad != pad);
+        self.pad_strips.retain(|_, strip| &strip.pad != pad);
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct PadData {
     surfaces: Vec<WlSurface>,
+    /// The groups, rings and strips announced, in the order of their indices.
+    groups: Vec<ZwpTabletPadGroupV2>,
+    rings: Vec<ZwpTabletPadRingV2>,
+    strips: Vec<ZwpTabletPadStripV2>,
+}
+
+/// A mode group of a pad.
+#[derive(Debug)]
+pub struct PadGroupData {
+    pad: ObjectId,
+    index: u32,
+}
+
+/// A ring or a strip of a pad, with the state of its current frame.
+#[derive(Debug)]
+pub struct PadControlData {
+    pad: ObjectId,
+    index: u32,
+    source: TabletPadSource,
+    /// The angle of a ring or the position of a strip.
+    value: Option<f64>,
+    stopped: bool,
+}
+
+impl PadControlData {
+    fn new(pad: ObjectId, index: u32) -> Self {
+        Self {
+            pad,
+            index,
+            source: TabletPadSource::Unknown,
+            value: None,
+            stopped: false,
+        }
+    }
+
+    /// Take the source and the value of the frame, `None` when nothing changed.
+    fn take_frame(&mut self) -> Option<(TabletPadSource, Option<f64>)> {
+        let source = std::mem::replace(&mut self.source, TabletPadSource::Unknown);
+        let stopped = std::mem::take(&mut self.stopped);
+        match self.value.take() {
+            _ if stopped => Some((source, None)),
+            Some(value) => Some((source, Some(value))),
+            None => None,
+        }
+    }
+}
+
+fn pad_source<E>(source: wayland_client::WEnum<E>, finger: E) -> TabletPadSource
+where
+    E: PartialEq,
+{
+    match source.into_result() {
+        Ok(source) if source == finger => TabletPadSource::Finger,
+        _ => TabletPadSource::Unknown,
+    }
 }
 
 #[derive(Debug)]
 pub struct ToolData {
     pointer: std::sync::Arc<crate::platform_impl::wayland::GenericPointer>,
-    eraser: bool,
+    info: TabletToolInfo,
     surface: Option<WlSurface>,
     contact: bool,
     x: f64,
@@ -181,7 +286,9 @@ impl Dispatch<ZwpTabletSeatV2, GlobalData, WinitState> for TabletState {
             return;
         };
         match event {
-            zwp_tablet_seat_v2::Event::TabletAdded { id } => tablet.tablets.push(id),
+            zwp_tablet_seat_v2::Event::TabletAdded { id } => {
+                tablet.tablets.insert(id.id(), TabletInfo::default());
+            }
             zwp_tablet_seat_v2::Event::ToolAdded { id } => {
                 let seat = tablet
                     .seats
@@ -206,6 +313,13 @@ impl Dispatch<ZwpTabletSeatV2, GlobalData, WinitState> for TabletState {
                     )
                     .expect("failed to create pointer with present capability.");
 
+                let info = TabletToolInfo {
+                    id: id.id().protocol_id() as u64,
+                    tool_type: TabletToolType::Pen,
+                    serial: None,
+                    hardware_id: None,
+                    capabilities: TabletToolCapabilities::default(),
+                };
                 tablet.tools.insert(
                     id.id(),
                     ToolData {
@@ -218,7 +332,7 @@ impl Dispatch<ZwpTabletSeatV2, GlobalData, WinitState> for TabletState {
                             },
                         )
                         .into(),
-                        eraser: Default::default(),
+                        info,
                         surface: Default::default(),
                         contact: Default::default(),
                         x: Default::default(),
@@ -248,21 +362,31 @@ impl Dispatch<ZwpTabletV2, GlobalData, WinitState> for TabletState {
         _conn: &Connection,
         _qhandle: &QueueHandle<WinitState>,
     ) {
+        let Some(tablet) = &mut state.tablet else {
+            return;
+        };
+        let Some(info) = tablet.tablets.get_mut(&proxy.id()) else {
+            return;
+        };
         match event {
-            zwp_tablet_v2::Event::Name { .. } => { /* not implemented */ }
-            zwp_tablet_v2::Event::Id { .. } => { /* not implemented */ }
-            zwp_tablet_v2::Event::Path { .. } => { /* not implemented */ }
-            zwp_tablet_v2::Event::Done => state
-                .events_sink
-                .push_device_event(DeviceEvent::Added, DeviceId),
+            zwp_tablet_v2::Event::Name { name } => info.name = Some(name),
+            zwp_tablet_v2::Event::Id { vid, pid } => info.usb_id = Some((vid, pid)),
+            zwp_tablet_v2::Event::Path { path } => info.paths.push(path.into()),
+            zwp_tablet_v2::Event::Done => {
+                state
+                    .events_sink
+                    .push_device_event(DeviceEvent::Added, DeviceId);
+                state
+                    .events_sink
+                    .push_device_event(DeviceEvent::TabletAdded(info.clone()), DeviceId);
+            }
 
             zwp_tablet_v2::Event::Removed => {
                 state
                     .events_sink
                     .push_device_event(DeviceEvent::Removed, DeviceId);
-                if let Some(tablet) = &mut state.tablet {
-                    tablet.tablets.retain(|other| other != proxy);
-                }
+                tablet.tablets.remove(&proxy.id());
+                proxy.destroy();
             }
             _ => unreachable!(),
         }
@@ -285,22 +409,32 @@ impl Dispatch<ZwpTabletPadV2, GlobalData, WinitState> for TabletState {
             return;
         };
         match event {
-            zwp_tablet_pad_v2::Event::Group { .. } => { /* not implemented */ }
+            zwp_tablet_pad_v2::Event::Group { pad_group } => {
+                let pad = tablet.pads.get_mut(&proxy.id()).unwrap();
+                let group = PadGroupData {
+                    pad: proxy.id(),
+                    index: pad.groups.len() as u32,
+                };
+                tablet.pad_groups.insert(pad_group.id(), group);
+                pad.groups.push(pad_group);
+            }
             zwp_tablet_pad_v2::Event::Path { .. } => { /* not implemented */ }
             zwp_tablet_pad_v2::Event::Buttons { .. } => { /* not implemented */ }
             zwp_tablet_pad_v2::Event::Done => {}
 
             zwp_tablet_pad_v2::Event::Button {
+                time,
                 button,
                 state: button_state,
-                ..
             } => {
+                let timestamp = state.event_clock.instant(time);
                 for surface in &tablet.pads.get(&proxy.id()).unwrap().surfaces {
-                    state.events_sink.push_window_event(
+                    state.events_sink.push_timed_window_event(
                         WindowEvent::TabletButton {
                             device_id: crate::event::DeviceId(
                                 crate::platform_impl::DeviceId::Wayland(DeviceId),
                             ),
+                            tool_id: None,
                             button: crate::event::TabletButton::Tablet(button),
                             state: match button_state.into_result() {
                                 Ok(zwp_tablet_pad_v2::ButtonState::Released) => {
@@ -313,6 +447,7 @@ impl Dispatch<ZwpTabletPadV2, GlobalData, WinitState> for TabletState {
                             },
                         },
                         make_wid(surface),
+                        timestamp,
                     );
                 }
             }
@@ -333,7 +468,7 @@ impl Dispatch<ZwpTabletPadV2, GlobalData, WinitState> for TabletState {
                     .retain(|other| other != &surface);
             }
             zwp_tablet_pad_v2::Event::Removed => {
-                tablet.pads.remove(&proxy.id());
+                tablet.remove_pad(proxy);
             }
             _ => unreachable!(),
         }
@@ -341,15 +476,140 @@ impl Dispatch<ZwpTabletPadV2, GlobalData, WinitState> for TabletState {
 }
 
 impl Dispatch<ZwpTabletPadGroupV2, GlobalData, WinitState> for TabletState {
+    wayland_client::event_created_child!(WinitState, ZwpTabletPadGroupV2, [
+        zwp_tablet_pad_group_v2::EVT_RING_OPCODE => (ZwpTabletPadRingV2, GlobalData),
+        zwp_tablet_pad_group_v2::EVT_STRIP_OPCODE => (ZwpTabletPadStripV2, GlobalData),
+    ]);
     fn event(
-        _state: &mut WinitState,
-        _proxy: &ZwpTabletPadGroupV2,
-        _event: <ZwpTabletPadGroupV2 as wayland_client::Proxy>::Event,
+        state: &mut WinitState,
+        proxy: &ZwpTabletPadGroupV2,
+        event: <ZwpTabletPadGroupV2 as wayland_client::Proxy>::Event,
         _data: &GlobalData,
         _conn: &Connection,
         _qhandle: &QueueHandle<WinitState>,
     ) {
-        /* not implemented */
+        let Some(tablet) = &mut state.tablet else {
+            return;
+        };
+        let Some(group) = tablet.pad_groups.get(&proxy.id()) else {
+            return;
+        };
+        let Some(pad) = tablet.pads.get_mut(&group.pad) else {
+            return;
+        };
+        match event {
+            zwp_tablet_pad_group_v2::Event::Ring { ring } => {
+                let data = PadControlData::new(group.pad.clone(), pad.rings.len() as u32);
+                tablet.pad_rings.insert(ring.id(), data);
+                pad.rings.push(ring);
+            }
+            zwp_tablet_pad_group_v2::Event::Strip { strip } => {
+                let data = PadControlData::new(group.pad.clone(), pad.strips.len() as u32);
+                tablet.pad_strips.insert(strip.id(), data);
+                pad.strips.push(strip);
+            }
+            zwp_tablet_pad_group_v2::Event::ModeSwitch { time, mode, .. } => {
+                let event = WindowEvent::TabletPadModeChanged {
+                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                        DeviceId,
+                    )),
+                    group: group.index,
+                    mode,
+                };
+                let timestamp = state.event_clock.instant(time);
+                tablet.push_pad_event(&mut state.events_sink, &group.pad, event, timestamp);
+            }
+            zwp_tablet_pad_group_v2::Event::Buttons { .. }
+            | zwp_tablet_pad_group_v2::Event::Modes { .. }
+            | zwp_tablet_pad_group_v2::Event::Done => (),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletPadRingV2, GlobalData, WinitState> for TabletState {
+    fn event(
+        state: &mut WinitState,
+        proxy: &ZwpTabletPadRingV2,
+        event: <ZwpTabletPadRingV2 as wayland_client::Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let Some(tablet) = &mut state.tablet else {
+            return;
+        };
+        let Some(ring) = tablet.pad_rings.get_mut(&proxy.id()) else {
+            return;
+        };
+        match event {
+            zwp_tablet_pad_ring_v2::Event::Source { source } => {
+                ring.source = pad_source(source, zwp_tablet_pad_ring_v2::Source::Finger)
+            }
+            zwp_tablet_pad_ring_v2::Event::Angle { degrees } => ring.value = Some(degrees),
+            zwp_tablet_pad_ring_v2::Event::Stop => ring.stopped = true,
+            zwp_tablet_pad_ring_v2::Event::Frame { time } => {
+                let Some((source, angle)) = ring.take_frame() else {
+                    return;
+                };
+                let event = WindowEvent::TabletPadRing {
+                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                        DeviceId,
+                    )),
+                    ring: ring.index,
+                    angle,
+                    source,
+                };
+                let pad = ring.pad.clone();
+                let timestamp = state.event_clock.instant(time);
+                tablet.push_pad_event(&mut state.events_sink, &pad, event, timestamp);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Dispatch<ZwpTabletPadStripV2, GlobalData, WinitState> for TabletState {
+    fn event(
+        state: &mut WinitState,
+        proxy: &ZwpTabletPadStripV2,
+        event: <ZwpTabletPadStripV2 as wayland_client::Proxy>::Event,
+        _data: &GlobalData,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<WinitState>,
+    ) {
+        let Some(tablet) = &mut state.tablet else {
+            return;
+        };
+        let Some(strip) = tablet.pad_strips.get_mut(&proxy.id()) else {
+            return;
+        };
+        match event {
+            zwp_tablet_pad_strip_v2::Event::Source { source } => {
+                strip.source = pad_source(source, zwp_tablet_pad_strip_v2::Source::Finger)
+            }
+            zwp_tablet_pad_strip_v2::Event::Position { position } => {
+                strip.value = Some(position as f64 / 65535.0)
+            }
+            zwp_tablet_pad_strip_v2::Event::Stop => strip.stopped = true,
+            zwp_tablet_pad_strip_v2::Event::Frame { time } => {
+                let Some((source, position)) = strip.take_frame() else {
+                    return;
+                };
+                let event = WindowEvent::TabletPadStrip {
+                    device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
+                        DeviceId,
+                    )),
+                    strip: strip.index,
+                    position,
+                    source,
+                };
+                let pad = strip.pad.clone();
+                let timestamp = state.event_clock.instant(time);
+                tablet.push_pad_event(&mut state.events_sink, &pad, event, timestamp);
+            }
+            _ => unreachable!(),
+        }
     }
 }
 
@@ -369,18 +629,54 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
 
         match event {
             zwp_tablet_tool_v2::Event::Type { tool_type } => {
-                tool.eraser = matches!(
-                    tool_type.into_result(),
-                    Ok(zwp_tablet_tool_v2::Type::Eraser)
-                )
+                use zwp_tablet_tool_v2::Type;
+                tool.info.tool_type = match tool_type.into_result() {
+                    Ok(Type::Eraser) => TabletToolType::Eraser,
+                    Ok(Type::Brush) => TabletToolType::Brush,
+                    Ok(Type::Pencil) => TabletToolType::Pencil,
+                    Ok(Type::Airbrush) => TabletToolType::Airbrush,
+                    Ok(Type::Finger) => TabletToolType::Finger,
+                    Ok(Type::Mouse) => TabletToolType::Mouse,
+                    Ok(Type::Lens) => TabletToolType::Lens,
+                    _ => TabletToolType::Pen,
+                }
+            }
+            zwp_tablet_tool_v2::Event::HardwareSerial {
+                hardware_serial_hi,
+                hardware_serial_lo,
+            } => {
+                tool.info.serial =
+                    Some((hardware_serial_hi as u64) << 32 | hardware_serial_lo as u64)
             }
-            zwp_tablet_tool_v2::Event::HardwareSerial { .. } => { /* not implemented */ }
-            zwp_tablet_tool_v2::Event::HardwareIdWacom { .. } => { /* not implemented */ }
-            zwp_tablet_tool_v2::Event::Capability { .. } => { /* not implemented */ }
-            zwp_tablet_tool_v2::Event::Done => {}
+            zwp_tablet_tool_v2::Event::HardwareIdWacom {
+                hardware_id_hi,
+                hardware_id_lo,
+            } => {
+                tool.info.hardware_id = Some((hardware_id_hi as u64) << 32 | hardware_id_lo as u64)
+            }
+            zwp_tablet_tool_v2::Event::Capability { capability } => {
+                use zwp_tablet_tool_v2::Capability;
+                let capabilities = &mut tool.info.capabilities;
+                match capability.into_result() {
+                    Ok(Capability::Pressure) => capabilities.pressure = true,
+                    Ok(Capability::Tilt) => capabilities.tilt = true,
+                    Ok(Capability::Rotation) => capabilities.rotation = true,
+                    Ok(Capability::Distance) => capabilities.distance = true,
+                    Ok(Capability::Slider) => capabilities.slider = true,
+                    Ok(Capability::Wheel) => capabilities.wheel = true,
+                    _ => (),
+                }
+            }
+            zwp_tablet_tool_v2::Event::Done => state
+                .events_sink
+                .push_device_event(DeviceEvent::TabletToolAdded(tool.info.clone()), DeviceId),
 
             zwp_tablet_tool_v2::Event::Removed => {
+                state
+                    .events_sink
+                    .push_device_event(DeviceEvent::TabletToolRemoved(tool.info.id), DeviceId);
                 tablet.tools.remove(&proxy.id());
+                proxy.destroy();
             }
             zwp_tablet_tool_v2::Event::ProximityIn { surface, .. } => {
                 let window_id = make_wid(&surface);
@@ -390,7 +686,8 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                             DeviceId,
                         )),
-                        inverted: tool.eraser,
+                        tool_id: tool.info.id,
+                        inverted: tool.info.tool_type == TabletToolType::Eraser,
                     },
                     window_id,
                 );
@@ -410,6 +707,7 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                             DeviceId,
                         )),
+                        tool_id: tool.info.id,
                     },
                     window_id,
                 );
@@ -428,9 +726,10 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                             DeviceId,
                         )),
-                        button: match tool.eraser {
-                            true => crate::event::TabletButton::Eraser,
-                            false => crate::event::TabletButton::Tip,
+                        tool_id: Some(tool.info.id),
+                        button: match tool.info.tool_type {
+                            TabletToolType::Eraser => crate::event::TabletButton::Eraser,
+                            _ => crate::event::TabletButton::Tip,
                         },
                         state: crate::event::ElementState::Pressed,
                     },
@@ -445,9 +744,10 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                             DeviceId,
                         )),
-                        button: match tool.eraser {
-                            true => crate::event::TabletButton::Eraser,
-                            false => crate::event::TabletButton::Tip,
+                        tool_id: Some(tool.info.id),
+                        button: match tool.info.tool_type {
+                            TabletToolType::Eraser => crate::event::TabletButton::Eraser,
+                            _ => crate::event::TabletButton::Tip,
                         },
                         state: crate::event::ElementState::Released,
                     },
@@ -488,6 +788,7 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                             DeviceId,
                         )),
+                        tool_id: Some(tool.info.id),
                         button: crate::event::TabletButton::Pen(button),
                         state: match button_state.into_result() {
                             Ok(zwp_tablet_tool_v2::ButtonState::Released) => {
@@ -502,10 +803,10 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                     make_wid(&surface),
                 );
             }
-            zwp_tablet_tool_v2::Event::Frame { .. } => {
+            zwp_tablet_tool_v2::Event::Frame { time } => {
                 let ToolData {
                     pointer: _,
-                    eraser: _,
+                    info,
                     contact: _,
                     surface,
                     x,
@@ -517,11 +818,13 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                     rotation,
                 } = &tool;
                 let Some(surface) = surface else { return };
-                state.events_sink.push_window_event(
+                let timestamp = state.event_clock.instant(time);
+                state.events_sink.push_timed_window_event(
                     WindowEvent::TabletPenMotion {
                         device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                             DeviceId,
                         )),
+                        tool_id: info.id,
                         location: PhysicalPosition::new(*x, *y),
                         pressure: *prerssure,
                         rotation: *rotation,
@@ -529,6 +832,7 @@ impl Dispatch<ZwpTabletToolV2, GlobalData, WinitState> for TabletState {
                         tilt: [*tilt_x, *tilt_y],
                     },
                     make_wid(&surface),
+                    timestamp,
                 );
             }
             _ => unreachable!(),
@@ -541,4 +845,6 @@ delegate_dispatch!(WinitState: [ZwpTabletSeatV2: GlobalData] => TabletState);
 delegate_dispatch!(WinitState: [ZwpTabletV2: GlobalData] => TabletState);
 delegate_dispatch!(WinitState: [ZwpTabletPadV2: GlobalData] => TabletState);
 delegate_dispatch!(WinitState: [ZwpTabletPadGroupV2: GlobalData] => TabletState);
+delegate_dispatch!(WinitState: [ZwpTabletPadRingV2: GlobalData] => TabletState);
+delegate_dispatch!(WinitState: [ZwpTabletPadStripV2: GlobalData] => TabletState);
 delegate_dispatch!(WinitState: [ZwpTabletToolV2: GlobalData] => TabletState);
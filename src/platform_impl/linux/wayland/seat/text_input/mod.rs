@@ -16,7 +16,7 @@ use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::
 use crate::event::{Ime, WindowEvent};
 use crate::platform_impl::wayland;
 use crate::platform_impl::wayland::state::WinitState;
-use crate::window::ImePurpose;
+use crate::window::{ImeHints, ImePurpose};
 
 pub struct TextInputState {
     text_input_manager: ZwpTextInputManagerV3,
@@ -75,7 +75,11 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
 
                 if window.ime_allowed() {
                     text_input.enable();
-                    text_input.set_content_type_by_purpose(window.ime_purpose());
+                    text_input
+                        .set_content_type_by_purpose(window.ime_purpose(), window.ime_hints());
+                    if let Some(surrounding_text) = window.ime_surrounding_text() {
+                        text_input.set_surrounding(surrounding_text);
+                    }
                     text_input.commit();
                     state
                         .events_sink
@@ -129,6 +133,12 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                 text_input_data.pending_preedit = None;
                 text_input_data.pending_commit = text;
             }
+            TextInputEvent::DeleteSurroundingText {
+                before_length,
+                after_length,
+            } => {
+                text_input_data.pending_delete = Some((before_length, after_length));
+            }
             TextInputEvent::Done { .. } => {
                 let window_id = match text_input_data.surface.as_ref() {
                     Some(surface) => wayland::make_wid(surface),
@@ -141,6 +151,17 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                     window_id,
                 );
 
+                // Delete the surrounding text before inserting the commit in its place.
+                if let Some((before, after)) = text_input_data.pending_delete.take() {
+                    state.events_sink.push_window_event(
+                        WindowEvent::Ime(Ime::DeleteSurrounding {
+                            before_bytes: before as usize,
+                            after_bytes: after as usize,
+                        }),
+                        window_id,
+                    );
+                }
+
                 // Send `Commit`.
                 if let Some(text) = text_input_data.pending_commit.take() {
                     state
@@ -160,26 +181,108 @@ impl Dispatch<ZwpTextInputV3, TextInputData, WinitState> for TextInputState {
                     );
                 }
             }
-            TextInputEvent::DeleteSurroundingText { .. } => {
-                // Not handled.
-            }
             _ => {}
         }
     }
 }
 
 pub trait ZwpTextInputV3Ext {
-    fn set_content_type_by_purpose(&self, purpose: ImePurpose);
+    fn set_content_type_by_purpose(&self, purpose: ImePurpose, hints: ImeHints);
+
+    fn set_surrounding(&self, surrounding_text: &SurroundingText);
 }
 
 impl ZwpTextInputV3Ext for ZwpTextInputV3 {
-    fn set_content_type_by_purpose(&self, purpose: ImePurpose) {
+    fn set_content_type_by_purpose(&self, purpose: ImePurpose, hints: ImeHints) {
         let (hint, purpose) = match purpose {
             ImePurpose::Normal => (ContentHint::None, ContentPurpose::Normal),
             ImePurpose::Password => (ContentHint::SensitiveData, ContentPurpose::Password),
             ImePurpose::Terminal => (ContentHint::None, ContentPurpose::Terminal),
+            ImePurpose::Alpha => (ContentHint::None, ContentPurpose::Alpha),
+            ImePurpose::Digits => (ContentHint::None, ContentPurpose::Digits),
+            ImePurpose::Number => (ContentHint::None, ContentPurpose::Number),
+            ImePurpose::Phone => (ContentHint::None, ContentPurpose::Phone),
+            ImePurpose::Url => (ContentHint::None, ContentPurpose::Url),
+            ImePurpose::Email => (ContentHint::None, ContentPurpose::Email),
+            ImePurpose::Name => (ContentHint::None, ContentPurpose::Name),
+            ImePurpose::Pin => (ContentHint::SensitiveData, ContentPurpose::Pin),
+            ImePurpose::Date => (ContentHint::None, ContentPurpose::Date),
+            ImePurpose::Time => (ContentHint::None, ContentPurpose::Time),
+            ImePurpose::DateTime => (ContentHint::None, ContentPurpose::Datetime),
         };
-        self.set_content_type(hint, purpose);
+        self.set_content_type(hint | content_hint(hints), purpose);
+    }
+
+    fn set_surrounding(&self, surrounding_text: &SurroundingText) {
+        let (text, cursor, anchor) = surrounding_text.trimmed();
+        self.set_surrounding_text(text.to_owned(), cursor as i32, anchor as i32);
+    }
+}
+
+/// The content hint of the IME hints.
+fn content_hint(hints: ImeHints) -> ContentHint {
+    let mut hint = ContentHint::None;
+    for (ime_hint, content_hint) in [
+        (ImeHints::COMPLETION, ContentHint::Completion),
+        (ImeHints::SPELLCHECK, ContentHint::Spellcheck),
+        (
+            ImeHints::AUTO_CAPITALIZATION,
+            ContentHint::AutoCapitalization,
+        ),
+        (ImeHints::LOWERCASE, ContentHint::Lowercase),
+        (ImeHints::UPPERCASE, ContentHint::Uppercase),
+        (ImeHints::TITLECASE, ContentHint::Titlecase),
+        (ImeHints::HIDDEN_TEXT, ContentHint::HiddenText),
+        (ImeHints::SENSITIVE_DATA, ContentHint::SensitiveData),
+        (ImeHints::LATIN, ContentHint::Latin),
+        (ImeHints::MULTILINE, ContentHint::Multiline),
+    ] {
+        if hints.contains(ime_hint) {
+            hint |= content_hint;
+        }
+    }
+    hint
+}
+
+/// The maximum length of the surrounding text, in bytes, which fits in a protocol message.
+const MAX_SURROUNDING_TEXT_BYTES: usize = 4000;
+
+/// The text around the cursor, with the byte offsets of the cursor and the selection anchor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurroundingText {
+    pub text: String,
+    pub cursor: usize,
+    pub anchor: usize,
+}
+
+impl SurroundingText {
+    /// The text trimmed to the limit of the protocol around the selection, or around the cursor
+    /// when the selection is too long, with the offsets in the trimmed text.
+    fn trimmed(&self) -> (&str, usize, usize) {
+        let text = self.text.as_str();
+        if text.len() <= MAX_SURROUNDING_TEXT_BYTES {
+            return (text, self.cursor, self.anchor);
+        }
+
+        let (low, high) = (self.cursor.min(self.anchor), self.cursor.max(self.anchor));
+        let center = if high - low <= MAX_SURROUNDING_TEXT_BYTES {
+            (low + high) / 2
+        } else {
+            self.cursor
+        };
+        let mut start = center
+            .saturating_sub(MAX_SURROUNDING_TEXT_BYTES / 2)
+            .min(text.len() - MAX_SURROUNDING_TEXT_BYTES);
+        let mut end = start + MAX_SURROUNDING_TEXT_BYTES;
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let offset = |position: usize| position.clamp(start, end) - start;
+        (&text[start..end], offset(self.cursor), offset(self.anchor))
     }
 }
 
@@ -199,6 +302,9 @@ pub struct TextInputDataInner {
 
     /// The preedit to submit on `done`.
     pending_preedit: Option<Preedit>,
+
+    /// The bytes before and after the cursor to delete on `done`.
+    pending_delete: Option<(u32, u32)>,
 }
 
 /// The state of the preedit.
@@ -210,3 +316,36 @@ struct Preedit {
 
 delegate_dispatch!(WinitState: [ZwpTextInputManagerV3: GlobalData] => TextInputState);
 delegate_dispatch!(WinitState: [ZwpTextInputV3: TextInputData] => TextInputState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surrounding_text_is_trimmed_around_the_cursor() {
+        let short = SurroundingText {
+            text: "한국어".to_owned(),
+            cursor: 6,
+            anchor: 3,
+        };
+        assert_eq!(short.trimmed(), ("한국어", 6, 3));
+
+        let long = SurroundingText {
+            text: "a".repeat(5000),
+            cursor: 4500,
+            anchor: 4500,
+        };
+        let (text, cursor, anchor) = long.trimmed();
+        assert_eq!((text.len(), cursor, anchor), (4000, 3500, 3500));
+
+        // The bounds are moved to the characters boundaries.
+        let long = SurroundingText {
+            text: "ก".repeat(2000),
+            cursor: 3000,
+            anchor: 3003,
+        };
+        let (text, cursor, anchor) = long.trimmed();
+        assert!(text.len() <= MAX_SURROUNDING_TEXT_BYTES);
+        assert_eq!(&text[cursor..anchor], "ก");
+    }
+}
@@ -19,8 +19,8 @@ impl TouchHandler for WinitState {
         _: &Connection,
         _: &QueueHandle<Self>,
         touch: &WlTouch,
-        _: u32,
-        _: u32,
+        serial: u32,
+        time: u32,
         surface: WlSurface,
         id: i32,
         position: (f64, f64),
@@ -34,13 +34,15 @@ impl TouchHandler for WinitState {
         let location = LogicalPosition::<f64>::from(position);
 
         let seat_state = self.seats.get_mut(&touch.seat().id()).unwrap();
+        seat_state.input_serial = Some(serial);
 
         // Update the state of the point.
         seat_state
             .touch_map
             .insert(id, TouchPoint { surface, location });
 
-        self.events_sink.push_window_event(
+        let timestamp = self.event_clock.instant(time);
+        self.events_sink.push_timed_window_event(
             WindowEvent::Touch(Touch {
                 device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                     DeviceId,
@@ -51,6 +53,7 @@ impl TouchHandler for WinitState {
                 id: id as u64,
             }),
             window_id,
+            timestamp,
         );
     }
 
@@ -60,7 +63,7 @@ impl TouchHandler for WinitState {
         _: &QueueHandle<Self>,
         touch: &WlTouch,
         _: u32,
-        _: u32,
+        time: u32,
         id: i32,
     ) {
         let seat_state = self.seats.get_mut(&touch.seat().id()).unwrap();
@@ -77,7 +80,8 @@ impl TouchHandler for WinitState {
             None => return,
         };
 
-        self.events_sink.push_window_event(
+        let timestamp = self.event_clock.instant(time);
+        self.events_sink.push_timed_window_event(
             WindowEvent::Touch(Touch {
                 device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                     DeviceId,
@@ -88,6 +92,7 @@ impl TouchHandler for WinitState {
                 id: id as u64,
             }),
             window_id,
+            timestamp,
         );
     }
 
@@ -96,7 +101,7 @@ impl TouchHandler for WinitState {
         _: &Connection,
         _: &QueueHandle<Self>,
         touch: &WlTouch,
-        _: u32,
+        time: u32,
         id: i32,
         position: (f64, f64),
     ) {
@@ -116,7 +121,8 @@ impl TouchHandler for WinitState {
 
         touch_point.location = LogicalPosition::<f64>::from(position);
 
-        self.events_sink.push_window_event(
+        let timestamp = self.event_clock.instant(time);
+        self.events_sink.push_timed_window_event(
             WindowEvent::Touch(Touch {
                 device_id: crate::event::DeviceId(crate::platform_impl::DeviceId::Wayland(
                     DeviceId,
@@ -127,6 +133,7 @@ impl TouchHandler for WinitState {
                 id: id as u64,
             }),
             window_id,
+            timestamp,
         );
     }
 
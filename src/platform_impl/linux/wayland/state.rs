@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 
@@ -12,9 +13,15 @@ use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::{Connection, Proxy, QueueHandle};
 
 use sctk::compositor::{CompositorHandler, CompositorState};
+use sctk::data_device_manager::data_source::CopyPasteSource;
+use sctk::data_device_manager::DataDeviceManagerState;
 use sctk::output::{OutputHandler, OutputState};
+use sctk::primary_selection::selection::PrimarySelectionSource;
+use sctk::primary_selection::PrimarySelectionManagerState;
 use sctk::registry::{ProvidesRegistryState, RegistryState};
 use sctk::seat::SeatState;
+use sctk::shell::wlr_layer::{LayerShell, LayerShellHandler, LayerSurface, LayerSurfaceConfigure};
+use sctk::shell::xdg::popup::{Popup, PopupConfigure, PopupHandler};
 use sctk::shell::xdg::window::{Window, WindowConfigure, WindowHandler};
 use sctk::shell::xdg::XdgShell;
 use sctk::shell::WaylandSurface;
@@ -22,15 +29,27 @@ use sctk::shm::slot::SlotPool;
 use sctk::shm::{Shm, ShmHandler};
 use sctk::subcompositor::SubcompositorState;
 
+use crate::dpi::PhysicalPosition;
+use crate::event::WindowEvent;
+use crate::platform_impl::click_counter::ClickCounter;
+use crate::platform_impl::common::click_settings::ClickSettings;
+#[cfg(dbus_ime)]
+use crate::platform_impl::common::dbus_ime::DBusIme;
+use crate::platform_impl::common::xkb_state::Layouts;
+use crate::platform_impl::event_clock::EventClock;
 use crate::platform_impl::wayland::event_loop::sink::EventSink;
 use crate::platform_impl::wayland::output::MonitorHandle;
 use crate::platform_impl::wayland::seat::{
-    PointerConstraintsState, RelativePointerState, TabletState, TextInputState, WinitSeatState,
+    Drag, DropOffer, OwnedSelection, PointerConstraintsState, PointerGesturesState,
+    RelativePointerState, TabletState, TextInputState, WinitSeatState,
 };
 use crate::platform_impl::wayland::types::kwin_blur::KWinBlurManager;
 use crate::platform_impl::wayland::types::wp_fractional_scaling::FractionalScalingManager;
+use crate::platform_impl::wayland::types::wp_idle_inhibit::IdleInhibitManager;
+use crate::platform_impl::wayland::types::wp_keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitManager;
 use crate::platform_impl::wayland::types::wp_viewporter::ViewporterState;
 use crate::platform_impl::wayland::types::xdg_activation::XdgActivationState;
+use crate::platform_impl::wayland::types::xdg_toplevel_icon::XdgToplevelIconManager;
 use crate::platform_impl::wayland::window::{WindowRequests, WindowState};
 use crate::platform_impl::wayland::{WaylandError, WindowId};
 use crate::platform_impl::OsError;
@@ -61,6 +80,9 @@ pub struct WinitState {
     /// The XDG shell that is used for widnows.
     pub xdg_shell: XdgShell,
 
+    /// The layer shell, used for the windows placed against the edges of a monitor.
+    pub layer_shell: Option<LayerShell>,
+
     /// The currently present windows.
     pub windows: RefCell<AHashMap<WindowId, Arc<Mutex<WindowState>>>>,
 
@@ -79,9 +101,34 @@ pub struct WinitState {
     /// Currently present cursor surfaces.
     pub pointer_surfaces: AHashMap<ObjectId, Arc<super::GenericPointer>>,
 
+    /// The keyboard layouts of the seat which has updated them last.
+    pub keyboard_layouts: Layouts,
+
     /// The state of the text input on the client.
     pub text_input_state: Option<TextInputState>,
 
+    /// The input method on D-Bus, used when the compositor doesn't support the text input.
+    #[cfg(dbus_ime)]
+    pub dbus_ime: Option<Arc<Mutex<DBusIme>>>,
+
+    /// The data device manager, used for the clipboard.
+    pub data_device_manager: Option<DataDeviceManagerState>,
+
+    /// The clipboard selection while it's owned by the application.
+    pub selection: Option<OwnedSelection<CopyPasteSource>>,
+
+    /// The primary selection manager.
+    pub primary_selection_manager: Option<PrimarySelectionManagerState>,
+
+    /// The primary selection while it's owned by the application.
+    pub primary_selection: Option<OwnedSelection<PrimarySelectionSource>>,
+
+    /// The drag started from one of the windows.
+    pub drag: Option<Drag>,
+
+    /// The drag over one of the windows.
+    pub drop_offer: Option<DropOffer>,
+
     /// Observed monitors.
     pub monitors: Arc<Mutex<Vec<MonitorHandle>>>,
 
@@ -89,12 +136,25 @@ pub struct WinitState {
     /// event loop run.
     pub events_sink: EventSink,
 
+    /// Converts the compositor timestamps of the input events, and clamps the timestamps of all
+    /// the events delivered.
+    pub event_clock: Rc<EventClock>,
+
+    /// Counts the consecutive clicks of the pointer buttons.
+    pub click_counter: ClickCounter,
+
+    /// The position of the synthetic cursor, used to tell apart consecutive synthetic clicks.
+    pub synthetic_cursor_position: PhysicalPosition<f64>,
+
     /// Xdg activation.
     pub xdg_activation: Option<XdgActivationState>,
 
     /// Relative pointer.
     pub relative_pointer: Option<RelativePointerState>,
 
+    /// Pointer gestures.
+    pub pointer_gestures: Option<PointerGesturesState>,
+
     /// Tablet.
     pub tablet: Option<TabletState>,
 
@@ -110,6 +170,15 @@ pub struct WinitState {
     /// KWin blur manager.
     pub kwin_blur_manager: Option<KWinBlurManager>,
 
+    /// The manager of the idle inhibitors.
+    pub idle_inhibit_manager: Option<IdleInhibitManager>,
+
+    /// The manager of the keyboard shortcuts inhibitors.
+    pub keyboard_shortcuts_inhibit_manager: Option<KeyboardShortcutsInhibitManager>,
+
+    /// The manager of the toplevel icons.
+    pub xdg_toplevel_icon_manager: Option<XdgToplevelIconManager>,
+
     /// Loop handle to re-register event sources, such as keyboard repeat.
     pub loop_handle: LoopHandle<'static, Self>,
 
@@ -144,9 +213,22 @@ impl WinitState {
 
         let seat_state = SeatState::new(globals, queue_handle);
 
+        let data_device_manager = DataDeviceManagerState::bind(globals, queue_handle).ok();
+        let primary_selection_manager =
+            PrimarySelectionManagerState::bind(globals, queue_handle).ok();
+
         let mut seats = AHashMap::default();
         for seat in seat_state.seats() {
-            seats.insert(seat.id(), WinitSeatState::new());
+            let data_device = data_device_manager
+                .as_ref()
+                .map(|manager| manager.get_data_device(queue_handle, &seat));
+            let primary_selection_device = primary_selection_manager
+                .as_ref()
+                .map(|manager| manager.get_selection_device(queue_handle, &seat));
+            seats.insert(
+                seat.id(),
+                WinitSeatState::new(data_device, primary_selection_device),
+            );
         }
 
         let mut tablet = TabletState::new(globals, queue_handle).ok();
@@ -166,6 +248,11 @@ impl WinitState {
         let shm = Shm::bind(globals, queue_handle).map_err(WaylandError::Bind)?;
         let custom_cursor_pool = Arc::new(Mutex::new(SlotPool::new(2, &shm).unwrap()));
 
+        // Wayland has no protocol for the double-click settings, so follow GTK, unless the
+        // settings portal has them.
+        let mut click_counter = ClickCounter::default();
+        ClickSettings::from_gtk().apply(&mut click_counter);
+
         Ok(Self {
             registry_state,
             compositor_state: Arc::new(compositor_state),
@@ -176,6 +263,7 @@ impl WinitState {
             custom_cursor_pool,
 
             xdg_shell: XdgShell::bind(globals, queue_handle).map_err(WaylandError::Bind)?,
+            layer_shell: LayerShell::bind(globals, queue_handle).ok(),
             xdg_activation: XdgActivationState::bind(globals, queue_handle).ok(),
 
             windows: Default::default(),
@@ -185,11 +273,28 @@ impl WinitState {
             viewporter_state,
             fractional_scaling_manager,
             kwin_blur_manager: KWinBlurManager::new(globals, queue_handle).ok(),
+            idle_inhibit_manager: IdleInhibitManager::new(globals, queue_handle).ok(),
+            keyboard_shortcuts_inhibit_manager: KeyboardShortcutsInhibitManager::new(
+                globals,
+                queue_handle,
+            )
+            .ok(),
+            xdg_toplevel_icon_manager: XdgToplevelIconManager::new(globals, queue_handle).ok(),
 
             seats,
+            keyboard_layouts: Layouts::default(),
             text_input_state: TextInputState::new(globals, queue_handle).ok(),
+            #[cfg(dbus_ime)]
+            dbus_ime: None,
+            data_device_manager,
+            selection: None,
+            primary_selection_manager,
+            primary_selection: None,
+            drag: None,
+            drop_offer: None,
 
             relative_pointer: RelativePointerState::new(globals, queue_handle).ok(),
+            pointer_gestures: PointerGesturesState::new(globals, queue_handle).ok(),
             tablet,
             pointer_constraints: PointerConstraintsState::new(globals, queue_handle)
                 .map(Arc::new)
@@ -198,6 +303,9 @@ impl WinitState {
 
             monitors: Arc::new(Mutex::new(monitors)),
             events_sink: EventSink::new(),
+            event_clock: Rc::new(EventClock::new()),
+            click_counter,
+            synthetic_cursor_position: Default::default(),
             loop_handle,
             // Make it true by default.
             dispatched_events: true,
@@ -314,6 +422,84 @@ impl WindowHandler for WinitState {
     }
 }
 
+impl PopupHandler for WinitState {
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        popup: &Popup,
+        configure: PopupConfigure,
+    ) {
+        let window_id = super::make_wid(popup.wl_surface());
+
+        let pos = if let Some(pos) = self
+            .window_compositor_updates
+            .iter()
+            .position(|update| update.window_id == window_id)
+        {
+            pos
+        } else {
+            self.window_compositor_updates
+                .push(WindowCompositorUpdate::new(window_id));
+            self.window_compositor_updates.len() - 1
+        };
+
+        self.window_compositor_updates[pos].resized |= self
+            .windows
+            .get_mut()
+            .get_mut(&window_id)
+            .expect("got configure for dead popup.")
+            .lock()
+            .unwrap()
+            .configure_popup(configure);
+    }
+
+    fn done(&mut self, _: &Connection, _: &QueueHandle<Self>, popup: &Popup) {
+        let window_id = super::make_wid(popup.wl_surface());
+        self.events_sink
+            .push_window_event(WindowEvent::PopupDismissed, window_id);
+    }
+}
+
+impl LayerShellHandler for WinitState {
+    fn closed(&mut self, _: &Connection, _: &QueueHandle<Self>, layer: &LayerSurface) {
+        let window_id = super::make_wid(layer.wl_surface());
+        Self::queue_close(&mut self.window_compositor_updates, window_id);
+    }
+
+    fn configure(
+        &mut self,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+        layer: &LayerSurface,
+        configure: LayerSurfaceConfigure,
+        _serial: u32,
+    ) {
+        let window_id = super::make_wid(layer.wl_surface());
+
+        let pos = if let Some(pos) = self
+            .window_compositor_updates
+            .iter()
+            .position(|update| update.window_id == window_id)
+        {
+            pos
+        } else {
+            self.window_compositor_updates
+                .push(WindowCompositorUpdate::new(window_id));
+            self.window_compositor_updates.len() - 1
+        };
+
+        self.window_compositor_updates[pos].resized |= self
+            .windows
+            .get_mut()
+            .get_mut(&window_id)
+            .expect("got configure for dead layer surface.")
+            .lock()
+            .unwrap()
+            .configure_layer(configure);
+    }
+}
+
 impl OutputHandler for WinitState {
     fn output_state(&mut self) -> &mut OutputState {
         &mut self.output_state
@@ -431,3 +617,5 @@ sctk::delegate_registry!(WinitState);
 sctk::delegate_shm!(WinitState);
 sctk::delegate_xdg_shell!(WinitState);
 sctk::delegate_xdg_window!(WinitState);
+sctk::delegate_xdg_popup!(WinitState);
+sctk::delegate_layer!(WinitState);
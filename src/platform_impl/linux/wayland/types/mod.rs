@@ -3,5 +3,8 @@
 pub mod cursor;
 pub mod kwin_blur;
 pub mod wp_fractional_scaling;
+pub mod wp_idle_inhibit;
+pub mod wp_keyboard_shortcuts_inhibit;
 pub mod wp_viewporter;
 pub mod xdg_activation;
+pub mod xdg_toplevel_icon;
@@ -0,0 +1,67 @@
+//! Handling of the idle inhibitors.
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Dispatch;
+use sctk::reexports::client::{delegate_dispatch, Connection, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::idle_inhibit::zv1::client::{
+    zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1, zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1,
+};
+
+use sctk::globals::GlobalData;
+
+use crate::platform_impl::wayland::state::WinitState;
+
+/// The manager of the idle inhibitors, which keep the screen on while their surface is visible.
+#[derive(Debug, Clone)]
+pub struct IdleInhibitManager {
+    manager: ZwpIdleInhibitManagerV1,
+}
+
+impl IdleInhibitManager {
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    pub fn inhibit(
+        &self,
+        surface: &WlSurface,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> ZwpIdleInhibitorV1 {
+        self.manager
+            .create_inhibitor(surface, queue_handle, GlobalData)
+    }
+}
+
+impl Dispatch<ZwpIdleInhibitManagerV1, GlobalData, WinitState> for IdleInhibitManager {
+    fn event(
+        _: &mut WinitState,
+        _: &ZwpIdleInhibitManagerV1,
+        _: <ZwpIdleInhibitManagerV1 as Proxy>::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        unreachable!("no events defined for zwp_idle_inhibit_manager_v1");
+    }
+}
+
+impl Dispatch<ZwpIdleInhibitorV1, GlobalData, WinitState> for IdleInhibitManager {
+    fn event(
+        _: &mut WinitState,
+        _: &ZwpIdleInhibitorV1,
+        _: <ZwpIdleInhibitorV1 as Proxy>::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        unreachable!("no events defined for zwp_idle_inhibitor_v1");
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpIdleInhibitManagerV1: GlobalData] => IdleInhibitManager);
+delegate_dispatch!(WinitState: [ZwpIdleInhibitorV1: GlobalData] => IdleInhibitManager);
@@ -0,0 +1,88 @@
+//! Handling of the keyboard shortcuts inhibitors.
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_seat::WlSeat;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::Dispatch;
+use sctk::reexports::client::{delegate_dispatch, Connection, Proxy, QueueHandle};
+use sctk::reexports::protocols::wp::keyboard_shortcuts_inhibit::zv1::client::{
+    zwp_keyboard_shortcuts_inhibit_manager_v1::ZwpKeyboardShortcutsInhibitManagerV1,
+    zwp_keyboard_shortcuts_inhibitor_v1::{self, ZwpKeyboardShortcutsInhibitorV1},
+};
+
+use sctk::globals::GlobalData;
+
+use crate::event::WindowEvent;
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::wayland::WindowId;
+
+/// The manager of the inhibitors, which send the compositor shortcuts to their surface.
+#[derive(Debug, Clone)]
+pub struct KeyboardShortcutsInhibitManager {
+    manager: ZwpKeyboardShortcutsInhibitManagerV1,
+}
+
+impl KeyboardShortcutsInhibitManager {
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Inhibit the shortcuts of the `seat` while the window of the `surface` is focused.
+    pub fn inhibit(
+        &self,
+        surface: &WlSurface,
+        seat: &WlSeat,
+        window_id: WindowId,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> ZwpKeyboardShortcutsInhibitorV1 {
+        self.manager
+            .inhibit_shortcuts(surface, seat, queue_handle, window_id)
+    }
+}
+
+impl Dispatch<ZwpKeyboardShortcutsInhibitManagerV1, GlobalData, WinitState>
+    for KeyboardShortcutsInhibitManager
+{
+    fn event(
+        _: &mut WinitState,
+        _: &ZwpKeyboardShortcutsInhibitManagerV1,
+        _: <ZwpKeyboardShortcutsInhibitManagerV1 as Proxy>::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        unreachable!("no events defined for zwp_keyboard_shortcuts_inhibit_manager_v1");
+    }
+}
+
+impl Dispatch<ZwpKeyboardShortcutsInhibitorV1, WindowId, WinitState>
+    for KeyboardShortcutsInhibitManager
+{
+    fn event(
+        state: &mut WinitState,
+        _: &ZwpKeyboardShortcutsInhibitorV1,
+        event: <ZwpKeyboardShortcutsInhibitorV1 as Proxy>::Event,
+        window_id: &WindowId,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        let inhibited = match event {
+            zwp_keyboard_shortcuts_inhibitor_v1::Event::Active => true,
+            // The compositor revoked the inhibitor, or the window lost the focus.
+            zwp_keyboard_shortcuts_inhibitor_v1::Event::Inactive => false,
+            _ => return,
+        };
+
+        state.events_sink.push_window_event(
+            WindowEvent::KeyboardShortcutsInhibited(inhibited),
+            *window_id,
+        );
+    }
+}
+
+delegate_dispatch!(WinitState: [ZwpKeyboardShortcutsInhibitManagerV1: GlobalData] => KeyboardShortcutsInhibitManager);
+delegate_dispatch!(WinitState: [ZwpKeyboardShortcutsInhibitorV1: WindowId] => KeyboardShortcutsInhibitManager);
@@ -0,0 +1,171 @@
+//! Handling of the toplevel icons, with `xdg_toplevel_icon_v1`.
+
+use sctk::reexports::client::globals::{BindError, GlobalList};
+use sctk::reexports::client::protocol::wl_shm::Format;
+use sctk::reexports::client::{delegate_dispatch, Connection, Dispatch, Proxy, QueueHandle};
+use sctk::reexports::protocols::xdg::shell::client::xdg_toplevel::XdgToplevel;
+
+use sctk::globals::GlobalData;
+use sctk::shm::slot::{Buffer, SlotPool};
+
+use crate::platform_impl::wayland::state::WinitState;
+use crate::platform_impl::PlatformIcon;
+
+use self::protocol::xdg_toplevel_icon_manager_v1::{self, XdgToplevelIconManagerV1};
+use self::protocol::xdg_toplevel_icon_v1::XdgToplevelIconV1;
+
+/// The `xdg-toplevel-icon-v1` protocol, which isn't in `wayland-protocols` yet.
+#[allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+#[allow(non_upper_case_globals, non_snake_case, unused_imports)]
+#[allow(missing_docs, clippy::all)]
+pub mod protocol {
+    use sctk::reexports::client as wayland_client;
+    use sctk::reexports::client::protocol::*;
+    use sctk::reexports::protocols::xdg::shell::client::*;
+
+    pub mod __interfaces {
+        use sctk::reexports::client::protocol::__interfaces::*;
+        use sctk::reexports::protocols::xdg::shell::client::__interfaces::*;
+        wayland_scanner::generate_interfaces!(
+            "src/platform_impl/linux/wayland/types/xdg-toplevel-icon-v1.xml"
+        );
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!(
+        "src/platform_impl/linux/wayland/types/xdg-toplevel-icon-v1.xml"
+    );
+}
+
+/// The manager of the toplevel icons.
+#[derive(Debug, Clone)]
+pub struct XdgToplevelIconManager {
+    manager: XdgToplevelIconManagerV1,
+}
+
+impl XdgToplevelIconManager {
+    pub fn new(
+        globals: &GlobalList,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Result<Self, BindError> {
+        let manager = globals.bind(queue_handle, 1..=1, GlobalData)?;
+        Ok(Self { manager })
+    }
+
+    /// Set the icon of the `toplevel`, from its theme `name` and its `pixels`, resetting it when
+    /// neither is given.
+    ///
+    /// The icon is applied with the next commit of the toplevel.
+    pub(crate) fn set_icon(
+        &self,
+        toplevel: &XdgToplevel,
+        name: Option<&str>,
+        pixels: Option<&PlatformIcon>,
+        pool: &mut SlotPool,
+        queue_handle: &QueueHandle<WinitState>,
+    ) -> Option<ToplevelIcon> {
+        if name.is_none() && pixels.is_none() {
+            self.manager.set_icon(toplevel, None);
+            return None;
+        }
+
+        let icon = self.manager.create_icon(queue_handle, ());
+        if let Some(name) = name {
+            icon.set_name(name.to_owned());
+        }
+
+        let buffer = pixels.and_then(|pixels| match create_buffer(pool, pixels) {
+            Ok(buffer) => Some(buffer),
+            Err(err) => {
+                log::warn!("Failed to create the buffer of the window icon: {err}");
+                None
+            }
+        });
+        if let Some(buffer) = &buffer {
+            icon.add_buffer(buffer.wl_buffer(), 1);
+        }
+
+        self.manager.set_icon(toplevel, Some(&icon));
+        Some(ToplevelIcon { icon, buffer })
+    }
+}
+
+/// The icon set on a toplevel, with the buffer of its pixels.
+///
+/// The buffer must be kept alive as long as the icon, which is destroyed when it's replaced.
+#[derive(Debug)]
+pub struct ToplevelIcon {
+    icon: XdgToplevelIconV1,
+    buffer: Option<Buffer>,
+}
+
+impl Drop for ToplevelIcon {
+    fn drop(&mut self) {
+        self.icon.destroy();
+        self.buffer.take();
+    }
+}
+
+/// Copy the pixels of the `icon` to a square `wl_shm` buffer, centering them when the icon isn't
+/// square.
+fn create_buffer(
+    pool: &mut SlotPool,
+    icon: &PlatformIcon,
+) -> Result<Buffer, sctk::shm::slot::CreateBufferError> {
+    let size = icon.width.max(icon.height);
+    let (buffer, canvas) =
+        pool.create_buffer(size as i32, size as i32, 4 * size as i32, Format::Argb8888)?;
+    canvas.fill(0);
+
+    let (x, y) = ((size - icon.width) / 2, (size - icon.height) / 2);
+    for (row, rgba_row) in icon.rgba.chunks_exact(4 * icon.width as usize).enumerate() {
+        let start = 4 * ((y as usize + row) * size as usize + x as usize);
+        let canvas_row = &mut canvas[start..start + rgba_row.len()];
+        for (canvas_chunk, rgba_chunk) in
+            canvas_row.chunks_exact_mut(4).zip(rgba_row.chunks_exact(4))
+        {
+            // The pixels are ARGB in little endian, premultiplied by the alpha.
+            let alpha = rgba_chunk[3] as u32;
+            let premultiply = |channel: u8| (channel as u32 * alpha / 255) as u8;
+            canvas_chunk[0] = premultiply(rgba_chunk[2]);
+            canvas_chunk[1] = premultiply(rgba_chunk[1]);
+            canvas_chunk[2] = premultiply(rgba_chunk[0]);
+            canvas_chunk[3] = rgba_chunk[3];
+        }
+    }
+
+    Ok(buffer)
+}
+
+impl Dispatch<XdgToplevelIconManagerV1, GlobalData, WinitState> for XdgToplevelIconManager {
+    fn event(
+        _: &mut WinitState,
+        _: &XdgToplevelIconManagerV1,
+        event: <XdgToplevelIconManagerV1 as Proxy>::Event,
+        _: &GlobalData,
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        // The icons are provided at a single size, so the preferred sizes are ignored.
+        match event {
+            xdg_toplevel_icon_manager_v1::Event::IconSize { .. }
+            | xdg_toplevel_icon_manager_v1::Event::Done => (),
+        }
+    }
+}
+
+impl Dispatch<XdgToplevelIconV1, (), WinitState> for XdgToplevelIconManager {
+    fn event(
+        _: &mut WinitState,
+        _: &XdgToplevelIconV1,
+        _: <XdgToplevelIconV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<WinitState>,
+    ) {
+        unreachable!("no events defined for xdg_toplevel_icon_v1");
+    }
+}
+
+delegate_dispatch!(WinitState: [XdgToplevelIconManagerV1: GlobalData] => XdgToplevelIconManager);
+delegate_dispatch!(WinitState: [XdgToplevelIconV1: ()] => XdgToplevelIconManager);
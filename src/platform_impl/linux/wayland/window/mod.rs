@@ -8,12 +8,13 @@ use sctk::reexports::client::protocol::wl_display::WlDisplay;
 use sctk::reexports::client::protocol::wl_surface::WlSurface;
 use sctk::reexports::client::Proxy;
 use sctk::reexports::client::QueueHandle;
+#[cfg(feature = "rwh_06")]
+use sctk::reexports::client::{backend::ObjectId, Connection};
 
 use sctk::compositor::{CompositorState, Region, SurfaceData};
 use sctk::reexports::protocols::xdg::activation::v1::client::xdg_activation_v1::XdgActivationV1;
-use sctk::shell::xdg::window::Window as SctkWindow;
+use sctk::shell::xdg::popup::Popup;
 use sctk::shell::xdg::window::WindowDecorations;
-use sctk::shell::WaylandSurface;
 
 use log::warn;
 
@@ -21,11 +22,14 @@ use crate::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
 use crate::event::{Ime, WindowEvent};
 use crate::event_loop::AsyncRequestSerial;
+use crate::platform::drag_and_drop::DragSource;
+use crate::platform::layer_shell::LayerShellAttributes;
+use crate::platform::popup::PopupAttributes;
 use crate::platform_impl::{
     Fullscreen, MonitorHandle as PlatformMonitorHandle, OsError, PlatformIcon,
 };
 use crate::window::{
-    Cursor, CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+    Cursor, CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
     WindowAttributes, WindowButtons, WindowLevel,
 };
 
@@ -35,14 +39,16 @@ use super::state::WinitState;
 use super::types::xdg_activation::XdgActivationTokenData;
 use super::{EventLoopWindowTarget, WaylandError, WindowId};
 
+mod shell;
 pub(crate) mod state;
 
+pub use shell::{PopupPlacement, WindowShell};
 pub use state::WindowState;
 
 /// The Wayland window.
 pub struct Window {
-    /// Reference to the underlying SCTK window.
-    window: SctkWindow,
+    /// Reference to the underlying SCTK window or popup.
+    window: WindowShell,
 
     /// Window id.
     window_id: WindowId,
@@ -89,7 +95,32 @@ impl Window {
 
         let monitors = state.monitors.clone();
 
-        let surface = state.compositor_state.create_surface(&queue_handle);
+        let platform_specific = &attributes.platform_specific;
+        let subsurface_parent = match (&platform_specific.popup, &platform_specific.layer_shell) {
+            #[cfg(feature = "rwh_06")]
+            (None, None) => {
+                Self::subsurface_parent(&event_loop_window_target.connection, &attributes)?
+            }
+            _ => None,
+        };
+
+        let (surface, subsurface) = match subsurface_parent {
+            Some(parent) => {
+                // The subsurface starts with the scale of its parent, when it's a winit window.
+                let scale_factor = state
+                    .windows
+                    .get_mut()
+                    .get(&super::make_wid(&parent))
+                    .map(|parent| parent.lock().unwrap().scale_factor())
+                    .unwrap_or(1.);
+                let subcompositor = state.subcompositor_state.as_ref().ok_or_else(|| {
+                    os_error!(OsError::Misc("the wl_subcompositor global isn't available"))
+                })?;
+                let (subsurface, surface) = subcompositor.create_subsurface(parent, &queue_handle);
+                (surface, Some((subsurface, scale_factor)))
+            }
+            None => (state.compositor_state.create_surface(&queue_handle), None),
+        };
         let compositor = state.compositor_state.clone();
         let xdg_activation = state
             .xdg_activation
@@ -109,10 +140,27 @@ impl Window {
             WindowDecorations::RequestClient
         };
 
-        let window =
-            state
-                .xdg_shell
-                .create_window(surface.clone(), default_decorations, &queue_handle);
+        let window = match (
+            &subsurface,
+            &platform_specific.popup,
+            &platform_specific.layer_shell,
+        ) {
+            (Some((subsurface, _)), ..) => WindowShell::Subsurface {
+                subsurface: subsurface.clone(),
+                surface: surface.clone(),
+            },
+            (None, Some(popup), _) => {
+                Self::create_popup(&mut state, &queue_handle, &surface, popup, size)?
+            }
+            (None, None, Some(layer_shell)) => {
+                Self::create_layer(&state, &queue_handle, &surface, layer_shell, size)?
+            }
+            (None, None, None) => WindowShell::Toplevel(state.xdg_shell.create_window(
+                surface.clone(),
+                default_decorations,
+                &queue_handle,
+            )),
+        };
 
         let mut window_state = WindowState::new(
             event_loop_window_target.connection.clone(),
@@ -123,6 +171,18 @@ impl Window {
             attributes.preferred_theme,
         );
 
+        // The subsurface is placed relative to its parent and shown with it, without a configure.
+        if let Some((subsurface, scale_factor)) = subsurface {
+            window_state.set_scale_factor(scale_factor);
+            if let Some(position) = attributes.position {
+                window_state.set_subsurface_position(position.to_logical(scale_factor));
+            }
+            if !attributes.platform_specific.subsurface_sync {
+                subsurface.set_desync();
+            }
+            window_state.configure_subsurface();
+        }
+
         // Set transparency hint.
         window_state.set_transparent(attributes.transparent);
 
@@ -132,13 +192,24 @@ impl Window {
         window_state.set_decorate(attributes.decorations);
 
         // Set the app_id.
-        if let Some(name) = attributes.platform_specific.name.map(|name| name.general) {
+        if let (Some(name), Some(window)) = (
+            attributes.platform_specific.name.map(|name| name.general),
+            window.toplevel(),
+        ) {
             window.set_app_id(name);
         }
 
         // Set the window title.
         window_state.set_title(attributes.title);
 
+        // Set the window icon.
+        if let Some(name) = attributes.platform_specific.icon_name {
+            window_state.set_icon_name(Some(name));
+        }
+        if let Some(icon) = attributes.window_icon {
+            window_state.set_window_icon(Some(icon.inner));
+        }
+
         // Set the min and max sizes. We must set the hints upon creating a window, so
         // we use the default `1.` scaling...
         let min_size = attributes.min_inner_size.map(|size| size.to_logical(1.));
@@ -150,22 +221,23 @@ impl Window {
         window_state.set_resizable(attributes.resizable);
 
         // Set startup mode.
-        match attributes.fullscreen.map(Into::into) {
-            Some(Fullscreen::Exclusive(_)) => {
-                warn!("`Fullscreen::Exclusive` is ignored on Wayland");
-            }
-            Some(Fullscreen::Borderless(monitor)) => {
-                let output = monitor.and_then(|monitor| match monitor {
-                    PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                    #[cfg(x11_platform)]
-                    PlatformMonitorHandle::X(_) => None,
-                });
-
-                window.set_fullscreen(output.as_ref())
-            }
-            _ if attributes.maximized => window.set_maximized(),
-            _ => (),
-        };
+        if let Some(window) = window.toplevel() {
+            match attributes.fullscreen.map(Into::into) {
+                Some(Fullscreen::Exclusive(_)) => {
+                    warn!("`Fullscreen::Exclusive` is ignored on Wayland");
+                }
+                Some(Fullscreen::Borderless(monitor)) => {
+                    let output = monitor.and_then(|monitor| match monitor {
+                        PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
+                        _ => None,
+                    });
+
+                    window.set_fullscreen(output.as_ref())
+                }
+                _ if attributes.maximized => window.set_maximized(),
+                _ => (),
+            };
+        }
 
         match attributes.cursor {
             Cursor::Icon(icon) => window_state.set_cursor(icon),
@@ -181,7 +253,7 @@ impl Window {
         }
 
         // XXX Do initial commit.
-        window.commit();
+        window.wl_surface().commit();
 
         // Add the window and window requests into the state.
         let window_state = Arc::new(Mutex::new(window_state));
@@ -194,6 +266,7 @@ impl Window {
         let window_requests = WindowRequests {
             redraw_requested: AtomicBool::new(true),
             closed: AtomicBool::new(false),
+            drag: Mutex::new(None),
         };
         let window_requests = Arc::new(window_requests);
         state
@@ -242,6 +315,111 @@ impl Window {
             window_events_sink,
         })
     }
+
+    /// The surface of the parent window, when the window is created as its subsurface.
+    #[cfg(feature = "rwh_06")]
+    fn subsurface_parent(
+        connection: &Connection,
+        attributes: &WindowAttributes,
+    ) -> Result<Option<WlSurface>, RootOsError> {
+        let handle = match attributes.parent_window() {
+            Some(rwh_06::RawWindowHandle::Wayland(handle)) => handle,
+            Some(_) => {
+                return Err(os_error!(OsError::Misc(
+                    "the parent window isn't a Wayland window"
+                )))
+            }
+            None => return Ok(None),
+        };
+
+        // SAFETY: the handle is valid, as required by `WindowBuilder::with_parent_window`.
+        let id =
+            unsafe { ObjectId::from_ptr(WlSurface::interface(), handle.surface.as_ptr().cast()) };
+        id.and_then(|id| WlSurface::from_id(connection, id))
+            .map(Some)
+            .map_err(|_| os_error!(OsError::Misc("the parent window isn't a wl_surface")))
+    }
+
+    /// Create the popup placed relative to its parent window.
+    fn create_popup(
+        state: &mut WinitState,
+        queue_handle: &QueueHandle<WinitState>,
+        surface: &WlSurface,
+        attributes: &PopupAttributes,
+        size: Size,
+    ) -> Result<WindowShell, RootOsError> {
+        let parent = state
+            .windows
+            .get_mut()
+            .get(&attributes.parent().0)
+            .cloned()
+            .ok_or_else(|| os_error!(OsError::Misc("the parent of the popup doesn't exist")))?;
+        let parent = parent.lock().unwrap();
+
+        let placement = PopupPlacement::new(
+            state.xdg_shell.xdg_wm_base().clone(),
+            attributes,
+            parent.scale_factor(),
+            parent.geometry_origin(),
+        );
+        let global_error = |_| os_error!(OsError::Misc("the xdg_wm_base global isn't bound"));
+        let positioner = placement
+            .positioner(size.to_logical(parent.scale_factor()))
+            .map_err(global_error)?;
+        let popup = Popup::from_surface(
+            parent.window.xdg_surface(),
+            &positioner,
+            queue_handle,
+            surface.clone(),
+            &state.xdg_shell,
+        )
+        .map_err(global_error)?;
+
+        // The popups of layer surfaces are created without a parent, and assigned to it.
+        if let WindowShell::Layer { surface, .. } = &parent.window {
+            surface.get_popup(popup.xdg_popup());
+        }
+
+        // The grab must be requested before the initial commit, in response to a user input.
+        if attributes.grab() {
+            match parent.latest_button_press() {
+                Some((seat, serial)) => popup.xdg_popup().grab(&seat, serial),
+                None => warn!("The popup can't grab the input without a button press"),
+            }
+        }
+
+        Ok(WindowShell::Popup { popup, placement })
+    }
+
+    /// Create the layer surface placed against the edges of a monitor.
+    fn create_layer(
+        state: &WinitState,
+        queue_handle: &QueueHandle<WinitState>,
+        surface: &WlSurface,
+        attributes: &LayerShellAttributes,
+        size: Size,
+    ) -> Result<WindowShell, RootOsError> {
+        let layer_shell = state.layer_shell.as_ref().ok_or_else(|| {
+            os_error!(OsError::Misc(
+                "the zwlr_layer_shell_v1 global isn't available"
+            ))
+        })?;
+        let output = attributes
+            .monitor()
+            .and_then(|monitor| match &monitor.inner {
+                PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy.clone()),
+                _ => None,
+            });
+
+        Ok(WindowShell::layer(
+            layer_shell,
+            queue_handle,
+            surface.clone(),
+            attributes,
+            output.as_ref(),
+            size.to_logical(1.),
+        ))
+    }
 }
 
 impl Window {
@@ -268,17 +446,36 @@ impl Window {
 
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        self.inner_position()
     }
 
     #[inline]
     pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
-        Err(NotSupportedError::new())
+        // Only the subsurfaces know their position, relative to their parent.
+        let window_state = self.window_state.lock().unwrap();
+        window_state
+            .subsurface_position()
+            .map(|position| position.to_physical(window_state.scale_factor()))
+            .ok_or_else(NotSupportedError::new)
     }
 
     #[inline]
-    pub fn set_outer_position(&self, _: Position) {
-        // Not possible on Wayland.
+    pub fn set_outer_position(&self, position: Position) {
+        // Only possible for the subsurfaces, relative to their parent.
+        let mut window_state = self.window_state.lock().unwrap();
+        let position = position.to_logical(window_state.scale_factor());
+        window_state.set_subsurface_position(position);
+    }
+
+    #[inline]
+    pub fn set_subsurface_sync(&self, sync: bool) {
+        if let WindowShell::Subsurface { subsurface, .. } = &self.window {
+            if sync {
+                subsurface.set_sync();
+            } else {
+                subsurface.set_desync();
+            }
+        }
     }
 
     #[inline]
@@ -421,6 +618,19 @@ impl Window {
         self.window_state.lock().unwrap().set_blur(blur);
     }
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) {
+        self.window_state.lock().unwrap().set_idle_inhibit(inhibit);
+    }
+
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, inhibit: bool) {
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_keyboard_shortcuts_inhibit(inhibit);
+    }
+
     #[inline]
     pub fn set_decorations(&self, decorate: bool) {
         self.window_state.lock().unwrap().set_decorate(decorate)
@@ -435,7 +645,17 @@ impl Window {
     pub fn set_window_level(&self, _level: WindowLevel) {}
 
     #[inline]
-    pub(crate) fn set_window_icon(&self, _window_icon: Option<PlatformIcon>) {}
+    pub(crate) fn set_window_icon(&self, window_icon: Option<PlatformIcon>) {
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_window_icon(window_icon);
+    }
+
+    #[inline]
+    pub fn set_icon_name(&self, name: Option<String>) {
+        self.window_state.lock().unwrap().set_icon_name(name);
+    }
 
     #[inline]
     pub fn set_minimized(&self, minimized: bool) {
@@ -445,7 +665,9 @@ impl Window {
             return;
         }
 
-        self.window.set_minimized();
+        if let Some(window) = self.window.toplevel() {
+            window.set_minimized();
+        }
     }
 
     #[inline]
@@ -461,10 +683,15 @@ impl Window {
 
     #[inline]
     pub fn set_maximized(&self, maximized: bool) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         if maximized {
-            self.window.set_maximized()
+            window.set_maximized()
         } else {
-            self.window.unset_maximized()
+            window.unset_maximized()
         }
     }
 
@@ -489,6 +716,11 @@ impl Window {
 
     #[inline]
     pub(crate) fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         match fullscreen {
             Some(Fullscreen::Exclusive(_)) => {
                 warn!("`Fullscreen::Exclusive` is ignored on Wayland");
@@ -496,13 +728,12 @@ impl Window {
             Some(Fullscreen::Borderless(monitor)) => {
                 let output = monitor.and_then(|monitor| match monitor {
                     PlatformMonitorHandle::Wayland(monitor) => Some(monitor.proxy),
-                    #[cfg(x11_platform)]
-                    PlatformMonitorHandle::X(_) => None,
+                    _ => None,
                 });
 
-                self.window.set_fullscreen(output.as_ref())
+                window.set_fullscreen(output.as_ref())
             }
-            None => self.window.unset_fullscreen(),
+            None => window.unset_fullscreen(),
         }
     }
 
@@ -588,6 +819,13 @@ impl Window {
         self.window_state.lock().unwrap().drag_window()
     }
 
+    #[inline]
+    pub fn start_drag(&self, source: DragSource) -> Result<(), ExternalError> {
+        *self.window_requests.drag.lock().unwrap() = Some(source);
+        self.event_loop_awakener.ping();
+        Ok(())
+    }
+
     #[inline]
     pub fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
         let surface = self.window.wl_surface();
@@ -635,6 +873,19 @@ impl Window {
         self.window_state.lock().unwrap().set_ime_purpose(purpose);
     }
 
+    #[inline]
+    pub fn set_ime_hints(&self, hints: ImeHints) {
+        self.window_state.lock().unwrap().set_ime_hints(hints);
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        self.window_state
+            .lock()
+            .unwrap()
+            .set_ime_surrounding_text(text, cursor, anchor);
+    }
+
     #[inline]
     pub fn focus_window(&self) {}
 
@@ -740,6 +991,9 @@ pub struct WindowRequests {
 
     /// Redraw Requested.
     pub redraw_requested: AtomicBool,
+
+    /// The data to drag from the window.
+    pub drag: Mutex<Option<DragSource>>,
 }
 
 impl WindowRequests {
@@ -750,6 +1004,10 @@ impl WindowRequests {
     pub fn take_redraw_requested(&self) -> bool {
         self.redraw_requested.swap(false, Ordering::Relaxed)
     }
+
+    pub fn take_drag(&self) -> Option<DragSource> {
+        self.drag.lock().unwrap().take()
+    }
 }
 
 impl TryFrom<&str> for Theme {
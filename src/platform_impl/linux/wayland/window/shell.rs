@@ -0,0 +1,307 @@
+//! The shell roles of the window surfaces.
+
+use sctk::error::GlobalError;
+use sctk::globals::ProvidesBoundGlobal;
+use sctk::reexports::client::protocol::wl_output::WlOutput;
+use sctk::reexports::client::protocol::wl_subsurface::WlSubsurface;
+use sctk::reexports::client::protocol::wl_surface::WlSurface;
+use sctk::reexports::client::{Proxy, QueueHandle};
+use sctk::reexports::protocols::xdg::shell::client::xdg_positioner::{Anchor, Gravity};
+use sctk::reexports::protocols::xdg::shell::client::xdg_surface::XdgSurface;
+use sctk::reexports::protocols::xdg::shell::client::xdg_wm_base::XdgWmBase;
+
+use sctk::shell::wlr_layer::{self, LayerShell, LayerSurface};
+use sctk::shell::xdg::popup::Popup;
+use sctk::shell::xdg::window::Window;
+use sctk::shell::xdg::{XdgPositioner, XdgShell, XdgSurface as _};
+use sctk::shell::{Unsupported, WaylandSurface};
+
+use crate::dpi::{LogicalPosition, LogicalSize};
+use crate::platform::layer_shell::{
+    KeyboardInteractivity, Layer, LayerAnchor, LayerShellAttributes,
+};
+use crate::platform::popup::{ConstraintAdjustment, PopupAnchor, PopupAttributes};
+use crate::platform_impl::wayland::state::WinitState;
+
+/// The role of the surface of a window.
+#[derive(Debug, Clone)]
+pub enum WindowShell {
+    /// A toplevel window, managed by the compositor.
+    Toplevel(Window),
+
+    /// A popup, placed by the compositor relative to its parent.
+    Popup {
+        popup: Popup,
+        placement: PopupPlacement,
+    },
+
+    /// A layer surface, placed by the compositor against the edges of a monitor.
+    Layer {
+        surface: LayerSurface,
+        anchor: LayerAnchor,
+    },
+
+    /// A subsurface, placed by the client relative to its parent surface.
+    Subsurface {
+        subsurface: WlSubsurface,
+        surface: WlSurface,
+    },
+}
+
+impl WindowShell {
+    /// Create the layer surface with its `attributes`, on the `output` or the one chosen by the
+    /// compositor.
+    pub fn layer(
+        layer_shell: &LayerShell,
+        queue_handle: &QueueHandle<WinitState>,
+        surface: WlSurface,
+        attributes: &LayerShellAttributes,
+        output: Option<&WlOutput>,
+        size: LogicalSize<u32>,
+    ) -> Self {
+        let surface = layer_shell.create_layer_surface(
+            queue_handle,
+            surface,
+            wlr_layer(attributes.layer()),
+            Some(attributes.namespace()),
+            output,
+        );
+
+        let anchor = attributes.anchor();
+        let (width, height) = layer_size(anchor, size);
+        let (top, right, bottom, left) = attributes.margin();
+        surface.set_size(width, height);
+        surface.set_anchor(wlr_anchor(anchor));
+        surface.set_exclusive_zone(attributes.exclusive_zone());
+        surface.set_margin(top, right, bottom, left);
+        let version = match surface.kind() {
+            wlr_layer::SurfaceKind::Wlr(wlr_surface) => wlr_surface.version(),
+            _ => 0,
+        };
+        surface.set_keyboard_interactivity(wlr_keyboard_interactivity(
+            attributes.keyboard_interactivity(),
+            version,
+        ));
+
+        Self::Layer { surface, anchor }
+    }
+
+    /// The toplevel window, `None` for other roles.
+    #[inline]
+    pub fn toplevel(&self) -> Option<&Window> {
+        match self {
+            Self::Toplevel(window) => Some(window),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn wl_surface(&self) -> &WlSurface {
+        match self {
+            Self::Toplevel(window) => window.wl_surface(),
+            Self::Popup { popup, .. } => popup.wl_surface(),
+            Self::Layer { surface, .. } => surface.wl_surface(),
+            Self::Subsurface { surface, .. } => surface,
+        }
+    }
+
+    /// The `xdg_surface`, `None` for layer surfaces and subsurfaces.
+    #[inline]
+    pub fn xdg_surface(&self) -> Option<&XdgSurface> {
+        match self {
+            Self::Toplevel(window) => Some(window.xdg_surface()),
+            Self::Popup { popup, .. } => Some(popup.xdg_surface()),
+            Self::Layer { .. } | Self::Subsurface { .. } => None,
+        }
+    }
+
+    /// Set the buffer scale, which isn't supported before `wl_surface` version 3.
+    pub fn set_buffer_scale(&self, scale: u32) -> Result<(), Unsupported> {
+        match self {
+            Self::Toplevel(window) => window.set_buffer_scale(scale),
+            Self::Popup { popup, .. } => popup.xdg_shell_surface().set_buffer_scale(scale),
+            Self::Layer { surface, .. } => surface.set_buffer_scale(scale),
+            Self::Subsurface { surface, .. } if surface.version() >= 3 => {
+                surface.set_buffer_scale(scale as i32);
+                Ok(())
+            }
+            Self::Subsurface { .. } => Err(Unsupported),
+        }
+    }
+
+    /// Ask the compositor to resize the layer surface, on the axes it isn't stretched on.
+    ///
+    /// Returns `false` for other roles.
+    pub fn resize_layer(&self, size: LogicalSize<u32>) -> bool {
+        match self {
+            Self::Layer { surface, anchor } => {
+                let (width, height) = layer_size(*anchor, size);
+                surface.set_size(width, height);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Ask the compositor to place the popup again, with its new `size`.
+    ///
+    /// Returns `false` when the popup can't be moved, for other roles or before `xdg_wm_base`
+    /// version 3.
+    pub fn reposition(&self, size: LogicalSize<u32>) -> bool {
+        let (popup, placement) = match self {
+            Self::Popup { popup, placement } if popup.xdg_popup().version() >= 3 => {
+                (popup, placement)
+            }
+            _ => return false,
+        };
+
+        match placement.positioner(size) {
+            Ok(positioner) => {
+                popup.reposition(&positioner, 0);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// The placement of a popup, in the logical coordinates of the parent window geometry.
+#[derive(Debug, Clone)]
+pub struct PopupPlacement {
+    wm_base: WmBase,
+    anchor_position: LogicalPosition<i32>,
+    anchor_size: LogicalSize<i32>,
+    anchor: PopupAnchor,
+    gravity: PopupAnchor,
+    constraint_adjustment: ConstraintAdjustment,
+    offset: LogicalPosition<i32>,
+}
+
+impl PopupPlacement {
+    /// The placement of the `popup`, for a parent with the `scale_factor` and the origin of its
+    /// window geometry at `geometry_origin` in its surface.
+    pub fn new(
+        wm_base: XdgWmBase,
+        popup: &PopupAttributes,
+        scale_factor: f64,
+        geometry_origin: (i32, i32),
+    ) -> Self {
+        let position: LogicalPosition<i32> = popup.anchor_position().to_logical(scale_factor);
+        Self {
+            wm_base: WmBase(wm_base),
+            anchor_position: LogicalPosition::new(
+                position.x - geometry_origin.0,
+                position.y - geometry_origin.1,
+            ),
+            anchor_size: popup.anchor_size().to_logical(scale_factor),
+            anchor: popup.anchor(),
+            gravity: popup.gravity(),
+            constraint_adjustment: popup.constraint_adjustment(),
+            offset: popup.offset().to_logical(scale_factor),
+        }
+    }
+
+    /// Create the positioner placing a popup of the `size`.
+    pub fn positioner(&self, size: LogicalSize<u32>) -> Result<XdgPositioner, GlobalError> {
+        let positioner = XdgPositioner::new(&self.wm_base)?;
+        positioner.set_size(size.width.max(1) as i32, size.height.max(1) as i32);
+        // The anchor rectangle must be at least 1x1.
+        positioner.set_anchor_rect(
+            self.anchor_position.x,
+            self.anchor_position.y,
+            self.anchor_size.width.max(1),
+            self.anchor_size.height.max(1),
+        );
+        positioner.set_anchor(xdg_anchor(self.anchor));
+        positioner.set_gravity(xdg_gravity(self.gravity));
+        positioner.set_constraint_adjustment(self.constraint_adjustment.bits());
+        positioner.set_offset(self.offset.x, self.offset.y);
+        // Follow the parent when it's moved or resized.
+        if positioner.version() >= 3 {
+            positioner.set_reactive();
+        }
+
+        Ok(positioner)
+    }
+}
+
+/// The `xdg_wm_base` creating the positioners, which was bound with the `XdgShell`.
+#[derive(Debug, Clone)]
+struct WmBase(XdgWmBase);
+
+impl ProvidesBoundGlobal<XdgWmBase, { XdgShell::API_VERSION_MAX }> for WmBase {
+    fn bound_global(&self) -> Result<XdgWmBase, GlobalError> {
+        Ok(self.0.clone())
+    }
+}
+
+/// The size of the layer surface, `0` on the axes it's stretched on between opposite edges.
+fn layer_size(anchor: LayerAnchor, size: LogicalSize<u32>) -> (u32, u32) {
+    let width = match anchor.contains(LayerAnchor::LEFT | LayerAnchor::RIGHT) {
+        true => 0,
+        false => size.width.max(1),
+    };
+    let height = match anchor.contains(LayerAnchor::TOP | LayerAnchor::BOTTOM) {
+        true => 0,
+        false => size.height.max(1),
+    };
+    (width, height)
+}
+
+fn wlr_layer(layer: Layer) -> wlr_layer::Layer {
+    match layer {
+        Layer::Background => wlr_layer::Layer::Background,
+        Layer::Bottom => wlr_layer::Layer::Bottom,
+        Layer::Top => wlr_layer::Layer::Top,
+        Layer::Overlay => wlr_layer::Layer::Overlay,
+    }
+}
+
+fn wlr_anchor(anchor: LayerAnchor) -> wlr_layer::Anchor {
+    // The bits are the ones of the protocol.
+    wlr_layer::Anchor::from_bits_truncate(anchor.bits())
+}
+
+/// The keyboard interactivity of the layer surface of `version`, which has no on demand focus
+/// before version 4.
+fn wlr_keyboard_interactivity(
+    interactivity: KeyboardInteractivity,
+    version: u32,
+) -> wlr_layer::KeyboardInteractivity {
+    match interactivity {
+        KeyboardInteractivity::None => wlr_layer::KeyboardInteractivity::None,
+        KeyboardInteractivity::Exclusive => wlr_layer::KeyboardInteractivity::Exclusive,
+        KeyboardInteractivity::OnDemand if version >= 4 => {
+            wlr_layer::KeyboardInteractivity::OnDemand
+        }
+        KeyboardInteractivity::OnDemand => wlr_layer::KeyboardInteractivity::None,
+    }
+}
+
+fn xdg_anchor(anchor: PopupAnchor) -> Anchor {
+    match anchor {
+        PopupAnchor::Center => Anchor::None,
+        PopupAnchor::Top => Anchor::Top,
+        PopupAnchor::Bottom => Anchor::Bottom,
+        PopupAnchor::Left => Anchor::Left,
+        PopupAnchor::Right => Anchor::Right,
+        PopupAnchor::TopLeft => Anchor::TopLeft,
+        PopupAnchor::BottomLeft => Anchor::BottomLeft,
+        PopupAnchor::TopRight => Anchor::TopRight,
+        PopupAnchor::BottomRight => Anchor::BottomRight,
+    }
+}
+
+fn xdg_gravity(gravity: PopupAnchor) -> Gravity {
+    match gravity {
+        PopupAnchor::Center => Gravity::None,
+        PopupAnchor::Top => Gravity::Top,
+        PopupAnchor::Bottom => Gravity::Bottom,
+        PopupAnchor::Left => Gravity::Left,
+        PopupAnchor::Right => Gravity::Right,
+        PopupAnchor::TopLeft => Gravity::TopLeft,
+        PopupAnchor::BottomLeft => Gravity::BottomLeft,
+        PopupAnchor::TopRight => Gravity::TopRight,
+        PopupAnchor::BottomRight => Gravity::BottomRight,
+    }
+}
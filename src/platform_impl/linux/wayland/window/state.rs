@@ -14,15 +14,17 @@ use sctk::reexports::csd_frame::{
     DecorationsFrame, FrameAction, FrameClick, ResizeEdge, WindowState as XdgWindowState,
 };
 use sctk::reexports::protocols::wp::fractional_scale::v1::client::wp_fractional_scale_v1::WpFractionalScaleV1;
+use sctk::reexports::protocols::wp::idle_inhibit::zv1::client::zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1;
+use sctk::reexports::protocols::wp::keyboard_shortcuts_inhibit::zv1::client::zwp_keyboard_shortcuts_inhibitor_v1::ZwpKeyboardShortcutsInhibitorV1;
 use sctk::reexports::protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 use sctk::reexports::protocols::wp::viewporter::client::wp_viewport::WpViewport;
 use sctk::reexports::protocols::xdg::shell::client::xdg_toplevel::ResizeEdge as XdgResizeEdge;
 
 use sctk::compositor::{CompositorState, Region, SurfaceData, SurfaceDataExt};
 use sctk::seat::pointer::PointerDataExt;
-use sctk::shell::xdg::window::{DecorationMode, Window, WindowConfigure};
-use sctk::shell::xdg::XdgSurface;
-use sctk::shell::WaylandSurface;
+use sctk::shell::wlr_layer::LayerSurfaceConfigure;
+use sctk::shell::xdg::popup::PopupConfigure;
+use sctk::shell::xdg::window::{DecorationMode, WindowConfigure};
 use sctk::shm::slot::SlotPool;
 use sctk::shm::Shm;
 use sctk::subcompositor::SubcompositorState;
@@ -32,18 +34,27 @@ use crate::cursor::CustomCursor as RootCustomCursor;
 use crate::dpi::{LogicalPosition, LogicalSize, PhysicalSize, Size};
 use crate::error::{ExternalError, NotSupportedError};
 use crate::event::WindowEvent;
+#[cfg(dbus_ime)]
+use crate::platform_impl::common::dbus_ime::DBusIme;
 use crate::platform_impl::wayland::event_loop::sink::EventSink;
 use crate::platform_impl::wayland::types::cursor::{CustomCursor, SelectedCursor};
 use crate::platform_impl::wayland::types::kwin_blur::KWinBlurManager;
+use crate::platform_impl::wayland::types::wp_idle_inhibit::IdleInhibitManager;
+use crate::platform_impl::wayland::types::wp_keyboard_shortcuts_inhibit::KeyboardShortcutsInhibitManager;
+use crate::platform_impl::wayland::types::xdg_toplevel_icon::{
+    ToplevelIcon, XdgToplevelIconManager,
+};
 use crate::platform_impl::wayland::{logical_to_physical_rounded, make_wid};
-use crate::platform_impl::{PlatformCustomCursor, WindowId};
-use crate::window::{CursorGrabMode, CursorIcon, ImePurpose, ResizeDirection, Theme};
+use crate::platform_impl::{PlatformCustomCursor, PlatformIcon, WindowId};
+use crate::window::{CursorGrabMode, CursorIcon, ImeHints, ImePurpose, ResizeDirection, Theme};
 
 use crate::platform_impl::wayland::seat::{
-    PointerConstraintsState, WinitPointerData, ZwpTextInputV3Ext,
+    PointerConstraintsState, SurroundingText, WinitPointerData, ZwpTextInputV3Ext,
 };
 use crate::platform_impl::wayland::state::{WindowCompositorUpdate, WinitState};
 
+use super::shell::WindowShell;
+
 #[cfg(feature = "sctk-adwaita")]
 pub type WinitFrame = sctk_adwaita::AdwaitaFrame<WinitState>;
 #[cfg(not(feature = "sctk-adwaita"))]
@@ -69,6 +80,12 @@ pub struct WindowState {
     /// The last received configure.
     pub last_configure: Option<WindowConfigure>,
 
+    /// Whether the popup or the layer surface received its initial configure.
+    shell_configured: bool,
+
+    /// The position of the subsurface relative to its parent surface.
+    subsurface_position: LogicalPosition<i32>,
+
     /// The pointers observed on the window.
     pub pointers: Vec<Weak<crate::platform_impl::wayland::GenericPointer>>,
 
@@ -113,9 +130,19 @@ pub struct WindowState {
     /// The current IME purpose.
     ime_purpose: ImePurpose,
 
+    /// The hints about the text expected from the IME.
+    ime_hints: ImeHints,
+
+    /// The text around the cursor, sent to the text inputs.
+    ime_surrounding_text: Option<SurroundingText>,
+
     /// The text inputs observed on the window.
     text_inputs: Vec<ZwpTextInputV3>,
 
+    /// The input method on D-Bus, used instead of the text inputs.
+    #[cfg(dbus_ime)]
+    dbus_ime: Option<Arc<Mutex<DBusIme>>>,
+
     /// The inner size of the window, as in without client side decorations.
     size: LogicalSize<u32>,
 
@@ -146,13 +173,30 @@ pub struct WindowState {
     blur: Option<OrgKdeKwinBlur>,
     blur_manager: Option<KWinBlurManager>,
 
+    /// The inhibitor keeping the screen on while the window is visible.
+    idle_inhibitor: Option<ZwpIdleInhibitorV1>,
+    idle_inhibit_manager: Option<IdleInhibitManager>,
+
+    /// Whether the compositor shortcuts should be sent to the window.
+    shortcuts_inhibit: bool,
+    /// The seats with the keyboard focus on the window, and the inhibitors created on them.
+    keyboard_seats: Vec<WlSeat>,
+    shortcuts_inhibitors: Vec<(WlSeat, ZwpKeyboardShortcutsInhibitorV1)>,
+    shortcuts_inhibit_manager: Option<KeyboardShortcutsInhibitManager>,
+
+    /// The window icon, from its theme name and its pixels.
+    icon_name: Option<String>,
+    icon: Option<PlatformIcon>,
+    toplevel_icon: Option<ToplevelIcon>,
+    icon_manager: Option<XdgToplevelIconManager>,
+
     /// Whether the client side decorations have pending move operations.
     ///
     /// The value is the serial of the event triggered moved.
     has_pending_move: Option<u32>,
 
-    /// The underlying SCTK window.
-    pub window: Window,
+    /// The underlying SCTK window or popup.
+    pub window: WindowShell,
 }
 
 impl WindowState {
@@ -162,7 +206,7 @@ impl WindowState {
         queue_handle: &QueueHandle<WinitState>,
         winit_state: &WinitState,
         initial_size: Size,
-        window: Window,
+        window: WindowShell,
         theme: Option<Theme>,
     ) -> Self {
         let compositor = winit_state.compositor_state.clone();
@@ -179,6 +223,16 @@ impl WindowState {
         Self {
             blur: None,
             blur_manager: winit_state.kwin_blur_manager.clone(),
+            idle_inhibitor: None,
+            idle_inhibit_manager: winit_state.idle_inhibit_manager.clone(),
+            shortcuts_inhibit: false,
+            keyboard_seats: Vec::new(),
+            shortcuts_inhibitors: Vec::new(),
+            shortcuts_inhibit_manager: winit_state.keyboard_shortcuts_inhibit_manager.clone(),
+            icon_name: None,
+            icon: None,
+            toplevel_icon: None,
+            icon_manager: winit_state.xdg_toplevel_icon_manager.clone(),
             compositor,
             connection,
             csd_fails: false,
@@ -193,7 +247,11 @@ impl WindowState {
             has_pending_move: None,
             ime_allowed: false,
             ime_purpose: ImePurpose::Normal,
+            ime_hints: ImeHints::empty(),
+            ime_surrounding_text: None,
             last_configure: None,
+            shell_configured: false,
+            subsurface_position: LogicalPosition::new(0, 0),
             max_inner_size: None,
             min_inner_size: MIN_WINDOW_SIZE,
             pointer_constraints,
@@ -207,6 +265,8 @@ impl WindowState {
             stateless_size: initial_size.to_logical(1.),
             initial_size: Some(initial_size),
             text_inputs: Vec::new(),
+            #[cfg(dbus_ime)]
+            dbus_ime: winit_state.dbus_ime.clone(),
             theme,
             title: String::default(),
             transparent: false,
@@ -271,13 +331,14 @@ impl WindowState {
             self.stateless_size = self.size;
         }
 
-        if let Some(subcompositor) = subcompositor.as_ref().filter(|_| {
+        let toplevel = self.window.toplevel();
+        if let Some((subcompositor, window)) = subcompositor.as_ref().zip(toplevel).filter(|_| {
             configure.decoration_mode == DecorationMode::Client
                 && self.frame.is_none()
                 && !self.csd_fails
         }) {
             match WinitFrame::new(
-                &self.window,
+                window,
                 shm,
                 #[cfg(feature = "sctk-adwaita")]
                 self.compositor.clone(),
@@ -380,6 +441,81 @@ impl WindowState {
         }
     }
 
+    /// Apply the configure of the popup, returning whether it was resized.
+    pub fn configure_popup(&mut self, configure: PopupConfigure) -> bool {
+        // The popup is placed with its initial size, the configure tells the size it fits in.
+        self.initial_size = None;
+        let new_size = LogicalSize::new(configure.width.max(1), configure.height.max(1)).cast();
+
+        let initial_configure = !std::mem::replace(&mut self.shell_configured, true);
+        if initial_configure || new_size != self.inner_size() {
+            self.resize(new_size);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Apply the configure of the layer surface, returning whether it was resized.
+    pub fn configure_layer(&mut self, configure: LayerSurfaceConfigure) -> bool {
+        if let Some(initial_size) = self.initial_size.take() {
+            self.size = initial_size.to_logical(self.scale_factor());
+        }
+
+        // The size is left to the client on the axes it's zero.
+        let (width, height) = configure.new_size;
+        let new_size = LogicalSize::new(
+            if width == 0 { self.size.width } else { width },
+            if height == 0 {
+                self.size.height
+            } else {
+                height
+            },
+        );
+
+        let initial_configure = !std::mem::replace(&mut self.shell_configured, true);
+        if initial_configure || new_size != self.inner_size() {
+            self.resize(new_size);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Apply the initial size of the subsurface, which is never configured by the compositor.
+    pub fn configure_subsurface(&mut self) {
+        if let Some(initial_size) = self.initial_size.take() {
+            self.size = initial_size.to_logical(self.scale_factor());
+        }
+
+        self.shell_configured = true;
+        self.resize(self.size);
+    }
+
+    /// The position of the subsurface relative to its parent surface, `None` for other roles.
+    pub fn subsurface_position(&self) -> Option<LogicalPosition<i32>> {
+        match self.window {
+            WindowShell::Subsurface { .. } => Some(self.subsurface_position),
+            _ => None,
+        }
+    }
+
+    /// Move the subsurface relative to its parent surface, with the next commit of the parent.
+    pub fn set_subsurface_position(&mut self, position: LogicalPosition<i32>) {
+        if let WindowShell::Subsurface { subsurface, .. } = &self.window {
+            subsurface.set_position(position.x, position.y);
+            self.subsurface_position = position;
+        }
+    }
+
+    /// The origin of the window geometry in the surface, which is offset by the decorations.
+    pub fn geometry_origin(&self) -> (i32, i32) {
+        self.frame
+            .as_ref()
+            .map(|frame| frame.location())
+            .unwrap_or_default()
+    }
+
     /// Compute the bounds for the inner size of the surface.
     fn inner_size_bounds(
         &self,
@@ -411,7 +547,10 @@ impl WindowState {
 
     /// Start interacting drag resize.
     pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
-        let xdg_toplevel = self.window.xdg_toplevel();
+        let xdg_toplevel = match self.window.toplevel() {
+            Some(window) => window.xdg_toplevel(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
 
         // TODO(kchibisov) handle touch serials.
         self.apply_on_poiner(|_, data| {
@@ -425,7 +564,10 @@ impl WindowState {
 
     /// Start the window drag.
     pub fn drag_window(&self) -> Result<(), ExternalError> {
-        let xdg_toplevel = self.window.xdg_toplevel();
+        let xdg_toplevel = match self.window.toplevel() {
+            Some(window) => window.xdg_toplevel(),
+            None => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+        };
         // TODO(kchibisov) handle touch serials.
         self.apply_on_poiner(|_, data| {
             let serial = data.latest_button_serial();
@@ -436,6 +578,20 @@ impl WindowState {
         Ok(())
     }
 
+    /// The seat with the latest button press over the window, and the serial of the press.
+    pub fn latest_button_press(&self) -> Option<(WlSeat, u32)> {
+        self.pointers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|pointer| {
+                let data = pointer.winit_data();
+                (data.seat().clone(), data.latest_button_serial())
+            })
+            // The serial is zero until a button is pressed.
+            .filter(|(_, serial)| *serial != 0)
+            .max_by_key(|(_, serial)| *serial)
+    }
+
     /// Tells whether the window should be closed.
     #[allow(clippy::too_many_arguments)]
     pub fn frame_click(
@@ -448,10 +604,12 @@ impl WindowState {
         window_id: WindowId,
         updates: &mut Vec<WindowCompositorUpdate>,
     ) -> Option<bool> {
+        // The decorations are only drawn for toplevel windows.
+        let window = self.window.toplevel()?;
         match self.frame.as_mut()?.on_click(timestamp, click, pressed)? {
-            FrameAction::Minimize => self.window.set_minimized(),
-            FrameAction::Maximize => self.window.set_maximized(),
-            FrameAction::UnMaximize => self.window.unset_maximized(),
+            FrameAction::Minimize => window.set_minimized(),
+            FrameAction::Maximize => window.set_maximized(),
+            FrameAction::UnMaximize => window.unset_maximized(),
             FrameAction::Close => WinitState::queue_close(updates, window_id),
             FrameAction::Move => self.has_pending_move = Some(serial),
             FrameAction::Resize(edge) => {
@@ -467,9 +625,9 @@ impl WindowState {
                     ResizeEdge::BottomRight => XdgResizeEdge::BottomRight,
                     _ => return None,
                 };
-                self.window.resize(seat, serial, edge);
+                window.resize(seat, serial, edge);
             }
-            FrameAction::ShowMenu(x, y) => self.window.show_window_menu(seat, serial, (x, y)),
+            FrameAction::ShowMenu(x, y) => window.show_window_menu(seat, serial, (x, y)),
             _ => (),
         };
 
@@ -499,7 +657,9 @@ impl WindowState {
             // If we have a cursor change, that means that cursor is over the decorations,
             // so try to apply move.
             if let Some(serial) = cursor.is_some().then_some(serial).flatten() {
-                self.window.move_(seat, serial);
+                if let Some(window) = self.window.toplevel() {
+                    window.move_(seat, serial);
+                }
                 None
             } else {
                 cursor
@@ -558,7 +718,7 @@ impl WindowState {
     /// Whether the window received initial configure event from the compositor.
     #[inline]
     pub fn is_configured(&self) -> bool {
-        self.last_configure.is_some()
+        self.last_configure.is_some() || self.shell_configured
     }
 
     #[inline]
@@ -647,7 +807,15 @@ impl WindowState {
 
     /// Try to resize the window when the user can do so.
     pub fn request_inner_size(&mut self, inner_size: Size) -> PhysicalSize<u32> {
-        if self
+        // The popup is resized once the compositor placed it again.
+        if let WindowShell::Popup { .. } = self.window {
+            self.window
+                .reposition(inner_size.to_logical(self.scale_factor()));
+        } else if let WindowShell::Layer { .. } = self.window {
+            // The layer surface is resized with the next configure.
+            self.window
+                .resize_layer(inner_size.to_logical(self.scale_factor()));
+        } else if self
             .last_configure
             .as_ref()
             .map(Self::is_stateless)
@@ -690,12 +858,14 @@ impl WindowState {
         self.reload_transparency_hint();
 
         // Set the window geometry.
-        self.window.xdg_surface().set_window_geometry(
-            x,
-            y,
-            outer_size.width as i32,
-            outer_size.height as i32,
-        );
+        if let Some(xdg_surface) = self.window.xdg_surface() {
+            xdg_surface.set_window_geometry(
+                x,
+                y,
+                outer_size.width as i32,
+                outer_size.height as i32,
+            );
+        }
 
         // Update the target viewport, this is used if and only if fractional scaling is in use.
         if let Some(viewport) = self.viewport.as_ref() {
@@ -731,11 +901,8 @@ impl WindowState {
             RootCustomCursor {
                 inner: PlatformCustomCursor::Wayland(cursor),
             } => cursor.0,
-            #[cfg(x11_platform)]
-            RootCustomCursor {
-                inner: PlatformCustomCursor::X(_),
-            } => {
-                log::error!("passed a X11 cursor to Wayland backend");
+            RootCustomCursor { inner: _ } => {
+                log::error!("passed a foreign cursor to Wayland backend");
                 return;
             }
         };
@@ -801,7 +968,9 @@ impl WindowState {
             .unwrap_or(size);
 
         self.min_inner_size = size;
-        self.window.set_min_size(Some(size.into()));
+        if let Some(window) = self.window.toplevel() {
+            window.set_min_size(Some(size.into()));
+        }
     }
 
     /// Set maximum inner window size.
@@ -814,7 +983,9 @@ impl WindowState {
         });
 
         self.max_inner_size = size;
-        self.window.set_max_size(size.map(Into::into));
+        if let Some(window) = self.window.toplevel() {
+            window.set_max_size(size.map(Into::into));
+        }
     }
 
     /// Set the CSD theme.
@@ -889,11 +1060,16 @@ impl WindowState {
     }
 
     pub fn show_window_menu(&self, position: LogicalPosition<u32>) {
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         // TODO(kchibisov) handle touch serials.
         self.apply_on_poiner(|_, data| {
             let serial = data.latest_button_serial();
             let seat = data.seat();
-            self.window.show_window_menu(seat, serial, position.into());
+            window.show_window_menu(seat, serial, position.into());
         });
     }
 
@@ -944,6 +1120,11 @@ impl WindowState {
 
         self.decorate = decorate;
 
+        let window = match self.window.toplevel() {
+            Some(window) => window,
+            None => return,
+        };
+
         match self
             .last_configure
             .as_ref()
@@ -951,12 +1132,9 @@ impl WindowState {
         {
             Some(DecorationMode::Server) if !self.decorate => {
                 // To disable decorations we should request client and hide the frame.
-                self.window
-                    .request_decoration_mode(Some(DecorationMode::Client))
+                window.request_decoration_mode(Some(DecorationMode::Client))
             }
-            _ if self.decorate => self
-                .window
-                .request_decoration_mode(Some(DecorationMode::Server)),
+            _ if self.decorate => window.request_decoration_mode(Some(DecorationMode::Server)),
             _ => (),
         }
 
@@ -973,6 +1151,20 @@ impl WindowState {
     #[inline]
     pub fn set_has_focus(&mut self, has_focus: bool) {
         self.has_focus = has_focus;
+
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| dbus_ime.set_focus(window_id, has_focus));
+    }
+
+    /// Run the closure with the input method on D-Bus, when it's used.
+    #[cfg(dbus_ime)]
+    fn with_dbus_ime<F: FnOnce(&mut DBusIme, WindowId)>(&self, f: F) {
+        if let Some(dbus_ime) = self.dbus_ime.as_ref() {
+            f(
+                &mut dbus_ime.lock().unwrap(),
+                make_wid(self.window.wl_surface()),
+            );
+        }
     }
 
     /// Returns `true` if the requested state was applied.
@@ -984,13 +1176,21 @@ impl WindowState {
             applied = true;
             if allowed {
                 text_input.enable();
-                text_input.set_content_type_by_purpose(self.ime_purpose);
+                text_input.set_content_type_by_purpose(self.ime_purpose, self.ime_hints);
+                if let Some(surrounding_text) = self.ime_surrounding_text.as_ref() {
+                    text_input.set_surrounding(surrounding_text);
+                }
             } else {
                 text_input.disable();
             }
             text_input.commit();
         }
 
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| {
+            applied |= dbus_ime.set_allowed(window_id, allowed);
+        });
+
         applied
     }
 
@@ -1005,6 +1205,11 @@ impl WindowState {
             text_input.set_cursor_rectangle(x, y, width, height);
             text_input.commit();
         }
+
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| {
+            dbus_ime.set_cursor_area(window_id, [x, y, width, height]);
+        });
     }
 
     /// Set the IME purpose.
@@ -1012,9 +1217,34 @@ impl WindowState {
         self.ime_purpose = purpose;
 
         for text_input in &self.text_inputs {
-            text_input.set_content_type_by_purpose(purpose);
+            text_input.set_content_type_by_purpose(purpose, self.ime_hints);
+            text_input.commit();
+        }
+
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| {
+            dbus_ime.set_content_type(window_id, purpose, self.ime_hints);
+        });
+    }
+
+    /// Set the IME hints.
+    pub fn set_ime_hints(&mut self, hints: ImeHints) {
+        self.ime_hints = hints;
+
+        for text_input in &self.text_inputs {
+            text_input.set_content_type_by_purpose(self.ime_purpose, hints);
             text_input.commit();
         }
+
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| {
+            dbus_ime.set_content_type(window_id, self.ime_purpose, hints);
+        });
+    }
+
+    /// Get the IME hints.
+    pub fn ime_hints(&self) -> ImeHints {
+        self.ime_hints
     }
 
     /// Get the IME purpose.
@@ -1022,6 +1252,43 @@ impl WindowState {
         self.ime_purpose
     }
 
+    /// Set the text around the cursor.
+    pub fn set_ime_surrounding_text(&mut self, text: String, cursor: usize, anchor: usize) {
+        if !text.is_char_boundary(cursor) || !text.is_char_boundary(anchor) {
+            warn!("The IME cursor and anchor must be on character boundaries of the text");
+            return;
+        }
+
+        let surrounding_text = SurroundingText {
+            text,
+            cursor,
+            anchor,
+        };
+        if self.ime_allowed {
+            for text_input in &self.text_inputs {
+                text_input.set_surrounding(&surrounding_text);
+                text_input.commit();
+            }
+        }
+
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| {
+            let SurroundingText {
+                text,
+                cursor,
+                anchor,
+            } = surrounding_text.clone();
+            dbus_ime.set_surrounding_text(window_id, text, cursor, anchor);
+        });
+
+        self.ime_surrounding_text = Some(surrounding_text);
+    }
+
+    /// Get the text around the cursor.
+    pub fn ime_surrounding_text(&self) -> Option<&SurroundingText> {
+        self.ime_surrounding_text.as_ref()
+    }
+
     /// Set the scale factor for the given window.
     #[inline]
     pub fn set_scale_factor(&mut self, scale_factor: f64) {
@@ -1057,6 +1324,107 @@ impl WindowState {
         }
     }
 
+    /// Keep the screen from blanking while the window is visible.
+    pub fn set_idle_inhibit(&mut self, inhibit: bool) {
+        if inhibit && self.idle_inhibitor.is_none() {
+            if let Some(idle_inhibit_manager) = self.idle_inhibit_manager.as_ref() {
+                self.idle_inhibitor = Some(
+                    idle_inhibit_manager.inhibit(self.window.wl_surface(), &self.queue_handle),
+                );
+            } else {
+                info!("Idle inhibit manager unavailable, unable to inhibit the idle state")
+            }
+        } else if !inhibit {
+            if let Some(idle_inhibitor) = self.idle_inhibitor.take() {
+                idle_inhibitor.destroy();
+            }
+        }
+    }
+
+    /// Send the compositor shortcuts to the window while it has the keyboard focus.
+    pub fn set_keyboard_shortcuts_inhibit(&mut self, inhibit: bool) {
+        self.shortcuts_inhibit = inhibit;
+        if inhibit {
+            if self.shortcuts_inhibit_manager.is_none() {
+                info!(
+                    "Keyboard shortcuts inhibit manager unavailable, unable to inhibit shortcuts"
+                );
+                return;
+            }
+
+            for seat in self.keyboard_seats.clone() {
+                self.inhibit_shortcuts(&seat);
+            }
+        } else {
+            for (_, inhibitor) in self.shortcuts_inhibitors.drain(..) {
+                inhibitor.destroy();
+            }
+        }
+    }
+
+    /// Create the shortcuts inhibitor of the `seat`, unless it already exists.
+    fn inhibit_shortcuts(&mut self, seat: &WlSeat) {
+        let manager = match self.shortcuts_inhibit_manager.as_ref() {
+            Some(manager) if self.shortcuts_inhibit => manager,
+            _ => return,
+        };
+
+        // The protocol forbids more than one inhibitor per surface and seat.
+        if self.shortcuts_inhibitors.iter().any(|(s, _)| s == seat) {
+            return;
+        }
+
+        let surface = self.window.wl_surface();
+        let inhibitor = manager.inhibit(surface, seat, make_wid(surface), &self.queue_handle);
+        self.shortcuts_inhibitors.push((seat.clone(), inhibitor));
+    }
+
+    /// The keyboard of the `seat` entered the window.
+    pub fn keyboard_entered(&mut self, seat: &WlSeat) {
+        if !self.keyboard_seats.contains(seat) {
+            self.keyboard_seats.push(seat.clone());
+        }
+        self.inhibit_shortcuts(seat);
+    }
+
+    /// The keyboard of the `seat` left the window.
+    pub fn keyboard_left(&mut self, seat: &WlSeat) {
+        self.keyboard_seats.retain(|s| s != seat);
+    }
+
+    /// Set the window icon from its pixels.
+    pub(crate) fn set_window_icon(&mut self, icon: Option<PlatformIcon>) {
+        self.icon = icon;
+        self.reload_icon();
+    }
+
+    /// Set the window icon from the icon theme, which is preferred to its pixels.
+    pub fn set_icon_name(&mut self, name: Option<String>) {
+        self.icon_name = name;
+        self.reload_icon();
+    }
+
+    /// Send the icon to the compositor, which applies it with the next commit.
+    fn reload_icon(&mut self) {
+        let (toplevel, icon_manager) = match (self.window.toplevel(), &self.icon_manager) {
+            (Some(toplevel), Some(icon_manager)) => (toplevel, icon_manager),
+            (Some(_), None) => {
+                info!("Toplevel icon manager unavailable, unable to change the window icon");
+                return;
+            }
+            _ => return,
+        };
+
+        let mut pool = self.custom_cursor_pool.lock().unwrap();
+        self.toplevel_icon = icon_manager.set_icon(
+            toplevel.xdg_toplevel(),
+            self.icon_name.as_deref(),
+            self.icon.as_ref(),
+            &mut pool,
+            &self.queue_handle,
+        );
+    }
+
     /// Set the window title to a new value.
     ///
     /// This will autmatically truncate the title to something meaningfull.
@@ -1076,7 +1444,9 @@ impl WindowState {
             frame.set_title(&title);
         }
 
-        self.window.set_title(&title);
+        if let Some(window) = self.window.toplevel() {
+            window.set_title(&title);
+        }
         self.title = title;
     }
 
@@ -1112,10 +1482,21 @@ impl WindowState {
 
 impl Drop for WindowState {
     fn drop(&mut self) {
+        #[cfg(dbus_ime)]
+        self.with_dbus_ime(|dbus_ime, window_id| dbus_ime.remove_window(window_id));
+
         if let Some(blur) = self.blur.take() {
             blur.release();
         }
 
+        if let Some(idle_inhibitor) = self.idle_inhibitor.take() {
+            idle_inhibitor.destroy();
+        }
+
+        for (_, inhibitor) in self.shortcuts_inhibitors.drain(..) {
+            inhibitor.destroy();
+        }
+
         if let Some(fs) = self.fractional_scale.take() {
             fs.destroy();
         }
@@ -1124,8 +1505,18 @@ impl Drop for WindowState {
             viewport.destroy();
         }
 
+        // The subsurfaces aren't managed by SCTK.
+        if let WindowShell::Subsurface {
+            subsurface,
+            surface,
+        } = &self.window
+        {
+            subsurface.destroy();
+            surface.destroy();
+        }
+
         // NOTE: the wl_surface used by the window is being cleaned up when
-        // dropping SCTK `Window`.
+        // dropping SCTK `Window` or `Popup`.
     }
 }
 
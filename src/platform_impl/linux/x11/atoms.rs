@@ -13,7 +13,7 @@ macro_rules! atom_manager {
 
         /// Indices into the `Atoms` struct.
         #[derive(Copy, Clone, Debug)]
-        #[allow(non_camel_case_types)]
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
         pub(crate) enum AtomName {
             $($name,)*
         }
@@ -55,6 +55,8 @@ atom_manager! {
     _NET_WM_STATE_HIDDEN,
     _NET_WM_STATE_MAXIMIZED_HORZ,
     _NET_WM_STATE_MAXIMIZED_VERT,
+    _NET_WM_STRUT,
+    _NET_WM_STRUT_PARTIAL,
     _NET_WM_WINDOW_TYPE,
 
     // Activation atoms.
@@ -85,12 +87,24 @@ atom_manager! {
     XdndDrop,
     XdndPosition,
     XdndStatus,
-    XdndActionPrivate,
+    XdndActionCopy,
+    XdndActionLink,
+    XdndActionMove,
     XdndSelection,
     XdndFinished,
     XdndTypeList,
+    XdndActionList,
     TextUriList: b"text/uri-list",
-    None: b"None",
+
+    // Clipboard Atoms
+    CLIPBOARD,
+    INCR,
+    MULTIPLE,
+    SAVE_TARGETS,
+    TARGETS,
+    TEXT,
+    TIMESTAMP,
+    _WINIT_SELECTION,
 
     // Miscellaneous Atoms
     _GTK_THEME_VARIANT,
@@ -100,7 +114,8 @@ atom_manager! {
     _NET_FRAME_EXTENTS,
     _NET_SUPPORTED,
     _NET_SUPPORTING_WM_CHECK,
-    _XEMBED
+    _XEMBED,
+    _XSETTINGS_SETTINGS
 }
 
 impl Index<AtomName> for Atoms {
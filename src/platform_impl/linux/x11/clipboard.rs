@@ -0,0 +1,415 @@
+//! The clipboard and the primary selection, implemented with the `CLIPBOARD` and `PRIMARY`
+//! selections as described by the [ICCCM].
+//!
+//! [ICCCM]: https://x.org/releases/X11R7.6/doc/xorg-docs/specs/ICCCM/icccm.html#use_of_selection_atoms
+
+use std::cell::RefCell;
+use std::mem::MaybeUninit;
+use std::os::raw::{c_int, c_ulong};
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::warn;
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{self, ConnectionExt as _};
+use x11rb::wrapper::ConnectionExt as _;
+
+use super::{atoms::*, ffi, EventLoopWindowTarget, X11Error, XConnection};
+use crate::platform::clipboard::{ClipboardContents, SelectionKind};
+use crate::platform_impl::OsError;
+
+/// How long to wait for the owner of the selection to respond.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The contents of the selection while it's owned by the application.
+struct Owned {
+    contents: ClipboardContents,
+    /// The atoms of the MIME types of the `contents`.
+    targets: Vec<(xproto::Atom, String)>,
+}
+
+/// The state of the selections.
+pub(crate) struct Clipboard {
+    /// The window owning the selections and receiving the data of other clients.
+    window: xproto::Window,
+    clipboard: RefCell<Option<Owned>>,
+    primary: RefCell<Option<Owned>>,
+}
+
+impl Clipboard {
+    pub fn new(xconn: &XConnection, root: xproto::Window) -> Result<Self, X11Error> {
+        let conn = xconn.xcb_connection();
+        let window = conn.generate_id()?;
+        conn.create_window(
+            x11rb::COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            xproto::WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &xproto::CreateWindowAux::new().event_mask(xproto::EventMask::PROPERTY_CHANGE),
+        )?
+        .check()?;
+
+        Ok(Self {
+            window,
+            clipboard: RefCell::new(None),
+            primary: RefCell::new(None),
+        })
+    }
+
+    fn owned(&self, kind: SelectionKind) -> &RefCell<Option<Owned>> {
+        match kind {
+            SelectionKind::Clipboard => &self.clipboard,
+            SelectionKind::Primary => &self.primary,
+        }
+    }
+}
+
+impl EventLoopWindowTarget {
+    pub(crate) fn set_selection(
+        &self,
+        kind: SelectionKind,
+        contents: ClipboardContents,
+    ) -> Result<(), OsError> {
+        self.own_selection(kind, contents)
+            .map_err(|err| OsError::XError(Arc::new(err)))
+    }
+
+    pub(crate) fn selection_mime_types(&self, kind: SelectionKind) -> Result<Vec<String>, OsError> {
+        if let Some(owned) = &*self.clipboard.owned(kind).borrow() {
+            return Ok(owned.contents.mime_types().map(String::from).collect());
+        }
+
+        self.convert_mime_types(kind)
+            .map_err(|err| OsError::XError(Arc::new(err)))
+    }
+
+    pub(crate) fn selection_data(
+        &self,
+        kind: SelectionKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, OsError> {
+        if let Some(owned) = &*self.clipboard.owned(kind).borrow() {
+            return Ok(owned.contents.data(mime_type).map(Vec::from));
+        }
+
+        self.convert_data(kind, mime_type)
+            .map_err(|err| OsError::XError(Arc::new(err)))
+    }
+
+    /// The atom of the selection.
+    fn selection_atom(&self, kind: SelectionKind) -> xproto::Atom {
+        match kind {
+            SelectionKind::Clipboard => self.xconn.atoms()[CLIPBOARD],
+            SelectionKind::Primary => xproto::AtomEnum::PRIMARY.into(),
+        }
+    }
+
+    /// The kind of the selection with the `atom`, when it's one of the supported ones.
+    fn selection_kind(&self, atom: c_ulong) -> Option<SelectionKind> {
+        [SelectionKind::Clipboard, SelectionKind::Primary]
+            .into_iter()
+            .find(|&kind| self.selection_atom(kind) as c_ulong == atom)
+    }
+
+    fn own_selection(
+        &self,
+        kind: SelectionKind,
+        contents: ClipboardContents,
+    ) -> Result<(), X11Error> {
+        let conn = self.xconn.xcb_connection();
+        let selection = self.selection_atom(kind);
+        let window = self.clipboard.window;
+
+        if contents.is_empty() {
+            // Only give up the selection when it's ours, not to clear the one of another client.
+            if self.clipboard.owned(kind).take().is_some() {
+                conn.set_selection_owner(x11rb::NONE, selection, self.xconn.timestamp())?
+                    .check()?;
+            }
+            return Ok(());
+        }
+
+        let cookies = contents
+            .mime_types()
+            .map(|mime_type| conn.intern_atom(false, mime_type.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let targets = cookies
+            .into_iter()
+            .zip(contents.mime_types())
+            .map(|(cookie, mime_type)| Ok((cookie.reply()?.atom, mime_type.to_owned())))
+            .collect::<Result<Vec<_>, X11Error>>()?;
+
+        conn.set_selection_owner(window, selection, self.xconn.timestamp())?
+            .check()?;
+        if conn.get_selection_owner(selection)?.reply()?.owner != window {
+            return Err(X11Error::InvalidSelectionOwner);
+        }
+
+        *self.clipboard.owned(kind).borrow_mut() = Some(Owned { contents, targets });
+        Ok(())
+    }
+
+    fn convert_mime_types(&self, kind: SelectionKind) -> Result<Vec<String>, X11Error> {
+        let atoms = self.xconn.atoms();
+        let conn = self.xconn.xcb_connection();
+
+        let selection = self.selection_atom(kind);
+        let time = self.xconn.timestamp();
+        let targets = match self.convert_selection(selection, atoms[TARGETS], time)? {
+            Some(data) => data,
+            None => return Ok(Vec::new()),
+        };
+
+        // The targets are a list of atoms in the byte order of the client.
+        let special = [
+            atoms[TARGETS],
+            atoms[MULTIPLE],
+            atoms[TIMESTAMP],
+            atoms[SAVE_TARGETS],
+        ];
+        let cookies = targets
+            .chunks_exact(4)
+            .map(|atom| xproto::Atom::from_ne_bytes(atom.try_into().unwrap()))
+            .filter(|atom| !special.contains(atom))
+            .map(|atom| conn.get_atom_name(atom))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        cookies
+            .into_iter()
+            .map(|cookie| Ok(String::from_utf8_lossy(&cookie.reply()?.name).into_owned()))
+            .collect()
+    }
+
+    fn convert_data(
+        &self,
+        kind: SelectionKind,
+        mime_type: &str,
+    ) -> Result<Option<Vec<u8>>, X11Error> {
+        let target = self
+            .xconn
+            .xcb_connection()
+            .intern_atom(false, mime_type.as_bytes())?
+            .reply()?
+            .atom;
+
+        self.convert_selection(self.selection_atom(kind), target, self.xconn.timestamp())
+    }
+
+    /// Ask the owner of the `selection` to convert it to the `target`, and wait for the data.
+    pub(super) fn convert_selection(
+        &self,
+        selection: xproto::Atom,
+        target: xproto::Atom,
+        time: xproto::Timestamp,
+    ) -> Result<Option<Vec<u8>>, X11Error> {
+        let atoms = self.xconn.atoms();
+        let conn = self.xconn.xcb_connection();
+        let property = atoms[_WINIT_SELECTION];
+        let window = self.clipboard.window;
+
+        conn.convert_selection(window, selection, target, property, time)?;
+
+        let notify = loop {
+            let event = match self.wait_for_event(ffi::SelectionNotify)? {
+                Some(event) => event,
+                None => return Err(X11Error::SelectionTimeout),
+            };
+
+            // Skip the responses to the earlier requests, which timed out.
+            let notify: &ffi::XSelectionEvent = event.as_ref();
+            if notify.selection == selection as c_ulong && notify.target == target as c_ulong {
+                break *notify;
+            }
+        };
+
+        // The conversion was refused.
+        if notify.property == 0 {
+            return Ok(None);
+        }
+
+        let reply = conn
+            .get_property(
+                true,
+                window,
+                property,
+                xproto::AtomEnum::ANY,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()?;
+        if reply.type_ != atoms[INCR] {
+            return Ok(Some(reply.value));
+        }
+
+        // The data is too large for a single property, thus it's sent in chunks, each of them
+        // announced by a new value of the property. An empty chunk ends the transfer.
+        let mut data = Vec::new();
+        loop {
+            let event = match self.wait_for_event(ffi::PropertyNotify)? {
+                Some(event) => event,
+                None => return Err(X11Error::SelectionTimeout),
+            };
+
+            let notify: &ffi::XPropertyEvent = event.as_ref();
+            if notify.atom != property as c_ulong || notify.state != ffi::PropertyNewValue {
+                continue;
+            }
+
+            let chunk = conn
+                .get_property(
+                    true,
+                    window,
+                    property,
+                    xproto::AtomEnum::ANY,
+                    0,
+                    u32::MAX / 4,
+                )?
+                .reply()?;
+            if chunk.value.is_empty() {
+                return Ok(Some(data));
+            }
+            data.extend_from_slice(&chunk.value);
+        }
+    }
+
+    /// Wait for an event of the `event_type` on the selection window, keeping the other events
+    /// queued for the event loop.
+    fn wait_for_event(&self, event_type: c_int) -> Result<Option<ffi::XEvent>, X11Error> {
+        self.xconn.flush_requests()?;
+
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let mut event = MaybeUninit::<ffi::XEvent>::uninit();
+            // Reads the pending events from the connection and removes the matching one from
+            // the queue.
+            let found = unsafe {
+                (self.xconn.xlib.XCheckTypedWindowEvent)(
+                    self.xconn.display,
+                    self.clipboard.window as c_ulong,
+                    event_type,
+                    event.as_mut_ptr(),
+                )
+            };
+            if found != 0 {
+                return Ok(Some(unsafe { event.assume_init() }));
+            }
+
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            if timeout.is_zero() {
+                return Ok(None);
+            }
+
+            let mut fd = libc::pollfd {
+                fd: self.xconn.xcb_connection().as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            unsafe { libc::poll(&mut fd, 1, timeout.as_millis() as c_int) };
+        }
+    }
+
+    /// Send the data of the owned selection to the requestor.
+    pub(super) fn handle_selection_request(&self, request: &ffi::XSelectionRequestEvent) {
+        let owned = self
+            .selection_kind(request.selection)
+            .filter(|_| request.owner == self.clipboard.window as c_ulong)
+            .map(|kind| self.clipboard.owned(kind).borrow());
+        let owned = owned.as_ref().and_then(|owned| owned.as_ref());
+
+        self.reply_selection_request(request, owned.map(|owned| &*owned.targets), |mime_type| {
+            owned?.contents.data(mime_type)
+        });
+    }
+
+    /// Convert the data to the requested target, one of the `targets`, and notify the requestor.
+    ///
+    /// The conversion is refused without `targets`, when the selection isn't owned.
+    pub(super) fn reply_selection_request<D: AsRef<[u8]>>(
+        &self,
+        request: &ffi::XSelectionRequestEvent,
+        targets: Option<&[(xproto::Atom, String)]>,
+        data: impl FnOnce(&str) -> Option<D>,
+    ) {
+        let atoms = self.xconn.atoms();
+        let conn = self.xconn.xcb_connection();
+        let requestor = request.requestor as xproto::Window;
+        let target = request.target as xproto::Atom;
+        // Obsolete clients don't set the property, the target is used instead.
+        let property = match request.property as xproto::Atom {
+            x11rb::NONE => target,
+            property => property,
+        };
+
+        let converted = targets.and_then(|targets| {
+            if target == atoms[TARGETS] {
+                let mut atoms = vec![atoms[TARGETS], atoms[TIMESTAMP]];
+                atoms.extend(targets.iter().map(|(atom, _)| *atom));
+                return Some(conn.change_property32(
+                    xproto::PropMode::REPLACE,
+                    requestor,
+                    property,
+                    xproto::AtomEnum::ATOM,
+                    &atoms,
+                ));
+            }
+
+            let (_, mime_type) = targets.iter().find(|(atom, _)| *atom == target)?;
+            let data = data(mime_type)?;
+            let data = data.as_ref();
+
+            // The data must fit into a single request, incremental transfers aren't supported.
+            let max_len = conn.maximum_request_bytes().saturating_sub(32);
+            if data.len() > max_len {
+                warn!("The selection data is too large to be sent to the requestor");
+                return None;
+            }
+
+            // Text requested as `TEXT` is sent in the encoding of the owner's choice.
+            let ty = if target == atoms[TEXT] {
+                atoms[UTF8_STRING]
+            } else {
+                target
+            };
+            Some(conn.change_property8(xproto::PropMode::REPLACE, requestor, property, ty, data))
+        });
+
+        let property = match converted {
+            Some(Ok(_)) => property,
+            _ => x11rb::NONE,
+        };
+        let notify = xproto::SelectionNotifyEvent {
+            response_type: xproto::SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: request.time as xproto::Timestamp,
+            requestor,
+            selection: request.selection as xproto::Atom,
+            target,
+            property,
+        };
+        let result = conn
+            .send_event(false, requestor, xproto::EventMask::NO_EVENT, notify)
+            .map(|_| ())
+            .and_then(|_| conn.flush());
+        if let Err(err) = result {
+            warn!("Failed to respond to the selection request: {err}");
+        }
+    }
+
+    /// Forget the contents of the selection, which is now owned by another client.
+    pub(super) fn handle_selection_clear(&self, clear: &ffi::XSelectionClearEvent) {
+        if clear.window != self.clipboard.window as c_ulong {
+            return;
+        }
+
+        if let Some(kind) = self.selection_kind(clear.selection) {
+            self.clipboard.owned(kind).take();
+        }
+    }
+}
@@ -1,53 +1,21 @@
-use std::{
-    io,
-    os::raw::*,
-    path::{Path, PathBuf},
-    str::Utf8Error,
-    sync::Arc,
-};
-
-use percent_encoding::percent_decode;
-use x11rb::protocol::xproto::{self, ConnectionExt};
-
-use super::{
-    atoms::{AtomName::None as DndNone, *},
-    util, CookieResultExt, X11Error, XConnection,
-};
+//! Dropping data on the windows, as the target of an [XDND] drag.
+//!
+//! [XDND]: https://www.freedesktop.org/wiki/Specifications/XDND/
 
-#[derive(Debug, Clone, Copy)]
-pub enum DndState {
-    Accepted,
-    Rejected,
-}
+use std::{os::raw::*, path::PathBuf, sync::Arc};
 
-#[derive(Debug)]
-pub enum DndDataParseError {
-    EmptyData,
-    InvalidUtf8(Utf8Error),
-    HostnameSpecified(String),
-    UnexpectedProtocol(String),
-    UnresolvablePath(io::Error),
-}
+use log::warn;
+use x11rb::protocol::xproto::{self, ConnectionExt};
 
-impl From<Utf8Error> for DndDataParseError {
-    fn from(e: Utf8Error) -> Self {
-        DndDataParseError::InvalidUtf8(e)
-    }
-}
-
-impl From<io::Error> for DndDataParseError {
-    fn from(e: io::Error) -> Self {
-        DndDataParseError::UnresolvablePath(e)
-    }
-}
+use super::{atoms::*, ffi, util, CookieResultExt, EventLoopWindowTarget, X11Error, XConnection};
+use crate::dpi::PhysicalPosition;
+use crate::event::{DndAction, WindowEvent};
+use crate::platform_impl::platform::common::dnd::{DndDataParseError, URI_LIST_MIME_TYPE};
+use crate::platform_impl::OsError;
 
+/// The files dragged over the windows, for `HoveredFile` and `DroppedFile`.
 pub(crate) struct Dnd {
     xconn: Arc<XConnection>,
-    // Populated by XdndEnter event handler
-    pub version: Option<c_long>,
-    pub type_list: Option<Vec<xproto::Atom>>,
-    // Populated by XdndPosition event handler
-    pub source_window: Option<xproto::Window>,
     // Populated by SelectionNotify event handler (triggered by XdndPosition event handler)
     pub result: Option<Result<Vec<PathBuf>, DndDataParseError>>,
 }
@@ -56,80 +24,14 @@ impl Dnd {
     pub fn new(xconn: Arc<XConnection>) -> Result<Self, X11Error> {
         Ok(Dnd {
             xconn,
-            version: None,
-            type_list: None,
-            source_window: None,
             result: None,
         })
     }
 
     pub fn reset(&mut self) {
-        self.version = None;
-        self.type_list = None;
-        self.source_window = None;
         self.result = None;
     }
 
-    pub unsafe fn send_status(
-        &self,
-        this_window: xproto::Window,
-        target_window: xproto::Window,
-        state: DndState,
-    ) -> Result<(), X11Error> {
-        let atoms = self.xconn.atoms();
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, atoms[XdndActionPrivate]),
-            DndState::Rejected => (0, atoms[DndNone]),
-        };
-        self.xconn
-            .send_client_msg(
-                target_window,
-                target_window,
-                atoms[XdndStatus] as _,
-                None,
-                [this_window, accepted, 0, 0, action as _],
-            )?
-            .ignore_error();
-
-        Ok(())
-    }
-
-    pub unsafe fn send_finished(
-        &self,
-        this_window: xproto::Window,
-        target_window: xproto::Window,
-        state: DndState,
-    ) -> Result<(), X11Error> {
-        let atoms = self.xconn.atoms();
-        let (accepted, action) = match state {
-            DndState::Accepted => (1, atoms[XdndActionPrivate]),
-            DndState::Rejected => (0, atoms[DndNone]),
-        };
-        self.xconn
-            .send_client_msg(
-                target_window,
-                target_window,
-                atoms[XdndFinished] as _,
-                None,
-                [this_window, accepted, action as _, 0, 0],
-            )?
-            .ignore_error();
-
-        Ok(())
-    }
-
-    pub unsafe fn get_type_list(
-        &self,
-        source_window: xproto::Window,
-    ) -> Result<Vec<xproto::Atom>, util::GetPropertyError> {
-        let atoms = self.xconn.atoms();
-        self.xconn.get_property(
-            source_window,
-            atoms[XdndTypeList],
-            xproto::Atom::from(xproto::AtomEnum::ATOM),
-        )
-    }
-
     pub unsafe fn convert_selection(&self, window: xproto::Window, time: xproto::Timestamp) {
         let atoms = self.xconn.atoms();
         self.xconn
@@ -152,33 +54,350 @@ impl Dnd {
         self.xconn
             .get_property(window, atoms[XdndSelection], atoms[TextUriList])
     }
+}
+
+/// The drag over one of the windows.
+pub(crate) struct DropOffer {
+    /// The window the drag is over.
+    window: xproto::Window,
+    /// The window the drag comes from, which owns the `XdndSelection`.
+    source_window: xproto::Window,
+    version: u32,
+    /// The atoms of the MIME types of the data.
+    types: Vec<(xproto::Atom, String)>,
+    /// The timestamp of the latest message, used to request the data.
+    time: xproto::Timestamp,
+    /// The latest position of the drag, in window coordinates.
+    position: Option<PhysicalPosition<f64>>,
+    /// The action proposed by the source with the latest position.
+    proposed_action: DndAction,
+    /// The response of the application, when it set one.
+    response: Option<Option<DndAction>>,
+    /// Whether the data was dropped, and the source waits for the drop to finish.
+    dropped: bool,
+}
+
+impl DropOffer {
+    /// The action performed on a drop at the current position, `None` when it's rejected.
+    ///
+    /// Files are accepted with the proposed action, unless the application responded otherwise.
+    fn action(&self) -> Option<DndAction> {
+        self.response.unwrap_or_else(|| {
+            self.types
+                .iter()
+                .any(|(_, mime_type)| mime_type == URI_LIST_MIME_TYPE)
+                .then_some(self.proposed_action)
+        })
+    }
+}
 
-    pub fn parse_data(&self, data: &mut [c_uchar]) -> Result<Vec<PathBuf>, DndDataParseError> {
-        if !data.is_empty() {
-            let mut path_list = Vec::new();
-            let decoded = percent_decode(data).decode_utf8()?.into_owned();
-            for uri in decoded.split("\r\n").filter(|u| !u.is_empty()) {
-                // The format is specified as protocol://host/path
-                // However, it's typically simply protocol:///path
-                let path_str = if uri.starts_with("file://") {
-                    let path_str = uri.replace("file://", "");
-                    if !path_str.starts_with('/') {
-                        // A hostname is specified
-                        // Supporting this case is beyond the scope of my mental health
-                        return Err(DndDataParseError::HostnameSpecified(path_str));
-                    }
-                    path_str
-                } else {
-                    // Only the file protocol is supported
-                    return Err(DndDataParseError::UnexpectedProtocol(uri.to_owned()));
-                };
-
-                let path = Path::new(&path_str).canonicalize()?;
-                path_list.push(path);
+impl EventLoopWindowTarget {
+    pub(crate) fn set_drop_action(&self, action: Option<DndAction>) {
+        if let Some(offer) = &mut *self.drop_offer.borrow_mut() {
+            if !offer.dropped {
+                offer.response = Some(action);
             }
-            Ok(path_list)
+        }
+    }
+
+    pub(crate) fn drop_mime_types(&self) -> Vec<String> {
+        match &*self.drop_offer.borrow() {
+            Some(offer) => offer.types.iter().map(|(_, ty)| ty.clone()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn drop_data(&self, mime_type: &str) -> Result<Option<Vec<u8>>, OsError> {
+        let (source_window, target, time) = match &*self.drop_offer.borrow() {
+            Some(offer) => match offer.types.iter().find(|(_, ty)| ty == mime_type) {
+                Some(&(atom, _)) => (offer.source_window, atom, offer.time),
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        // The data dragged from one of the windows can't be requested from the server, since
+        // the request would only be answered by the event loop.
+        if let Some(data) = self.drag_data(source_window, mime_type) {
+            return Ok(data);
+        }
+
+        self.convert_selection(self.xconn.atoms()[XdndSelection], target, time)
+            .map_err(|err| OsError::XError(Arc::new(err)))
+    }
+
+    /// Start the drag over the `window`, announced by the `XdndEnter` message.
+    pub(super) fn enter_drop(&self, enter: &ffi::XClientMessageEvent) {
+        self.finish_drop();
+
+        let offer = self
+            .create_drop_offer(enter)
+            .map_err(|err| warn!("Failed to read the types of the dragged data: {err}"))
+            .ok();
+        *self.drop_offer.borrow_mut() = offer;
+    }
+
+    fn create_drop_offer(&self, enter: &ffi::XClientMessageEvent) -> Result<DropOffer, X11Error> {
+        let atoms = self.xconn.atoms();
+        let conn = self.xconn.xcb_connection();
+        let source_window = enter.data.get_long(0) as xproto::Window;
+        let flags = enter.data.get_long(1);
+        let version = (flags >> 24) as u32;
+
+        // The first three types are in the message, all of them in a property otherwise.
+        let has_more_types = flags & 1 == 1;
+        let types: Vec<xproto::Atom> = if has_more_types {
+            conn.get_property(
+                false,
+                source_window,
+                atoms[XdndTypeList],
+                xproto::AtomEnum::ATOM,
+                0,
+                u32::MAX / 4,
+            )?
+            .reply()?
+            .value32()
+            .into_iter()
+            .flatten()
+            .collect()
         } else {
-            Err(DndDataParseError::EmptyData)
+            (2..5)
+                .map(|i| enter.data.get_long(i) as xproto::Atom)
+                .filter(|&atom| atom != x11rb::NONE)
+                .collect()
+        };
+
+        let cookies = types
+            .iter()
+            .map(|&atom| conn.get_atom_name(atom))
+            .collect::<Result<Vec<_>, _>>()?;
+        let types = types
+            .into_iter()
+            .zip(cookies)
+            .map(|(atom, cookie)| {
+                let name = cookie.reply()?.name;
+                Ok((atom, String::from_utf8_lossy(&name).into_owned()))
+            })
+            .collect::<Result<Vec<_>, X11Error>>()?;
+
+        Ok(DropOffer {
+            window: enter.window as xproto::Window,
+            source_window,
+            version,
+            types,
+            time: x11rb::CURRENT_TIME,
+            position: None,
+            proposed_action: DndAction::Copy,
+            response: None,
+            dropped: false,
+        })
+    }
+
+    /// Move the drag over the window, with the `XdndPosition` message.
+    ///
+    /// The drag enters the window with its first position. The status is sent with
+    /// [`Self::send_drop_status`], once the application handled the events.
+    pub(super) fn handle_drop_position(
+        &self,
+        position: &ffi::XClientMessageEvent,
+    ) -> Vec<WindowEvent> {
+        let atoms = self.xconn.atoms();
+        let mut slot = self.drop_offer.borrow_mut();
+        let offer = match &mut *slot {
+            Some(offer) if !offer.dropped => offer,
+            _ => return Vec::new(),
+        };
+
+        // The coordinates are relative to the root window, packed as `x << 16 | y`.
+        let packed = position.data.get_long(2);
+        let (x, y) = ((packed >> 16) as i16, packed as i16);
+        let (x, y) = match self
+            .xconn
+            .xcb_connection()
+            .translate_coordinates(self.root, offer.window, x, y)
+            .map_err(X11Error::from)
+            .and_then(|cookie| Ok(cookie.reply()?))
+        {
+            Ok(reply) => (reply.dst_x, reply.dst_y),
+            Err(err) => {
+                warn!("Failed to translate the position of the drag: {err}");
+                (0, 0)
+            }
+        };
+        let position_in_window = PhysicalPosition::new(x as f64, y as f64);
+
+        // The time is only specified in version 1 and up, where it's `CurrentTime` otherwise.
+        offer.time = position.data.get_long(3) as xproto::Timestamp;
+        self.xconn.set_timestamp(offer.time);
+
+        // The action is only specified in version 2 and up.
+        let action_atom = position.data.get_long(4) as xproto::Atom;
+        offer.proposed_action = match offer.version {
+            2.. => self.dnd_action(action_atom).unwrap_or(DndAction::Copy),
+            _ => DndAction::Copy,
+        };
+
+        let mut events = Vec::new();
+        if offer.position.is_none() {
+            // The actions are listed when the source asks the user which one to perform.
+            let actions = self
+                .xconn
+                .get_property::<xproto::Atom>(
+                    offer.source_window,
+                    atoms[XdndActionList],
+                    xproto::AtomEnum::ATOM.into(),
+                )
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|atom| self.dnd_action(atom))
+                .collect::<Vec<_>>();
+            let actions = if actions.is_empty() {
+                vec![offer.proposed_action]
+            } else {
+                actions
+            };
+
+            events.push(WindowEvent::DragEntered {
+                position: position_in_window,
+                mime_types: offer.types.iter().map(|(_, ty)| ty.clone()).collect(),
+                actions,
+            });
         }
+        offer.position = Some(position_in_window);
+
+        events.push(WindowEvent::DragMoved {
+            position: position_in_window,
+            action: offer.proposed_action,
+        });
+        events
+    }
+
+    /// Respond to the latest `XdndPosition` message, with the action of the application.
+    pub(super) fn send_drop_status(&self) {
+        let slot = self.drop_offer.borrow();
+        let offer = match &*slot {
+            Some(offer) if !offer.dropped => offer,
+            _ => return,
+        };
+
+        let action = offer.action();
+        // Another position is requested for each motion, since the response may change anywhere.
+        let flags = action.is_some() as u32 | 2;
+        let action = action.map_or(x11rb::NONE, |action| self.xdnd_action_atom(action));
+        self.send_drop_message(offer, XdndStatus, [flags, 0, 0, action]);
+    }
+
+    /// Drop the data on the window, with the `XdndDrop` message.
+    ///
+    /// An accepted drop finishes with [`Self::finish_drop`], once the application read the data.
+    pub(super) fn handle_drop(&self, drop: &ffi::XClientMessageEvent) -> Option<WindowEvent> {
+        let mut slot = self.drop_offer.borrow_mut();
+        let offer = slot.as_mut().filter(|offer| !offer.dropped)?;
+        let position = offer.position?;
+
+        // The time is only specified in version 1 and up, where it's `CurrentTime` otherwise.
+        offer.time = drop.data.get_long(2) as xproto::Timestamp;
+        self.xconn.set_timestamp(offer.time);
+
+        match offer.action() {
+            Some(action) => {
+                offer.dropped = true;
+                Some(WindowEvent::DragDropped { position, action })
+            }
+            None => {
+                let offer = slot.take().unwrap();
+                self.send_drop_message(&offer, XdndFinished, [0, x11rb::NONE, 0, 0]);
+                Some(WindowEvent::DragLeft)
+            }
+        }
+    }
+
+    /// Cancel the drag over the window, with the `XdndLeave` message.
+    pub(super) fn handle_drop_leave(&self) -> Option<WindowEvent> {
+        let mut slot = self.drop_offer.borrow_mut();
+        match &*slot {
+            Some(offer) if !offer.dropped => {
+                let entered = offer.position.is_some();
+                slot.take();
+                entered.then_some(WindowEvent::DragLeft)
+            }
+            _ => None,
+        }
+    }
+
+    /// Tell the source the dropped data was handled, once the application could read it.
+    pub(super) fn finish_drop(&self) {
+        let mut slot = self.drop_offer.borrow_mut();
+        let offer = match slot.take() {
+            Some(offer) if offer.dropped => offer,
+            offer => {
+                *slot = offer;
+                return;
+            }
+        };
+
+        let action = offer.action();
+        let action = action.map_or(x11rb::NONE, |action| self.xdnd_action_atom(action));
+        self.send_drop_message(&offer, XdndFinished, [1, action, 0, 0]);
+    }
+
+    /// Send the message to the source, the target window being the first field of the data.
+    fn send_drop_message(&self, offer: &DropOffer, message: AtomName, data: [u32; 4]) {
+        let [a, b, c, d] = data;
+        let result = self
+            .xconn
+            .send_client_msg(
+                offer.source_window,
+                offer.source_window,
+                self.xconn.atoms()[message],
+                None,
+                [offer.window, a, b, c, d],
+            )
+            .map(|_| ())
+            .and_then(|_| self.xconn.flush_requests().map_err(Into::into));
+        if let Err(err) = result {
+            warn!("Failed to send {message:?} to the drag source: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(types: &[&str]) -> DropOffer {
+        DropOffer {
+            window: 1,
+            source_window: 2,
+            version: 5,
+            types: types
+                .iter()
+                .zip(1..)
+                .map(|(&ty, atom)| (atom, ty.to_owned()))
+                .collect(),
+            time: x11rb::CURRENT_TIME,
+            position: None,
+            proposed_action: DndAction::Move,
+            response: None,
+            dropped: false,
+        }
+    }
+
+    #[test]
+    fn drop_action() {
+        // Files are accepted with the proposed action, anything else is rejected.
+        assert_eq!(
+            offer(&["text/plain", URI_LIST_MIME_TYPE]).action(),
+            Some(DndAction::Move)
+        );
+        assert_eq!(offer(&["text/plain"]).action(), None);
+
+        // The response of the application overrides both.
+        let mut files = offer(&[URI_LIST_MIME_TYPE]);
+        files.response = Some(None);
+        assert_eq!(files.action(), None);
+        let mut text = offer(&["text/plain"]);
+        text.response = Some(Some(DndAction::Copy));
+        assert_eq!(text.action(), Some(DndAction::Copy));
     }
 }
@@ -0,0 +1,442 @@
+//! Dragging data out of the windows, as the source of an [XDND] drag.
+//!
+//! The pointer is grabbed by the window the drag started from for the whole drag, its motion is
+//! forwarded to the XDND-aware window under the cursor, and the data is sent through the
+//! `XdndSelection`, which the window owns.
+//!
+//! [XDND]: https://www.freedesktop.org/wiki/Specifications/XDND/
+
+use std::os::raw::c_long;
+
+use log::warn;
+use x11rb::protocol::xinput::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{self, ConnectionExt as _};
+use x11rb::wrapper::ConnectionExt as _;
+
+use super::{atoms::*, ffi, util, CustomCursor, EventLoopWindowTarget, X11Error};
+use crate::cursor::OnlyCursorImageBuilder;
+use crate::event::{DndAction, WindowEvent};
+use crate::platform::drag_and_drop::DragSource;
+
+/// The version of the protocol implemented here, which is used unless the target only supports
+/// an older one.
+const XDND_VERSION: u32 = 5;
+
+/// The oldest version of the protocol supported by the targets.
+const XDND_MIN_VERSION: u32 = 3;
+
+/// The events to send to the windows, as a result of the drag.
+pub(super) type DragEvents = Vec<(xproto::Window, WindowEvent)>;
+
+/// The drag in progress.
+pub(crate) struct Drag {
+    /// The window the drag started from, which owns the `XdndSelection`.
+    window: xproto::Window,
+    source: DragSource,
+    /// The atoms of the MIME types of the data.
+    targets: Vec<(xproto::Atom, String)>,
+    /// The cursor showing the icon, which must live as long as the grab.
+    _cursor: Option<CustomCursor>,
+    /// The XDND-aware window under the cursor.
+    target: Option<DropTarget>,
+    /// The action last reported to the window.
+    action: Option<DndAction>,
+    /// Whether the data was dropped, and the drag waits for the target to finish.
+    dropped: bool,
+}
+
+struct DropTarget {
+    window: xproto::Window,
+    version: u32,
+    /// The action picked by the target, when it accepts the drop.
+    action: Option<DndAction>,
+    /// Whether an `XdndPosition` is waiting for its `XdndStatus`.
+    waiting_status: bool,
+    /// The latest position, sent once the target responds to the previous one.
+    pending_position: Option<(i16, i16, xproto::Timestamp)>,
+}
+
+impl EventLoopWindowTarget {
+    /// Start dragging the `source` from the `window`, cancelling the previous drag.
+    pub(super) fn start_drag(&self, window: xproto::Window, source: DragSource) -> DragEvents {
+        let mut events = DragEvents::new();
+        if let Some(drag) = self.drag.take() {
+            events.extend(self.cancel_drag(drag));
+        }
+
+        match self.begin_drag(window, source) {
+            Ok(drag) => *self.drag.borrow_mut() = Some(drag),
+            Err(err) => {
+                warn!("Failed to start the drag: {err}");
+                events.push((window, WindowEvent::DragSourceFinished { action: None }));
+            }
+        }
+
+        events
+    }
+
+    fn begin_drag(&self, window: xproto::Window, source: DragSource) -> Result<Drag, X11Error> {
+        let atoms = self.xconn.atoms();
+        let conn = self.xconn.xcb_connection();
+
+        let cookies = source
+            .mime_types()
+            .map(|mime_type| conn.intern_atom(false, mime_type.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let targets = cookies
+            .into_iter()
+            .zip(source.mime_types())
+            .map(|(cookie, mime_type)| Ok((cookie.reply()?.atom, mime_type.to_owned())))
+            .collect::<Result<Vec<_>, X11Error>>()?;
+
+        // The targets only get the first three types in `XdndEnter`, the others are listed here.
+        let type_list = targets.iter().map(|(atom, _)| *atom).collect::<Vec<_>>();
+        conn.change_property32(
+            xproto::PropMode::REPLACE,
+            window,
+            atoms[XdndTypeList],
+            xproto::AtomEnum::ATOM,
+            &type_list,
+        )?;
+        conn.set_selection_owner(window, atoms[XdndSelection], self.xconn.timestamp())?;
+
+        let cursor = source.icon().and_then(|icon| {
+            let icon = &icon.inner;
+            let width = u16::try_from(icon.width).ok()?;
+            let height = u16::try_from(icon.height).ok()?;
+            let builder = OnlyCursorImageBuilder::from_rgba(
+                icon.rgba.clone(),
+                width,
+                height,
+                width / 2,
+                height / 2,
+            )
+            .map_err(|err| warn!("The drag icon can't be used as a cursor: {err}"))
+            .ok()?;
+            Some(CustomCursor::build(builder, self))
+        });
+
+        // The pointer is grabbed again to change the cursor and to keep the grab when the
+        // button that started it is released.
+        let mask = xinput::XIEventMask::MOTION
+            | xinput::XIEventMask::BUTTON_PRESS
+            | xinput::XIEventMask::BUTTON_RELEASE
+            | xinput::XIEventMask::ENTER
+            | xinput::XIEventMask::LEAVE;
+        let status = conn
+            .xinput_xi_grab_device(
+                window,
+                self.xconn.timestamp(),
+                cursor
+                    .as_ref()
+                    .map_or(x11rb::NONE, |cursor| cursor.id() as xproto::Cursor),
+                util::VIRTUAL_CORE_POINTER,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+                xinput::GrabOwner::NO_OWNER,
+                &[mask.into()],
+            )?
+            .reply()?
+            .status;
+        if status != xproto::GrabStatus::SUCCESS {
+            return Err(X11Error::PointerGrab(status));
+        }
+
+        Ok(Drag {
+            window,
+            source,
+            targets,
+            _cursor: cursor,
+            target: None,
+            action: None,
+            dropped: false,
+        })
+    }
+
+    /// End the drag before the drop, as if it was rejected.
+    fn cancel_drag(&self, drag: Drag) -> DragEvents {
+        if !drag.dropped {
+            if let Some(target) = &drag.target {
+                self.send_xdnd_message(drag.window, target.window, XdndLeave, [0; 4]);
+            }
+            self.ungrab_drag_pointer();
+        }
+
+        vec![(
+            drag.window,
+            WindowEvent::DragSourceFinished { action: None },
+        )]
+    }
+
+    /// Follow the cursor at the root coordinates with the drag.
+    pub(super) fn handle_drag_motion(
+        &self,
+        root_x: i16,
+        root_y: i16,
+        time: xproto::Timestamp,
+    ) -> DragEvents {
+        let mut drag = self.drag.borrow_mut();
+        let drag = match &mut *drag {
+            Some(drag) if !drag.dropped => drag,
+            _ => return DragEvents::new(),
+        };
+
+        let target = self.find_drop_target(root_x, root_y).unwrap_or_else(|err| {
+            warn!("Failed to find the drop target: {err}");
+            None
+        });
+
+        let mut events = DragEvents::new();
+        if drag.target.as_ref().map(|target| target.window) != target.map(|(window, _)| window) {
+            if let Some(old) = drag.target.take() {
+                self.send_xdnd_message(drag.window, old.window, XdndLeave, [0; 4]);
+            }
+            if drag.action.take().is_some() {
+                events.push((drag.window, WindowEvent::DragSourceAction { action: None }));
+            }
+
+            if let Some((window, version)) = target {
+                let more_types = (drag.targets.len() > 3) as u32;
+                let mut types = [x11rb::NONE; 3];
+                for (ty, (atom, _)) in types.iter_mut().zip(&drag.targets) {
+                    *ty = *atom;
+                }
+                let [first, second, third] = types;
+                let data = [version << 24 | more_types, first, second, third];
+                self.send_xdnd_message(drag.window, window, XdndEnter, data);
+
+                drag.target = Some(DropTarget {
+                    window,
+                    version,
+                    action: None,
+                    waiting_status: false,
+                    pending_position: None,
+                });
+            }
+        }
+
+        let target = match &mut drag.target {
+            Some(target) => target,
+            None => return events,
+        };
+        if target.waiting_status {
+            target.pending_position = Some((root_x, root_y, time));
+        } else {
+            target.waiting_status = true;
+            let window = target.window;
+            self.send_xdnd_position(&drag.source, drag.window, window, root_x, root_y, time);
+        }
+
+        events
+    }
+
+    /// Drop the data on the target accepting it, or cancel the drag.
+    pub(super) fn handle_drag_release(&self, time: xproto::Timestamp) -> DragEvents {
+        let mut slot = self.drag.borrow_mut();
+        let drag = match &mut *slot {
+            Some(drag) if !drag.dropped => drag,
+            _ => return DragEvents::new(),
+        };
+
+        match &drag.target {
+            Some(target) if target.action.is_some() => {
+                self.ungrab_drag_pointer();
+                self.send_xdnd_message(drag.window, target.window, XdndDrop, [0, time, 0, 0]);
+                drag.dropped = true;
+                DragEvents::new()
+            }
+            _ => {
+                let drag = slot.take().unwrap();
+                drop(slot);
+                self.cancel_drag(drag)
+            }
+        }
+    }
+
+    /// Update the action accepted by the target.
+    pub(super) fn handle_drag_status(&self, status: &ffi::XClientMessageEvent) -> DragEvents {
+        let mut drag = self.drag.borrow_mut();
+        let drag = match &mut *drag {
+            Some(drag) if !drag.dropped => drag,
+            _ => return DragEvents::new(),
+        };
+        let target = match &mut drag.target {
+            Some(target) if target.window as c_long == status.data.get_long(0) => target,
+            _ => return DragEvents::new(),
+        };
+
+        let accepted = status.data.get_long(1) & 1 != 0;
+        target.action = accepted.then(|| {
+            self.dnd_action(status.data.get_long(4) as xproto::Atom)
+                .unwrap_or(drag.source.preferred_action())
+        });
+        target.waiting_status = false;
+
+        let action = target.action;
+        let window = target.window;
+        if let Some((x, y, time)) = target.pending_position.take() {
+            target.waiting_status = true;
+            self.send_xdnd_position(&drag.source, drag.window, window, x, y, time);
+        }
+
+        if drag.action == action {
+            return DragEvents::new();
+        }
+        drag.action = action;
+        vec![(drag.window, WindowEvent::DragSourceAction { action })]
+    }
+
+    /// End the dropped drag with the action performed by the target.
+    pub(super) fn handle_drag_finished(&self, finished: &ffi::XClientMessageEvent) -> DragEvents {
+        let mut slot = self.drag.borrow_mut();
+        let (drag, target) = match &*slot {
+            Some(drag) if drag.dropped => match &drag.target {
+                Some(target) if target.window as c_long == finished.data.get_long(0) => {
+                    (drag, target)
+                }
+                _ => return DragEvents::new(),
+            },
+            _ => return DragEvents::new(),
+        };
+
+        // The result of the drop is only reported since the version 5.
+        let action = if target.version < 5 {
+            target.action
+        } else if finished.data.get_long(1) & 1 != 0 {
+            self.dnd_action(finished.data.get_long(2) as xproto::Atom)
+                .or(target.action)
+        } else {
+            None
+        };
+
+        let window = drag.window;
+        slot.take();
+        vec![(window, WindowEvent::DragSourceFinished { action })]
+    }
+
+    /// Send the dragged data to the target.
+    pub(super) fn handle_drag_selection_request(&self, request: &ffi::XSelectionRequestEvent) {
+        let drag = self.drag.borrow();
+        let drag = drag
+            .as_ref()
+            .filter(|drag| request.owner == drag.window as std::os::raw::c_ulong);
+        self.reply_selection_request(request, drag.map(|drag| &*drag.targets), |mime_type| {
+            drag?.source.data(mime_type)
+        });
+    }
+
+    /// The dragged data in the `mime_type`, when the drag was started from the `window`.
+    pub(super) fn drag_data(
+        &self,
+        window: xproto::Window,
+        mime_type: &str,
+    ) -> Option<Option<Vec<u8>>> {
+        let drag = self.drag.borrow();
+        let drag = drag.as_ref().filter(|drag| drag.window == window)?;
+        Some(drag.source.data(mime_type))
+    }
+
+    /// Find the XDND-aware window at the root coordinates, with the version of the protocol to
+    /// use with it.
+    fn find_drop_target(&self, x: i16, y: i16) -> Result<Option<(xproto::Window, u32)>, X11Error> {
+        let conn = self.xconn.xcb_connection();
+
+        let mut window = self.root;
+        loop {
+            let child = conn
+                .translate_coordinates(self.root, window, x, y)?
+                .reply()?
+                .child;
+            if child == x11rb::NONE {
+                return Ok(None);
+            }
+            window = child;
+
+            // The property is on the toplevel window, below the frame of the window manager.
+            let aware = conn
+                .get_property(
+                    false,
+                    window,
+                    self.xconn.atoms()[XdndAware],
+                    xproto::AtomEnum::ATOM,
+                    0,
+                    1,
+                )?
+                .reply()?;
+            if let Some(version) = aware.value32().and_then(|mut value| value.next()) {
+                if version >= XDND_MIN_VERSION {
+                    return Ok(Some((window, version.min(XDND_VERSION))));
+                }
+            }
+        }
+    }
+
+    fn send_xdnd_position(
+        &self,
+        source: &DragSource,
+        source_window: xproto::Window,
+        window: xproto::Window,
+        x: i16,
+        y: i16,
+        time: xproto::Timestamp,
+    ) {
+        let position = (x as u16 as u32) << 16 | y as u16 as u32;
+        let action = self.xdnd_action_atom(source.preferred_action());
+        self.send_xdnd_message(
+            source_window,
+            window,
+            XdndPosition,
+            [0, position, time, action],
+        );
+    }
+
+    /// Send the message to the target, the source window being the first field of the data.
+    fn send_xdnd_message(
+        &self,
+        source_window: xproto::Window,
+        window: xproto::Window,
+        message: AtomName,
+        data: [u32; 4],
+    ) {
+        let [a, b, c, d] = data;
+        let result = self
+            .xconn
+            .send_client_msg(
+                window,
+                window,
+                self.xconn.atoms()[message],
+                None,
+                [source_window, a, b, c, d],
+            )
+            .map(|_| ())
+            .and_then(|_| self.xconn.flush_requests().map_err(Into::into));
+        if let Err(err) = result {
+            warn!("Failed to send {message:?} to the drop target: {err}");
+        }
+    }
+
+    fn ungrab_drag_pointer(&self) {
+        let result = self
+            .xconn
+            .xcb_connection()
+            .xinput_xi_ungrab_device(self.xconn.timestamp(), util::VIRTUAL_CORE_POINTER);
+        if let Err(err) = result {
+            warn!("Failed to ungrab the pointer after the drag: {err}");
+        }
+    }
+
+    pub(super) fn xdnd_action_atom(&self, action: DndAction) -> xproto::Atom {
+        let atoms = self.xconn.atoms();
+        match action {
+            DndAction::Copy => atoms[XdndActionCopy],
+            DndAction::Move => atoms[XdndActionMove],
+            DndAction::Link => atoms[XdndActionLink],
+        }
+    }
+
+    pub(super) fn dnd_action(&self, atom: xproto::Atom) -> Option<DndAction> {
+        [DndAction::Copy, DndAction::Move, DndAction::Link]
+            .into_iter()
+            .find(|&action| self.xdnd_action_atom(action) == atom)
+    }
+}
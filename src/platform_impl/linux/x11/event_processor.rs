@@ -5,6 +5,7 @@ use std::{
     rc::Rc,
     slice,
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 use x11rb::x11_utils::Serialize;
@@ -16,17 +17,33 @@ use x11rb::{
     x11_utils::ExtensionInformation,
 };
 
+use log::warn;
+
+use super::tablet::{self, TabletAxes};
 use super::{
     atoms::*, ffi, get_xtarget, mkdid, mkwid, util, CookieResultExt, Device, DeviceId, DeviceInfo,
-    Dnd, DndState, GenericEventCookie, ImeReceiver, ScrollOrientation, UnownedWindow, WindowId,
+    Dnd, GenericEventCookie, ImeReceiver, ScrollOrientation, UnownedWindow, WindowId, XConnection,
 };
 
+#[cfg(dbus_ime)]
+use crate::platform_impl::platform::common::dbus_ime::KeyInput;
 use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, ElementState, Event, Ime, RawKeyEvent, TouchPhase, WindowEvent},
+    event::{
+        DeviceEvent, ElementState, Event, Ime, RawKeyEvent, TabletToolId, TabletToolInfo,
+        TouchPhase, WindowEvent,
+    },
     event_loop::EventLoopWindowTarget as RootELW,
     keyboard::ModifiersState,
-    platform_impl::platform::common::{keymap, xkb_state::KbdState},
+    platform::synthetic_input::SyntheticInput,
+    platform_impl::click_counter::ClickCounter,
+    platform_impl::event_clock::EventClock,
+    platform_impl::platform::common::{
+        click_settings::ClickSettings,
+        dnd::{parse_uri_list, URI_LIST_MIME_TYPE},
+        keymap,
+        xkb_state::{KbdState, SyntheticKeys},
+    },
 };
 use crate::{
     event::InnerSizeWriter,
@@ -53,23 +70,68 @@ pub(super) struct EventProcessor {
     //
     // Used to detect key repeats.
     pub(super) held_key_press: Option<u32>,
+    // The synthetic keys which are currently pressed.
+    //
+    // Used to release them when the window loses focus, since the server doesn't know about them.
+    pub(super) synthetic_keys: SyntheticKeys,
     pub(super) first_touch: Option<u64>,
     // Currently focused window belonging to this process
     pub(super) active_window: Option<xproto::Window>,
     /// Latest modifiers we've sent for the user to trigger change in event.
     pub(super) modifiers: Cell<ModifiersState>,
     pub(super) is_composing: bool,
+    /// Converts the server timestamps of the events.
+    pub(super) event_clock: Rc<EventClock>,
+    pub(super) click_counter: ClickCounter,
+    /// The window and the device of the tablet tool in proximity, since XInput2 doesn't report
+    /// the proximity events.
+    pub(super) tablet_tool: Option<(xproto::Window, xinput::DeviceId)>,
+    /// The scale of the pinch gesture at its previous event, since the events carry the scale
+    /// from its beginning.
+    pub(super) pinch_scale: f64,
+    /// The tools of the tablets found when the event loop was created, announced on its first
+    /// iteration.
+    pub(super) startup_tablet_tools: Vec<(xinput::DeviceId, TabletToolInfo)>,
 }
 
 impl EventProcessor {
-    pub(super) fn init_device(&self, device: xinput::DeviceId) {
+    /// Initialize the `device`, or all of them, returning the tools of the tablets among them.
+    pub(super) fn init_device(
+        &self,
+        device: xinput::DeviceId,
+    ) -> Vec<(xinput::DeviceId, TabletToolInfo)> {
         let wt = get_xtarget(&self.target);
         let mut devices = self.devices.borrow_mut();
+        let mut tablets = Vec::new();
         if let Some(info) = DeviceInfo::get(&wt.xconn, device as _) {
             for info in info.iter() {
-                devices.insert(DeviceId(info.deviceid as _), Device::new(info));
+                let mut device = Device::new(info);
+                device.tablet = TabletAxes::from_device(&wt.xconn, info);
+                tablets.push((info.deviceid as xinput::DeviceId, device.tablet.clone()));
+                devices.insert(DeviceId(info.deviceid as _), device);
             }
         }
+        tablet::tablet_tools(
+            tablets
+                .iter()
+                .map(|(device, tablet)| (*device, tablet.as_ref())),
+        )
+    }
+
+    /// Announce the tools of the tablets found when the event loop was created, which aren't
+    /// announced by the hot-plug events.
+    pub(super) fn announce_startup_tablet_tools<T: 'static, F: FnMut(Event<T>)>(
+        &mut self,
+        mut callback: F,
+    ) {
+        let timestamp = Instant::now();
+        for (device_id, tool) in self.startup_tablet_tools.drain(..) {
+            callback(Event::DeviceEvent {
+                device_id: mkdid(device_id),
+                event: DeviceEvent::TabletToolAdded(tool),
+                timestamp,
+            });
+        }
     }
 
     pub(crate) fn with_window<F, Ret>(&self, window_id: xproto::Window, callback: F) -> Option<Ret>
@@ -157,6 +219,9 @@ impl EventProcessor {
             return;
         }
 
+        // The events without a server timestamp are stamped with the time they were received at.
+        let timestamp = Instant::now();
+
         let event_type = xev.get_type();
         match event_type {
             ffi::ClientMessage => {
@@ -169,6 +234,7 @@ impl EventProcessor {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::CloseRequested,
+                        timestamp,
                     });
                 } else if client_msg.data.get_long(0) as xproto::Atom == wt.net_wm_ping {
                     let response_msg: &mut ffi::XClientMessageEvent = xev.as_mut();
@@ -196,112 +262,85 @@ impl EventProcessor {
                         )
                         .expect_then_ignore_error("Failed to send `ClientMessage` event.");
                 } else if client_msg.message_type == atoms[XdndEnter] as c_ulong {
-                    let source_window = client_msg.data.get_long(0) as xproto::Window;
-                    let flags = client_msg.data.get_long(1);
-                    let version = flags >> 24;
-                    self.dnd.version = Some(version);
-                    let has_more_types = flags - (flags & (c_long::max_value() - 1)) == 1;
-                    if !has_more_types {
-                        let type_list = vec![
-                            client_msg.data.get_long(2) as xproto::Atom,
-                            client_msg.data.get_long(3) as xproto::Atom,
-                            client_msg.data.get_long(4) as xproto::Atom,
-                        ];
-                        self.dnd.type_list = Some(type_list);
-                    } else if let Ok(more_types) = unsafe { self.dnd.get_type_list(source_window) }
-                    {
-                        self.dnd.type_list = Some(more_types);
-                    }
+                    self.dnd.reset();
+                    wt.enter_drop(client_msg);
                 } else if client_msg.message_type == atoms[XdndPosition] as c_ulong {
-                    // This event occurs every time the mouse moves while a file's being dragged
-                    // over our window. We emit HoveredFile in response; while the macOS backend
-                    // does that upon a drag entering, XDND doesn't have access to the actual drop
-                    // data until this event. For parity with other platforms, we only emit
-                    // `HoveredFile` the first time, though if winit's API is later extended to
-                    // supply position updates with `HoveredFile` or another event, implementing
-                    // that here would be trivial.
-
-                    let source_window = client_msg.data.get_long(0) as xproto::Window;
-
-                    // Equivalent to `(x << shift) | y`
-                    // where `shift = mem::size_of::<c_short>() * 8`
-                    // Note that coordinates are in "desktop space", not "window space"
-                    // (in X11 parlance, they're root window coordinates)
-                    //let packed_coordinates = client_msg.data.get_long(2);
-                    //let shift = mem::size_of::<libc::c_short>() * 8;
-                    //let x = packed_coordinates >> shift;
-                    //let y = packed_coordinates & !(x << shift);
-
-                    // By our own state flow, `version` should never be `None` at this point.
-                    let version = self.dnd.version.unwrap_or(5);
-
-                    // Action is specified in versions 2 and up, though we don't need it anyway.
-                    //let action = client_msg.data.get_long(4);
-
-                    let accepted = if let Some(ref type_list) = self.dnd.type_list {
-                        type_list.contains(&atoms[TextUriList])
-                    } else {
-                        false
-                    };
-
-                    if accepted {
-                        self.dnd.source_window = Some(source_window);
-                        unsafe {
-                            if self.dnd.result.is_none() {
-                                let time = if version >= 1 {
-                                    client_msg.data.get_long(3) as xproto::Timestamp
-                                } else {
-                                    // In version 0, time isn't specified
-                                    x11rb::CURRENT_TIME
-                                };
-
-                                // Log this timestamp.
-                                wt.xconn.set_timestamp(time);
-
-                                // This results in the `SelectionNotify` event below
-                                self.dnd.convert_selection(window, time);
-                            }
-                            self.dnd
-                                .send_status(window, source_window, DndState::Accepted)
-                                .expect("Failed to send `XdndStatus` message.");
+                    // This event occurs every time the mouse moves while data's being dragged
+                    // over our window, the first one entering it.
+                    let events = wt.handle_drop_position(client_msg);
+
+                    // XDND doesn't have access to the actual drop data until this event, thus
+                    // the dragged files are requested once the drag entered, for `HoveredFile`.
+                    if let Some(WindowEvent::DragEntered { mime_types, .. }) = events.first() {
+                        if mime_types.iter().any(|ty| ty == URI_LIST_MIME_TYPE) {
+                            // In version 0, time isn't specified and the field is `CurrentTime`.
+                            let time = client_msg.data.get_long(3) as xproto::Timestamp;
+
+                            // This results in the `SelectionNotify` event below
+                            unsafe { self.dnd.convert_selection(window, time) };
                         }
-                    } else {
-                        unsafe {
-                            self.dnd
-                                .send_status(window, source_window, DndState::Rejected)
-                                .expect("Failed to send `XdndStatus` message.");
-                        }
-                        self.dnd.reset();
                     }
+
+                    for event in events {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event,
+                            timestamp,
+                        });
+                    }
+
+                    // The status reflects the response of the application to the events.
+                    wt.send_drop_status();
                 } else if client_msg.message_type == atoms[XdndDrop] as c_ulong {
-                    let (source_window, state) = if let Some(source_window) = self.dnd.source_window
-                    {
+                    let event = wt.handle_drop(client_msg);
+                    if let Some(WindowEvent::DragDropped { .. }) = event {
                         if let Some(Ok(ref path_list)) = self.dnd.result {
                             for path in path_list {
                                 callback(Event::WindowEvent {
                                     window_id,
                                     event: WindowEvent::DroppedFile(path.clone()),
+                                    timestamp,
                                 });
                             }
                         }
-                        (source_window, DndState::Accepted)
-                    } else {
-                        // `source_window` won't be part of our DND state if we already rejected the drop in our
-                        // `XdndPosition` handler.
-                        let source_window = client_msg.data.get_long(0) as xproto::Window;
-                        (source_window, DndState::Rejected)
-                    };
-                    unsafe {
-                        self.dnd
-                            .send_finished(window, source_window, state)
-                            .expect("Failed to send `XdndFinished` message.");
+                    }
+                    if let Some(event) = event {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event,
+                            timestamp,
+                        });
                     }
                     self.dnd.reset();
+                } else if client_msg.message_type == atoms[XdndStatus] as c_ulong {
+                    for (window, event) in wt.handle_drag_status(client_msg) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event,
+                            timestamp,
+                        });
+                    }
+                } else if client_msg.message_type == atoms[XdndFinished] as c_ulong {
+                    for (window, event) in wt.handle_drag_finished(client_msg) {
+                        callback(Event::WindowEvent {
+                            window_id: mkwid(window),
+                            event,
+                            timestamp,
+                        });
+                    }
                 } else if client_msg.message_type == atoms[XdndLeave] as c_ulong {
                     self.dnd.reset();
+                    if let Some(event) = wt.handle_drop_leave() {
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event,
+                            timestamp,
+                        });
+                    }
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::HoveredFileCancelled,
+                        timestamp,
                     });
                 }
             }
@@ -314,18 +353,20 @@ impl EventProcessor {
 
                 // Set the timestamp.
                 wt.xconn.set_timestamp(xsel.time as xproto::Timestamp);
+                let timestamp = self.event_clock.instant(xsel.time as u32);
 
                 if xsel.property == atoms[XdndSelection] as c_ulong {
                     let mut result = None;
 
                     // This is where we receive data from drag and drop
-                    if let Ok(mut data) = unsafe { self.dnd.read_data(window) } {
-                        let parse_result = self.dnd.parse_data(&mut data);
+                    if let Ok(data) = unsafe { self.dnd.read_data(window) } {
+                        let parse_result = parse_uri_list(&data);
                         if let Ok(ref path_list) = parse_result {
                             for path in path_list {
                                 callback(Event::WindowEvent {
                                     window_id,
                                     event: WindowEvent::HoveredFile(path.clone()),
+                                    timestamp,
                                 });
                             }
                         }
@@ -336,6 +377,21 @@ impl EventProcessor {
                 }
             }
 
+            ffi::SelectionRequest => {
+                let xsel: &ffi::XSelectionRequestEvent = xev.as_ref();
+                wt.xconn.set_timestamp(xsel.time as xproto::Timestamp);
+                if xsel.selection == atoms[XdndSelection] as c_ulong {
+                    wt.handle_drag_selection_request(xsel);
+                } else {
+                    wt.handle_selection_request(xsel);
+                }
+            }
+
+            ffi::SelectionClear => {
+                let xsel: &ffi::XSelectionClearEvent = xev.as_ref();
+                wt.handle_selection_clear(xsel);
+            }
+
             ffi::ConfigureNotify => {
                 let xev: &ffi::XConfigureEvent = xev.as_ref();
                 let xwindow = xev.window as xproto::Window;
@@ -413,6 +469,7 @@ impl EventProcessor {
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: WindowEvent::Moved(outer.into()),
+                                timestamp,
                             });
                         }
                         outer
@@ -466,6 +523,7 @@ impl EventProcessor {
                                         &inner_size,
                                     )),
                                 },
+                                timestamp,
                             });
 
                             let new_inner_size = *inner_size.lock().unwrap();
@@ -513,6 +571,7 @@ impl EventProcessor {
                         callback(Event::WindowEvent {
                             window_id,
                             event: WindowEvent::Resized(new_inner_size.into()),
+                            timestamp,
                         });
                     }
                 }
@@ -548,7 +607,20 @@ impl EventProcessor {
                 callback(Event::WindowEvent {
                     window_id,
                     event: WindowEvent::Focused(focus),
+                    timestamp,
                 });
+
+                // The popup must be viewable to grab the input, so it grabs whenever it's shown.
+                if let Some(parent) = self
+                    .with_window(window, |window| window.popup_grab_parent())
+                    .flatten()
+                {
+                    wt.grab_popup(window, parent);
+                }
+            }
+            ffi::UnmapNotify => {
+                let xev: &ffi::XUnmapEvent = xev.as_ref();
+                wt.remove_popup(xev.window as xproto::Window);
             }
             ffi::DestroyNotify => {
                 let xev: &ffi::XDestroyWindowEvent = xev.as_ref();
@@ -559,6 +631,12 @@ impl EventProcessor {
                 // In the event that the window's been destroyed without being dropped first, we
                 // cleanup again here.
                 wt.windows.borrow_mut().remove(&WindowId(window as _));
+                wt.remove_popup(window);
+
+                #[cfg(dbus_ime)]
+                if let Some(dbus_ime) = wt.dbus_ime.borrow_mut().as_mut() {
+                    dbus_ime.remove_window(WindowId(window as _));
+                }
 
                 // Since all XIM stuff needs to happen from the same thread, we destroy the input
                 // context here instead of when dropping the window.
@@ -570,6 +648,7 @@ impl EventProcessor {
                 callback(Event::WindowEvent {
                     window_id,
                     event: WindowEvent::Destroyed,
+                    timestamp,
                 });
             }
             ffi::PropertyNotify => {
@@ -578,6 +657,9 @@ impl EventProcessor {
 
                 if atom == xproto::Atom::from(xproto::AtomEnum::RESOURCE_MANAGER) {
                     self.process_dpi_change(&mut callback);
+                } else if atom == atoms[_XSETTINGS_SETTINGS] {
+                    Self::click_settings(&wt.xconn, Some(xev.window as xproto::Window))
+                        .apply(&mut self.click_counter);
                 }
             }
 
@@ -587,6 +669,7 @@ impl EventProcessor {
                 callback(Event::WindowEvent {
                     window_id: mkwid(xwindow),
                     event: WindowEvent::Occluded(xev.state == ffi::VisibilityFullyObscured),
+                    timestamp,
                 });
                 self.with_window(xwindow, |window| {
                     window.visibility_notify();
@@ -605,6 +688,7 @@ impl EventProcessor {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::RedrawRequested,
+                        timestamp,
                     });
                 }
             }
@@ -615,6 +699,7 @@ impl EventProcessor {
 
                 // Set the timestamp.
                 wt.xconn.set_timestamp(xkev.time as xproto::Timestamp);
+                let timestamp = self.event_clock.instant(xkev.time as u32);
 
                 let window = match self.active_window {
                     Some(window) => window,
@@ -661,6 +746,23 @@ impl EventProcessor {
                     ElementState::Released
                 };
 
+                // The input method on D-Bus gets the keys first, and consumes the ones it handles.
+                #[cfg(dbus_ime)]
+                if keycode != 0 {
+                    if let Some(dbus_ime) = wt.dbus_ime.borrow_mut().as_mut() {
+                        let key = KeyInput::new(
+                            self.kb_state.get_one_sym_raw(keycode),
+                            keycode,
+                            self.kb_state.mods_state(),
+                            state == ElementState::Pressed,
+                            xkev.time as u32,
+                        );
+                        if dbus_ime.process_key(WindowId(window as _), key) {
+                            return;
+                        }
+                    }
+                }
+
                 if keycode != 0 && !self.is_composing {
                     let event = self.kb_state.process_key_event(keycode, state, repeat);
                     callback(Event::WindowEvent {
@@ -670,6 +772,7 @@ impl EventProcessor {
                             event,
                             is_synthetic: false,
                         },
+                        timestamp,
                     });
                 } else if let Some(ic) = wt.ime.borrow().get_context(window as ffi::Window) {
                     let written = wt.xconn.lookup_utf8(ic, xkev);
@@ -677,12 +780,14 @@ impl EventProcessor {
                         let event = Event::WindowEvent {
                             window_id,
                             event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                            timestamp,
                         };
                         callback(event);
 
                         let event = Event::WindowEvent {
                             window_id,
                             event: WindowEvent::Ime(Ime::Commit(written)),
+                            timestamp,
                         };
 
                         self.is_composing = false;
@@ -721,6 +826,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         if (xev.flags & ffi::XIPointerEmulated) != 0 {
                             // Deliver multi-touch events instead of emulated mouse events.
@@ -732,31 +838,10 @@ impl EventProcessor {
                         } else {
                             Released
                         };
-                        match xev.detail as u32 {
-                            ffi::Button1 => callback(Event::WindowEvent {
-                                window_id,
-                                event: MouseInput {
-                                    device_id,
-                                    state,
-                                    button: Left,
-                                },
-                            }),
-                            ffi::Button2 => callback(Event::WindowEvent {
-                                window_id,
-                                event: MouseInput {
-                                    device_id,
-                                    state,
-                                    button: Middle,
-                                },
-                            }),
-                            ffi::Button3 => callback(Event::WindowEvent {
-                                window_id,
-                                event: MouseInput {
-                                    device_id,
-                                    state,
-                                    button: Right,
-                                },
-                            }),
+                        let button = match xev.detail as u32 {
+                            ffi::Button1 => Left,
+                            ffi::Button2 => Middle,
+                            ffi::Button3 => Right,
 
                             // Suppress emulated scroll wheel clicks, since we handle the real motion events for those.
                             // In practice, even clicky scroll wheels appear to be reported by evdev (and XInput2 in
@@ -776,35 +861,73 @@ impl EventProcessor {
                                             },
                                             phase: TouchPhase::Moved,
                                         },
+                                        timestamp,
                                     });
                                 }
+                                return;
                             }
 
-                            8 => callback(Event::WindowEvent {
-                                window_id,
-                                event: MouseInput {
-                                    device_id,
-                                    state,
-                                    button: Back,
-                                },
-                            }),
-                            9 => callback(Event::WindowEvent {
-                                window_id,
-                                event: MouseInput {
-                                    device_id,
-                                    state,
-                                    button: Forward,
-                                },
-                            }),
+                            8 => Back,
+                            9 => Forward,
+                            x => Other(x as u16),
+                        };
 
-                            x => callback(Event::WindowEvent {
+                        let position = PhysicalPosition::new(xev.event_x, xev.event_y);
+                        if state == Pressed {
+                            let window = xev.event as xproto::Window;
+                            for popup in wt.dismiss_popups(window, position) {
+                                callback(Event::WindowEvent {
+                                    window_id: mkwid(popup),
+                                    event: WindowEvent::PopupDismissed,
+                                    timestamp,
+                                });
+                            }
+                        }
+
+                        let click_count = self
+                            .click_counter
+                            .count(window_id, state, button, position, timestamp);
+                        callback(Event::WindowEvent {
+                            window_id,
+                            event: MouseInput {
+                                device_id,
+                                state,
+                                button,
+                                click_count,
+                            },
+                            timestamp,
+                        });
+
+                        // The tablet tools move the pointer too, and report their buttons.
+                        let source_id = xev.sourceid as xinput::DeviceId;
+                        let tablet_button = self
+                            .devices
+                            .borrow()
+                            .get(&DeviceId(source_id))
+                            .and_then(|device| device.tablet.as_ref())
+                            .and_then(|tablet| tablet.button(xev.detail as u32));
+                        if let Some(button) = tablet_button {
+                            callback(Event::WindowEvent {
                                 window_id,
-                                event: MouseInput {
-                                    device_id,
+                                event: WindowEvent::TabletButton {
+                                    device_id: mkdid(source_id),
+                                    tool_id: Some(source_id as TabletToolId),
+                                    button,
                                     state,
-                                    button: Other(x as u16),
                                 },
-                            }),
+                                timestamp,
+                            });
+                        }
+
+                        // Releasing the button ends the drag started from one of the windows.
+                        if state == Released {
+                            for (window, event) in wt.handle_drag_release(xev.time as _) {
+                                callback(Event::WindowEvent {
+                                    window_id: mkwid(window),
+                                    event,
+                                    timestamp,
+                                });
+                            }
                         }
                     }
                     ffi::XI_Motion => {
@@ -812,12 +935,26 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         let device_id = mkdid(xev.deviceid as xinput::DeviceId);
                         let window = xev.event as xproto::Window;
                         let window_id = mkwid(window);
                         let new_cursor_pos = (xev.event_x, xev.event_y);
 
+                        let drag_events = wt.handle_drag_motion(
+                            xev.root_x as i16,
+                            xev.root_y as i16,
+                            xev.time as xproto::Timestamp,
+                        );
+                        for (window, event) in drag_events {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event,
+                                timestamp,
+                            });
+                        }
+
                         let cursor_moved = self.with_window(window, |window| {
                             let mut shared_state_lock = window.shared_state_lock();
                             util::maybe_change(&mut shared_state_lock.cursor_pos, new_cursor_pos)
@@ -831,6 +968,7 @@ impl EventProcessor {
                                     device_id,
                                     position,
                                 },
+                                timestamp,
                             });
                         } else if cursor_moved.is_none() {
                             return;
@@ -857,10 +995,13 @@ impl EventProcessor {
                             for i in 0..xev.valuators.mask_len * 8 {
                                 if ffi::XIMaskIsSet(mask, i) {
                                     let x = unsafe { *value };
+                                    if let Some(tablet) = physical_device.tablet.as_mut() {
+                                        tablet.update(i, x);
+                                    }
                                     if let Some(&mut (_, ref mut info)) = physical_device
                                         .scroll_axes
                                         .iter_mut()
-                                        .find(|&&mut (axis, _)| axis == i as _)
+                                        .find(|&&mut (axis, _)| axis == i)
                                     {
                                         let delta = (x - info.position) / info.increment;
                                         info.position = x;
@@ -879,6 +1020,7 @@ impl EventProcessor {
                                                 },
                                                 phase: TouchPhase::Moved,
                                             },
+                                            timestamp,
                                         });
                                     } else {
                                         events.push(Event::WindowEvent {
@@ -888,11 +1030,56 @@ impl EventProcessor {
                                                 axis: i as u32,
                                                 value: unsafe { *value },
                                             },
+                                            timestamp,
                                         });
                                     }
                                     value = unsafe { value.offset(1) };
                                 }
                             }
+
+                            // The tool is in proximity of the window it moves in, until it leaves
+                            // the window or another device moves the pointer.
+                            let source_id = xev.sourceid as xinput::DeviceId;
+                            let tablet_tool = physical_device
+                                .tablet
+                                .as_ref()
+                                .map(|tablet| ((window, source_id), tablet));
+                            if self.tablet_tool != tablet_tool.as_ref().map(|(tool, _)| *tool) {
+                                if let Some((window, source_id)) = self.tablet_tool.take() {
+                                    events.push(Event::WindowEvent {
+                                        window_id: mkwid(window),
+                                        event: WindowEvent::TabletPenLeave {
+                                            device_id: mkdid(source_id),
+                                            tool_id: source_id as TabletToolId,
+                                        },
+                                        timestamp,
+                                    });
+                                }
+                                if let Some((tool, tablet)) = &tablet_tool {
+                                    self.tablet_tool = Some(*tool);
+                                    events.push(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::TabletPenEnter {
+                                            device_id: mkdid(source_id),
+                                            tool_id: source_id as TabletToolId,
+                                            inverted: tablet.inverted(),
+                                        },
+                                        timestamp,
+                                    });
+                                }
+                            }
+                            if let Some((_, tablet)) = tablet_tool {
+                                let location = PhysicalPosition::new(xev.event_x, xev.event_y);
+                                events.push(Event::WindowEvent {
+                                    window_id,
+                                    event: tablet.motion(
+                                        mkdid(source_id),
+                                        source_id as TabletToolId,
+                                        location,
+                                    ),
+                                    timestamp,
+                                });
+                            }
                         }
                         for event in events {
                             callback(event);
@@ -904,6 +1091,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         let window = xev.event as xproto::Window;
                         let window_id = mkwid(window);
@@ -933,6 +1121,7 @@ impl EventProcessor {
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: CursorEntered { device_id },
+                                timestamp,
                             });
 
                             let position = PhysicalPosition::new(xev.event_x, xev.event_y);
@@ -943,6 +1132,7 @@ impl EventProcessor {
                                     device_id,
                                     position,
                                 },
+                                timestamp,
                             });
                         }
                     }
@@ -952,6 +1142,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         // Leave, FocusIn, and FocusOut can be received by a window that's already
                         // been destroyed, which the user presumably doesn't want to deal with.
@@ -962,8 +1153,25 @@ impl EventProcessor {
                                 event: CursorLeft {
                                     device_id: mkdid(xev.deviceid as xinput::DeviceId),
                                 },
+                                timestamp,
                             });
                         }
+
+                        if let Some((tool_window, source_id)) = self.tablet_tool {
+                            if tool_window == window {
+                                self.tablet_tool = None;
+                                if !window_closed {
+                                    callback(Event::WindowEvent {
+                                        window_id: mkwid(window),
+                                        event: WindowEvent::TabletPenLeave {
+                                            device_id: mkdid(source_id),
+                                            tool_id: source_id as TabletToolId,
+                                        },
+                                        timestamp,
+                                    });
+                                }
+                            }
+                        }
                     }
                     ffi::XI_FocusIn => {
                         let xev: &ffi::XIFocusInEvent = unsafe { &*(xev.data as *const _) };
@@ -971,12 +1179,18 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         wt.ime
                             .borrow_mut()
                             .focus(xev.event)
                             .expect("Failed to focus input context");
 
+                        #[cfg(dbus_ime)]
+                        if let Some(dbus_ime) = wt.dbus_ime.borrow_mut().as_mut() {
+                            dbus_ime.set_focus(WindowId(window as _), true);
+                        }
+
                         if self.active_window != Some(window) {
                             self.active_window = Some(window);
 
@@ -985,18 +1199,29 @@ impl EventProcessor {
                             let window_id = mkwid(window);
                             let position = PhysicalPosition::new(xev.event_x, xev.event_y);
 
+                            let mut inhibited = None;
                             if let Some(window) = self.with_window(window, Arc::clone) {
                                 window.shared_state_lock().has_focus = true;
+                                inhibited = window.update_keyboard_grab();
                             }
 
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: Focused(true),
+                                timestamp,
                             });
 
+                            if let Some(inhibited) = inhibited {
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::KeyboardShortcutsInhibited(inhibited),
+                                    timestamp,
+                                });
+                            }
+
                             let modifiers: crate::keyboard::ModifiersState =
                                 self.kb_state.mods_state().into();
-                            self.send_modifiers(modifiers, &mut callback);
+                            self.send_modifiers(modifiers, timestamp, &mut callback);
 
                             // The deviceid for this event is for a keyboard instead of a pointer,
                             // so we have to do a little extra work.
@@ -1013,6 +1238,7 @@ impl EventProcessor {
                                     device_id: mkdid(pointer_id as _),
                                     position,
                                 },
+                                timestamp,
                             });
 
                             // Issue key press events for all pressed keys
@@ -1021,6 +1247,7 @@ impl EventProcessor {
                                 window_id,
                                 ElementState::Pressed,
                                 &mut self.kb_state,
+                                timestamp,
                                 &mut callback,
                             );
                         }
@@ -1031,6 +1258,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         if !self.window_exists(window) {
                             return;
@@ -1041,6 +1269,11 @@ impl EventProcessor {
                             .unfocus(xev.event)
                             .expect("Failed to unfocus input context");
 
+                        #[cfg(dbus_ime)]
+                        if let Some(dbus_ime) = wt.dbus_ime.borrow_mut().as_mut() {
+                            dbus_ime.set_focus(WindowId(window as _), false);
+                        }
+
                         if self.active_window.take() == Some(window) {
                             let window_id = mkwid(window);
 
@@ -1052,21 +1285,43 @@ impl EventProcessor {
                                 window_id,
                                 ElementState::Released,
                                 &mut self.kb_state,
+                                timestamp,
                                 &mut callback,
                             );
+                            // Issue key release events for all pressed synthetic keys
+                            for event in self.synthetic_keys.release_all(&mut self.kb_state) {
+                                callback(Event::WindowEvent {
+                                    window_id,
+                                    event: WindowEvent::KeyboardInput {
+                                        device_id: mkdid(util::VIRTUAL_CORE_KEYBOARD),
+                                        event,
+                                        is_synthetic: true,
+                                    },
+                                    timestamp,
+                                });
+                            }
+
                             // Clear this so detecting key repeats is consistently handled when the
                             // window regains focus.
                             self.held_key_press = None;
 
-                            self.send_modifiers(ModifiersState::empty(), &mut callback);
+                            self.send_modifiers(ModifiersState::empty(), timestamp, &mut callback);
 
                             if let Some(window) = self.with_window(window, Arc::clone) {
                                 window.shared_state_lock().has_focus = false;
+                                if let Some(inhibited) = window.update_keyboard_grab() {
+                                    callback(Event::WindowEvent {
+                                        window_id,
+                                        event: WindowEvent::KeyboardShortcutsInhibited(inhibited),
+                                        timestamp,
+                                    });
+                                }
                             }
 
                             callback(Event::WindowEvent {
                                 window_id,
                                 event: Focused(false),
+                                timestamp,
                             })
                         }
                     }
@@ -1076,6 +1331,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         let window = xev.event as xproto::Window;
                         let window_id = mkwid(window);
@@ -1099,6 +1355,7 @@ impl EventProcessor {
                                         device_id: mkdid(util::VIRTUAL_CORE_POINTER),
                                         position: location.cast(),
                                     },
+                                    timestamp,
                                 });
                             }
 
@@ -1111,15 +1368,98 @@ impl EventProcessor {
                                     force: None, // TODO
                                     id,
                                 }),
+                                timestamp,
                             })
                         }
                     }
 
+                    ffi::XI_GesturePinchBegin
+                    | ffi::XI_GesturePinchUpdate
+                    | ffi::XI_GesturePinchEnd => {
+                        let xev: &ffi::XIGesturePinchEvent = unsafe { &*(xev.data as *const _) };
+
+                        // Set the timestamp.
+                        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
+
+                        let window = xev.event as xproto::Window;
+                        let (magnification, rotation, phase) = match xev.evtype {
+                            ffi::XI_GesturePinchBegin => {
+                                self.pinch_scale = 1.0;
+                                (0.0, 0.0, TouchPhase::Started)
+                            }
+                            ffi::XI_GesturePinchUpdate => {
+                                // The delta is applied to the magnification of the previous event,
+                                // and the rotation is clockwise.
+                                let magnification = xev.scale / self.pinch_scale - 1.0;
+                                self.pinch_scale = xev.scale;
+                                (magnification, -xev.delta_angle, TouchPhase::Moved)
+                            }
+                            _ if xev.flags & ffi::XIGesturePinchEventCancelled != 0 => {
+                                (0.0, 0.0, TouchPhase::Cancelled)
+                            }
+                            _ => (0.0, 0.0, TouchPhase::Ended),
+                        };
+                        if self.window_exists(window) {
+                            let device_id = mkdid(xev.deviceid as xinput::DeviceId);
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::PinchGesture {
+                                    device_id,
+                                    delta: magnification,
+                                    phase,
+                                },
+                                timestamp,
+                            });
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::RotationGesture {
+                                    device_id,
+                                    delta: rotation as f32,
+                                    phase,
+                                },
+                                timestamp,
+                            });
+                        }
+                    }
+                    ffi::XI_GestureSwipeBegin
+                    | ffi::XI_GestureSwipeUpdate
+                    | ffi::XI_GestureSwipeEnd => {
+                        let xev: &ffi::XIGestureSwipeEvent = unsafe { &*(xev.data as *const _) };
+
+                        // Set the timestamp.
+                        wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
+
+                        let window = xev.event as xproto::Window;
+                        let phase = match xev.evtype {
+                            ffi::XI_GestureSwipeBegin => TouchPhase::Started,
+                            ffi::XI_GestureSwipeUpdate => TouchPhase::Moved,
+                            _ if xev.flags & ffi::XIGestureSwipeEventCancelled != 0 => {
+                                TouchPhase::Cancelled
+                            }
+                            _ => TouchPhase::Ended,
+                        };
+                        if self.window_exists(window) {
+                            callback(Event::WindowEvent {
+                                window_id: mkwid(window),
+                                event: WindowEvent::SwipeGesture {
+                                    device_id: mkdid(xev.deviceid as xinput::DeviceId),
+                                    fingers: xev.detail as u32,
+                                    delta: PhysicalPosition::new(xev.delta_x, xev.delta_y),
+                                    phase,
+                                },
+                                timestamp,
+                            });
+                        }
+                    }
+
                     ffi::XI_RawButtonPress | ffi::XI_RawButtonRelease => {
                         let xev: &ffi::XIRawEvent = unsafe { &*(xev.data as *const _) };
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         if xev.flags & ffi::XIPointerEmulated == 0 {
                             callback(Event::DeviceEvent {
@@ -1132,6 +1472,7 @@ impl EventProcessor {
                                         _ => unreachable!(),
                                     },
                                 },
+                                timestamp,
                             });
                         }
                     }
@@ -1141,6 +1482,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         let did = mkdid(xev.deviceid as xinput::DeviceId);
 
@@ -1171,6 +1513,7 @@ impl EventProcessor {
                                         axis: i as u32,
                                         value: x,
                                     },
+                                    timestamp,
                                 });
                                 value = unsafe { value.offset(1) };
                             }
@@ -1179,6 +1522,7 @@ impl EventProcessor {
                             callback(Event::DeviceEvent {
                                 device_id: did,
                                 event: DeviceEvent::MouseMotion { delta: mouse_delta },
+                                timestamp,
                             });
                         }
                         if scroll_delta != (0.0, 0.0) {
@@ -1187,6 +1531,7 @@ impl EventProcessor {
                                 event: DeviceEvent::MouseWheel {
                                     delta: LineDelta(scroll_delta.0, scroll_delta.1),
                                 },
+                                timestamp,
                             });
                         }
                     }
@@ -1195,6 +1540,7 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         let state = match xev.evtype {
                             ffi::XI_RawKeyPress => Pressed,
@@ -1215,6 +1561,7 @@ impl EventProcessor {
                                 physical_key,
                                 state,
                             }),
+                            timestamp,
                         });
                     }
 
@@ -1223,24 +1570,53 @@ impl EventProcessor {
 
                         // Set the timestamp.
                         wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                        let timestamp = self.event_clock.instant(xev.time as u32);
 
                         for info in
                             unsafe { slice::from_raw_parts(xev.info, xev.num_info as usize) }
                         {
                             if 0 != info.flags & (ffi::XISlaveAdded | ffi::XIMasterAdded) {
-                                self.init_device(info.deviceid as xinput::DeviceId);
+                                let device_id = info.deviceid as xinput::DeviceId;
+                                let tools = self.init_device(device_id);
                                 callback(Event::DeviceEvent {
-                                    device_id: mkdid(info.deviceid as xinput::DeviceId),
+                                    device_id: mkdid(device_id),
                                     event: DeviceEvent::Added,
+                                    timestamp,
                                 });
+
+                                for (device_id, tool) in tools {
+                                    callback(Event::DeviceEvent {
+                                        device_id: mkdid(device_id),
+                                        event: DeviceEvent::TabletToolAdded(tool),
+                                        timestamp,
+                                    });
+                                }
                             } else if 0 != info.flags & (ffi::XISlaveRemoved | ffi::XIMasterRemoved)
                             {
                                 callback(Event::DeviceEvent {
                                     device_id: mkdid(info.deviceid as xinput::DeviceId),
                                     event: DeviceEvent::Removed,
+                                    timestamp,
                                 });
-                                let mut devices = self.devices.borrow_mut();
-                                devices.remove(&DeviceId(info.deviceid as xinput::DeviceId));
+                                let tablet_tool = self
+                                    .devices
+                                    .borrow_mut()
+                                    .remove(&DeviceId(info.deviceid as xinput::DeviceId))
+                                    .and_then(|device| device.tablet);
+                                if tablet_tool.is_some() {
+                                    callback(Event::DeviceEvent {
+                                        device_id: mkdid(info.deviceid as xinput::DeviceId),
+                                        event: DeviceEvent::TabletToolRemoved(
+                                            info.deviceid as TabletToolId,
+                                        ),
+                                        timestamp,
+                                    });
+                                }
+                                if self.tablet_tool.map(|(_, device)| device)
+                                    == Some(info.deviceid as xinput::DeviceId)
+                                {
+                                    self.tablet_tool = None;
+                                }
                             }
                         }
                     }
@@ -1249,7 +1625,7 @@ impl EventProcessor {
                 }
             }
             _ => {
-                if event_type == self.xkbext.first_event as _ {
+                if event_type == self.xkbext.first_event as c_int {
                     let xev = unsafe { &*(xev as *const _ as *const ffi::XkbAnyEvent) };
                     match xev.xkb_type {
                         ffi::XkbNewKeyboardNotify => {
@@ -1259,6 +1635,7 @@ impl EventProcessor {
 
                             // Set the timestamp.
                             wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                            let timestamp = self.event_clock.instant(xev.time as u32);
 
                             let keycodes_changed_flag = 0x1;
                             let geometry_changed_flag = 0x1 << 1;
@@ -1273,12 +1650,18 @@ impl EventProcessor {
                             {
                                 unsafe { self.kb_state.init_with_x11_keymap() };
                                 let modifiers = self.kb_state.mods_state();
-                                self.send_modifiers(modifiers.into(), &mut callback);
+                                self.send_modifiers(modifiers.into(), timestamp, &mut callback);
+                                self.send_keyboard_layout(wt, timestamp, &mut callback);
                             }
                         }
                         ffi::XkbMapNotify => {
                             unsafe { self.kb_state.init_with_x11_keymap() };
-                            self.send_modifiers(self.kb_state.mods_state().into(), &mut callback);
+                            self.send_modifiers(
+                                self.kb_state.mods_state().into(),
+                                timestamp,
+                                &mut callback,
+                            );
+                            self.send_keyboard_layout(wt, timestamp, &mut callback);
                         }
                         ffi::XkbStateNotify => {
                             let xev =
@@ -1286,6 +1669,7 @@ impl EventProcessor {
 
                             // Set the timestamp.
                             wt.xconn.set_timestamp(xev.time as xproto::Timestamp);
+                            let timestamp = self.event_clock.instant(xev.time as u32);
 
                             // NOTE: Modifiers could update without a prior event updating them,
                             // thus diffing the state before and after is not reliable.
@@ -1299,7 +1683,12 @@ impl EventProcessor {
                                 xev.locked_group as u32,
                             );
 
-                            self.send_modifiers(self.kb_state.mods_state().into(), &mut callback);
+                            self.send_modifiers(
+                                self.kb_state.mods_state().into(),
+                                timestamp,
+                                &mut callback,
+                            );
+                            self.send_keyboard_layout(wt, timestamp, &mut callback);
                         }
                         _ => {}
                     }
@@ -1312,14 +1701,31 @@ impl EventProcessor {
 
         // Handle IME requests.
         while let Ok(request) = self.ime_receiver.try_recv() {
+            #[cfg(dbus_ime)]
+            if wt.uses_dbus_ime() {
+                if let Some((window_id, event)) = self.process_dbus_ime_request(request) {
+                    callback(Event::WindowEvent {
+                        window_id: mkwid(window_id.0 as xproto::Window),
+                        event: WindowEvent::Ime(event),
+                        timestamp,
+                    });
+                }
+                continue;
+            }
+
             let mut ime = wt.ime.borrow_mut();
             match request {
-                ImeRequest::Position(window_id, x, y) => {
+                ImeRequest::Area(window_id, x, y, _, _) => {
                     ime.send_xim_spot(window_id, x, y);
                 }
                 ImeRequest::Allow(window_id, allowed) => {
                     ime.set_ime_allowed(window_id, allowed);
                 }
+                // XIM has no selection, so only the cursor is used.
+                ImeRequest::SurroundingText(window_id, text, cursor, _) => {
+                    ime.set_surrounding_text(window_id, text, cursor);
+                }
+                ImeRequest::ContentType(..) => {}
             }
         }
 
@@ -1331,6 +1737,7 @@ impl EventProcessor {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::Ime(Ime::Enabled),
+                        timestamp,
                     });
                 }
                 ImeEvent::Start => {
@@ -1338,14 +1745,23 @@ impl EventProcessor {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::Ime(Ime::Preedit("".to_owned(), None)),
+                        timestamp,
                     });
                 }
-                ImeEvent::Update(text, position) => {
+                ImeEvent::Update(text, position, segments) => {
                     if self.is_composing {
                         callback(Event::WindowEvent {
                             window_id,
                             event: WindowEvent::Ime(Ime::Preedit(text, Some((position, position)))),
+                            timestamp,
                         });
+                        if !segments.is_empty() {
+                            callback(Event::WindowEvent {
+                                window_id,
+                                event: WindowEvent::Ime(Ime::PreeditSegments(segments)),
+                                timestamp,
+                            });
+                        }
                     }
                 }
                 ImeEvent::End => {
@@ -1354,6 +1770,17 @@ impl EventProcessor {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                        timestamp,
+                    });
+                }
+                ImeEvent::DeleteSurrounding(before_bytes, after_bytes) => {
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::Ime(Ime::DeleteSurrounding {
+                            before_bytes,
+                            after_bytes,
+                        }),
+                        timestamp,
                     });
                 }
                 ImeEvent::Disabled => {
@@ -1361,9 +1788,173 @@ impl EventProcessor {
                     callback(Event::WindowEvent {
                         window_id,
                         event: WindowEvent::Ime(Ime::Disabled),
+                        timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Forward the IME request to the input method on D-Bus, returning the event of the window
+    /// when it enables or disables the IME.
+    #[cfg(dbus_ime)]
+    fn process_dbus_ime_request(&self, request: ImeRequest) -> Option<(WindowId, Ime)> {
+        let wt = get_xtarget(&self.target);
+        let mut dbus_ime = wt.dbus_ime.borrow_mut();
+        let dbus_ime = dbus_ime.as_mut()?;
+        match request {
+            ImeRequest::Area(window, x, y, width, height) => {
+                // The input method takes the coordinates of the root window.
+                let origin = wt
+                    .xconn
+                    .translate_coords(window as xproto::Window, wt.root)
+                    .map(|origin| (origin.dst_x, origin.dst_y))
+                    .unwrap_or_default();
+                let area = [
+                    i32::from(origin.0) + i32::from(x),
+                    i32::from(origin.1) + i32::from(y),
+                    i32::from(width),
+                    i32::from(height),
+                ];
+                dbus_ime.set_cursor_area(WindowId(window as _), area);
+                None
+            }
+            ImeRequest::Allow(window, allowed) => {
+                let window_id = WindowId(window as _);
+                if !dbus_ime.set_allowed(window_id, allowed) {
+                    return None;
+                }
+                let event = if allowed { Ime::Enabled } else { Ime::Disabled };
+                Some((window_id, event))
+            }
+            ImeRequest::SurroundingText(window, text, cursor, anchor) => {
+                dbus_ime.set_surrounding_text(WindowId(window as _), text, cursor, anchor);
+                None
+            }
+            ImeRequest::ContentType(window, purpose, hints) => {
+                dbus_ime.set_content_type(WindowId(window as _), purpose, hints);
+                None
+            }
+        }
+    }
+
+    /// Process the input injected by the application.
+    pub(super) fn process_synthetic_input<T: 'static, F>(
+        &mut self,
+        window_id: WindowId,
+        input: SyntheticInput,
+        mut callback: F,
+    ) where
+        F: FnMut(Event<T>),
+    {
+        let window = window_id.0 as xproto::Window;
+        if !self.window_exists(window) {
+            return;
+        }
+
+        let window_id = mkwid(window);
+        let timestamp = Instant::now();
+        match input {
+            SyntheticInput::KeyboardInput {
+                physical_key,
+                state,
+                repeat,
+            } => {
+                let keycode = match keymap::physicalkey_to_scancode(physical_key) {
+                    Some(scancode) => scancode + KEYCODE_OFFSET as u32,
+                    None => return,
+                };
+
+                let (event, modifiers_changed) =
+                    self.synthetic_keys
+                        .process(&mut self.kb_state, keycode, state, repeat);
+                callback(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::KeyboardInput {
+                        device_id: mkdid(util::VIRTUAL_CORE_KEYBOARD),
+                        event,
+                        is_synthetic: false,
+                    },
+                    timestamp,
+                });
+
+                // The server doesn't know about the key, so update the modifiers ourselves.
+                if modifiers_changed {
+                    let modifiers = self.kb_state.mods_state().into();
+                    self.send_modifiers_to(window_id, modifiers, timestamp, &mut callback);
+                }
+            }
+            SyntheticInput::CursorMoved { position } => {
+                let cursor_moved = self.with_window(window, |window| {
+                    let mut shared_state_lock = window.shared_state_lock();
+                    util::maybe_change(&mut shared_state_lock.cursor_pos, (position.x, position.y))
+                });
+                if cursor_moved == Some(true) {
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::CursorMoved {
+                            device_id: mkdid(util::VIRTUAL_CORE_POINTER),
+                            position,
+                        },
+                        timestamp,
                     });
                 }
             }
+            SyntheticInput::MouseInput { state, button } => {
+                let position = self
+                    .with_window(window, |window| window.shared_state_lock().cursor_pos)
+                    .flatten()
+                    .unwrap_or_default();
+                let click_count =
+                    self.click_counter
+                        .count(window_id, state, button, position.into(), timestamp);
+                callback(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::MouseInput {
+                        device_id: mkdid(util::VIRTUAL_CORE_POINTER),
+                        state,
+                        button,
+                        click_count,
+                    },
+                    timestamp,
+                });
+            }
+            SyntheticInput::Touch {
+                phase,
+                location,
+                force,
+                id,
+            } => {
+                // Mouse cursor position changes when touch events are received.
+                // Only the first concurrently active touch ID moves the mouse cursor.
+                if is_first_touch(&mut self.first_touch, &mut self.num_touch, id, phase) {
+                    callback(Event::WindowEvent {
+                        window_id,
+                        event: WindowEvent::CursorMoved {
+                            device_id: mkdid(util::VIRTUAL_CORE_POINTER),
+                            position: location,
+                        },
+                        timestamp,
+                    });
+                }
+
+                callback(Event::WindowEvent {
+                    window_id,
+                    event: WindowEvent::Touch(crate::event::Touch {
+                        device_id: mkdid(util::VIRTUAL_CORE_POINTER),
+                        phase,
+                        location,
+                        force,
+                        id,
+                    }),
+                    timestamp,
+                })
+            }
+            SyntheticInput::Ime(ime) => callback(Event::WindowEvent {
+                window_id,
+                event: WindowEvent::Ime(ime),
+                timestamp,
+            }),
         }
     }
 
@@ -1373,6 +1964,7 @@ impl EventProcessor {
     fn send_modifiers<T: 'static, F: FnMut(Event<T>)>(
         &self,
         modifiers: ModifiersState,
+        timestamp: Instant,
         callback: &mut F,
     ) {
         let window_id = match self.active_window {
@@ -1380,10 +1972,39 @@ impl EventProcessor {
             None => return,
         };
 
+        self.send_modifiers_to(window_id, modifiers, timestamp, callback);
+    }
+
+    /// Send modifiers for the given window.
+    fn send_modifiers_to<T: 'static, F: FnMut(Event<T>)>(
+        &self,
+        window_id: crate::window::WindowId,
+        modifiers: ModifiersState,
+        timestamp: Instant,
+        callback: &mut F,
+    ) {
         if self.modifiers.replace(modifiers) != modifiers {
             callback(Event::WindowEvent {
                 window_id,
                 event: WindowEvent::ModifiersChanged(self.modifiers.get().into()),
+                timestamp,
+            });
+        }
+    }
+
+    /// Send the active keyboard layout to the focused window when it has changed.
+    fn send_keyboard_layout<T: 'static, F: FnMut(Event<T>)>(
+        &self,
+        wt: &super::EventLoopWindowTarget,
+        timestamp: Instant,
+        callback: &mut F,
+    ) {
+        let layout = wt.keyboard_layouts.borrow_mut().update(&self.kb_state);
+        if let (Some(layout), Some(window)) = (layout, self.active_window) {
+            callback(Event::WindowEvent {
+                window_id: mkwid(window),
+                event: WindowEvent::KeyboardLayoutChanged(layout),
+                timestamp,
             });
         }
     }
@@ -1393,6 +2014,7 @@ impl EventProcessor {
         window_id: crate::window::WindowId,
         state: ElementState,
         kb_state: &mut KbdState,
+        timestamp: Instant,
         callback: &mut F,
     ) where
         F: FnMut(Event<T>),
@@ -1415,10 +2037,27 @@ impl EventProcessor {
                     event,
                     is_synthetic: true,
                 },
+                timestamp,
             });
         }
     }
 
+    /// The double-click settings of the XSETTINGS `manager`, or of GTK.
+    pub(super) fn click_settings(
+        xconn: &XConnection,
+        manager: Option<xproto::Window>,
+    ) -> ClickSettings {
+        let xsettings = manager
+            .map(|manager| xconn.xsettings_click_settings(manager))
+            .transpose()
+            .unwrap_or_else(|err| {
+                warn!("Failed to read the XSETTINGS: {err}");
+                None
+            })
+            .unwrap_or_default();
+        xsettings.or(ClickSettings::from_gtk())
+    }
+
     fn process_dpi_change<T: 'static, F>(&self, callback: &mut F)
     where
         F: FnMut(Event<T>),
@@ -1 +1,108 @@
+#![allow(non_upper_case_globals)]
+
+use std::os::raw::{c_char, c_double, c_int, c_ulong, c_ushort};
+
 pub use x11_dl::{error::OpenError, xcursor::*, xinput2::*, xlib::*, xlib_xcb::*};
+
+// The touchpad gestures of XInput 2.4, which `x11-dl` doesn't declare.
+
+pub const XI_GesturePinchBegin: i32 = 27;
+pub const XI_GesturePinchUpdate: i32 = 28;
+pub const XI_GesturePinchEnd: i32 = 29;
+pub const XI_GestureSwipeBegin: i32 = 30;
+pub const XI_GestureSwipeUpdate: i32 = 31;
+pub const XI_GestureSwipeEnd: i32 = 32;
+
+pub const XIGesturePinchEventCancelled: i32 = 1 << 0;
+pub const XIGestureSwipeEventCancelled: i32 = 1 << 0;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIGesturePinchEvent {
+    pub _type: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub scale: c_double,
+    pub delta_angle: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIGestureSwipeEvent {
+    pub _type: c_int,
+    pub serial: c_ulong,
+    pub send_event: Bool,
+    pub display: *mut Display,
+    pub extension: c_int,
+    pub evtype: c_int,
+    pub time: Time,
+    pub deviceid: c_int,
+    pub sourceid: c_int,
+    pub detail: c_int,
+    pub root: Window,
+    pub event: Window,
+    pub child: Window,
+    pub root_x: c_double,
+    pub root_y: c_double,
+    pub event_x: c_double,
+    pub event_y: c_double,
+    pub delta_x: c_double,
+    pub delta_y: c_double,
+    pub delta_unaccel_x: c_double,
+    pub delta_unaccel_y: c_double,
+    pub flags: c_int,
+    pub mods: XIModifierState,
+    pub group: XIGroupState,
+}
+
+// The preedit feedback of XIM, which `x11-dl` doesn't declare.
+
+pub const XIMReverse: XIMFeedback = 1;
+pub const XIMUnderline: XIMFeedback = 1 << 1;
+pub const XIMHighlight: XIMFeedback = 1 << 2;
+pub const XIMPrimary: XIMFeedback = 1 << 5;
+
+// The string conversion of XIM, which `x11-dl` doesn't declare.
+
+pub const XIMStringConversionSubstitution: c_ushort = 0x0001;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIMStringConversionText {
+    pub length: c_ushort,
+    pub feedback: *mut c_ushort,
+    pub encoding_is_wchar: Bool,
+    pub mbs: *mut c_char,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct XIMStringConversionCallbackStruct {
+    pub position: c_ushort,
+    // The `XIMCaretDirection`, as an integer since the server could send any value.
+    pub direction: c_int,
+    pub operation: c_ushort,
+    pub factor: c_ushort,
+    pub text: *mut XIMStringConversionText,
+}
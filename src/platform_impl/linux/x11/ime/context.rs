@@ -1,10 +1,12 @@
-use std::ffi::CStr;
-use std::os::raw::c_short;
+use std::ffi::{CStr, CString};
+use std::ops::Range;
+use std::os::raw::{c_int, c_short};
 use std::sync::Arc;
-use std::{mem, ptr};
+use std::{iter, mem, ptr};
 
 use x11_dl::xlib::{XIMCallback, XIMPreeditCaretCallbackStruct, XIMPreeditDrawCallbackStruct};
 
+use crate::event::{PreeditSegment, PreeditStyle};
 use crate::platform_impl::platform::x11::ime::input_method::{Style, XIMStyle};
 use crate::platform_impl::platform::x11::ime::{ImeEvent, ImeEventSender};
 
@@ -41,6 +43,7 @@ extern "C" fn preedit_start_callback(
     let client_data = unsafe { &mut *(client_data as *mut ImeContextClientData) };
 
     client_data.text.clear();
+    client_data.feedback.clear();
     client_data.cursor_pos = 0;
     client_data
         .event_sender
@@ -59,6 +62,7 @@ extern "C" fn preedit_done_callback(
 
     // Drop text buffer and reset cursor position on done.
     client_data.text = Vec::new();
+    client_data.feedback = Vec::new();
     client_data.cursor_pos = 0;
 
     client_data
@@ -73,6 +77,47 @@ fn calc_byte_position(text: &[char], pos: usize) -> usize {
         .fold(0, |byte_pos, text| byte_pos + text.len_utf8())
 }
 
+/// The styled segments of the preedit, from the feedback of each of its characters.
+fn preedit_segments(text: &[char], feedback: &[ffi::XIMFeedback]) -> Vec<PreeditSegment> {
+    let mut segments: Vec<PreeditSegment> = Vec::new();
+    let mut byte_pos = 0;
+    for (chr, &feedback) in text.iter().zip(feedback) {
+        let range = byte_pos..byte_pos + chr.len_utf8();
+        byte_pos = range.end;
+
+        let style = PreeditStyle {
+            underline: feedback & ffi::XIMUnderline != 0,
+            highlight: feedback & ffi::XIMHighlight != 0,
+            reverse: feedback & ffi::XIMReverse != 0,
+            selected: feedback & ffi::XIMPrimary != 0,
+        };
+        if style == PreeditStyle::default() {
+            continue;
+        }
+
+        match segments.last_mut() {
+            Some(segment) if segment.style == style && segment.range.end == range.start => {
+                segment.range.end = range.end;
+            }
+            _ => segments.push(PreeditSegment { range, style }),
+        }
+    }
+    segments
+}
+
+/// Send the current preedit, with its cursor and styling.
+fn send_preedit_update(client_data: &ImeContextClientData) {
+    let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
+    let segments = preedit_segments(&client_data.text, &client_data.feedback);
+    client_data
+        .event_sender
+        .send((
+            client_data.window,
+            ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos, segments),
+        ))
+        .expect("failed to send preedit update event");
+}
+
 /// Preedit text information to be drawn inline by the client.
 extern "C" fn preedit_draw_callback(
     _xim: ffi::XIM,
@@ -96,39 +141,54 @@ extern "C" fn preedit_draw_callback(
     }
 
     // NULL indicate text deletion
-    let mut new_chars = if call_data.text.is_null() {
-        Vec::new()
+    let (mut new_chars, mut new_feedback) = if call_data.text.is_null() {
+        (Vec::new(), Vec::new())
     } else {
         let xim_text = unsafe { &mut *(call_data.text) };
         if xim_text.encoding_is_wchar > 0 {
             return;
         }
 
+        let feedback = if xim_text.feedback.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(xim_text.feedback, xim_text.length as usize) }
+        };
+
         let new_text = unsafe { xim_text.string.multi_byte };
 
+        // Without string, only the feedback of the changed range is updated.
         if new_text.is_null() {
+            for (old, &new) in client_data.feedback[chg_range.clone()]
+                .iter_mut()
+                .zip(feedback)
+            {
+                *old = new;
+            }
+            send_preedit_update(client_data);
             return;
         }
 
         let new_text = unsafe { CStr::from_ptr(new_text) };
 
-        String::from(new_text.to_str().expect("Invalid UTF-8 String from IME"))
-            .chars()
-            .collect()
+        let new_chars: Vec<char> =
+            String::from(new_text.to_str().expect("Invalid UTF-8 String from IME"))
+                .chars()
+                .collect();
+        let mut new_feedback = feedback.to_vec();
+        new_feedback.resize(new_chars.len(), 0);
+        (new_chars, new_feedback)
     };
     let mut old_text_tail = client_data.text.split_off(chg_range.end);
     client_data.text.truncate(chg_range.start);
     client_data.text.append(&mut new_chars);
     client_data.text.append(&mut old_text_tail);
-    let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
+    let mut old_feedback_tail = client_data.feedback.split_off(chg_range.end);
+    client_data.feedback.truncate(chg_range.start);
+    client_data.feedback.append(&mut new_feedback);
+    client_data.feedback.append(&mut old_feedback_tail);
 
-    client_data
-        .event_sender
-        .send((
-            client_data.window,
-            ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos),
-        ))
-        .expect("failed to send preedit update event");
+    send_preedit_update(client_data);
 }
 
 /// Handling of cursor movements in preedit text.
@@ -142,15 +202,87 @@ extern "C" fn preedit_caret_callback(
 
     if call_data.direction == ffi::XIMCaretDirection::XIMAbsolutePosition {
         client_data.cursor_pos = call_data.position as usize;
-        let cursor_byte_pos = calc_byte_position(&client_data.text, client_data.cursor_pos);
+        send_preedit_update(client_data);
+    }
+}
+
+/// The range of the surrounding text requested by the server, as the `factor` characters from the
+/// `position` away from the cursor in the `direction`.
+fn string_conversion_range(
+    text: &str,
+    cursor: usize,
+    position: usize,
+    direction: c_int,
+    factor: usize,
+) -> Range<usize> {
+    // The byte offsets of the characters, and of the end of the text.
+    let boundaries: Vec<usize> = text
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .chain(iter::once(text.len()))
+        .collect();
+    let last = boundaries.len() - 1;
+    let cursor = boundaries.binary_search(&cursor).unwrap_or_else(|idx| idx);
+
+    let (start, end) = if direction == ffi::XIMCaretDirection::XIMBackwardChar as c_int {
+        let end = cursor.saturating_sub(position);
+        (end.saturating_sub(factor), end)
+    } else if direction == ffi::XIMCaretDirection::XIMForwardChar as c_int {
+        let start = (cursor + position).min(last);
+        (start, (start + factor).min(last))
+    } else {
+        (cursor, cursor)
+    };
+    boundaries[start]..boundaries[end]
+}
 
+/// The server requests the surrounding text, to replace it when the operation is a substitution.
+extern "C" fn string_conversion_callback(
+    _xim: ffi::XIM,
+    client_data: ffi::XPointer,
+    call_data: ffi::XPointer,
+) {
+    let client_data = unsafe { &mut *(client_data as *mut ImeContextClientData) };
+    let call_data = unsafe { &mut *(call_data as *mut ffi::XIMStringConversionCallbackStruct) };
+
+    let (text, cursor) = client_data
+        .surrounding_text
+        .as_ref()
+        .map_or(("", 0), |(text, cursor)| (text.as_str(), *cursor));
+    let range = string_conversion_range(
+        text,
+        cursor,
+        call_data.position as usize,
+        call_data.direction,
+        call_data.factor as usize,
+    );
+
+    // The reply must stay alive after the callback, so it's kept in the client data.
+    let converted = CString::new(&text[range.clone()]).unwrap_or_default();
+    let length = converted.to_string_lossy().chars().count();
+    client_data.conversion_text = converted;
+    client_data.conversion_feedback = vec![0; length];
+    client_data.conversion = ffi::XIMStringConversionText {
+        length: length as _,
+        feedback: client_data.conversion_feedback.as_mut_ptr(),
+        encoding_is_wchar: ffi::False,
+        mbs: client_data.conversion_text.as_ptr() as *mut _,
+    };
+    call_data.text = &mut client_data.conversion;
+
+    // The server replaces the text with its commit, so the text adjacent to the cursor is deleted.
+    if call_data.operation == ffi::XIMStringConversionSubstitution && !range.is_empty() {
+        let event = if range.end == cursor {
+            ImeEvent::DeleteSurrounding(range.len(), 0)
+        } else if range.start == cursor {
+            ImeEvent::DeleteSurrounding(0, range.len())
+        } else {
+            return;
+        };
         client_data
             .event_sender
-            .send((
-                client_data.window,
-                ImeEvent::Update(client_data.text.iter().collect(), cursor_byte_pos),
-            ))
-            .expect("failed to send preedit update event");
+            .send((client_data.window, event))
+            .expect("failed to send delete surrounding event");
     }
 }
 
@@ -184,7 +316,15 @@ struct ImeContextClientData {
     window: ffi::Window,
     event_sender: ImeEventSender,
     text: Vec<char>,
+    /// The feedback of each character of the preedit.
+    feedback: Vec<ffi::XIMFeedback>,
     cursor_pos: usize,
+    /// The text around the cursor set by the application, with the byte offset of the cursor.
+    surrounding_text: Option<(String, usize)>,
+    /// The reply to the last string conversion, pointing to the text and feedback.
+    conversion: ffi::XIMStringConversionText,
+    conversion_text: CString,
+    conversion_feedback: Vec<u16>,
 }
 
 // XXX: this struct doesn't destroy its XIC resource when dropped.
@@ -197,7 +337,7 @@ pub struct ImeContext {
     pub(crate) style: Style,
     // Since the data is passed shared between X11 XIM callbacks, but couldn't be direclty free from
     // there we keep the pointer to automatically deallocate it.
-    _client_data: Box<ImeContextClientData>,
+    client_data: Box<ImeContextClientData>,
 }
 
 impl ImeContext {
@@ -213,7 +353,17 @@ impl ImeContext {
             window,
             event_sender,
             text: Vec::new(),
+            feedback: Vec::new(),
             cursor_pos: 0,
+            surrounding_text: None,
+            conversion: ffi::XIMStringConversionText {
+                length: 0,
+                feedback: ptr::null_mut(),
+                encoding_is_wchar: ffi::False,
+                mbs: ptr::null_mut(),
+            },
+            conversion_text: CString::default(),
+            conversion_feedback: Vec::new(),
         }));
 
         let ic = match style as _ {
@@ -237,11 +387,23 @@ impl ImeContext {
             .check_errors()
             .map_err(ImeContextCreationError::XError)?;
 
+        // The servers not supporting the string conversion ignore the callback.
+        let string_conversion_callback =
+            create_xim_callback(client_data as ffi::XPointer, string_conversion_callback);
+        unsafe {
+            (xconn.xlib.XSetICValues)(
+                ic,
+                ffi::XNStringConversionCallback_0.as_ptr() as *const _,
+                &string_conversion_callback as *const _,
+                ptr::null_mut::<()>(),
+            )
+        };
+
         let mut context = ImeContext {
             ic,
             ic_spot: ffi::XPoint { x: 0, y: 0 },
             style,
-            _client_data: unsafe { Box::from_raw(client_data) },
+            client_data: unsafe { Box::from_raw(client_data) },
         };
 
         // Set the spot location, if it's present.
@@ -350,6 +512,15 @@ impl ImeContext {
         !matches!(self.style, Style::None(_))
     }
 
+    /// Set the text around the cursor, provided to the server on its string conversions.
+    pub(crate) fn set_surrounding_text(&mut self, surrounding_text: Option<(String, usize)>) {
+        self.client_data.surrounding_text = surrounding_text;
+    }
+
+    pub(crate) fn surrounding_text(&self) -> Option<(String, usize)> {
+        self.client_data.surrounding_text.clone()
+    }
+
     // Set the spot for preedit text. Setting spot isn't working with libX11 when preedit callbacks
     // are being used. Certain IMEs do show selection window, but it's placed in bottom left of the
     // window and couldn't be changed.
@@ -383,3 +554,71 @@ impl ImeContext {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preedit_segments_merge_the_same_feedback() {
+        let text: Vec<char> = "漢字変換".chars().collect();
+        let feedback = [
+            ffi::XIMReverse | ffi::XIMPrimary,
+            ffi::XIMReverse | ffi::XIMPrimary,
+            ffi::XIMUnderline,
+            0,
+        ];
+        let selected = PreeditStyle {
+            reverse: true,
+            selected: true,
+            ..Default::default()
+        };
+        let underline = PreeditStyle {
+            underline: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            preedit_segments(&text, &feedback),
+            vec![
+                PreeditSegment {
+                    range: 0..6,
+                    style: selected
+                },
+                PreeditSegment {
+                    range: 6..9,
+                    style: underline
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn string_conversion_range_is_in_characters() {
+        let backward = ffi::XIMCaretDirection::XIMBackwardChar as c_int;
+        let forward = ffi::XIMCaretDirection::XIMForwardChar as c_int;
+        let text = "สวัสดี abc";
+        let cursor = "สวัสดี".len();
+
+        assert_eq!(
+            &text[string_conversion_range(text, cursor, 0, backward, 2)],
+            "ดี"
+        );
+        assert_eq!(
+            &text[string_conversion_range(text, cursor, 1, backward, 100)],
+            "สวัสด"
+        );
+        assert_eq!(
+            &text[string_conversion_range(text, cursor, 1, forward, 2)],
+            "ab"
+        );
+        assert_eq!(
+            string_conversion_range(text, text.len(), 0, forward, 2),
+            text.len()..text.len()
+        );
+        let word = ffi::XIMCaretDirection::XIMForwardWord as c_int;
+        assert_eq!(
+            string_conversion_range(text, cursor, 0, word, 1),
+            cursor..cursor
+        );
+    }
+}
@@ -15,6 +15,8 @@ use log::debug;
 use serde::{Deserialize, Serialize};
 
 use super::{ffi, util, XConnection, XError};
+use crate::event::PreeditSegment;
+use crate::window::{ImeHints, ImePurpose};
 
 pub use self::context::ImeContextCreationError;
 use self::{
@@ -29,8 +31,9 @@ use self::{
 pub enum ImeEvent {
     Enabled,
     Start,
-    Update(String, usize),
+    Update(String, usize, Vec<PreeditSegment>),
     End,
+    DeleteSurrounding(usize, usize),
     Disabled,
 }
 
@@ -40,12 +43,24 @@ pub type ImeEventReceiver = Receiver<(ffi::Window, ImeEvent)>;
 pub type ImeEventSender = Sender<(ffi::Window, ImeEvent)>;
 
 /// Request to control XIM handler from the window.
+///
+/// Some of the fields are only read by the input methods on D-Bus.
+#[cfg_attr(not(dbus_ime), allow(dead_code))]
 pub enum ImeRequest {
-    /// Set IME spot position for given `window_id`.
-    Position(ffi::Window, i16, i16),
+    /// Set IME cursor area, as its position and size, for given `window_id`.
+    ///
+    /// XIM only uses the position, as the spot of the cursor.
+    Area(ffi::Window, i16, i16, u16, u16),
 
     /// Allow IME input for the given `window_id`.
     Allow(ffi::Window, bool),
+
+    /// Set the text around the cursor, with the byte offsets of the cursor and the selection
+    /// anchor, for the given `window_id`.
+    SurroundingText(ffi::Window, String, usize, usize),
+
+    /// Set the purpose and the hints of the text for the given `window_id`, which XIM ignores.
+    ContentType(ffi::Window, ImePurpose, ImeHints),
 }
 
 #[derive(Debug)]
@@ -235,11 +250,31 @@ impl Ime {
             }
         }
 
+        // Keep the surrounding text of the previous context.
+        let surrounding_text = match self.inner.contexts.get(&window) {
+            Some(Some(context)) => context.surrounding_text(),
+            _ => None,
+        };
+
         // Remove context for that window.
         let _ = self.remove_context(window);
 
         // Create new context supporting IME input.
         let _ = self.create_context(window, allowed);
+
+        if let Some(&mut Some(ref mut context)) = self.inner.contexts.get_mut(&window) {
+            context.set_surrounding_text(surrounding_text);
+        }
+    }
+
+    pub fn set_surrounding_text(&mut self, window: ffi::Window, text: String, cursor: usize) {
+        if self.is_destroyed() {
+            return;
+        }
+
+        if let Some(&mut Some(ref mut context)) = self.inner.contexts.get_mut(&window) {
+            context.set_surrounding_text(Some((text, cursor)));
+        }
     }
 }
 
@@ -0,0 +1,188 @@
+//! Layer surfaces, as docks placed by the application against the edges of a monitor.
+
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::platform::layer_shell::{Layer, LayerAnchor, LayerShellAttributes};
+use crate::platform::x11::WindowType;
+use crate::window::WindowLevel;
+
+/// Place the layer surface of `size` against the edges of the monitor with the `bounds`.
+pub(crate) fn place_layer(
+    layer_shell: &LayerShellAttributes,
+    scale_factor: f64,
+    size: PhysicalSize<u32>,
+    bounds: (PhysicalPosition<i32>, PhysicalSize<u32>),
+) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let (top, right, bottom, left) = margin(layer_shell, scale_factor);
+    let anchor = layer_shell.anchor();
+    let (bounds_position, bounds_size) = bounds;
+
+    let (x, width) = place_on_axis(
+        (bounds_position.x, bounds_size.width),
+        size.width,
+        (left, right),
+        (
+            anchor.contains(LayerAnchor::LEFT),
+            anchor.contains(LayerAnchor::RIGHT),
+        ),
+    );
+    let (y, height) = place_on_axis(
+        (bounds_position.y, bounds_size.height),
+        size.height,
+        (top, bottom),
+        (
+            anchor.contains(LayerAnchor::TOP),
+            anchor.contains(LayerAnchor::BOTTOM),
+        ),
+    );
+
+    (
+        PhysicalPosition::new(x, y),
+        PhysicalSize::new(width, height),
+    )
+}
+
+/// Place the surface on one axis, within the start and length of the bounds, stretching it
+/// between the margins when it's anchored to both edges and centering it when it's anchored to
+/// neither.
+fn place_on_axis(
+    (bounds_start, bounds_length): (i32, u32),
+    length: u32,
+    (margin_start, margin_end): (i32, i32),
+    (anchored_start, anchored_end): (bool, bool),
+) -> (i32, u32) {
+    let bounds_end = bounds_start + bounds_length as i32;
+    match (anchored_start, anchored_end) {
+        (true, true) => {
+            let length = bounds_length as i32 - margin_start - margin_end;
+            (bounds_start + margin_start, length.max(1) as u32)
+        }
+        (true, false) => (bounds_start + margin_start, length),
+        (false, true) => (bounds_end - margin_end - length as i32, length),
+        (false, false) => (
+            bounds_start + (bounds_length as i32 - length as i32) / 2,
+            length,
+        ),
+    }
+}
+
+/// The `_NET_WM_STRUT_PARTIAL` reserving the exclusive zone of the surface placed at `position`
+/// with `size`, in the root window of `root_size`.
+///
+/// Returns `None` when the surface doesn't reserve an exclusive zone.
+pub(crate) fn strut_partial(
+    layer_shell: &LayerShellAttributes,
+    scale_factor: f64,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    root_size: PhysicalSize<u32>,
+) -> Option<[u32; 12]> {
+    let zone = layer_shell.exclusive_zone();
+    let edge = exclusive_edge(layer_shell.anchor()).filter(|_| zone > 0)?;
+    let zone = (zone as f64 * scale_factor).round() as i32;
+
+    // The struts are the distances from the edges of the root window, which include the margin
+    // the surface is placed at.
+    let (start_x, end_x) = (position.x, position.x + size.width as i32 - 1);
+    let (start_y, end_y) = (position.y, position.y + size.height as i32 - 1);
+    let mut strut = [0; 12];
+    let (distance, index) = match edge {
+        LayerAnchor::TOP => (start_y + zone, 2),
+        LayerAnchor::BOTTOM => (root_size.height as i32 - end_y - 1 + zone, 3),
+        LayerAnchor::LEFT => (start_x + zone, 0),
+        _ => (root_size.width as i32 - end_x - 1 + zone, 1),
+    };
+    let (start, end) = match index {
+        0 | 1 => (start_y, end_y),
+        _ => (start_x, end_x),
+    };
+    strut[index] = distance.max(0) as u32;
+    strut[4 + 2 * index] = start.max(0) as u32;
+    strut[5 + 2 * index] = end.max(0) as u32;
+
+    Some(strut)
+}
+
+/// The edge the exclusive zone is reserved along, when the surface is anchored to a single edge
+/// or to an edge and both of the perpendicular ones.
+fn exclusive_edge(anchor: LayerAnchor) -> Option<LayerAnchor> {
+    let horizontal = LayerAnchor::LEFT | LayerAnchor::RIGHT;
+    let vertical = LayerAnchor::TOP | LayerAnchor::BOTTOM;
+    [
+        LayerAnchor::TOP,
+        LayerAnchor::BOTTOM,
+        LayerAnchor::LEFT,
+        LayerAnchor::RIGHT,
+    ]
+    .into_iter()
+    .find(|&edge| {
+        let perpendicular = match vertical.contains(edge) {
+            true => horizontal,
+            false => vertical,
+        };
+        anchor == edge || anchor == edge | perpendicular
+    })
+}
+
+/// The top, right, bottom and left margins, in physical pixels.
+fn margin(layer_shell: &LayerShellAttributes, scale_factor: f64) -> (i32, i32, i32, i32) {
+    let scale = |margin: i32| (margin as f64 * scale_factor).round() as i32;
+    let (top, right, bottom, left) = layer_shell.margin();
+    (scale(top), scale(right), scale(bottom), scale(left))
+}
+
+/// The window type of the surface, a desktop window on the background layer and a dock otherwise.
+pub(crate) fn window_type(layer_shell: &LayerShellAttributes) -> WindowType {
+    match layer_shell.layer() {
+        Layer::Background => WindowType::Desktop,
+        _ => WindowType::Dock,
+    }
+}
+
+/// The level of the surface, the docks being kept above the regular windows by the window manager.
+pub(crate) fn window_level(layer_shell: &LayerShellAttributes) -> WindowLevel {
+    match layer_shell.layer() {
+        Layer::Bottom => WindowLevel::AlwaysOnBottom,
+        Layer::Overlay => WindowLevel::AlwaysOnTop,
+        Layer::Background | Layer::Top => WindowLevel::Normal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: (PhysicalPosition<i32>, PhysicalSize<u32>) = (
+        PhysicalPosition::new(1920, 0),
+        PhysicalSize::new(1280, 1024),
+    );
+
+    #[test]
+    fn dock_reserves_anchored_edge() {
+        let panel = LayerShellAttributes::new(Layer::Top)
+            .with_anchor(LayerAnchor::TOP | LayerAnchor::LEFT | LayerAnchor::RIGHT)
+            .with_exclusive_zone(30)
+            .with_margin(4, 0, 0, 0);
+        let (position, size) = place_layer(&panel, 1.0, PhysicalSize::new(100, 30), BOUNDS);
+        assert_eq!(position, PhysicalPosition::new(1920, 4));
+        assert_eq!(size, PhysicalSize::new(1280, 30));
+
+        let strut = strut_partial(&panel, 1.0, position, size, PhysicalSize::new(3200, 1080));
+        assert_eq!(strut, Some([0, 0, 34, 0, 0, 0, 0, 0, 1920, 3199, 0, 0]));
+
+        // Along the bottom of the monitor, which is above the bottom of the root window.
+        let dock = LayerShellAttributes::new(Layer::Top)
+            .with_anchor(LayerAnchor::BOTTOM)
+            .with_exclusive_zone(48);
+        let (position, size) = place_layer(&dock, 1.0, PhysicalSize::new(400, 48), BOUNDS);
+        assert_eq!(position, PhysicalPosition::new(2360, 976));
+        let strut = strut_partial(&dock, 1.0, position, size, PhysicalSize::new(3200, 1080));
+        assert_eq!(strut, Some([0, 0, 0, 104, 0, 0, 0, 0, 0, 0, 2360, 2759]));
+
+        // Anchored to opposite edges, without an exclusive zone.
+        let overlay = dock.with_anchor(LayerAnchor::LEFT | LayerAnchor::RIGHT);
+        assert_eq!(
+            strut_partial(&overlay, 1.0, position, size, PhysicalSize::new(3200, 1080)),
+            None
+        );
+    }
+}
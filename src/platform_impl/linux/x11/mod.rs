@@ -2,11 +2,16 @@
 
 mod activation;
 mod atoms;
+mod clipboard;
 mod dnd;
+mod drag_source;
 mod event_processor;
 pub mod ffi;
 mod ime;
+mod layer_shell;
 mod monitor;
+mod popup;
+mod tablet;
 pub mod util;
 mod window;
 mod xdisplay;
@@ -62,17 +67,34 @@ use x11rb::{
 
 pub(super) use self::util::CustomCursor;
 use self::{
-    dnd::{Dnd, DndState},
+    clipboard::Clipboard,
+    dnd::{Dnd, DropOffer},
+    drag_source::Drag,
     event_processor::EventProcessor,
     ime::{Ime, ImeCreationError, ImeReceiver, ImeRequest, ImeSender},
+    popup::PopupGrab,
+};
+#[cfg(dbus_ime)]
+use super::common::dbus_ime::{self, DBusIme};
+use super::{
+    common::{
+        keymap,
+        xkb_state::{KbdState, Layouts},
+    },
+    ControlFlow, OsError, PlatformSpecificEventLoopAttributes,
 };
-use super::{common::xkb_state::KbdState, ControlFlow, OsError};
 use crate::{
     error::{EventLoopError, OsError as RootOsError},
-    event::{Event, StartCause, WindowEvent},
+    event::{Event, KeyboardLayout, StartCause, WindowEvent},
     event_loop::{DeviceEvents, EventLoopClosed, EventLoopWindowTarget as RootELW},
-    platform::pump_events::PumpStatus,
-    platform_impl::platform::{min_timeout, WindowId},
+    platform::{
+        drag_and_drop::DragSource, pump_events::PumpStatus, synthetic_input::SyntheticInput,
+    },
+    platform_impl::{
+        click_counter::ClickCounter,
+        event_clock::EventClock,
+        platform::{min_timeout, WindowId},
+    },
     window::WindowAttributes,
 };
 
@@ -153,7 +175,21 @@ pub struct EventLoopWindowTarget {
     windows: RefCell<HashMap<WindowId, Weak<UnownedWindow>>>,
     redraw_sender: WakeSender<WindowId>,
     activation_sender: WakeSender<ActivationToken>,
+    synthetic_input_sender: WakeSender<(WindowId, SyntheticInput)>,
+    drag_sender: WakeSender<(WindowId, DragSource)>,
+    shortcuts_inhibit_sender: WakeSender<WindowId>,
+    /// The input method on D-Bus, used instead of XIM when it's running.
+    #[cfg(dbus_ime)]
+    dbus_ime: RefCell<Option<DBusIme>>,
     device_events: Cell<DeviceEvents>,
+    clipboard: Clipboard,
+    drag: RefCell<Option<Drag>>,
+    drop_offer: RefCell<Option<DropOffer>>,
+    popup_grabs: RefCell<Vec<PopupGrab>>,
+    /// The keyboard layouts, kept up to date by the event processor.
+    keyboard_layouts: RefCell<Layouts>,
+    /// Whether the server sends the touchpad gestures.
+    xinput_gestures: bool,
 }
 
 pub struct EventLoop<T: 'static> {
@@ -161,9 +197,16 @@ pub struct EventLoop<T: 'static> {
     event_loop: Loop<'static, EventLoopState>,
     waker: calloop::ping::Ping,
     event_processor: EventProcessor,
+    /// Clamps the timestamps of all the events delivered, shared with the `event_processor`.
+    event_clock: Rc<EventClock>,
     redraw_receiver: PeekableReceiver<WindowId>,
     user_receiver: PeekableReceiver<T>,
     activation_receiver: PeekableReceiver<ActivationToken>,
+    synthetic_input_receiver: PeekableReceiver<(WindowId, SyntheticInput)>,
+    drag_receiver: PeekableReceiver<(WindowId, DragSource)>,
+    shortcuts_inhibit_receiver: PeekableReceiver<WindowId>,
+    #[cfg(dbus_ime)]
+    dbus_ime_receiver: PeekableReceiver<(WindowId, crate::event::Ime)>,
     user_sender: Sender<T>,
     target: Rc<RootELW>,
 
@@ -191,7 +234,11 @@ impl<T: 'static> Clone for EventLoopProxy<T> {
 }
 
 impl<T: 'static> EventLoop<T> {
-    pub(crate) fn new(xconn: Arc<XConnection>) -> EventLoop<T> {
+    #[cfg_attr(not(dbus_ime), allow(unused_variables))]
+    pub(crate) fn new(
+        xconn: Arc<XConnection>,
+        attributes: &PlatformSpecificEventLoopAttributes,
+    ) -> EventLoop<T> {
         let root = xconn.default_root().root;
         let atoms = xconn.atoms();
 
@@ -249,13 +296,15 @@ impl<T: 'static> EventLoop<T> {
             .expect("Failed to query XKB extension")
             .expect("X server missing XKB extension");
 
-        // Check for XInput2 support.
-        xconn
+        // Check for XInput2 support, with the touchpad gestures of XInput 2.4.
+        let xinput_version = xconn
             .xcb_connection()
-            .xinput_xi_query_version(2, 3)
+            .xinput_xi_query_version(2, 4)
             .expect("Failed to send XInput2 query version request")
             .reply()
             .expect("Error while checking for XInput2 query version reply");
+        let xinput_gestures =
+            (xinput_version.major_version, xinput_version.minor_version) >= (2, 4);
 
         xconn.update_cached_wm_info(root);
 
@@ -296,8 +345,45 @@ impl<T: 'static> EventLoop<T> {
         // Create a channel for sending user events.
         let (user_sender, user_channel) = mpsc::channel();
 
+        // Create a channel for injecting synthetic input.
+        let (synthetic_input_sender, synthetic_input_channel) = mpsc::channel();
+
+        // Create a channel for starting the drags.
+        let (drag_sender, drag_channel) = mpsc::channel();
+
+        // Create a channel for updating the keyboard grabs inhibiting the shortcuts.
+        let (shortcuts_inhibit_sender, shortcuts_inhibit_channel) = mpsc::channel();
+
+        // Connect to the input method on D-Bus, falling back to XIM when it isn't running.
+        #[cfg(dbus_ime)]
+        let (dbus_ime_sender, dbus_ime_channel) = mpsc::channel();
+        #[cfg(dbus_ime)]
+        let dbus_ime = attributes.dbus_ime.and_then(|backend| {
+            let sender = WakeSender {
+                sender: dbus_ime_sender,
+                waker: waker.clone(),
+            };
+            let sink = move |window_id, event| {
+                let _ = sender.send((window_id, event));
+            };
+            DBusIme::new(backend, dbus_ime::Display::X11, sink)
+                .map_err(|err| warn!("Failed to connect to the input method on D-Bus: {err}"))
+                .ok()
+        });
+
+        // The XSETTINGS manager reports the changes of the double-click settings.
+        let xsettings_manager = xconn.watch_xsettings().unwrap_or_else(|err| {
+            warn!("Failed to watch the XSETTINGS: {err}");
+            None
+        });
+
+        let clipboard =
+            Clipboard::new(&xconn, root).expect("Failed to create the clipboard window");
+
         let kb_state =
             KbdState::from_x11_xkb(xconn.xcb_connection().get_raw_xcb_connection()).unwrap();
+        let mut keyboard_layouts = Layouts::default();
+        keyboard_layouts.update(&kb_state);
 
         let window_target = EventLoopWindowTarget {
             ime,
@@ -317,7 +403,27 @@ impl<T: 'static> EventLoop<T> {
                 sender: activation_token_sender, // not used again so no clone
                 waker: waker.clone(),
             },
+            synthetic_input_sender: WakeSender {
+                sender: synthetic_input_sender, // not used again so no clone
+                waker: waker.clone(),
+            },
+            drag_sender: WakeSender {
+                sender: drag_sender, // not used again so no clone
+                waker: waker.clone(),
+            },
+            shortcuts_inhibit_sender: WakeSender {
+                sender: shortcuts_inhibit_sender, // not used again so no clone
+                waker: waker.clone(),
+            },
+            #[cfg(dbus_ime)]
+            dbus_ime: RefCell::new(dbus_ime),
             device_events: Default::default(),
+            clipboard,
+            drag: RefCell::new(None),
+            drop_offer: RefCell::new(None),
+            popup_grabs: RefCell::new(Vec::new()),
+            keyboard_layouts: RefCell::new(keyboard_layouts),
+            xinput_gestures,
         };
 
         // Set initial device event filter.
@@ -328,7 +434,8 @@ impl<T: 'static> EventLoop<T> {
             _marker: PhantomData,
         });
 
-        let event_processor = EventProcessor {
+        let event_clock = Rc::new(EventClock::new());
+        let mut event_processor = EventProcessor {
             target: target.clone(),
             dnd,
             devices: Default::default(),
@@ -340,10 +447,16 @@ impl<T: 'static> EventLoop<T> {
             kb_state,
             num_touch: 0,
             held_key_press: None,
+            synthetic_keys: Default::default(),
+            event_clock: event_clock.clone(),
+            click_counter: ClickCounter::default(),
             first_touch: None,
             active_window: None,
             modifiers: Default::default(),
             is_composing: false,
+            tablet_tool: None,
+            pinch_scale: 1.0,
+            startup_tablet_tools: Vec::new(),
         };
 
         // Register for device hotplug events
@@ -367,15 +480,23 @@ impl<T: 'static> EventLoop<T> {
             )
             .unwrap();
 
-        event_processor.init_device(ALL_DEVICES);
+        event_processor.startup_tablet_tools = event_processor.init_device(ALL_DEVICES);
+        EventProcessor::click_settings(&get_xtarget(&target).xconn, xsettings_manager)
+            .apply(&mut event_processor.click_counter);
 
         EventLoop {
             loop_running: false,
             event_loop,
             waker,
             event_processor,
+            event_clock,
             redraw_receiver: PeekableReceiver::from_recv(redraw_channel),
             activation_receiver: PeekableReceiver::from_recv(activation_token_channel),
+            synthetic_input_receiver: PeekableReceiver::from_recv(synthetic_input_channel),
+            drag_receiver: PeekableReceiver::from_recv(drag_channel),
+            shortcuts_inhibit_receiver: PeekableReceiver::from_recv(shortcuts_inhibit_channel),
+            #[cfg(dbus_ime)]
+            dbus_ime_receiver: PeekableReceiver::from_recv(dbus_ime_channel),
             user_receiver: PeekableReceiver::from_recv(user_channel),
             user_sender,
             target,
@@ -460,9 +581,17 @@ impl<T: 'static> EventLoop<T> {
     }
 
     fn has_pending(&mut self) -> bool {
+        #[cfg(dbus_ime)]
+        if self.dbus_ime_receiver.has_incoming() {
+            return true;
+        }
+
         self.event_processor.poll()
             || self.user_receiver.has_incoming()
             || self.redraw_receiver.has_incoming()
+            || self.synthetic_input_receiver.has_incoming()
+            || self.drag_receiver.has_incoming()
+            || self.shortcuts_inhibit_receiver.has_incoming()
     }
 
     pub fn poll_events_with_timeout<F>(&mut self, mut timeout: Option<Duration>, mut callback: F)
@@ -547,6 +676,12 @@ impl<T: 'static> EventLoop<T> {
     where
         F: FnMut(Event<T>, &RootELW),
     {
+        // The events stamped with the time they were received at interleave with the ones
+        // stamped with the server time, so clamp them all on the way out.
+        let event_clock = self.event_clock.clone();
+        let callback =
+            &mut |event: Event<T>, target: &RootELW| callback(event_clock.stamp(event), target);
+
         callback(crate::event::Event::NewEvents(cause), &self.target);
 
         // NB: For consistency all platforms must emit a 'resumed' event even though X11
@@ -555,6 +690,10 @@ impl<T: 'static> EventLoop<T> {
             callback(crate::event::Event::Resumed, &self.target);
         }
 
+        let target = &self.target;
+        self.event_processor
+            .announce_startup_tablet_tools(|event| callback(event, target));
+
         // Process all pending events
         self.drain_events(callback);
 
@@ -574,6 +713,7 @@ impl<T: 'static> EventLoop<T> {
                             serial,
                             token: crate::window::ActivationToken::_new(token),
                         },
+                        timestamp: Instant::now(),
                     },
                     &self.target,
                 ),
@@ -584,6 +724,57 @@ impl<T: 'static> EventLoop<T> {
             }
         }
 
+        // Start the requested drags.
+        while let Ok((window_id, source)) = self.drag_receiver.try_recv() {
+            let wt = get_xtarget(&self.target);
+            for (window, event) in wt.start_drag(window_id.0 as xproto::Window, source) {
+                callback(
+                    Event::WindowEvent {
+                        window_id: mkwid(window),
+                        event,
+                        timestamp: Instant::now(),
+                    },
+                    &self.target,
+                );
+            }
+        }
+
+        // Update the keyboard grabs inhibiting the shortcuts. Releasing the grab because the
+        // application disabled the inhibition isn't reported.
+        while let Ok(window_id) = self.shortcuts_inhibit_receiver.try_recv() {
+            let inhibited = self
+                .event_processor
+                .with_window(window_id.0 as xproto::Window, |window| {
+                    window.update_keyboard_grab().filter(|&inhibited| {
+                        inhibited || window.shared_state_lock().shortcuts_inhibit
+                    })
+                })
+                .flatten();
+            if let Some(inhibited) = inhibited {
+                callback(
+                    Event::WindowEvent {
+                        window_id: crate::window::WindowId(window_id),
+                        event: WindowEvent::KeyboardShortcutsInhibited(inhibited),
+                        timestamp: Instant::now(),
+                    },
+                    &self.target,
+                );
+            }
+        }
+
+        // Send the events of the input method on D-Bus.
+        #[cfg(dbus_ime)]
+        while let Ok((window_id, event)) = self.dbus_ime_receiver.try_recv() {
+            callback(
+                Event::WindowEvent {
+                    window_id: crate::window::WindowId(window_id),
+                    event: WindowEvent::Ime(event),
+                    timestamp: Instant::now(),
+                },
+                &self.target,
+            );
+        }
+
         // Empty the user event buffer
         {
             while let Ok(event) = self.user_receiver.try_recv() {
@@ -605,6 +796,7 @@ impl<T: 'static> EventLoop<T> {
                     Event::WindowEvent {
                         window_id,
                         event: WindowEvent::RedrawRequested,
+                        timestamp: Instant::now(),
                     },
                     &self.target,
                 );
@@ -615,6 +807,9 @@ impl<T: 'static> EventLoop<T> {
         {
             callback(crate::event::Event::AboutToWait, &self.target);
         }
+
+        // The dropped data can't be read anymore.
+        get_xtarget(&self.target).finish_drop();
     }
 
     fn drain_events<F>(&mut self, callback: &mut F)
@@ -631,6 +826,7 @@ impl<T: 'static> EventLoop<T> {
                 if let Event::WindowEvent {
                     window_id: crate::window::WindowId(wid),
                     event: WindowEvent::RedrawRequested,
+                    ..
                 } = event
                 {
                     wt.redraw_sender.send(wid).unwrap();
@@ -639,6 +835,12 @@ impl<T: 'static> EventLoop<T> {
                 }
             });
         }
+
+        // The synthetic input is processed after the real one to preserve the ordering.
+        while let Ok((window_id, input)) = self.synthetic_input_receiver.try_recv() {
+            self.event_processor
+                .process_synthetic_input(window_id, input, |event| callback(event, target));
+        }
     }
 
     fn control_flow(&self) -> ControlFlow {
@@ -673,7 +875,6 @@ impl<T> AsRawFd for EventLoop<T> {
 pub(crate) fn get_xtarget(target: &RootELW) -> &EventLoopWindowTarget {
     match target.p {
         super::EventLoopWindowTarget::X(ref target) => target,
-        #[cfg(wayland_platform)]
         _ => unreachable!(),
     }
 }
@@ -685,6 +886,15 @@ impl EventLoopWindowTarget {
         &self.xconn
     }
 
+    /// Whether the IME goes through the input method on D-Bus instead of XIM.
+    #[inline]
+    pub(crate) fn uses_dbus_ime(&self) -> bool {
+        #[cfg(dbus_ime)]
+        return self.dbus_ime.borrow().is_some();
+        #[cfg(not(dbus_ime))]
+        false
+    }
+
     pub fn available_monitors(&self) -> impl Iterator<Item = MonitorHandle> {
         self.xconn.available_monitors().into_iter().flatten()
     }
@@ -716,6 +926,35 @@ impl EventLoopWindowTarget {
             .expect_then_ignore_error("Failed to update device event filter");
     }
 
+    /// Queue the synthetic input, which is processed during the next loop iteration.
+    pub(crate) fn inject_input(
+        &self,
+        window_id: WindowId,
+        input: SyntheticInput,
+    ) -> Result<(), OsError> {
+        if !self.windows.borrow().contains_key(&window_id) {
+            return Err(OsError::Misc("no such window"));
+        }
+
+        if let SyntheticInput::KeyboardInput { physical_key, .. } = input {
+            if keymap::physicalkey_to_scancode(physical_key).is_none() {
+                return Err(OsError::Misc("the key has no scancode"));
+            }
+        }
+
+        self.synthetic_input_sender
+            .send((window_id, input))
+            .map_err(|_| OsError::Misc("the event loop is closed"))
+    }
+
+    pub(crate) fn keyboard_layout(&self) -> Option<KeyboardLayout> {
+        self.keyboard_layouts.borrow().active.clone()
+    }
+
+    pub(crate) fn keyboard_layouts(&self) -> Vec<String> {
+        self.keyboard_layouts.borrow().names.clone()
+    }
+
     #[cfg(feature = "rwh_05")]
     pub fn raw_display_handle_rwh_05(&self) -> rwh_05::RawDisplayHandle {
         let mut display_handle = rwh_05::XlibDisplayHandle::empty();
@@ -824,6 +1063,16 @@ impl DeviceId {
     pub const unsafe fn dummy() -> Self {
         DeviceId(0)
     }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_raw(raw: xinput::DeviceId) -> Self {
+        DeviceId(raw)
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_raw(self) -> xinput::DeviceId {
+        self.0
+    }
 }
 
 pub(crate) struct Window(Arc<UnownedWindow>);
@@ -855,6 +1104,9 @@ impl Drop for Window {
         let window = self.deref();
         let xconn = &window.xconn;
 
+        // The screen saver is suspended for the lifetime of the window.
+        window.set_idle_inhibit(false);
+
         if let Ok(c) = xconn
             .xcb_connection()
             .destroy_window(window.id().0 as xproto::Window)
@@ -893,6 +1145,15 @@ pub enum X11Error {
 
     /// Could not find a matching X11 visual for this visualid
     NoSuchVisual(xproto::Visualid),
+
+    /// Another client took the ownership of the selection.
+    InvalidSelectionOwner,
+
+    /// The owner of the selection didn't respond in time.
+    SelectionTimeout,
+
+    /// The pointer couldn't be grabbed.
+    PointerGrab(xproto::GrabStatus),
 }
 
 impl fmt::Display for X11Error {
@@ -917,6 +1178,9 @@ impl fmt::Display for X11Error {
                     visualid
                 )
             }
+            X11Error::InvalidSelectionOwner => write!(f, "Failed to take the selection"),
+            X11Error::SelectionTimeout => write!(f, "The selection owner didn't respond in time"),
+            X11Error::PointerGrab(status) => write!(f, "Failed to grab the pointer: {:?}", status),
         }
     }
 }
@@ -1042,6 +1306,8 @@ fn mkdid(w: xinput::DeviceId) -> crate::event::DeviceId {
 struct Device {
     _name: String,
     scroll_axes: Vec<(i32, ScrollAxis)>,
+    // The axes of the tablet tools, set when the device is initialized.
+    tablet: Option<tablet::TabletAxes>,
     // For master devices, this is the paired device (pointer <-> keyboard).
     // For slave devices, this is the master.
     attachment: c_int,
@@ -1090,6 +1356,7 @@ impl Device {
         let mut device = Device {
             _name: name.into_owned(),
             scroll_axes,
+            tablet: None,
             attachment: info.attachment,
         };
         device.reset_scroll_position(info);
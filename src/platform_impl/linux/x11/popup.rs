@@ -0,0 +1,298 @@
+//! Popups, as override-redirect windows placed relative to their parent.
+
+use log::warn;
+use x11rb::protocol::xproto::{self, ConnectionExt};
+
+use super::{EventLoopWindowTarget, X11Error};
+use crate::dpi::{PhysicalPosition, PhysicalSize};
+use crate::platform::popup::{ConstraintAdjustment, PopupAnchor, PopupAttributes};
+
+/// A popup grabbing the input, until a click outside of it dismisses it.
+pub(crate) struct PopupGrab {
+    window: xproto::Window,
+    parent: xproto::Window,
+}
+
+/// Place the `popup` of `size` relative to its parent at `parent_position`, within the `bounds`
+/// of the monitor, following the constraint adjustments like `xdg_positioner`.
+pub(crate) fn place_popup(
+    popup: &PopupAttributes,
+    scale_factor: f64,
+    parent_position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    bounds: (PhysicalPosition<i32>, PhysicalSize<u32>),
+) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let anchor_position: PhysicalPosition<i32> = popup.anchor_position().to_physical(scale_factor);
+    let anchor_size: PhysicalSize<i32> = popup.anchor_size().to_physical(scale_factor);
+    let offset: PhysicalPosition<i32> = popup.offset().to_physical(scale_factor);
+    let anchor = direction(popup.anchor());
+    let gravity = direction(popup.gravity());
+    let adjustment = popup.constraint_adjustment();
+    let (bounds_position, bounds_size) = bounds;
+
+    let (x, width) = place_on_axis(
+        (parent_position.x + anchor_position.x, anchor_size.width),
+        (anchor.0, gravity.0),
+        offset.x,
+        size.width,
+        (bounds_position.x, bounds_size.width),
+        (
+            adjustment.contains(ConstraintAdjustment::FLIP_X),
+            adjustment.contains(ConstraintAdjustment::SLIDE_X),
+            adjustment.contains(ConstraintAdjustment::RESIZE_X),
+        ),
+    );
+    let (y, height) = place_on_axis(
+        (parent_position.y + anchor_position.y, anchor_size.height),
+        (anchor.1, gravity.1),
+        offset.y,
+        size.height,
+        (bounds_position.y, bounds_size.height),
+        (
+            adjustment.contains(ConstraintAdjustment::FLIP_Y),
+            adjustment.contains(ConstraintAdjustment::SLIDE_Y),
+            adjustment.contains(ConstraintAdjustment::RESIZE_Y),
+        ),
+    );
+
+    (
+        PhysicalPosition::new(x, y),
+        PhysicalSize::new(width, height),
+    )
+}
+
+/// The horizontal and vertical directions of the edge or corner, each being `-1`, `0` or `1`.
+fn direction(anchor: PopupAnchor) -> (i32, i32) {
+    match anchor {
+        PopupAnchor::Center => (0, 0),
+        PopupAnchor::Top => (0, -1),
+        PopupAnchor::Bottom => (0, 1),
+        PopupAnchor::Left => (-1, 0),
+        PopupAnchor::Right => (1, 0),
+        PopupAnchor::TopLeft => (-1, -1),
+        PopupAnchor::BottomLeft => (-1, 1),
+        PopupAnchor::TopRight => (1, -1),
+        PopupAnchor::BottomRight => (1, 1),
+    }
+}
+
+/// Place the popup on one axis, with the start and length of the anchor rectangle, the directions
+/// of the anchor and the gravity, and the start and length of the bounds.
+///
+/// The adjustments are flipping, sliding and resizing, tried in that order.
+fn place_on_axis(
+    (anchor_start, anchor_length): (i32, i32),
+    (anchor, gravity): (i32, i32),
+    offset: i32,
+    length: u32,
+    (bounds_start, bounds_length): (i32, u32),
+    (flip, slide, resize): (bool, bool, bool),
+) -> (i32, u32) {
+    let length = length as i32;
+    let bounds_end = bounds_start + bounds_length as i32;
+    let place = |anchor: i32, gravity: i32, offset: i32| {
+        let point = anchor_start + anchor_length * (anchor + 1) / 2;
+        point + offset - length * (1 - gravity) / 2
+    };
+    let fits = |start: i32| start >= bounds_start && start + length <= bounds_end;
+
+    let mut start = place(anchor, gravity, offset);
+    if fits(start) {
+        return (start, length as u32);
+    }
+
+    if flip {
+        let flipped = place(-anchor, -gravity, -offset);
+        if fits(flipped) {
+            return (flipped, length as u32);
+        }
+    }
+
+    if slide {
+        // Prefer showing the start of the popup when it doesn't fit at all.
+        start = start.min(bounds_end - length).max(bounds_start);
+        if fits(start) || !resize {
+            return (start, length as u32);
+        }
+    }
+
+    if resize {
+        let end = (start + length).min(bounds_end);
+        start = start.max(bounds_start);
+        if end > start {
+            return (start, (end - start) as u32);
+        }
+    }
+
+    (start, length as u32)
+}
+
+impl EventLoopWindowTarget {
+    /// Grab the input for the popup `window`, dismissing it on a click outside of it.
+    pub(super) fn grab_popup(&self, window: xproto::Window, parent: xproto::Window) {
+        let mut grabs = self.popup_grabs.borrow_mut();
+        if grabs.iter().any(|grab| grab.window == window) {
+            return;
+        }
+        grabs.push(PopupGrab { window, parent });
+        drop(grabs);
+        self.grab_popup_input(window);
+    }
+
+    /// Dismiss the popups a button press on the `window` at `position` was outside of.
+    ///
+    /// Returns the dismissed popups, from the top one.
+    pub(super) fn dismiss_popups(
+        &self,
+        window: xproto::Window,
+        position: PhysicalPosition<f64>,
+    ) -> Vec<xproto::Window> {
+        let mut grabs = self.popup_grabs.borrow_mut();
+        let index = match grabs.iter().position(|grab| grab.window == window) {
+            // The presses outside of the windows are reported to the grabbing popup.
+            Some(index) if self.window_contains(window, position) => index + 1,
+            // Presses on the parents dismiss the popups too.
+            _ => 0,
+        };
+        if index == grabs.len() {
+            return Vec::new();
+        }
+
+        let parent = grabs[index].parent;
+        let dismissed = grabs.drain(index..).rev().map(|grab| grab.window).collect();
+        drop(grabs);
+        self.restore_popup_grab(parent);
+        dismissed
+    }
+
+    /// Forget the popup `window` once it's hidden or destroyed, giving the input back to its
+    /// parent.
+    pub(super) fn remove_popup(&self, window: xproto::Window) {
+        let mut grabs = self.popup_grabs.borrow_mut();
+        if let Some(index) = grabs.iter().position(|grab| grab.window == window) {
+            let grab = grabs.remove(index);
+            drop(grabs);
+            self.restore_popup_grab(grab.parent);
+        }
+    }
+
+    fn window_contains(&self, window: xproto::Window, position: PhysicalPosition<f64>) -> bool {
+        match self.xconn.get_geometry(window) {
+            Ok(geometry) => {
+                (0.0..geometry.width as f64).contains(&position.x)
+                    && (0.0..geometry.height as f64).contains(&position.y)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Give the input to the top popup left, or back to the `parent` of the last one.
+    fn restore_popup_grab(&self, parent: xproto::Window) {
+        let top = self.popup_grabs.borrow().last().map(|grab| grab.window);
+        match top {
+            Some(window) => self.grab_popup_input(window),
+            None => {
+                let conn = self.xconn.xcb_connection();
+                let result = conn
+                    .ungrab_pointer(x11rb::CURRENT_TIME)
+                    .and_then(|_| {
+                        conn.set_input_focus(
+                            xproto::InputFocus::PARENT,
+                            parent,
+                            x11rb::CURRENT_TIME,
+                        )
+                    })
+                    .map(|_| ())
+                    .map_err(X11Error::from)
+                    .and_then(|_| self.xconn.flush_requests().map_err(Into::into));
+                if let Err(err) = result {
+                    warn!("Failed to release the input grabbed by the popup: {err}");
+                }
+            }
+        }
+    }
+
+    /// Focus the popup and grab the pointer, reporting the presses outside of the windows to it.
+    fn grab_popup_input(&self, window: xproto::Window) {
+        let conn = self.xconn.xcb_connection();
+        let result = conn
+            .set_input_focus(xproto::InputFocus::PARENT, window, x11rb::CURRENT_TIME)
+            .map_err(X11Error::from)
+            .and_then(|_| {
+                Ok(conn
+                    .grab_pointer(
+                        true,
+                        window,
+                        xproto::EventMask::BUTTON_PRESS
+                            | xproto::EventMask::BUTTON_RELEASE
+                            | xproto::EventMask::ENTER_WINDOW
+                            | xproto::EventMask::LEAVE_WINDOW
+                            | xproto::EventMask::POINTER_MOTION,
+                        xproto::GrabMode::ASYNC,
+                        xproto::GrabMode::ASYNC,
+                        x11rb::NONE,
+                        x11rb::NONE,
+                        x11rb::CURRENT_TIME,
+                    )?
+                    .reply()?)
+            });
+        match result {
+            Ok(reply) if reply.status == xproto::GrabStatus::SUCCESS => (),
+            Ok(reply) => warn!(
+                "Failed to grab the pointer for the popup: {:?}",
+                reply.status
+            ),
+            Err(err) => warn!("Failed to grab the pointer for the popup: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dpi::{LogicalPosition, LogicalSize};
+    use crate::window::WindowId;
+
+    const BOUNDS: (PhysicalPosition<i32>, PhysicalSize<u32>) =
+        (PhysicalPosition::new(0, 0), PhysicalSize::new(1000, 800));
+
+    fn place(popup: &PopupAttributes, size: (u32, u32)) -> ((i32, i32), (u32, u32)) {
+        let (position, size) = place_popup(
+            popup,
+            1.0,
+            PhysicalPosition::new(100, 100),
+            size.into(),
+            BOUNDS,
+        );
+        (position.into(), size.into())
+    }
+
+    #[test]
+    fn popup_is_placed_around_anchor() {
+        let parent = unsafe { WindowId::dummy() };
+        let button = |y| {
+            PopupAttributes::new(
+                parent,
+                LogicalPosition::new(10, y),
+                LogicalSize::new(80, 20),
+            )
+        };
+
+        // Below the button, from its bottom left corner.
+        assert_eq!(place(&button(10), (200, 300)), ((110, 130), (200, 300)));
+
+        // Flipped above the button near the bottom of the screen.
+        assert_eq!(place(&button(500), (200, 300)), ((110, 300), (200, 300)));
+
+        // Slid to the left near the right edge.
+        let popup = button(10).with_anchor(PopupAnchor::TopRight);
+        assert_eq!(place(&popup, (850, 300)), ((150, 110), (850, 300)));
+
+        // Centered on the button, and resized to the screen.
+        let popup = button(10)
+            .with_anchor(PopupAnchor::Center)
+            .with_gravity(PopupAnchor::Center)
+            .with_constraint_adjustment(ConstraintAdjustment::RESIZE_Y);
+        assert_eq!(place(&popup, (100, 1000)), ((100, 0), (100, 620)));
+    }
+}
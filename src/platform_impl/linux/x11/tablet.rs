@@ -0,0 +1,279 @@
+//! Tablet tools, as the XInput2 devices reporting the pressure of a pen.
+
+use std::ffi::CStr;
+
+use x11rb::protocol::xinput;
+use x11rb::protocol::xproto::ConnectionExt;
+
+use super::{ffi, Device, XConnection};
+use crate::dpi::PhysicalPosition;
+use crate::event::{
+    DeviceId, TabletButton, TabletToolCapabilities, TabletToolId, TabletToolInfo, TabletToolType,
+    WindowEvent,
+};
+
+/// The valuators of a tablet tool, identified by their labels.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TabletAxes {
+    eraser: bool,
+    pressure: Valuator,
+    tilt_x: Option<Valuator>,
+    tilt_y: Option<Valuator>,
+    wheel: Option<Valuator>,
+    distance: Option<Valuator>,
+}
+
+/// A valuator with its range and latest value, since the events only carry the changed ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Valuator {
+    number: i32,
+    min: f64,
+    max: f64,
+    value: f64,
+}
+
+impl Valuator {
+    /// The value in the range of the valuator, from `0.0` to `1.0`.
+    fn normalized(&self) -> f64 {
+        if self.max > self.min {
+            ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl TabletAxes {
+    /// The axes of the physical device, `None` when it isn't a tablet tool.
+    pub(crate) fn from_device(xconn: &XConnection, info: &ffi::XIDeviceInfo) -> Option<Self> {
+        if !Device::physical_device(info) {
+            return None;
+        }
+
+        let valuators: Vec<_> = Device::classes(info)
+            .iter()
+            .filter(|&&class_ptr| unsafe { (*class_ptr)._type } == ffi::XIValuatorClass)
+            .map(|&class_ptr| unsafe { &*(class_ptr as *const ffi::XIValuatorClassInfo) })
+            .filter(|valuator| valuator.label != 0)
+            .collect();
+
+        let conn = xconn.xcb_connection();
+        let cookies = valuators
+            .iter()
+            .map(|valuator| conn.get_atom_name(valuator.label as _))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        let labels = cookies
+            .into_iter()
+            .map(|cookie| Ok(String::from_utf8_lossy(&cookie.reply()?.name).into_owned()))
+            .collect::<Result<Vec<_>, x11rb::errors::ReplyError>>()
+            .ok()?;
+
+        let name = unsafe { CStr::from_ptr(info.name) }.to_string_lossy();
+        let valuators = valuators.iter().map(|valuator| Valuator {
+            number: valuator.number,
+            min: valuator.min,
+            max: valuator.max,
+            value: valuator.value,
+        });
+        Self::new(&name, labels.iter().map(String::as_str).zip(valuators))
+    }
+
+    /// The axes from the labeled valuators, `None` without a pressure valuator.
+    fn new<'a>(
+        name: &str,
+        valuators: impl IntoIterator<Item = (&'a str, Valuator)>,
+    ) -> Option<Self> {
+        let (mut pressure, mut tilt_x, mut tilt_y, mut wheel, mut distance) =
+            (None, None, None, None, None);
+        for (label, valuator) in valuators {
+            match label {
+                "Abs Pressure" => pressure = Some(valuator),
+                "Abs Tilt X" => tilt_x = Some(valuator),
+                "Abs Tilt Y" => tilt_y = Some(valuator),
+                "Abs Wheel" => wheel = Some(valuator),
+                "Abs Distance" => distance = Some(valuator),
+                _ => (),
+            }
+        }
+
+        Some(Self {
+            // The drivers add a device for the eraser end of the pens.
+            eraser: name.to_lowercase().contains("eraser"),
+            pressure: pressure?,
+            tilt_x,
+            tilt_y,
+            wheel,
+            distance,
+        })
+    }
+
+    /// Whether the tool is the eraser end of a pen.
+    pub(crate) fn inverted(&self) -> bool {
+        self.eraser
+    }
+
+    /// The description of the tool, with the axes it reports.
+    pub(crate) fn info(&self, id: TabletToolId) -> TabletToolInfo {
+        TabletToolInfo {
+            id,
+            tool_type: match self.eraser {
+                true => TabletToolType::Eraser,
+                false => TabletToolType::Pen,
+            },
+            serial: None,
+            hardware_id: None,
+            capabilities: TabletToolCapabilities {
+                pressure: true,
+                tilt: self.tilt_x.is_some() && self.tilt_y.is_some(),
+                rotation: self.wheel.is_some(),
+                distance: self.distance.is_some(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Update the valuator with the `number`, if it's one of the axes.
+    pub(crate) fn update(&mut self, number: i32, value: f64) {
+        let valuators = [
+            Some(&mut self.pressure),
+            self.tilt_x.as_mut(),
+            self.tilt_y.as_mut(),
+            self.wheel.as_mut(),
+            self.distance.as_mut(),
+        ];
+        for valuator in valuators.into_iter().flatten() {
+            if valuator.number == number {
+                valuator.value = value;
+            }
+        }
+    }
+
+    /// The motion of the tool at the `location` in the window, with the latest values of the axes.
+    pub(crate) fn motion(
+        &self,
+        device_id: DeviceId,
+        tool_id: TabletToolId,
+        location: PhysicalPosition<f64>,
+    ) -> WindowEvent {
+        // The drivers report the tilt in degrees.
+        let tilt = |valuator: Option<Valuator>| valuator.map_or(0.0, |valuator| valuator.value);
+        WindowEvent::TabletPenMotion {
+            device_id,
+            tool_id,
+            location,
+            pressure: self.pressure.normalized(),
+            rotation: self
+                .wheel
+                .map_or(0.0, |valuator| valuator.normalized() * 360.0),
+            distance: self.distance.map_or(0.0, |valuator| valuator.normalized()),
+            tilt: [tilt(self.tilt_x), tilt(self.tilt_y)],
+        }
+    }
+
+    /// The tablet button of the pointer `button` pressed with the tool.
+    pub(crate) fn button(&self, button: u32) -> Option<TabletButton> {
+        match button {
+            1 if self.eraser => Some(TabletButton::Eraser),
+            1 => Some(TabletButton::Tip),
+            // The buttons on the barrel, like `BTN_STYLUS` and `BTN_STYLUS2` on Wayland.
+            2 => Some(TabletButton::Pen(0)),
+            3 => Some(TabletButton::Pen(1)),
+            // The scroll wheel emulation.
+            4..=7 => None,
+            button => Some(TabletButton::Pen(button - 6)),
+        }
+    }
+}
+
+/// The tools of the tablet devices, ordered by the ids of the devices.
+///
+/// The id of a tool is the one of its device.
+pub(crate) fn tablet_tools<'a>(
+    devices: impl IntoIterator<Item = (xinput::DeviceId, Option<&'a TabletAxes>)>,
+) -> Vec<(xinput::DeviceId, TabletToolInfo)> {
+    let mut tools: Vec<_> = devices
+        .into_iter()
+        .filter_map(|(device, tablet)| Some((device, tablet?.info(device as TabletToolId))))
+        .collect();
+    tools.sort_by_key(|&(device, _)| device);
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valuator(number: i32, min: f64, max: f64) -> Valuator {
+        Valuator {
+            number,
+            min,
+            max,
+            value: min,
+        }
+    }
+
+    #[test]
+    fn pen_axes_are_normalized() {
+        let valuators = [
+            ("Abs X", valuator(0, 0.0, 44704.0)),
+            ("Abs Y", valuator(1, 0.0, 27940.0)),
+            ("Abs Pressure", valuator(2, 0.0, 2047.0)),
+            ("Abs Tilt X", valuator(3, -64.0, 63.0)),
+            ("Abs Tilt Y", valuator(4, -64.0, 63.0)),
+            ("Abs Wheel", valuator(5, -900.0, 899.0)),
+        ];
+        let mut pen = TabletAxes::new("Wacom Intuos Pro M Pen stylus", valuators).unwrap();
+        assert!(!pen.inverted());
+        let capabilities = pen.info(11).capabilities;
+        assert!(capabilities.tilt && capabilities.rotation && !capabilities.distance);
+        assert_eq!(pen.button(1), Some(TabletButton::Tip));
+        assert_eq!(pen.button(3), Some(TabletButton::Pen(1)));
+
+        pen.update(2, 2047.0);
+        pen.update(3, 30.0);
+        pen.update(4, -15.0);
+        pen.update(5, -0.5);
+        let device_id = unsafe { DeviceId::dummy() };
+        let motion = pen.motion(device_id, 11, PhysicalPosition::new(10.0, 20.0));
+        assert_eq!(
+            motion,
+            WindowEvent::TabletPenMotion {
+                device_id,
+                tool_id: 11,
+                location: PhysicalPosition::new(10.0, 20.0),
+                pressure: 1.0,
+                rotation: 180.0,
+                distance: 0.0,
+                tilt: [30.0, -15.0],
+            }
+        );
+
+        // The eraser end of the pen, and a mouse without pressure.
+        let eraser = [("Abs Pressure", valuator(2, 0.0, 2047.0))];
+        let eraser = TabletAxes::new("Wacom Intuos Pro M Pen eraser", eraser).unwrap();
+        assert!(eraser.inverted());
+        assert_eq!(eraser.info(12).tool_type, TabletToolType::Eraser);
+        assert_eq!(eraser.button(1), Some(TabletButton::Eraser));
+        assert_eq!(
+            TabletAxes::new(
+                "Logitech USB Receiver",
+                [("Rel X", valuator(0, -1.0, -1.0))]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn tablet_tools_of_devices() {
+        let pen = [("Abs Pressure", valuator(2, 0.0, 2047.0))];
+        let pen = TabletAxes::new("Wacom Intuos Pro M Pen stylus", pen).unwrap();
+        let eraser = [("Abs Pressure", valuator(2, 0.0, 2047.0))];
+        let eraser = TabletAxes::new("Wacom Intuos Pro M Pen eraser", eraser).unwrap();
+
+        // The devices found at startup, which are iterated in any order, along with a mouse.
+        let tools = tablet_tools([(14, Some(&eraser)), (9, None), (13, Some(&pen))]);
+        assert_eq!(tools, [(13, pen.info(13)), (14, eraser.info(14))]);
+        assert_eq!(tools[1].1.tool_type, TabletToolType::Eraser);
+    }
+}
@@ -158,6 +158,11 @@ impl CustomCursor {
             }
         }
     }
+
+    /// The XID of the cursor.
+    pub(crate) fn id(&self) -> ffi::Cursor {
+        self.inner.cursor
+    }
 }
 
 #[derive(Debug)]
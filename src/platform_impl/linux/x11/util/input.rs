@@ -14,19 +14,45 @@ pub const VIRTUAL_CORE_KEYBOARD: u16 = 3;
 // To test if `lookup_utf8` works correctly, set this to 1.
 const TEXT_BUFFER_SIZE: usize = 1024;
 
+/// Set the bits of the XInput `events` in the `mask`, adding the words they need.
+///
+/// The events from the 32nd on don't fit in a single [`xinput::XIEventMask`].
+pub fn add_xinput_events(
+    mask: &mut Vec<xinput::XIEventMask>,
+    events: impl IntoIterator<Item = c_int>,
+) {
+    for event in events {
+        let (word, bit) = (event as usize / 32, event as u32 % 32);
+        if mask.len() <= word {
+            mask.resize(word + 1, 0u32.into());
+        }
+        mask[word] = (u32::from(mask[word]) | 1 << bit).into();
+    }
+}
+
 impl XConnection {
     pub fn select_xinput_events(
         &self,
         window: xproto::Window,
         device_id: u16,
         mask: xinput::XIEventMask,
+    ) -> Result<VoidCookie<'_>, X11Error> {
+        self.select_xinput_event_masks(window, device_id, vec![mask])
+    }
+
+    /// Select the XInput events with a `mask` of several words, see [`add_xinput_events`].
+    pub fn select_xinput_event_masks(
+        &self,
+        window: xproto::Window,
+        device_id: u16,
+        mask: Vec<xinput::XIEventMask>,
     ) -> Result<VoidCookie<'_>, X11Error> {
         self.xcb_connection()
             .xinput_xi_select_events(
                 window,
                 &[xinput::EventMask {
                     deviceid: device_id,
-                    mask: vec![mask],
+                    mask,
                 }],
             )
             .map_err(Into::into)
@@ -109,3 +135,21 @@ impl XConnection {
         str::from_utf8(bytes).unwrap_or("").to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gesture_events_mask() {
+        let mut mask = vec![xinput::XIEventMask::MOTION];
+        add_xinput_events(
+            &mut mask,
+            ffi::XI_GesturePinchBegin..=ffi::XI_GestureSwipeEnd,
+        );
+        assert_eq!(
+            mask.into_iter().map(u32::from).collect::<Vec<_>>(),
+            [u32::from(xinput::XIEventMask::MOTION) | 0b1_1111 << 27, 1]
+        );
+    }
+}
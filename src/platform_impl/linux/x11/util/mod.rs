@@ -12,6 +12,7 @@ pub(crate) mod memory;
 mod randr;
 mod window_property;
 mod wm;
+mod xsettings;
 
 pub use self::{cursor::*, geometry::*, hint::*, input::*, window_property::*, wm::*};
 
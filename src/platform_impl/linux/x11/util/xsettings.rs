@@ -0,0 +1,207 @@
+//! Reading of the XSETTINGS, as described by the [specification].
+//!
+//! [specification]: https://specifications.freedesktop.org/xsettings-spec/0.5/
+
+use std::time::Duration;
+
+use super::*;
+use crate::platform_impl::platform::common::click_settings::ClickSettings;
+
+const SETTING_INTEGER: u8 = 0;
+const SETTING_STRING: u8 = 1;
+const SETTING_COLOR: u8 = 2;
+
+impl XConnection {
+    /// Find the XSETTINGS manager of the default screen, and select the changes of its settings,
+    /// which are reported as `PropertyNotify` of `_XSETTINGS_SETTINGS` on the returned window.
+    pub fn watch_xsettings(&self) -> Result<Option<xproto::Window>, X11Error> {
+        let selection = format!("_XSETTINGS_S{}", self.default_screen_index());
+        let selection = self
+            .xcb_connection()
+            .intern_atom(false, selection.as_bytes())?
+            .reply()?
+            .atom;
+
+        let owner = self
+            .xcb_connection()
+            .get_selection_owner(selection)?
+            .reply()?
+            .owner;
+        if owner == x11rb::NONE {
+            return Ok(None);
+        }
+
+        self.xcb_connection()
+            .change_window_attributes(
+                owner,
+                &xproto::ChangeWindowAttributesAux::new()
+                    .event_mask(xproto::EventMask::PROPERTY_CHANGE),
+            )?
+            .check()?;
+        Ok(Some(owner))
+    }
+
+    /// Read the double-click settings from the XSETTINGS `manager` window.
+    pub fn xsettings_click_settings(
+        &self,
+        manager: xproto::Window,
+    ) -> Result<ClickSettings, X11Error> {
+        let settings = self.atoms()[_XSETTINGS_SETTINGS];
+        let reply = self
+            .xcb_connection()
+            .get_property(false, manager, settings, settings, 0, u32::MAX / 4)?
+            .reply()?;
+        if reply.format != 8 {
+            return Ok(ClickSettings::default());
+        }
+
+        Ok(parse_click_settings(&reply.value).unwrap_or_default())
+    }
+}
+
+/// Parse the `Net/DoubleClickTime` and `Net/DoubleClickDistance` out of the `_XSETTINGS_SETTINGS`.
+fn parse_click_settings(data: &[u8]) -> Option<ClickSettings> {
+    let mut reader = Reader {
+        data,
+        big_endian: match *data.first()? {
+            0 => false,
+            1 => true,
+            _ => return None,
+        },
+    };
+
+    // The byte order, padding and serial.
+    reader.skip(8)?;
+    let count = reader.card32()?;
+
+    let mut settings = ClickSettings::default();
+    for _ in 0..count {
+        let kind = reader.card8()?;
+        reader.skip(1)?;
+        let name_len = reader.card16()? as usize;
+        let name = reader.bytes(name_len)?;
+        reader.skip(pad(name_len))?;
+        // The serial of the last change.
+        reader.skip(4)?;
+
+        match kind {
+            SETTING_INTEGER => {
+                let value = reader.card32()? as i32;
+                match name {
+                    b"Net/DoubleClickTime" if value > 0 => {
+                        settings.interval = Some(Duration::from_millis(value as u64));
+                    }
+                    b"Net/DoubleClickDistance" if value >= 0 => {
+                        settings.distance = Some(value as f64);
+                    }
+                    _ => (),
+                }
+            }
+            SETTING_STRING => {
+                let len = reader.card32()? as usize;
+                reader.skip(len)?;
+                reader.skip(pad(len))?;
+            }
+            SETTING_COLOR => reader.skip(8)?,
+            _ => return None,
+        }
+    }
+
+    Some(settings)
+}
+
+/// The padding to the 4 byte boundary.
+fn pad(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Option<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn card8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn card16(&mut self) -> Option<u16> {
+        let bytes = self.bytes(2)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn card32(&mut self) -> Option<u32> {
+        let bytes = self.bytes(4)?.try_into().ok()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(data: &mut Vec<u8>, kind: u8, name: &[u8], value: &[u8]) {
+        data.extend_from_slice(&[kind, 0]);
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name);
+        data.resize(data.len() + pad(name.len()), 0);
+        data.extend_from_slice(&7u32.to_le_bytes());
+        data.extend_from_slice(value);
+    }
+
+    #[test]
+    fn parse_settings() {
+        let mut data = vec![0, 0, 0, 0];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&4u32.to_le_bytes());
+
+        let mut theme = 5u32.to_le_bytes().to_vec();
+        theme.extend_from_slice(b"Theme\0\0\0");
+        setting(&mut data, SETTING_STRING, b"Net/ThemeName", &theme);
+        setting(&mut data, SETTING_COLOR, b"Gtk/Color", &[0; 8]);
+        setting(
+            &mut data,
+            SETTING_INTEGER,
+            b"Net/DoubleClickTime",
+            &300u32.to_le_bytes(),
+        );
+        setting(
+            &mut data,
+            SETTING_INTEGER,
+            b"Net/DoubleClickDistance",
+            &6u32.to_le_bytes(),
+        );
+
+        assert_eq!(
+            parse_click_settings(&data),
+            Some(ClickSettings {
+                interval: Some(Duration::from_millis(300)),
+                distance: Some(6.),
+            })
+        );
+
+        // Truncated data is rejected.
+        assert_eq!(parse_click_settings(&data[..data.len() - 2]), None);
+    }
+}
@@ -5,6 +5,7 @@ use std::{
     os::raw::*,
     path::Path,
     sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
 };
 
 use log::{debug, info, warn};
@@ -13,6 +14,7 @@ use x11rb::{
     properties::{WmHints, WmSizeHints, WmSizeHintsSpecification},
     protocol::{
         randr,
+        screensaver::ConnectionExt as _,
         shape::SK,
         xfixes::{ConnectionExt, RegionWrapper},
         xinput,
@@ -26,7 +28,7 @@ use crate::{
     error::{ExternalError, NotSupportedError, OsError as RootOsError},
     event::{Event, InnerSizeWriter, WindowEvent},
     event_loop::AsyncRequestSerial,
-    platform::x11::WindowType,
+    platform::{drag_and_drop::DragSource, layer_shell::KeyboardInteractivity, x11::WindowType},
     platform_impl::{
         x11::{
             atoms::*, xinput_fp1616_to_float, MonitorHandle as X11MonitorHandle, WakeSender,
@@ -36,13 +38,13 @@ use crate::{
         PlatformIcon, VideoModeHandle as PlatformVideoModeHandle,
     },
     window::{
-        CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType, WindowAttributes,
-        WindowButtons, WindowLevel,
+        CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+        WindowAttributes, WindowButtons, WindowLevel,
     },
 };
 
 use super::{
-    ffi,
+    ffi, layer_shell, popup,
     util::{self, SelectedCursor},
     CookieResultExt, EventLoopWindowTarget, ImeRequest, ImeSender, VoidCookie, WindowId,
     XConnection,
@@ -75,6 +77,14 @@ pub struct SharedState {
     pub has_focus: bool,
     // Use `Option` to not apply hittest logic when it was never requested.
     pub cursor_hittest: Option<bool>,
+    // Whether the window suspends the screen saver.
+    pub idle_inhibited: bool,
+    // Whether the window grabs the keyboard while it's focused, and whether it's grabbed.
+    pub shortcuts_inhibit: bool,
+    pub keyboard_grabbed: bool,
+    // The content type of the IME, which only the input methods on D-Bus use.
+    pub ime_purpose: ImePurpose,
+    pub ime_hints: ImeHints,
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -116,6 +126,11 @@ impl SharedState {
             base_size: None,
             has_focus: false,
             cursor_hittest: None,
+            idle_inhibited: false,
+            shortcuts_inhibit: false,
+            keyboard_grabbed: false,
+            ime_purpose: ImePurpose::default(),
+            ime_hints: ImeHints::default(),
         })
     }
 }
@@ -139,6 +154,10 @@ pub(crate) struct UnownedWindow {
     pub shared_state: Mutex<SharedState>,
     redraw_sender: WakeSender<WindowId>,
     activation_sender: WakeSender<super::ActivationToken>,
+    drag_sender: WakeSender<(WindowId, DragSource)>,
+    shortcuts_inhibit_sender: WakeSender<WindowId>,
+    /// The parent of the popup, when it grabs the input while it's mapped.
+    popup_grab_parent: Option<xproto::Window>, // never changes
 }
 
 macro_rules! leap {
@@ -188,6 +207,19 @@ impl UnownedWindow {
                 })
                 .unwrap_or_else(|| monitors.swap_remove(0))
         };
+        // Layer surfaces are placed on their monitor.
+        let layer_shell = window_attrs
+            .platform_specific
+            .layer_shell
+            .as_ref()
+            .filter(|_| window_attrs.platform_specific.popup.is_none());
+        let guessed_monitor = match layer_shell
+            .and_then(|layer_shell| layer_shell.monitor())
+            .map(|monitor| &monitor.inner)
+        {
+            Some(PlatformMonitorHandle::X(monitor)) => monitor.clone(),
+            _ => guessed_monitor,
+        };
         let scale_factor = guessed_monitor.scale_factor();
 
         info!("Guessed window scale factor: {}", scale_factor);
@@ -227,6 +259,48 @@ impl UnownedWindow {
             dimensions
         };
 
+        // Popups are placed relative to their parent, within the monitor of their anchor.
+        let popup = window_attrs.platform_specific.popup.as_ref();
+        let popup_parent = popup.map(|popup| u64::from(popup.parent().0) as xproto::Window);
+        let (position, dimensions) = match popup.zip(popup_parent) {
+            Some((popup, popup_parent)) => {
+                let origin = leap!(xconn.translate_coords(popup_parent, root));
+                let parent_position: PhysicalPosition<i32> =
+                    PhysicalPosition::new(origin.dst_x.into(), origin.dst_y.into());
+                let anchor: PhysicalPosition<i32> =
+                    popup.anchor_position().to_physical(scale_factor);
+                let (x, y) = (parent_position.x + anchor.x, parent_position.y + anchor.y);
+                let monitor = monitors
+                    .iter()
+                    .find(|monitor| monitor.rect.contains_point(x.into(), y.into()))
+                    .unwrap_or(&guessed_monitor);
+
+                let (position, size) = popup::place_popup(
+                    popup,
+                    scale_factor,
+                    parent_position,
+                    dimensions.into(),
+                    (monitor.position(), monitor.size()),
+                );
+                (Some(position), size.into())
+            }
+            None => (position, dimensions),
+        };
+
+        // Layer surfaces are placed against the edges of their monitor.
+        let (position, dimensions) = match layer_shell {
+            Some(layer_shell) => {
+                let (position, size) = layer_shell::place_layer(
+                    layer_shell,
+                    scale_factor,
+                    dimensions.into(),
+                    (guessed_monitor.position(), guessed_monitor.size()),
+                );
+                (Some(position), size.into())
+            }
+            None => (position, dimensions),
+        };
+
         let screen_id = match window_attrs.platform_specific.x11.screen_id {
             Some(id) => id,
             None => xconn.default_screen_index() as c_int,
@@ -293,7 +367,7 @@ impl UnownedWindow {
 
             aux = aux.event_mask(event_mask).border_pixel(0);
 
-            if window_attrs.platform_specific.x11.override_redirect {
+            if window_attrs.platform_specific.x11.override_redirect || popup.is_some() {
                 aux = aux.override_redirect(true as u32);
             }
 
@@ -374,6 +448,12 @@ impl UnownedWindow {
             shared_state: SharedState::new(guessed_monitor, &window_attrs),
             redraw_sender: event_loop.redraw_sender.clone(),
             activation_sender: event_loop.activation_sender.clone(),
+            drag_sender: event_loop.drag_sender.clone(),
+            shortcuts_inhibit_sender: event_loop.shortcuts_inhibit_sender.clone(),
+            popup_grab_parent: popup
+                .zip(popup_parent)
+                .filter(|(popup, _)| popup.grab())
+                .map(|(_, popup_parent)| popup_parent),
         };
 
         // Title must be set before mapping. Some tiling window managers (i.e. i3) use the window
@@ -441,8 +521,54 @@ impl UnownedWindow {
                 flusher.ignore_error()
             }
 
-            leap!(window.set_window_types(window_attrs.platform_specific.x11.x11_window_types))
+            // Popups are menus or tooltips, unless their type was set.
+            let mut window_types = window_attrs.platform_specific.x11.x11_window_types;
+            if let Some((popup, popup_parent)) = popup.zip(popup_parent) {
+                if window_types == [WindowType::Normal] {
+                    window_types = vec![match popup.grab() {
+                        true => WindowType::PopupMenu,
+                        false => WindowType::Tooltip,
+                    }];
+                }
+
+                leap!(xconn.change_property(
+                    window.xwindow,
+                    xproto::Atom::from(xproto::AtomEnum::WM_TRANSIENT_FOR),
+                    xproto::Atom::from(xproto::AtomEnum::WINDOW),
+                    xproto::PropMode::REPLACE,
+                    &[popup_parent],
+                ))
                 .ignore_error();
+            }
+
+            // Layer surfaces are docks reserving their exclusive zone, unless their type was set.
+            if let Some(layer_shell) = layer_shell {
+                if window_types == [WindowType::Normal] {
+                    window_types = vec![layer_shell::window_type(layer_shell)];
+                }
+
+                let root_size = leap!(xconn.get_geometry(root));
+                let root_size = PhysicalSize::new(root_size.width.into(), root_size.height.into());
+                if let Some(strut) = layer_shell::strut_partial(
+                    layer_shell,
+                    scale_factor,
+                    position.unwrap_or_default(),
+                    dimensions.into(),
+                    root_size,
+                ) {
+                    leap!(window.set_strut(strut)).ignore_error();
+                }
+
+                if layer_shell.keyboard_interactivity() == KeyboardInteractivity::None {
+                    let wm_hints = WmHints {
+                        input: Some(false),
+                        ..Default::default()
+                    };
+                    leap!(wm_hints.set(xconn.xcb_connection(), window.xwindow)).ignore_error();
+                }
+            }
+
+            leap!(window.set_window_types(window_types)).ignore_error();
 
             // Set size hints.
             let mut min_inner_size = window_attrs
@@ -542,20 +668,30 @@ impl UnownedWindow {
             }
 
             // Select XInput2 events
-            let mask = xinput::XIEventMask::MOTION
-                | xinput::XIEventMask::BUTTON_PRESS
-                | xinput::XIEventMask::BUTTON_RELEASE
-                | xinput::XIEventMask::ENTER
-                | xinput::XIEventMask::LEAVE
-                | xinput::XIEventMask::FOCUS_IN
-                | xinput::XIEventMask::FOCUS_OUT
-                | xinput::XIEventMask::TOUCH_BEGIN
-                | xinput::XIEventMask::TOUCH_UPDATE
-                | xinput::XIEventMask::TOUCH_END;
-            leap!(xconn.select_xinput_events(window.xwindow, super::ALL_MASTER_DEVICES, mask))
+            let mut mask = vec![
+                xinput::XIEventMask::MOTION
+                    | xinput::XIEventMask::BUTTON_PRESS
+                    | xinput::XIEventMask::BUTTON_RELEASE
+                    | xinput::XIEventMask::ENTER
+                    | xinput::XIEventMask::LEAVE
+                    | xinput::XIEventMask::FOCUS_IN
+                    | xinput::XIEventMask::FOCUS_OUT
+                    | xinput::XIEventMask::TOUCH_BEGIN
+                    | xinput::XIEventMask::TOUCH_UPDATE
+                    | xinput::XIEventMask::TOUCH_END,
+            ];
+            if event_loop.xinput_gestures {
+                // Selecting them fails with the servers older than XInput 2.4.
+                util::add_xinput_events(
+                    &mut mask,
+                    ffi::XI_GesturePinchBegin..=ffi::XI_GestureSwipeEnd,
+                );
+            }
+            leap!(xconn.select_xinput_event_masks(window.xwindow, super::ALL_MASTER_DEVICES, mask))
                 .ignore_error();
 
-            {
+            // The input method on D-Bus gets the keys instead of XIM.
+            if !event_loop.uses_dbus_ime() {
                 let result = event_loop
                     .ime
                     .borrow_mut()
@@ -582,7 +718,11 @@ impl UnownedWindow {
                 }
             }
 
-            leap!(window.set_window_level_inner(window_attrs.window_level)).ignore_error();
+            let window_level = match layer_shell {
+                Some(layer_shell) => layer_shell::window_level(layer_shell),
+                None => window_attrs.window_level,
+            };
+            leap!(window.set_window_level_inner(window_level)).ignore_error();
         }
 
         window.set_cursor(window_attrs.cursor);
@@ -662,6 +802,28 @@ impl UnownedWindow {
         )
     }
 
+    /// Reserve the exclusive zone of the dock, with both `_NET_WM_STRUT_PARTIAL` and the older
+    /// `_NET_WM_STRUT`.
+    fn set_strut(&self, strut: [u32; 12]) -> Result<VoidCookie<'_>, X11Error> {
+        let atoms = self.xconn.atoms();
+        self.xconn
+            .change_property(
+                self.xwindow,
+                atoms[_NET_WM_STRUT],
+                xproto::Atom::from(xproto::AtomEnum::CARDINAL),
+                xproto::PropMode::REPLACE,
+                &strut[..4],
+            )?
+            .ignore_error();
+        self.xconn.change_property(
+            self.xwindow,
+            atoms[_NET_WM_STRUT_PARTIAL],
+            xproto::Atom::from(xproto::AtomEnum::CARDINAL),
+            xproto::PropMode::REPLACE,
+            &strut,
+        )
+    }
+
     pub fn set_theme_inner(&self, theme: Option<Theme>) -> Result<VoidCookie<'_>, X11Error> {
         let atoms = self.xconn.atoms();
         let hint_atom = atoms[_GTK_THEME_VARIANT];
@@ -809,7 +971,6 @@ impl UnownedWindow {
                     Fullscreen::Borderless(None) => {
                         (None, self.shared_state_lock().last_monitor.clone())
                     }
-                    #[cfg(wayland_platform)]
                     _ => unreachable!(),
                 };
 
@@ -974,6 +1135,7 @@ impl UnownedWindow {
                     scale_factor: new_monitor.scale_factor,
                     inner_size_writer: InnerSizeWriter::new(Arc::downgrade(&inner_size)),
                 },
+                timestamp: Instant::now(),
             });
 
             let new_inner_size = *inner_size.lock().unwrap();
@@ -1103,6 +1265,89 @@ impl UnownedWindow {
     #[inline]
     pub fn set_blur(&self, _blur: bool) {}
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) {
+        let mut shared_state = self.shared_state_lock();
+        if shared_state.idle_inhibited == inhibit {
+            return;
+        }
+
+        // The server counts the suspensions of each client, so they're undone one by one.
+        match self
+            .xconn
+            .xcb_connection()
+            .screensaver_suspend(inhibit as u32)
+        {
+            Ok(cookie) => {
+                cookie.ignore_error();
+                shared_state.idle_inhibited = inhibit;
+            }
+            Err(err) => warn!("Failed to suspend the screen saver: {err}"),
+        }
+    }
+
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, inhibit: bool) {
+        self.shared_state_lock().shortcuts_inhibit = inhibit;
+        // The event loop updates the grab, since it reports the result.
+        if self.shortcuts_inhibit_sender.send(self.id()).is_err() {
+            warn!("Failed to inhibit the keyboard shortcuts: the event loop is closed");
+        }
+    }
+
+    /// Grab the keyboard while the window is focused and inhibits the shortcuts, and release it
+    /// otherwise.
+    ///
+    /// Returns whether the keyboard is grabbed, when it changed or when the grab failed. A grab
+    /// that fails, e.g. because another client holds the keyboard, is tried again on the next
+    /// focus change.
+    pub(crate) fn update_keyboard_grab(&self) -> Option<bool> {
+        let mut shared_state = self.shared_state_lock();
+        let grab = shared_state.shortcuts_inhibit && shared_state.has_focus;
+        if shared_state.keyboard_grabbed == grab {
+            return None;
+        }
+
+        let conn = self.xconn.xcb_connection();
+        if !grab {
+            shared_state.keyboard_grabbed = false;
+            let result = conn
+                .ungrab_keyboard(x11rb::CURRENT_TIME)
+                .map_err(X11Error::from)
+                .and_then(|_| self.xconn.flush_requests().map_err(Into::into));
+            if let Err(err) = result {
+                warn!("Failed to release the keyboard grab: {err}");
+            }
+            return Some(false);
+        }
+
+        // The key events are still reported to the windows of the application.
+        let result = conn
+            .grab_keyboard(
+                true,
+                self.xwindow,
+                x11rb::CURRENT_TIME,
+                xproto::GrabMode::ASYNC,
+                xproto::GrabMode::ASYNC,
+            )
+            .map_err(X11Error::from)
+            .and_then(|cookie| Ok(cookie.reply()?));
+        match result {
+            Ok(reply) if reply.status == xproto::GrabStatus::SUCCESS => {
+                shared_state.keyboard_grabbed = true;
+                Some(true)
+            }
+            Ok(reply) => {
+                warn!("Failed to grab the keyboard: {:?}", reply.status);
+                Some(false)
+            }
+            Err(err) => {
+                warn!("Failed to grab the keyboard: {err}");
+                Some(false)
+            }
+        }
+    }
+
     fn set_decorations_inner(&self, decorations: bool) -> Result<VoidCookie<'_>, X11Error> {
         self.shared_state_lock().is_decorated = decorations;
         let mut hints = self.xconn.get_motif_hints(self.xwindow);
@@ -1571,10 +1816,9 @@ impl UnownedWindow {
 
                 *self.selected_cursor.lock().unwrap() = SelectedCursor::Custom(cursor);
             }
-            #[cfg(wayland_platform)]
-            Cursor::Custom(RootCustomCursor {
-                inner: PlatformCustomCursor::Wayland(_),
-            }) => log::error!("passed a Wayland cursor to X11 backend"),
+            Cursor::Custom(RootCustomCursor { inner: _ }) => {
+                log::error!("passed a foreign cursor to X11 backend")
+            }
         }
     }
 
@@ -1805,12 +2049,16 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_ime_cursor_area(&self, spot: Position, _size: Size) {
-        let (x, y) = spot.to_physical::<i32>(self.scale_factor()).into();
-        let _ = self.ime_sender.lock().unwrap().send(ImeRequest::Position(
+    pub fn set_ime_cursor_area(&self, spot: Position, size: Size) {
+        let scale_factor = self.scale_factor();
+        let (x, y) = spot.to_physical::<i32>(scale_factor).into();
+        let (width, height) = size.to_physical::<u32>(scale_factor).into();
+        let _ = self.ime_sender.lock().unwrap().send(ImeRequest::Area(
             self.xwindow as ffi::Window,
             x,
             y,
+            width,
+            height,
         ));
     }
 
@@ -1824,7 +2072,55 @@ impl UnownedWindow {
     }
 
     #[inline]
-    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        let hints = {
+            let mut shared_state = self.shared_state_lock();
+            shared_state.ime_purpose = purpose;
+            shared_state.ime_hints
+        };
+        self.send_ime_content_type(purpose, hints);
+    }
+
+    #[inline]
+    pub fn set_ime_hints(&self, hints: ImeHints) {
+        let purpose = {
+            let mut shared_state = self.shared_state_lock();
+            shared_state.ime_hints = hints;
+            shared_state.ime_purpose
+        };
+        self.send_ime_content_type(purpose, hints);
+    }
+
+    fn send_ime_content_type(&self, purpose: ImePurpose, hints: ImeHints) {
+        let _ = self
+            .ime_sender
+            .lock()
+            .unwrap()
+            .send(ImeRequest::ContentType(
+                self.xwindow as ffi::Window,
+                purpose,
+                hints,
+            ));
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        if !text.is_char_boundary(cursor) || !text.is_char_boundary(anchor) {
+            warn!("The IME cursor and anchor must be on character boundaries of the text");
+            return;
+        }
+
+        let _ = self
+            .ime_sender
+            .lock()
+            .unwrap()
+            .send(ImeRequest::SurroundingText(
+                self.xwindow as ffi::Window,
+                text,
+                cursor,
+                anchor,
+            ));
+    }
 
     #[inline]
     pub fn focus_window(&self) {
@@ -1909,6 +2205,13 @@ impl UnownedWindow {
         Ok(serial)
     }
 
+    #[inline]
+    pub fn start_drag(&self, source: DragSource) -> Result<(), ExternalError> {
+        self.drag_sender
+            .send((self.id(), source))
+            .map_err(|_| ExternalError::Os(os_error!(OsError::Misc("the event loop is closed"))))
+    }
+
     #[inline]
     pub fn id(&self) -> WindowId {
         WindowId(self.xwindow as _)
@@ -1985,6 +2288,11 @@ impl UnownedWindow {
     pub fn set_content_protected(&self, _protected: bool) {}
 
     #[inline]
+    /// The parent of the popup, when it grabs the input while it's mapped.
+    pub(crate) fn popup_grab_parent(&self) -> Option<xproto::Window> {
+        self.popup_grab_parent
+    }
+
     pub fn has_focus(&self) -> bool {
         self.shared_state_lock().has_focus
     }
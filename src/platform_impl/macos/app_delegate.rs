@@ -7,7 +7,7 @@ use std::sync::{mpsc, Arc, Mutex};
 use std::time::Instant;
 
 use icrate::AppKit::{NSApplication, NSApplicationActivationPolicy, NSApplicationDelegate};
-use icrate::Foundation::{MainThreadMarker, NSObject, NSObjectProtocol, NSSize};
+use icrate::Foundation::{MainThreadMarker, NSObject, NSObjectProtocol, NSProcessInfo, NSSize};
 use objc2::rc::Id;
 use objc2::runtime::AnyObject;
 use objc2::{declare_class, msg_send_id, mutability, ClassType, DeclaredClass};
@@ -20,6 +20,7 @@ use super::{menu, WindowId, DEVICE_ID};
 use crate::dpi::PhysicalSize;
 use crate::event::{DeviceEvent, Event, InnerSizeWriter, StartCause, WindowEvent};
 use crate::event_loop::{ControlFlow, EventLoopWindowTarget as RootWindowTarget};
+use crate::platform_impl::event_clock::{self, EventClock};
 use crate::window::WindowId as RootWindowId;
 
 #[derive(Debug, Default)]
@@ -47,6 +48,8 @@ pub(super) struct State {
     wait_timeout: Cell<Option<Instant>>,
     pending_events: RefCell<VecDeque<QueuedEvent>>,
     pending_redraw: RefCell<Vec<WindowId>>,
+    /// Clamps the timestamps of all the events delivered.
+    event_clock: EventClock,
 }
 
 declare_class!(
@@ -259,14 +262,32 @@ impl ApplicationDelegate {
         self.ivars()
             .pending_events
             .borrow_mut()
-            .push_back(QueuedEvent::WindowEvent(window_id, event));
+            .push_back(QueuedEvent::WindowEvent(
+                window_id,
+                event,
+                self.event_timestamp(),
+            ));
     }
 
     pub fn queue_device_event(&self, event: DeviceEvent) {
         self.ivars()
             .pending_events
             .borrow_mut()
-            .push_back(QueuedEvent::DeviceEvent(event));
+            .push_back(QueuedEvent::DeviceEvent(event, self.event_timestamp()));
+    }
+
+    /// The time the event being handled by the application was generated at, converted from
+    /// `NSEvent.timestamp`, which counts the seconds since the system booted.
+    fn event_timestamp(&self) -> Instant {
+        let mtm = MainThreadMarker::from(self);
+        match NSApplication::sharedApplication(mtm).currentEvent() {
+            Some(event) => {
+                let age =
+                    unsafe { NSProcessInfo::processInfo().systemUptime() - event.timestamp() };
+                event_clock::instant_from_age(age)
+            }
+            None => Instant::now(),
+        }
     }
 
     pub fn queue_static_scale_factor_changed_event(
@@ -293,6 +314,7 @@ impl ApplicationDelegate {
             self.handle_nonuser_event(Event::WindowEvent {
                 window_id: RootWindowId(window_id),
                 event: WindowEvent::RedrawRequested,
+                timestamp: Instant::now(),
             });
             self.ivars().in_callback.set(false);
 
@@ -315,7 +337,7 @@ impl ApplicationDelegate {
 
     fn handle_nonuser_event(&self, event: Event<Never>) {
         if let Some(ref mut callback) = *self.ivars().callback.borrow_mut() {
-            callback.handle_nonuser_event(event)
+            callback.handle_nonuser_event(self.ivars().event_clock.stamp(event))
         }
     }
 
@@ -403,16 +425,18 @@ impl ApplicationDelegate {
         let events = mem::take(&mut *self.ivars().pending_events.borrow_mut());
         for event in events {
             match event {
-                QueuedEvent::WindowEvent(window_id, event) => {
+                QueuedEvent::WindowEvent(window_id, event, timestamp) => {
                     self.handle_nonuser_event(Event::WindowEvent {
                         window_id: RootWindowId(window_id),
                         event,
+                        timestamp,
                     });
                 }
-                QueuedEvent::DeviceEvent(event) => {
+                QueuedEvent::DeviceEvent(event, timestamp) => {
                     self.handle_nonuser_event(Event::DeviceEvent {
                         device_id: DEVICE_ID,
                         event,
+                        timestamp,
                     });
                 }
                 QueuedEvent::ScaleFactorChanged {
@@ -430,9 +454,12 @@ impl ApplicationDelegate {
                                     &new_inner_size,
                                 )),
                             },
+                            timestamp: Instant::now(),
                         };
 
-                        callback.handle_nonuser_event(scale_factor_changed_event);
+                        callback.handle_nonuser_event(
+                            self.ivars().event_clock.stamp(scale_factor_changed_event),
+                        );
 
                         let physical_size = *new_inner_size.lock().unwrap();
                         drop(new_inner_size);
@@ -443,8 +470,10 @@ impl ApplicationDelegate {
                         let resized_event = Event::WindowEvent {
                             window_id: RootWindowId(window.id()),
                             event: WindowEvent::Resized(physical_size),
+                            timestamp: Instant::now(),
                         };
-                        callback.handle_nonuser_event(resized_event);
+                        callback
+                            .handle_nonuser_event(self.ivars().event_clock.stamp(resized_event));
                     }
                 }
             }
@@ -455,6 +484,7 @@ impl ApplicationDelegate {
             self.handle_nonuser_event(Event::WindowEvent {
                 window_id: RootWindowId(window_id),
                 event: WindowEvent::RedrawRequested,
+                timestamp: Instant::now(),
             });
         }
 
@@ -486,8 +516,9 @@ impl ApplicationDelegate {
 
 #[derive(Debug)]
 pub(crate) enum QueuedEvent {
-    WindowEvent(WindowId, WindowEvent),
-    DeviceEvent(DeviceEvent),
+    /// The window and device events are queued with the time they were generated at.
+    WindowEvent(WindowId, WindowEvent, Instant),
+    DeviceEvent(DeviceEvent, Instant),
     ScaleFactorChanged {
         window: Id<WinitWindow>,
         suggested_size: PhysicalSize<u32>,
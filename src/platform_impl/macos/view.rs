@@ -1020,10 +1020,14 @@ impl WinitView {
 
         self.update_modifiers(event, false);
 
+        // AppKit counts the clicks with the system settings, both on the press and the release.
+        let click_count = unsafe { event.clickCount() }.max(1) as u32;
+
         self.queue_event(WindowEvent::MouseInput {
             device_id: DEVICE_ID,
             state: button_state,
             button,
+            click_count,
         });
     }
 
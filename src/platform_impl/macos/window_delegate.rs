@@ -39,7 +39,7 @@ use crate::error::{ExternalError, NotSupportedError, OsError as RootOsError};
 use crate::event::WindowEvent;
 use crate::platform::macos::{OptionAsAlt, WindowExtMacOS};
 use crate::window::{
-    Cursor, CursorGrabMode, Icon, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+    Cursor, CursorGrabMode, Icon, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
     WindowAttributes, WindowButtons, WindowLevel,
 };
 
@@ -828,6 +828,10 @@ impl WindowDelegate {
         }
     }
 
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {}
+
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {}
+
     pub fn set_visible(&self, visible: bool) {
         match visible {
             true => self.window().makeKeyAndOrderFront(None),
@@ -1517,6 +1521,12 @@ impl WindowDelegate {
     #[inline]
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    #[inline]
+    pub fn set_ime_hints(&self, _hints: ImeHints) {}
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn focus_window(&self) {
         let mtm = MainThreadMarker::from(self);
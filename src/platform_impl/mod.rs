@@ -25,6 +25,23 @@ mod platform;
 
 pub use self::platform::*;
 
+#[cfg(any(
+    windows_platform,
+    x11_platform,
+    wayland_platform,
+    web_platform,
+    orbital_platform
+))]
+pub(crate) mod click_counter;
+#[cfg(any(
+    windows_platform,
+    macos_platform,
+    x11_platform,
+    wayland_platform,
+    web_platform
+))]
+pub(crate) mod event_clock;
+
 /// Helper for converting between platform-specific and generic [`VideoModeHandle`]/[`MonitorHandle`]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum Fullscreen {
@@ -14,6 +14,7 @@ use orbclient::{
 };
 
 use crate::{
+    dpi::PhysicalPosition,
     error::EventLoopError,
     event::{self, Ime, Modifiers, StartCause},
     event_loop::{self, ControlFlow, DeviceEvents},
@@ -21,6 +22,7 @@ use crate::{
         Key, KeyCode, KeyLocation, ModifiersKeys, ModifiersState, NativeKey, NativeKeyCode,
         PhysicalKey,
     },
+    platform_impl::click_counter::ClickCounter,
     window::WindowId as RootWindowId,
 };
 
@@ -150,6 +152,8 @@ bitflags! {
 struct EventState {
     keyboard: KeyboardModifierState,
     mouse: MouseButtonState,
+    cursor_position: PhysicalPosition<f64>,
+    click_counter: ClickCounter,
     resize_opt: Option<(u32, u32)>,
 }
 
@@ -357,6 +361,7 @@ impl<T: 'static> EventLoop<T> {
                             },
                             is_synthetic: false,
                         },
+                        timestamp: Instant::now(),
                     });
 
                     // If the state of the modifiers has changed, send the event.
@@ -364,6 +369,7 @@ impl<T: 'static> EventLoop<T> {
                         event_handler(event::Event::WindowEvent {
                             window_id: RootWindowId(window_id),
                             event: event::WindowEvent::ModifiersChanged(event_state.modifiers()),
+                            timestamp: Instant::now(),
                         })
                     }
                 }
@@ -372,19 +378,23 @@ impl<T: 'static> EventLoop<T> {
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::Ime(Ime::Preedit("".into(), None)),
+                    timestamp: Instant::now(),
                 });
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::Ime(Ime::Commit(character.into())),
+                    timestamp: Instant::now(),
                 });
             }
             EventOption::Mouse(MouseEvent { x, y }) => {
+                event_state.cursor_position = (x, y).into();
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::CursorMoved {
                         device_id: event::DeviceId(DeviceId),
                         position: (x, y).into(),
                     },
+                    timestamp: Instant::now(),
                 });
             }
             EventOption::Button(ButtonEvent {
@@ -393,13 +403,23 @@ impl<T: 'static> EventLoop<T> {
                 right,
             }) => {
                 while let Some((button, state)) = event_state.mouse(left, middle, right) {
+                    let timestamp = Instant::now();
+                    let click_count = event_state.click_counter.count(
+                        RootWindowId(window_id),
+                        state,
+                        button,
+                        event_state.cursor_position,
+                        timestamp,
+                    );
                     event_handler(event::Event::WindowEvent {
                         window_id: RootWindowId(window_id),
                         event: event::WindowEvent::MouseInput {
                             device_id: event::DeviceId(DeviceId),
                             state,
                             button,
+                            click_count,
                         },
+                        timestamp,
                     });
                 }
             }
@@ -411,30 +431,35 @@ impl<T: 'static> EventLoop<T> {
                         delta: event::MouseScrollDelta::LineDelta(x as f32, y as f32),
                         phase: event::TouchPhase::Moved,
                     },
+                    timestamp: Instant::now(),
                 });
             }
             EventOption::Quit(QuitEvent {}) => {
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::CloseRequested,
+                    timestamp: Instant::now(),
                 });
             }
             EventOption::Focus(FocusEvent { focused }) => {
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::Focused(focused),
+                    timestamp: Instant::now(),
                 });
             }
             EventOption::Move(MoveEvent { x, y }) => {
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::Moved((x, y).into()),
+                    timestamp: Instant::now(),
                 });
             }
             EventOption::Resize(ResizeEvent { width, height }) => {
                 event_handler(event::Event::WindowEvent {
                     window_id: RootWindowId(window_id),
                     event: event::WindowEvent::Resized((width, height).into()),
+                    timestamp: Instant::now(),
                 });
 
                 // Acknowledge resize after event loop.
@@ -448,6 +473,7 @@ impl<T: 'static> EventLoop<T> {
                         event: event::WindowEvent::CursorEntered {
                             device_id: event::DeviceId(DeviceId),
                         },
+                        timestamp: Instant::now(),
                     });
                 } else {
                     event_handler(event::Event::WindowEvent {
@@ -455,6 +481,7 @@ impl<T: 'static> EventLoop<T> {
                         event: event::WindowEvent::CursorLeft {
                             device_id: event::DeviceId(DeviceId),
                         },
+                        timestamp: Instant::now(),
                     });
                 }
             }
@@ -502,6 +529,7 @@ impl<T: 'static> EventLoop<T> {
                     event::Event::WindowEvent {
                         window_id: RootWindowId(window_id),
                         event: event::WindowEvent::Resized((properties.w, properties.h).into()),
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -511,6 +539,7 @@ impl<T: 'static> EventLoop<T> {
                     event::Event::WindowEvent {
                         window_id: RootWindowId(window_id),
                         event: event::WindowEvent::Moved((properties.x, properties.y).into()),
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -525,6 +554,7 @@ impl<T: 'static> EventLoop<T> {
                     event::Event::WindowEvent {
                         window_id: RootWindowId(destroy_id),
                         event: event::WindowEvent::Destroyed,
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
@@ -596,6 +626,7 @@ impl<T: 'static> EventLoop<T> {
                     event::Event::WindowEvent {
                         window_id: RootWindowId(window_id),
                         event: event::WindowEvent::RedrawRequested,
+                        timestamp: Instant::now(),
                     },
                     &self.window_target,
                 );
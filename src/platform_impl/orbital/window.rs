@@ -9,7 +9,7 @@ use crate::{
     error,
     platform_impl::Fullscreen,
     window,
-    window::ImePurpose,
+    window::{ImeHints, ImePurpose},
 };
 
 use super::{
@@ -259,6 +259,12 @@ impl Window {
     #[inline]
     pub fn set_blur(&self, _blur: bool) {}
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {}
+
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {}
+
     #[inline]
     pub fn set_visible(&self, _visibility: bool) {}
 
@@ -342,6 +348,12 @@ impl Window {
     #[inline]
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    #[inline]
+    pub fn set_ime_hints(&self, _hints: ImeHints) {}
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn focus_window(&self) {}
 
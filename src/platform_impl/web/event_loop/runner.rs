@@ -8,6 +8,7 @@ use crate::event::{
 };
 use crate::event_loop::{ControlFlow, DeviceEvents};
 use crate::platform::web::PollStrategy;
+use crate::platform_impl::event_clock::EventClock;
 use crate::platform_impl::platform::backend::EventListenerHandle;
 use crate::platform_impl::platform::r#async::{DispatchRunner, Waker, WakerSpawner};
 use crate::platform_impl::platform::window::Inner;
@@ -62,6 +63,7 @@ pub struct Execution {
     destroy_pending: RefCell<VecDeque<WindowId>>,
     page_transition_event_handle: RefCell<Option<backend::PageTransitionEventHandle>>,
     device_events: Cell<DeviceEvents>,
+    event_clock: EventClock,
     on_mouse_move: OnEventHandle<PointerEvent>,
     on_wheel: OnEventHandle<WheelEvent>,
     on_mouse_press: OnEventHandle<PointerEvent>,
@@ -124,7 +126,7 @@ impl Runner {
 
     fn handle_single_event(&mut self, runner: &Shared, event: impl Into<EventWrapper>) {
         match event.into() {
-            EventWrapper::Event(event) => (self.event_handler)(event),
+            EventWrapper::Event(event) => (self.event_handler)(runner.0.event_clock.stamp(event)),
             EventWrapper::ScaleChange {
                 canvas,
                 size,
@@ -133,7 +135,7 @@ impl Runner {
                 if let Some(canvas) = canvas.upgrade() {
                     canvas.borrow().handle_scale_change(
                         runner,
-                        |event| (self.event_handler)(event),
+                        |event| (self.event_handler)(runner.0.event_clock.stamp(event)),
                         size,
                         scale,
                     )
@@ -177,6 +179,7 @@ impl Shared {
                 destroy_pending: RefCell::new(VecDeque::new()),
                 page_transition_event_handle: RefCell::new(None),
                 device_events: Cell::default(),
+                event_clock: EventClock::new(),
                 on_mouse_move: RefCell::new(None),
                 on_wheel: RefCell::new(None),
                 on_mouse_press: RefCell::new(None),
@@ -266,6 +269,7 @@ impl Shared {
 
                 // chorded button event
                 let device_id = RootDeviceId(DeviceId(event.pointer_id()));
+                let timestamp = backend::event::timestamp(&window, &event);
 
                 if let Some(button) = backend::event::mouse_button(&event) {
                     debug_assert_eq!(
@@ -285,6 +289,7 @@ impl Shared {
                             button: button.to_id(),
                             state,
                         },
+                        timestamp,
                     });
 
                     return;
@@ -296,6 +301,7 @@ impl Shared {
                     let delta = delta
                         .delta(&event)
                         .to_physical(backend::scale_factor(&window));
+                    let timestamp = backend::event::timestamp(&window, &event);
 
                     let x_motion = (delta.x != 0.0).then_some(Event::DeviceEvent {
                         device_id,
@@ -303,6 +309,7 @@ impl Shared {
                             axis: 0,
                             value: delta.x,
                         },
+                        timestamp,
                     });
 
                     let y_motion = (delta.y != 0.0).then_some(Event::DeviceEvent {
@@ -311,6 +318,7 @@ impl Shared {
                             axis: 1,
                             value: delta.y,
                         },
+                        timestamp,
                     });
 
                     x_motion
@@ -321,6 +329,7 @@ impl Shared {
                             event: DeviceEvent::MouseMotion {
                                 delta: (delta.x, delta.y),
                             },
+                            timestamp,
                         }))
                 }));
             }),
@@ -339,6 +348,7 @@ impl Shared {
                     runner.send_event(Event::DeviceEvent {
                         device_id: RootDeviceId(DeviceId(0)),
                         event: DeviceEvent::MouseWheel { delta },
+                        timestamp: backend::event::timestamp(&window, &event),
                     });
                 }
             }),
@@ -363,6 +373,7 @@ impl Shared {
                         button: button.to_id(),
                         state: ElementState::Pressed,
                     },
+                    timestamp: backend::event::timestamp(runner.window(), &event),
                 });
             }),
         ));
@@ -386,6 +397,7 @@ impl Shared {
                         button: button.to_id(),
                         state: ElementState::Released,
                     },
+                    timestamp: backend::event::timestamp(runner.window(), &event),
                 });
             }),
         ));
@@ -404,6 +416,7 @@ impl Shared {
                         physical_key: backend::event::key_code(&event),
                         state: ElementState::Pressed,
                     }),
+                    timestamp: backend::event::timestamp(runner.window(), &event),
                 });
             }),
         ));
@@ -422,6 +435,7 @@ impl Shared {
                         physical_key: backend::event::key_code(&event),
                         state: ElementState::Released,
                     }),
+                    timestamp: backend::event::timestamp(runner.window(), &event),
                 });
             }),
         ));
@@ -445,6 +459,7 @@ impl Shared {
                                 runner.send_event(Event::WindowEvent {
                                     window_id: *id,
                                     event: WindowEvent::Occluded(!is_visible),
+                                    timestamp: Instant::now(),
                                 });
                             }
                         }
@@ -566,6 +581,7 @@ impl Shared {
             self.handle_event(Event::WindowEvent {
                 window_id: id,
                 event: crate::event::WindowEvent::Destroyed,
+                timestamp: Instant::now(),
             });
             self.0.redraw_pending.borrow_mut().remove(&id);
         }
@@ -587,6 +603,7 @@ impl Shared {
             self.handle_event(Event::WindowEvent {
                 window_id,
                 event: WindowEvent::RedrawRequested,
+                timestamp: Instant::now(),
             });
         }
 
@@ -5,6 +5,7 @@ use std::iter;
 use std::rc::{Rc, Weak};
 
 use web_sys::Element;
+use web_time::Instant;
 
 use super::runner::{EventWrapper, Execution};
 use super::{
@@ -20,6 +21,7 @@ use crate::event::{
 use crate::event_loop::{ControlFlow, DeviceEvents};
 use crate::keyboard::ModifiersState;
 use crate::platform::web::PollStrategy;
+use crate::platform_impl::click_counter::ClickCounter;
 use crate::platform_impl::platform::r#async::Waker;
 use crate::window::{Theme, WindowId as RootWindowId};
 
@@ -76,7 +78,7 @@ impl EventLoopWindowTarget {
         let runner = self.runner.clone();
         let has_focus = canvas.has_focus.clone();
         let modifiers = self.modifiers.clone();
-        canvas.on_blur(move || {
+        canvas.on_blur(move |timestamp| {
             has_focus.set(false);
 
             let clear_modifiers = (!modifiers.get().is_empty()).then(|| {
@@ -84,6 +86,7 @@ impl EventLoopWindowTarget {
                 Event::WindowEvent {
                     window_id: RootWindowId(id),
                     event: WindowEvent::ModifiersChanged(ModifiersState::empty().into()),
+                    timestamp,
                 }
             });
 
@@ -93,17 +96,19 @@ impl EventLoopWindowTarget {
                     .chain(iter::once(Event::WindowEvent {
                         window_id: RootWindowId(id),
                         event: WindowEvent::Focused(false),
+                        timestamp,
                     })),
             );
         });
 
         let runner = self.runner.clone();
         let has_focus = canvas.has_focus.clone();
-        canvas.on_focus(move || {
+        canvas.on_focus(move |timestamp| {
             if !has_focus.replace(true) {
                 runner.send_event(Event::WindowEvent {
                     window_id: RootWindowId(id),
                     event: WindowEvent::Focused(true),
+                    timestamp,
                 });
             }
         });
@@ -124,18 +129,26 @@ impl EventLoopWindowTarget {
             self.runner.send_event(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Focused(true),
+                timestamp: Instant::now(),
             })
         }
 
         let runner = self.runner.clone();
         let modifiers = self.modifiers.clone();
         canvas.on_keyboard_press(
-            move |physical_key, logical_key, text, location, repeat, active_modifiers| {
+            move |physical_key,
+                  logical_key,
+                  text,
+                  location,
+                  repeat,
+                  active_modifiers,
+                  timestamp| {
                 let modifiers_changed = (modifiers.get() != active_modifiers).then(|| {
                     modifiers.set(active_modifiers);
                     Event::WindowEvent {
                         window_id: RootWindowId(id),
                         event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                        timestamp,
                     }
                 });
 
@@ -157,6 +170,7 @@ impl EventLoopWindowTarget {
                             },
                             is_synthetic: false,
                         },
+                        timestamp,
                     })
                     .chain(modifiers_changed),
                 );
@@ -166,12 +180,19 @@ impl EventLoopWindowTarget {
         let runner = self.runner.clone();
         let modifiers = self.modifiers.clone();
         canvas.on_keyboard_release(
-            move |physical_key, logical_key, text, location, repeat, active_modifiers| {
+            move |physical_key,
+                  logical_key,
+                  text,
+                  location,
+                  repeat,
+                  active_modifiers,
+                  timestamp| {
                 let modifiers_changed = (modifiers.get() != active_modifiers).then(|| {
                     modifiers.set(active_modifiers);
                     Event::WindowEvent {
                         window_id: RootWindowId(id),
                         event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                        timestamp,
                     }
                 });
 
@@ -193,6 +214,7 @@ impl EventLoopWindowTarget {
                             },
                             is_synthetic: false,
                         },
+                        timestamp,
                     })
                     .chain(modifiers_changed),
                 )
@@ -200,17 +222,19 @@ impl EventLoopWindowTarget {
         );
 
         let has_focus = canvas.has_focus.clone();
+        let click_counter = Rc::new(RefCell::new(ClickCounter::default()));
         canvas.on_cursor_leave({
             let runner = self.runner.clone();
             let has_focus = has_focus.clone();
             let modifiers = self.modifiers.clone();
 
-            move |active_modifiers, pointer_id| {
+            move |active_modifiers, pointer_id, timestamp| {
                 let focus = (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                     modifiers.set(active_modifiers);
                     Event::WindowEvent {
                         window_id: RootWindowId(id),
                         event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                        timestamp,
                     }
                 });
 
@@ -219,6 +243,7 @@ impl EventLoopWindowTarget {
                     event: WindowEvent::CursorLeft {
                         device_id: RootDeviceId(DeviceId(pointer_id)),
                     },
+                    timestamp,
                 });
 
                 if focus.is_some() || pointer.is_some() {
@@ -232,12 +257,13 @@ impl EventLoopWindowTarget {
             let has_focus = has_focus.clone();
             let modifiers = self.modifiers.clone();
 
-            move |active_modifiers, pointer_id| {
+            move |active_modifiers, pointer_id, timestamp| {
                 let focus = (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                     modifiers.set(active_modifiers);
                     Event::WindowEvent {
                         window_id: RootWindowId(id),
                         event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                        timestamp,
                     }
                 });
 
@@ -246,6 +272,7 @@ impl EventLoopWindowTarget {
                     event: WindowEvent::CursorEntered {
                         device_id: RootDeviceId(DeviceId(pointer_id)),
                     },
+                    timestamp,
                 });
 
                 if focus.is_some() || pointer.is_some() {
@@ -260,12 +287,13 @@ impl EventLoopWindowTarget {
                 let has_focus = has_focus.clone();
                 let modifiers = self.modifiers.clone();
 
-                move |active_modifiers| {
+                move |active_modifiers, timestamp| {
                     if has_focus.get() && modifiers.get() != active_modifiers {
                         modifiers.set(active_modifiers);
                         runner.send_event(Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                            timestamp,
                         })
                     }
                 }
@@ -276,26 +304,33 @@ impl EventLoopWindowTarget {
                 let modifiers = self.modifiers.clone();
 
                 move |active_modifiers, pointer_id, events| {
+                    let mut events = events.peekable();
                     let modifiers =
                         (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                             modifiers.set(active_modifiers);
                             Event::WindowEvent {
                                 window_id: RootWindowId(id),
                                 event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                                timestamp: events
+                                    .peek()
+                                    .map_or_else(Instant::now, |&(_, timestamp)| timestamp),
                             }
                         });
 
-                    runner.send_events(modifiers.into_iter().chain(events.flat_map(|position| {
-                        let device_id = RootDeviceId(DeviceId(pointer_id));
+                    runner.send_events(modifiers.into_iter().chain(events.flat_map(
+                        |(position, timestamp)| {
+                            let device_id = RootDeviceId(DeviceId(pointer_id));
 
-                        iter::once(Event::WindowEvent {
-                            window_id: RootWindowId(id),
-                            event: WindowEvent::CursorMoved {
-                                device_id,
-                                position,
-                            },
-                        })
-                    })));
+                            iter::once(Event::WindowEvent {
+                                window_id: RootWindowId(id),
+                                event: WindowEvent::CursorMoved {
+                                    device_id,
+                                    position,
+                                },
+                                timestamp,
+                            })
+                        },
+                    )));
                 }
             },
             {
@@ -304,17 +339,21 @@ impl EventLoopWindowTarget {
                 let modifiers = self.modifiers.clone();
 
                 move |active_modifiers, device_id, events| {
+                    let mut events = events.peekable();
                     let modifiers =
                         (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                             modifiers.set(active_modifiers);
                             Event::WindowEvent {
                                 window_id: RootWindowId(id),
                                 event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                                timestamp: events
+                                    .peek()
+                                    .map_or_else(Instant::now, |&(_, _, timestamp)| timestamp),
                             }
                         });
 
                     runner.send_events(modifiers.into_iter().chain(events.map(
-                        |(location, force)| Event::WindowEvent {
+                        |(location, force, timestamp)| Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::Touch(Touch {
                                 id: device_id as u64,
@@ -323,6 +362,7 @@ impl EventLoopWindowTarget {
                                 force: Some(force),
                                 location,
                             }),
+                            timestamp,
                         },
                     )));
                 }
@@ -331,18 +371,21 @@ impl EventLoopWindowTarget {
                 let runner = self.runner.clone();
                 let has_focus = has_focus.clone();
                 let modifiers = self.modifiers.clone();
+                let click_counter = click_counter.clone();
 
                 move |active_modifiers,
                       pointer_id,
                       position: crate::dpi::PhysicalPosition<f64>,
                       buttons,
-                      button| {
+                      button,
+                      timestamp| {
                     let modifiers =
                         (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                             modifiers.set(active_modifiers);
                             Event::WindowEvent {
                                 window_id: RootWindowId(id),
                                 event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                                timestamp,
                             }
                         });
 
@@ -353,6 +396,13 @@ impl EventLoopWindowTarget {
                     } else {
                         ElementState::Released
                     };
+                    let click_count = click_counter.borrow_mut().count(
+                        RootWindowId(id),
+                        state,
+                        button,
+                        position,
+                        timestamp,
+                    );
 
                     // A chorded button event may come in without any prior CursorMoved events,
                     // therefore we should send a CursorMoved event to make sure that the
@@ -364,6 +414,7 @@ impl EventLoopWindowTarget {
                                 device_id,
                                 position,
                             },
+                            timestamp,
                         },
                         Event::WindowEvent {
                             window_id: RootWindowId(id),
@@ -371,7 +422,9 @@ impl EventLoopWindowTarget {
                                 device_id,
                                 state,
                                 button,
+                                click_count,
                             },
+                            timestamp,
                         },
                     ]));
                 }
@@ -383,12 +436,13 @@ impl EventLoopWindowTarget {
                 let runner = self.runner.clone();
                 let modifiers = self.modifiers.clone();
 
-                move |active_modifiers| {
+                move |active_modifiers, timestamp| {
                     if modifiers.get() != active_modifiers {
                         modifiers.set(active_modifiers);
                         runner.send_event(Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                            timestamp,
                         })
                     }
                 }
@@ -396,13 +450,23 @@ impl EventLoopWindowTarget {
             {
                 let runner = self.runner.clone();
                 let modifiers = self.modifiers.clone();
+                let click_counter = click_counter.clone();
+
+                move |active_modifiers, pointer_id, position, button, timestamp| {
+                    let click_count = click_counter.borrow_mut().count(
+                        RootWindowId(id),
+                        ElementState::Pressed,
+                        button,
+                        position,
+                        timestamp,
+                    );
 
-                move |active_modifiers, pointer_id, position, button| {
                     let modifiers = (modifiers.get() != active_modifiers).then(|| {
                         modifiers.set(active_modifiers);
                         Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                            timestamp,
                         }
                     });
 
@@ -418,6 +482,7 @@ impl EventLoopWindowTarget {
                                 device_id,
                                 position,
                             },
+                            timestamp,
                         },
                         Event::WindowEvent {
                             window_id: RootWindowId(id),
@@ -425,7 +490,9 @@ impl EventLoopWindowTarget {
                                 device_id,
                                 state: ElementState::Pressed,
                                 button,
+                                click_count,
                             },
+                            timestamp,
                         },
                     ]));
                 }
@@ -434,12 +501,13 @@ impl EventLoopWindowTarget {
                 let runner = self.runner.clone();
                 let modifiers = self.modifiers.clone();
 
-                move |active_modifiers, device_id, location, force| {
+                move |active_modifiers, device_id, location, force, timestamp| {
                     let modifiers = (modifiers.get() != active_modifiers).then(|| {
                         modifiers.set(active_modifiers);
                         Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                            timestamp,
                         }
                     });
 
@@ -453,6 +521,7 @@ impl EventLoopWindowTarget {
                                 force: Some(force),
                                 location,
                             }),
+                            timestamp,
                         },
                     )))
                 }
@@ -465,12 +534,13 @@ impl EventLoopWindowTarget {
                 let has_focus = has_focus.clone();
                 let modifiers = self.modifiers.clone();
 
-                move |active_modifiers| {
+                move |active_modifiers, timestamp| {
                     if has_focus.get() && modifiers.get() != active_modifiers {
                         modifiers.set(active_modifiers);
                         runner.send_event(Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                            timestamp,
                         });
                     }
                 }
@@ -479,14 +549,24 @@ impl EventLoopWindowTarget {
                 let runner = self.runner.clone();
                 let has_focus = has_focus.clone();
                 let modifiers = self.modifiers.clone();
+                let click_counter = click_counter.clone();
+
+                move |active_modifiers, pointer_id, position, button, timestamp| {
+                    let click_count = click_counter.borrow_mut().count(
+                        RootWindowId(id),
+                        ElementState::Released,
+                        button,
+                        position,
+                        timestamp,
+                    );
 
-                move |active_modifiers, pointer_id, position, button| {
                     let modifiers =
                         (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                             modifiers.set(active_modifiers);
                             Event::WindowEvent {
                                 window_id: RootWindowId(id),
                                 event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                                timestamp,
                             }
                         });
 
@@ -502,6 +582,7 @@ impl EventLoopWindowTarget {
                                 device_id,
                                 position,
                             },
+                            timestamp,
                         },
                         Event::WindowEvent {
                             window_id: RootWindowId(id),
@@ -509,7 +590,9 @@ impl EventLoopWindowTarget {
                                 device_id,
                                 state: ElementState::Released,
                                 button,
+                                click_count,
                             },
+                            timestamp,
                         },
                     ]));
                 }
@@ -519,13 +602,14 @@ impl EventLoopWindowTarget {
                 let has_focus = has_focus.clone();
                 let modifiers = self.modifiers.clone();
 
-                move |active_modifiers, device_id, location, force| {
+                move |active_modifiers, device_id, location, force, timestamp| {
                     let modifiers =
                         (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                             modifiers.set(active_modifiers);
                             Event::WindowEvent {
                                 window_id: RootWindowId(id),
                                 event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                                timestamp,
                             }
                         });
 
@@ -539,6 +623,7 @@ impl EventLoopWindowTarget {
                                 force: Some(force),
                                 location,
                             }),
+                            timestamp,
                         },
                     )));
                 }
@@ -547,13 +632,14 @@ impl EventLoopWindowTarget {
 
         let runner = self.runner.clone();
         let modifiers = self.modifiers.clone();
-        canvas.on_mouse_wheel(move |pointer_id, delta, active_modifiers| {
+        canvas.on_mouse_wheel(move |pointer_id, delta, active_modifiers, timestamp| {
             let modifiers_changed =
                 (has_focus.get() && modifiers.get() != active_modifiers).then(|| {
                     modifiers.set(active_modifiers);
                     Event::WindowEvent {
                         window_id: RootWindowId(id),
                         event: WindowEvent::ModifiersChanged(active_modifiers.into()),
+                        timestamp,
                     }
                 });
 
@@ -565,12 +651,13 @@ impl EventLoopWindowTarget {
                         delta,
                         phase: TouchPhase::Moved,
                     },
+                    timestamp,
                 },
             )));
         });
 
         let runner = self.runner.clone();
-        canvas.on_touch_cancel(move |device_id, location, force| {
+        canvas.on_touch_cancel(move |device_id, location, force, timestamp| {
             runner.send_event(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Touch(Touch {
@@ -580,6 +667,7 @@ impl EventLoopWindowTarget {
                     force: Some(force),
                     location,
                 }),
+                timestamp,
             });
         });
 
@@ -593,6 +681,7 @@ impl EventLoopWindowTarget {
             runner.send_event(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::ThemeChanged(theme),
+                timestamp: Instant::now(),
             });
         });
 
@@ -621,6 +710,7 @@ impl EventLoopWindowTarget {
                         runner.send_event(Event::WindowEvent {
                             window_id: RootWindowId(id),
                             event: WindowEvent::Resized(new_size),
+                            timestamp: Instant::now(),
                         });
                         runner.request_redraw(RootWindowId(id));
                     }
@@ -637,6 +727,7 @@ impl EventLoopWindowTarget {
                 runner.send_event(Event::WindowEvent {
                     window_id: RootWindowId(id),
                     event: WindowEvent::Occluded(!is_intersecting),
+                    timestamp: Instant::now(),
                 });
             }
 
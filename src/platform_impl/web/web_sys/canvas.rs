@@ -9,6 +9,7 @@ use web_sys::{
     CssStyleDeclaration, Document, Event, FocusEvent, HtmlCanvasElement, KeyboardEvent,
     PointerEvent, WheelEvent,
 };
+use web_time::Instant;
 
 use crate::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
 use crate::error::OsError as RootOE;
@@ -254,26 +255,30 @@ impl Canvas {
 
     pub fn on_blur<F>(&mut self, mut handler: F)
     where
-        F: 'static + FnMut(),
+        F: 'static + FnMut(Instant),
     {
-        self.on_blur = Some(self.common.add_event("blur", move |_: FocusEvent| {
-            handler();
+        let window = self.common.window.clone();
+        self.on_blur = Some(self.common.add_event("blur", move |event: FocusEvent| {
+            handler(event::timestamp(&window, &event));
         }));
     }
 
     pub fn on_focus<F>(&mut self, mut handler: F)
     where
-        F: 'static + FnMut(),
+        F: 'static + FnMut(Instant),
     {
-        self.on_focus = Some(self.common.add_event("focus", move |_: FocusEvent| {
-            handler();
+        let window = self.common.window.clone();
+        self.on_focus = Some(self.common.add_event("focus", move |event: FocusEvent| {
+            handler(event::timestamp(&window, &event));
         }));
     }
 
     pub fn on_keyboard_release<F>(&mut self, mut handler: F)
     where
-        F: 'static + FnMut(PhysicalKey, Key, Option<SmolStr>, KeyLocation, bool, ModifiersState),
+        F: 'static
+            + FnMut(PhysicalKey, Key, Option<SmolStr>, KeyLocation, bool, ModifiersState, Instant),
     {
+        let window = self.common.window.clone();
         let prevent_default = Rc::clone(&self.prevent_default);
         self.on_keyboard_release =
             Some(self.common.add_event("keyup", move |event: KeyboardEvent| {
@@ -289,14 +294,17 @@ impl Canvas {
                     event::key_location(&event),
                     event.repeat(),
                     modifiers,
+                    event::timestamp(&window, &event),
                 );
             }));
     }
 
     pub fn on_keyboard_press<F>(&mut self, mut handler: F)
     where
-        F: 'static + FnMut(PhysicalKey, Key, Option<SmolStr>, KeyLocation, bool, ModifiersState),
+        F: 'static
+            + FnMut(PhysicalKey, Key, Option<SmolStr>, KeyLocation, bool, ModifiersState, Instant),
     {
+        let window = self.common.window.clone();
         let prevent_default = Rc::clone(&self.prevent_default);
         self.on_keyboard_press = Some(self.common.add_event(
             "keydown",
@@ -313,6 +321,7 @@ impl Canvas {
                     event::key_location(&event),
                     event.repeat(),
                     modifiers,
+                    event::timestamp(&window, &event),
                 );
             },
         ));
@@ -320,14 +329,14 @@ impl Canvas {
 
     pub fn on_cursor_leave<F>(&mut self, handler: F)
     where
-        F: 'static + FnMut(ModifiersState, Option<i32>),
+        F: 'static + FnMut(ModifiersState, Option<i32>, Instant),
     {
         self.pointer_handler.on_cursor_leave(&self.common, handler)
     }
 
     pub fn on_cursor_enter<F>(&mut self, handler: F)
     where
-        F: 'static + FnMut(ModifiersState, Option<i32>),
+        F: 'static + FnMut(ModifiersState, Option<i32>, Instant),
     {
         self.pointer_handler.on_cursor_enter(&self.common, handler)
     }
@@ -338,9 +347,9 @@ impl Canvas {
         mouse_handler: M,
         touch_handler: T,
     ) where
-        MOD: 'static + FnMut(ModifiersState),
-        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton),
-        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force),
+        MOD: 'static + FnMut(ModifiersState, Instant),
+        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton, Instant),
+        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force, Instant),
     {
         self.pointer_handler.on_mouse_release(
             &self.common,
@@ -356,9 +365,9 @@ impl Canvas {
         mouse_handler: M,
         touch_handler: T,
     ) where
-        MOD: 'static + FnMut(ModifiersState),
-        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton),
-        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force),
+        MOD: 'static + FnMut(ModifiersState, Instant),
+        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton, Instant),
+        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force, Instant),
     {
         self.pointer_handler.on_mouse_press(
             &self.common,
@@ -376,11 +385,17 @@ impl Canvas {
         touch_handler: T,
         button_handler: B,
     ) where
-        MOD: 'static + FnMut(ModifiersState),
-        M: 'static + FnMut(ModifiersState, i32, &mut dyn Iterator<Item = PhysicalPosition<f64>>),
+        MOD: 'static + FnMut(ModifiersState, Instant),
+        M: 'static
+            + FnMut(ModifiersState, i32, &mut dyn Iterator<Item = (PhysicalPosition<f64>, Instant)>),
         T: 'static
-            + FnMut(ModifiersState, i32, &mut dyn Iterator<Item = (PhysicalPosition<f64>, Force)>),
-        B: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, ButtonsState, MouseButton),
+            + FnMut(
+                ModifiersState,
+                i32,
+                &mut dyn Iterator<Item = (PhysicalPosition<f64>, Force, Instant)>,
+            ),
+        B: 'static
+            + FnMut(ModifiersState, i32, PhysicalPosition<f64>, ButtonsState, MouseButton, Instant),
     {
         self.pointer_handler.on_cursor_move(
             &self.common,
@@ -394,14 +409,14 @@ impl Canvas {
 
     pub fn on_touch_cancel<F>(&mut self, handler: F)
     where
-        F: 'static + FnMut(i32, PhysicalPosition<f64>, Force),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>, Force, Instant),
     {
         self.pointer_handler.on_touch_cancel(&self.common, handler)
     }
 
     pub fn on_mouse_wheel<F>(&mut self, mut handler: F)
     where
-        F: 'static + FnMut(i32, MouseScrollDelta, ModifiersState),
+        F: 'static + FnMut(i32, MouseScrollDelta, ModifiersState, Instant),
     {
         let window = self.common.window.clone();
         let prevent_default = Rc::clone(&self.prevent_default);
@@ -412,7 +427,7 @@ impl Canvas {
 
             if let Some(delta) = event::mouse_scroll_delta(&window, &event) {
                 let modifiers = event::mouse_modifiers(&event);
-                handler(0, delta, modifiers);
+                handler(0, delta, modifiers, event::timestamp(&window, &event));
             }
         }));
     }
@@ -502,6 +517,7 @@ impl Canvas {
                     scale_factor: scale,
                     inner_size_writer: InnerSizeWriter::new(Arc::downgrade(&new_size)),
                 },
+                timestamp: Instant::now(),
             });
 
             let new_size = *new_size.lock().unwrap();
@@ -525,6 +541,7 @@ impl Canvas {
             runner.send_event(crate::event::Event::WindowEvent {
                 window_id: RootWindowId(self.id),
                 event: crate::event::WindowEvent::Resized(new_size),
+                timestamp: Instant::now(),
             })
         }
     }
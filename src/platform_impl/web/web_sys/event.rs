@@ -1,6 +1,7 @@
 use crate::dpi::LogicalPosition;
 use crate::event::{MouseButton, MouseScrollDelta};
 use crate::keyboard::{Key, KeyLocation, ModifiersState, NamedKey, PhysicalKey};
+use crate::platform_impl::event_clock;
 
 use smol_str::SmolStr;
 use std::cell::OnceCell;
@@ -8,6 +9,7 @@ use std::convert::TryInto;
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{KeyboardEvent, MouseEvent, PointerEvent, WheelEvent};
+use web_time::Instant;
 
 bitflags::bitflags! {
     // https://www.w3.org/TR/pointerevents3/#the-buttons-property
@@ -47,6 +49,17 @@ impl From<MouseButton> for ButtonsState {
     }
 }
 
+/// The time the `event` was generated at, converted from `Event.timeStamp`, which uses the same
+/// clock as `performance.now()`.
+pub fn timestamp(window: &web_sys::Window, event: &web_sys::Event) -> Instant {
+    match window.performance() {
+        Some(performance) => {
+            event_clock::instant_from_age((performance.now() - event.time_stamp()) / 1000.)
+        }
+        None => Instant::now(),
+    }
+}
+
 pub fn mouse_buttons(event: &MouseEvent) -> ButtonsState {
     ButtonsState::from_bits_retain(event.buttons())
 }
@@ -10,6 +10,7 @@ use crate::keyboard::ModifiersState;
 
 use event::ButtonsState;
 use web_sys::PointerEvent;
+use web_time::Instant;
 
 #[allow(dead_code)]
 pub(super) struct PointerHandler {
@@ -35,8 +36,9 @@ impl PointerHandler {
 
     pub fn on_cursor_leave<F>(&mut self, canvas_common: &Common, mut handler: F)
     where
-        F: 'static + FnMut(ModifiersState, Option<i32>),
+        F: 'static + FnMut(ModifiersState, Option<i32>, Instant),
     {
+        let window = canvas_common.window.clone();
         self.on_cursor_leave = Some(canvas_common.add_event(
             "pointerout",
             move |event: PointerEvent| {
@@ -47,15 +49,16 @@ impl PointerHandler {
                 // other platforms.
                 let pointer_id = (event.pointer_type() == "mouse").then(|| event.pointer_id());
 
-                handler(modifiers, pointer_id);
+                handler(modifiers, pointer_id, event::timestamp(&window, &event));
             },
         ));
     }
 
     pub fn on_cursor_enter<F>(&mut self, canvas_common: &Common, mut handler: F)
     where
-        F: 'static + FnMut(ModifiersState, Option<i32>),
+        F: 'static + FnMut(ModifiersState, Option<i32>, Instant),
     {
+        let window = canvas_common.window.clone();
         self.on_cursor_enter = Some(canvas_common.add_event(
             "pointerover",
             move |event: PointerEvent| {
@@ -66,7 +69,7 @@ impl PointerHandler {
                 // other platforms.
                 let pointer_id = (event.pointer_type() == "mouse").then(|| event.pointer_id());
 
-                handler(modifiers, pointer_id);
+                handler(modifiers, pointer_id, event::timestamp(&window, &event));
             },
         ));
     }
@@ -78,15 +81,16 @@ impl PointerHandler {
         mut mouse_handler: M,
         mut touch_handler: T,
     ) where
-        MOD: 'static + FnMut(ModifiersState),
-        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton),
-        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force),
+        MOD: 'static + FnMut(ModifiersState, Instant),
+        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton, Instant),
+        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force, Instant),
     {
         let window = canvas_common.window.clone();
         self.on_pointer_release = Some(canvas_common.add_event(
             "pointerup",
             move |event: PointerEvent| {
                 let modifiers = event::mouse_modifiers(&event);
+                let timestamp = event::timestamp(&window, &event);
 
                 match event.pointer_type().as_str() {
                     "touch" => touch_handler(
@@ -94,14 +98,16 @@ impl PointerHandler {
                         event.pointer_id(),
                         event::mouse_position(&event).to_physical(super::scale_factor(&window)),
                         Force::Normalized(event.pressure() as f64),
+                        timestamp,
                     ),
                     "mouse" => mouse_handler(
                         modifiers,
                         event.pointer_id(),
                         event::mouse_position(&event).to_physical(super::scale_factor(&window)),
                         event::mouse_button(&event).expect("no mouse button released"),
+                        timestamp,
                     ),
-                    _ => modifier_handler(modifiers),
+                    _ => modifier_handler(modifiers, timestamp),
                 }
             },
         ));
@@ -115,9 +121,9 @@ impl PointerHandler {
         mut touch_handler: T,
         prevent_default: Rc<Cell<bool>>,
     ) where
-        MOD: 'static + FnMut(ModifiersState),
-        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton),
-        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force),
+        MOD: 'static + FnMut(ModifiersState, Instant),
+        M: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, MouseButton, Instant),
+        T: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, Force, Instant),
     {
         let window = canvas_common.window.clone();
         let canvas = canvas_common.raw().clone();
@@ -132,6 +138,7 @@ impl PointerHandler {
                 }
 
                 let modifiers = event::mouse_modifiers(&event);
+                let timestamp = event::timestamp(&window, &event);
 
                 match event.pointer_type().as_str() {
                     "touch" => {
@@ -140,6 +147,7 @@ impl PointerHandler {
                             event.pointer_id(),
                             event::mouse_position(&event).to_physical(super::scale_factor(&window)),
                             Force::Normalized(event.pressure() as f64),
+                            timestamp,
                         );
                     }
                     "mouse" => {
@@ -148,6 +156,7 @@ impl PointerHandler {
                             event.pointer_id(),
                             event::mouse_position(&event).to_physical(super::scale_factor(&window)),
                             event::mouse_button(&event).expect("no mouse button pressed"),
+                            timestamp,
                         );
 
                         // Error is swallowed here since the error would occur every time the mouse is
@@ -155,7 +164,7 @@ impl PointerHandler {
                         // this could fail, that we care if it fails.
                         let _e = canvas.set_pointer_capture(event.pointer_id());
                     }
-                    _ => modifier_handler(modifiers),
+                    _ => modifier_handler(modifiers, timestamp),
                 }
             },
         ));
@@ -170,11 +179,17 @@ impl PointerHandler {
         mut button_handler: B,
         prevent_default: Rc<Cell<bool>>,
     ) where
-        MOD: 'static + FnMut(ModifiersState),
-        M: 'static + FnMut(ModifiersState, i32, &mut dyn Iterator<Item = PhysicalPosition<f64>>),
+        MOD: 'static + FnMut(ModifiersState, Instant),
+        M: 'static
+            + FnMut(ModifiersState, i32, &mut dyn Iterator<Item = (PhysicalPosition<f64>, Instant)>),
         T: 'static
-            + FnMut(ModifiersState, i32, &mut dyn Iterator<Item = (PhysicalPosition<f64>, Force)>),
-        B: 'static + FnMut(ModifiersState, i32, PhysicalPosition<f64>, ButtonsState, MouseButton),
+            + FnMut(
+                ModifiersState,
+                i32,
+                &mut dyn Iterator<Item = (PhysicalPosition<f64>, Force, Instant)>,
+            ),
+        B: 'static
+            + FnMut(ModifiersState, i32, PhysicalPosition<f64>, ButtonsState, MouseButton, Instant),
     {
         let window = canvas_common.window.clone();
         let canvas = canvas_common.raw().clone();
@@ -187,7 +202,7 @@ impl PointerHandler {
 
                 if let "touch" | "mouse" = pointer_type.as_str() {
                 } else {
-                    modifier_handler(modifiers);
+                    modifier_handler(modifiers, event::timestamp(&window, &event));
                     return;
                 }
 
@@ -213,6 +228,7 @@ impl PointerHandler {
                         event::mouse_position(&event).to_physical(super::scale_factor(&window)),
                         event::mouse_buttons(&event),
                         button,
+                        event::timestamp(&window, &event),
                     );
 
                     return;
@@ -224,8 +240,12 @@ impl PointerHandler {
                     "mouse" => mouse_handler(
                         modifiers,
                         id,
-                        &mut event::pointer_move_event(event)
-                            .map(|event| event::mouse_position(&event).to_physical(scale)),
+                        &mut event::pointer_move_event(event).map(|event| {
+                            (
+                                event::mouse_position(&event).to_physical(scale),
+                                event::timestamp(&window, &event),
+                            )
+                        }),
                     ),
                     "touch" => touch_handler(
                         modifiers,
@@ -234,6 +254,7 @@ impl PointerHandler {
                             (
                                 event::mouse_position(&event).to_physical(scale),
                                 Force::Normalized(event.pressure() as f64),
+                                event::timestamp(&window, &event),
                             )
                         }),
                     ),
@@ -245,7 +266,7 @@ impl PointerHandler {
 
     pub fn on_touch_cancel<F>(&mut self, canvas_common: &Common, mut handler: F)
     where
-        F: 'static + FnMut(i32, PhysicalPosition<f64>, Force),
+        F: 'static + FnMut(i32, PhysicalPosition<f64>, Force, Instant),
     {
         let window = canvas_common.window.clone();
         self.on_touch_cancel = Some(canvas_common.add_event(
@@ -256,6 +277,7 @@ impl PointerHandler {
                         event.pointer_id(),
                         event::mouse_position(&event).to_physical(super::scale_factor(&window)),
                         Force::Normalized(event.pressure() as f64),
+                        event::timestamp(&window, &event),
                     );
                 }
             },
@@ -2,7 +2,7 @@ use crate::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
 use crate::error::{ExternalError, NotSupportedError, OsError as RootOE};
 use crate::icon::Icon;
 use crate::window::{
-    Cursor, CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+    Cursor, CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
     WindowAttributes, WindowButtons, WindowId as RootWI, WindowLevel,
 };
 
@@ -130,6 +130,10 @@ impl Inner {
 
     pub fn set_blur(&self, _blur: bool) {}
 
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {}
+
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {}
+
     pub fn set_visible(&self, _visible: bool) {
         // Intentionally a no-op
     }
@@ -351,10 +355,45 @@ impl Inner {
     }
 
     #[inline]
-    pub fn set_ime_purpose(&self, _purpose: ImePurpose) {
-        // Currently not implemented
+    pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+        // The `inputmode` picks the layout of the on-screen keyboards.
+        let input_mode = match purpose {
+            ImePurpose::Digits | ImePurpose::Pin => "numeric",
+            ImePurpose::Number => "decimal",
+            ImePurpose::Phone => "tel",
+            ImePurpose::Url => "url",
+            ImePurpose::Email => "email",
+            _ => "text",
+        };
+        self.canvas.borrow().set_attribute("inputmode", input_mode)
     }
 
+    #[inline]
+    pub fn set_ime_hints(&self, hints: ImeHints) {
+        let autocapitalize = if hints.contains(ImeHints::UPPERCASE) {
+            "characters"
+        } else if hints.contains(ImeHints::TITLECASE) {
+            "words"
+        } else if hints.contains(ImeHints::AUTO_CAPITALIZATION) {
+            "sentences"
+        } else {
+            "none"
+        };
+        let canvas = self.canvas.borrow();
+        canvas.set_attribute("autocapitalize", autocapitalize);
+        canvas.set_attribute(
+            "spellcheck",
+            if hints.contains(ImeHints::SPELLCHECK) {
+                "true"
+            } else {
+                "false"
+            },
+        );
+    }
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn focus_window(&self) {
         let _ = self.canvas.borrow().raw().focus();
@@ -4,6 +4,7 @@ use std::{
     path::PathBuf,
     ptr,
     sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
 };
 
 use windows_sys::{
@@ -100,6 +101,7 @@ impl FileDropHandler {
                 drop_handler.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(drop_handler.window)),
                     event: HoveredFile(filename),
+                    timestamp: Instant::now(),
                 });
             })
         };
@@ -137,6 +139,7 @@ impl FileDropHandler {
             drop_handler.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(drop_handler.window)),
                 event: HoveredFileCancelled,
+                timestamp: Instant::now(),
             });
         }
 
@@ -157,6 +160,7 @@ impl FileDropHandler {
                 drop_handler.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(drop_handler.window)),
                     event: DroppedFile(filename),
+                    timestamp: Instant::now(),
                 });
             })
         };
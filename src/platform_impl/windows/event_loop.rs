@@ -35,7 +35,8 @@ use windows_sys::Win32::{
         Input::{
             Ime::{GCS_COMPSTR, GCS_RESULTSTR, ISC_SHOWUICOMPOSITIONWINDOW},
             KeyboardAndMouse::{
-                ReleaseCapture, SetCapture, TrackMouseEvent, TME_LEAVE, TRACKMOUSEEVENT,
+                GetDoubleClickTime, ReleaseCapture, SetCapture, TrackMouseEvent, TME_LEAVE,
+                TRACKMOUSEEVENT,
             },
             Pointer::{POINTER_FLAG_DOWN, POINTER_FLAG_UP, POINTER_FLAG_UPDATE},
             Touch::{
@@ -46,23 +47,24 @@ use windows_sys::Win32::{
         },
         WindowsAndMessaging::{
             CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetClientRect,
-            GetCursorPos, GetMenu, GetMessageW, KillTimer, LoadCursorW, PeekMessageW, PostMessageW,
-            RegisterClassExW, RegisterWindowMessageA, SetCursor, SetTimer, SetWindowPos,
-            TranslateMessage, CREATESTRUCTW, GIDC_ARRIVAL, GIDC_REMOVAL, GWL_STYLE, GWL_USERDATA,
-            HTCAPTION, HTCLIENT, MINMAXINFO, MNC_CLOSE, MSG, NCCALCSIZE_PARAMS, PM_REMOVE, PT_PEN,
-            PT_TOUCH, RI_MOUSE_HWHEEL, RI_MOUSE_WHEEL, SC_MINIMIZE, SC_RESTORE, SIZE_MAXIMIZED,
-            SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, WHEEL_DELTA, WINDOWPOS,
-            WM_CAPTURECHANGED, WM_CLOSE, WM_CREATE, WM_DESTROY, WM_DPICHANGED, WM_ENTERSIZEMOVE,
-            WM_EXITSIZEMOVE, WM_GETMINMAXINFO, WM_IME_COMPOSITION, WM_IME_ENDCOMPOSITION,
-            WM_IME_SETCONTEXT, WM_IME_STARTCOMPOSITION, WM_INPUT, WM_INPUT_DEVICE_CHANGE,
-            WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN,
-            WM_MBUTTONUP, WM_MENUCHAR, WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_NCACTIVATE,
-            WM_NCCALCSIZE, WM_NCCREATE, WM_NCDESTROY, WM_NCLBUTTONDOWN, WM_PAINT, WM_POINTERDOWN,
-            WM_POINTERUP, WM_POINTERUPDATE, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SETCURSOR,
-            WM_SETFOCUS, WM_SETTINGCHANGE, WM_SIZE, WM_SYSCOMMAND, WM_SYSKEYDOWN, WM_SYSKEYUP,
-            WM_TOUCH, WM_WINDOWPOSCHANGED, WM_WINDOWPOSCHANGING, WM_XBUTTONDOWN, WM_XBUTTONUP,
-            WNDCLASSEXW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT,
-            WS_OVERLAPPED, WS_POPUP, WS_VISIBLE,
+            GetCursorPos, GetMenu, GetMessageTime, GetMessageW, GetSystemMetrics, KillTimer,
+            LoadCursorW, PeekMessageW, PostMessageW, RegisterClassExW, RegisterWindowMessageA,
+            SetCursor, SetTimer, SetWindowPos, TranslateMessage, CREATESTRUCTW, GIDC_ARRIVAL,
+            GIDC_REMOVAL, GWL_STYLE, GWL_USERDATA, HTCAPTION, HTCLIENT, MINMAXINFO, MNC_CLOSE, MSG,
+            NCCALCSIZE_PARAMS, PM_REMOVE, PT_PEN, PT_TOUCH, RI_MOUSE_HWHEEL, RI_MOUSE_WHEEL,
+            SC_MINIMIZE, SC_RESTORE, SIZE_MAXIMIZED, SM_CXDOUBLECLK, SWP_NOACTIVATE, SWP_NOMOVE,
+            SWP_NOSIZE, SWP_NOZORDER, WHEEL_DELTA, WINDOWPOS, WM_CAPTURECHANGED, WM_CLOSE,
+            WM_CREATE, WM_DESTROY, WM_DPICHANGED, WM_ENTERSIZEMOVE, WM_EXITSIZEMOVE,
+            WM_GETMINMAXINFO, WM_IME_COMPOSITION, WM_IME_ENDCOMPOSITION, WM_IME_SETCONTEXT,
+            WM_IME_STARTCOMPOSITION, WM_INPUT, WM_INPUT_DEVICE_CHANGE, WM_KEYDOWN, WM_KEYUP,
+            WM_KILLFOCUS, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MENUCHAR,
+            WM_MOUSEHWHEEL, WM_MOUSEMOVE, WM_MOUSEWHEEL, WM_NCACTIVATE, WM_NCCALCSIZE, WM_NCCREATE,
+            WM_NCDESTROY, WM_NCLBUTTONDOWN, WM_PAINT, WM_POINTERDOWN, WM_POINTERUP,
+            WM_POINTERUPDATE, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SETCURSOR, WM_SETFOCUS,
+            WM_SETTINGCHANGE, WM_SIZE, WM_SYSCOMMAND, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_TOUCH,
+            WM_WINDOWPOSCHANGED, WM_WINDOWPOSCHANGING, WM_XBUTTONDOWN, WM_XBUTTONUP, WNDCLASSEXW,
+            WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT, WS_OVERLAPPED,
+            WS_POPUP, WS_VISIBLE,
         },
     },
 };
@@ -71,8 +73,8 @@ use crate::{
     dpi::{PhysicalPosition, PhysicalSize},
     error::EventLoopError,
     event::{
-        DeviceEvent, Event, Force, Ime, InnerSizeWriter, RawKeyEvent, Touch, TouchPhase,
-        WindowEvent,
+        DeviceEvent, ElementState, Event, Force, Ime, InnerSizeWriter, MouseButton, RawKeyEvent,
+        Touch, TouchPhase, WindowEvent,
     },
     event_loop::{ControlFlow, DeviceEvents, EventLoopClosed, EventLoopWindowTarget as RootELW},
     keyboard::ModifiersState,
@@ -947,6 +949,39 @@ fn normalize_pointer_pressure(pressure: u32) -> Option<Force> {
     }
 }
 
+/// The time the message being processed was generated at, for the input events.
+fn message_time<T>(runner: &EventLoopRunner<T>) -> Instant {
+    runner
+        .event_clock
+        .instant(unsafe { GetMessageTime() } as u32)
+}
+
+/// Count the consecutive clicks with the double-click settings of the system.
+fn click_count(
+    window: HWND,
+    userdata: &WindowData,
+    state: ElementState,
+    button: MouseButton,
+    lparam: LPARAM,
+) -> u32 {
+    let x = super::get_x_lparam(lparam as u32) as f64;
+    let y = super::get_y_lparam(lparam as u32) as f64;
+
+    let mut window_state = userdata.window_state_lock();
+    let counter = &mut window_state.click_counter;
+    // The settings can be changed at any time, so query them on every click.
+    counter.interval = Duration::from_millis(unsafe { GetDoubleClickTime() } as u64);
+    // The metric is the size of the rectangle centered on the first click.
+    counter.distance = unsafe { GetSystemMetrics(SM_CXDOUBLECLK) } as f64 / 2.0;
+    counter.count(
+        RootWindowId(WindowId(window)),
+        state,
+        button,
+        PhysicalPosition::new(x, y),
+        message_time(&userdata.event_loop_runner),
+    )
+}
+
 /// Emit a `ModifiersChanged` event whenever modifiers have changed.
 /// Returns the current modifier state
 fn update_modifiers(window: HWND, userdata: &WindowData) {
@@ -967,6 +1002,7 @@ fn update_modifiers(window: HWND, userdata: &WindowData) {
         userdata.send_event(Event::WindowEvent {
             window_id: RootWindowId(WindowId(window)),
             event: ModifiersChanged(modifiers.into()),
+            timestamp: message_time(&userdata.event_loop_runner),
         });
     }
 }
@@ -979,6 +1015,7 @@ unsafe fn gain_active_focus(window: HWND, userdata: &WindowData) {
     userdata.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window)),
         event: Focused(true),
+        timestamp: Instant::now(),
     });
 }
 
@@ -989,11 +1026,13 @@ unsafe fn lose_active_focus(window: HWND, userdata: &WindowData) {
     userdata.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window)),
         event: ModifiersChanged(ModifiersState::empty().into()),
+        timestamp: Instant::now(),
     });
 
     userdata.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window)),
         event: Focused(false),
+        timestamp: Instant::now(),
     });
 }
 
@@ -1099,6 +1138,7 @@ unsafe fn public_window_callback_inner(
                     event: event.event,
                     is_synthetic: event.is_synthetic,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
         }
     };
@@ -1180,6 +1220,7 @@ unsafe fn public_window_callback_inner(
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: CloseRequested,
+                timestamp: Instant::now(),
             });
             result = ProcResult::Value(0);
         }
@@ -1190,6 +1231,7 @@ unsafe fn public_window_callback_inner(
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: Destroyed,
+                timestamp: Instant::now(),
             });
             result = ProcResult::Value(0);
         }
@@ -1211,6 +1253,7 @@ unsafe fn public_window_callback_inner(
                 userdata.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(window)),
                     event: WindowEvent::RedrawRequested,
+                    timestamp: Instant::now(),
                 });
             }
 
@@ -1312,6 +1355,7 @@ unsafe fn public_window_callback_inner(
                 userdata.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(window)),
                     event: Moved(physical_position),
+                    timestamp: Instant::now(),
                 });
             }
 
@@ -1328,6 +1372,7 @@ unsafe fn public_window_callback_inner(
             let event = Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: Resized(physical_size),
+                timestamp: Instant::now(),
             };
 
             {
@@ -1357,6 +1402,7 @@ unsafe fn public_window_callback_inner(
                 userdata.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(window)),
                     event: WindowEvent::Ime(Ime::Enabled),
+                    timestamp: Instant::now(),
                 });
             }
 
@@ -1377,6 +1423,7 @@ unsafe fn public_window_callback_inner(
                     userdata.send_event(Event::WindowEvent {
                         window_id: RootWindowId(WindowId(window)),
                         event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                        timestamp: Instant::now(),
                     });
                 }
 
@@ -1389,10 +1436,12 @@ unsafe fn public_window_callback_inner(
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
                             event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                            timestamp: Instant::now(),
                         });
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
                             event: WindowEvent::Ime(Ime::Commit(text)),
+                            timestamp: Instant::now(),
                         });
                     }
                 }
@@ -1404,11 +1453,20 @@ unsafe fn public_window_callback_inner(
                     {
                         userdata.window_state_lock().ime_state = ImeState::Preedit;
                         let cursor_range = first.map(|f| (f, last.unwrap_or(f)));
+                        let segments = unsafe { ime_context.get_composing_segments(&text) };
 
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
                             event: WindowEvent::Ime(Ime::Preedit(text, cursor_range)),
+                            timestamp: Instant::now(),
                         });
+                        if !segments.is_empty() {
+                            userdata.send_event(Event::WindowEvent {
+                                window_id: RootWindowId(WindowId(window)),
+                                event: WindowEvent::Ime(Ime::PreeditSegments(segments)),
+                                timestamp: Instant::now(),
+                            });
+                        }
                     }
                 }
             }
@@ -1431,10 +1489,12 @@ unsafe fn public_window_callback_inner(
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
                             event: WindowEvent::Ime(Ime::Preedit(String::new(), None)),
+                            timestamp: Instant::now(),
                         });
                         userdata.send_event(Event::WindowEvent {
                             window_id: RootWindowId(WindowId(window)),
                             event: WindowEvent::Ime(Ime::Commit(text)),
+                            timestamp: Instant::now(),
                         });
                     }
                 }
@@ -1444,6 +1504,7 @@ unsafe fn public_window_callback_inner(
                 userdata.send_event(Event::WindowEvent {
                     window_id: RootWindowId(WindowId(window)),
                     event: WindowEvent::Ime(Ime::Disabled),
+                    timestamp: Instant::now(),
                 });
             }
 
@@ -1504,6 +1565,7 @@ unsafe fn public_window_callback_inner(
                             event: CursorEntered {
                                 device_id: DEVICE_ID,
                             },
+                            timestamp: message_time(&userdata.event_loop_runner),
                         });
 
                         // Calling TrackMouseEvent in order to receive mouse leave events.
@@ -1527,6 +1589,7 @@ unsafe fn public_window_callback_inner(
                             event: CursorLeft {
                                 device_id: DEVICE_ID,
                             },
+                            timestamp: message_time(&userdata.event_loop_runner),
                         });
                     }
                     PointerMoveKind::None => drop(w),
@@ -1549,6 +1612,7 @@ unsafe fn public_window_callback_inner(
                         device_id: DEVICE_ID,
                         position,
                     },
+                    timestamp: message_time(&userdata.event_loop_runner),
                 });
             }
 
@@ -1569,6 +1633,7 @@ unsafe fn public_window_callback_inner(
                 event: CursorLeft {
                     device_id: DEVICE_ID,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
 
             result = ProcResult::Value(0);
@@ -1589,6 +1654,7 @@ unsafe fn public_window_callback_inner(
                     delta: LineDelta(0.0, value),
                     phase: TouchPhase::Moved,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
 
             result = ProcResult::Value(0);
@@ -1609,6 +1675,7 @@ unsafe fn public_window_callback_inner(
                     delta: LineDelta(value, 0.0),
                     phase: TouchPhase::Moved,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
 
             result = ProcResult::Value(0);
@@ -1635,13 +1702,16 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let click_count = click_count(window, userdata, Pressed, Left, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Left,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1655,13 +1725,16 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let click_count = click_count(window, userdata, Released, Left, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Left,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1675,13 +1748,16 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let click_count = click_count(window, userdata, Pressed, Right, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Right,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1695,13 +1771,16 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let click_count = click_count(window, userdata, Released, Right, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Right,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1715,13 +1794,16 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let click_count = click_count(window, userdata, Pressed, Middle, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Pressed,
                     button: Middle,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1735,13 +1817,16 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let click_count = click_count(window, userdata, Released, Middle, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Released,
                     button: Middle,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1757,17 +1842,21 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let button = match xbutton {
+                1 => Back,
+                2 => Forward,
+                _ => Other(xbutton),
+            };
+            let click_count = click_count(window, userdata, Pressed, button, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Pressed,
-                    button: match xbutton {
-                        1 => Back,
-                        2 => Forward,
-                        _ => Other(xbutton),
-                    },
+                    button,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1783,17 +1872,21 @@ unsafe fn public_window_callback_inner(
 
             update_modifiers(window, userdata);
 
+            let button = match xbutton {
+                1 => Back,
+                2 => Forward,
+                _ => Other(xbutton),
+            };
+            let click_count = click_count(window, userdata, Released, button, lparam);
             userdata.send_event(Event::WindowEvent {
                 window_id: RootWindowId(WindowId(window)),
                 event: MouseInput {
                     device_id: DEVICE_ID,
                     state: Released,
-                    button: match xbutton {
-                        1 => Back,
-                        2 => Forward,
-                        _ => Other(xbutton),
-                    },
+                    button,
+                    click_count,
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
             result = ProcResult::Value(0);
         }
@@ -1852,6 +1945,7 @@ unsafe fn public_window_callback_inner(
                             id: input.dwID as u64,
                             device_id: DEVICE_ID,
                         }),
+                        timestamp: message_time(&userdata.event_loop_runner),
                     });
                 }
             }
@@ -2000,6 +2094,7 @@ unsafe fn public_window_callback_inner(
                             id: pointer_info.pointerId as u64,
                             device_id: DEVICE_ID,
                         }),
+                        timestamp: message_time(&userdata.event_loop_runner),
                     });
                 }
 
@@ -2176,6 +2271,7 @@ unsafe fn public_window_callback_inner(
                     scale_factor: new_scale_factor,
                     inner_size_writer: InnerSizeWriter::new(Arc::downgrade(&new_inner_size)),
                 },
+                timestamp: Instant::now(),
             });
 
             let new_physical_inner_size = *new_inner_size.lock().unwrap();
@@ -2328,6 +2424,7 @@ unsafe fn public_window_callback_inner(
                     userdata.send_event(Event::WindowEvent {
                         window_id: RootWindowId(WindowId(window)),
                         event: ThemeChanged(new_theme),
+                        timestamp: Instant::now(),
                     });
                 }
             }
@@ -2411,6 +2508,7 @@ unsafe extern "system" fn thread_event_target_callback(
             userdata.send_event(Event::DeviceEvent {
                 device_id: wrap_device_id(lparam as u32),
                 event,
+                timestamp: message_time(&userdata.event_loop_runner),
             });
 
             0
@@ -2473,6 +2571,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: Motion { axis: 0, value: x },
+                    timestamp: message_time(&userdata.event_loop_runner),
                 });
             }
 
@@ -2480,6 +2579,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: Motion { axis: 1, value: y },
+                    timestamp: message_time(&userdata.event_loop_runner),
                 });
             }
 
@@ -2487,6 +2587,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                 userdata.send_event(Event::DeviceEvent {
                     device_id,
                     event: MouseMotion { delta: (x, y) },
+                    timestamp: message_time(&userdata.event_loop_runner),
                 });
             }
         }
@@ -2500,6 +2601,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                 event: MouseWheel {
                     delta: LineDelta(0.0, delta),
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
         }
         if util::has_flag(button_flags as u32, RI_MOUSE_HWHEEL) {
@@ -2510,6 +2612,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                 event: MouseWheel {
                     delta: LineDelta(delta, 0.0),
                 },
+                timestamp: message_time(&userdata.event_loop_runner),
             });
         }
 
@@ -2522,6 +2625,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                         button: button as _,
                         state,
                     },
+                    timestamp: message_time(&userdata.event_loop_runner),
                 });
             }
         }
@@ -2544,6 +2648,7 @@ unsafe fn handle_raw_input(userdata: &ThreadMsgTargetData, data: RAWINPUT) {
                     physical_key,
                     state,
                 }),
+                timestamp: message_time(&userdata.event_loop_runner),
             });
         }
     }
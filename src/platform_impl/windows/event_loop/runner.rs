@@ -13,9 +13,12 @@ use windows_sys::Win32::Foundation::HWND;
 use crate::{
     dpi::PhysicalSize,
     event::{Event, InnerSizeWriter, StartCause, WindowEvent},
-    platform_impl::platform::{
-        event_loop::{WindowData, GWL_USERDATA},
-        get_window_long,
+    platform_impl::{
+        event_clock::EventClock,
+        platform::{
+            event_loop::{WindowData, GWL_USERDATA},
+            get_window_long,
+        },
     },
     window::WindowId,
 };
@@ -42,6 +45,10 @@ pub(crate) struct EventLoopRunner<T: 'static> {
     event_handler: EventHandler<T>,
     event_buffer: RefCell<VecDeque<BufferedEvent<T>>>,
 
+    // Converts the message times of the input events, and clamps the timestamps of all the
+    // events delivered
+    pub(super) event_clock: EventClock,
+
     panic_error: Cell<Option<PanicError>>,
 }
 
@@ -78,6 +85,7 @@ impl<T> EventLoopRunner<T> {
             last_events_cleared: Cell::new(Instant::now()),
             event_handler: Cell::new(None),
             event_buffer: RefCell::new(VecDeque::new()),
+            event_clock: EventClock::new(),
         }
     }
 
@@ -120,6 +128,7 @@ impl<T> EventLoopRunner<T> {
             last_events_cleared: _,
             event_handler,
             event_buffer: _,
+            event_clock: _,
         } = self;
         interrupt_msg_dispatch.set(false);
         runner_state.set(RunnerState::Uninitialized);
@@ -247,7 +256,7 @@ impl<T> EventLoopRunner<T> {
             let mut event_handler = self.event_handler.take()
                 .expect("either event handler is re-entrant (likely), or no event handler is registered (very unlikely)");
 
-            event_handler(event);
+            event_handler(self.event_clock.stamp(event));
 
             assert!(self.event_handler.replace(Some(event_handler)).is_none());
         });
@@ -385,6 +394,7 @@ impl<T> BufferedEvent<T> {
                         inner_size_writer,
                     },
                 window_id,
+                ..
             } => BufferedEvent::ScaleFactorChanged(
                 window_id,
                 scale_factor,
@@ -410,6 +420,7 @@ impl<T> BufferedEvent<T> {
                         scale_factor,
                         inner_size_writer: InnerSizeWriter::new(Arc::downgrade(&new_inner_size)),
                     },
+                    timestamp: Instant::now(),
                 });
                 let inner_size = *new_inner_size.lock().unwrap();
                 drop(new_inner_size);
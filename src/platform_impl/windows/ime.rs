@@ -10,9 +10,10 @@ use windows_sys::Win32::{
     UI::{
         Input::Ime::{
             ImmAssociateContextEx, ImmGetCompositionStringW, ImmGetContext, ImmReleaseContext,
-            ImmSetCandidateWindow, ImmSetCompositionWindow, ATTR_TARGET_CONVERTED,
-            ATTR_TARGET_NOTCONVERTED, CANDIDATEFORM, CFS_EXCLUDE, CFS_POINT, COMPOSITIONFORM,
-            GCS_COMPATTR, GCS_COMPSTR, GCS_CURSORPOS, GCS_RESULTSTR, IACE_CHILDREN, IACE_DEFAULT,
+            ImmSetCandidateWindow, ImmSetCompositionWindow, ATTR_CONVERTED, ATTR_INPUT,
+            ATTR_INPUT_ERROR, ATTR_TARGET_CONVERTED, ATTR_TARGET_NOTCONVERTED, CANDIDATEFORM,
+            CFS_EXCLUDE, CFS_POINT, COMPOSITIONFORM, GCS_COMPATTR, GCS_COMPSTR, GCS_CURSORPOS,
+            GCS_RESULTSTR, IACE_CHILDREN, IACE_DEFAULT,
         },
         WindowsAndMessaging::{GetSystemMetrics, SM_IMMENABLED},
     },
@@ -20,6 +21,7 @@ use windows_sys::Win32::{
 
 use crate::{
     dpi::{Position, Size},
+    event::{PreeditSegment, PreeditStyle},
     platform::windows::HWND,
 };
 
@@ -69,6 +71,53 @@ impl ImeContext {
         Some((text, first, last))
     }
 
+    /// The styling of the clauses of the composing `text`, from the composition attributes.
+    pub unsafe fn get_composing_segments(&self, text: &str) -> Vec<PreeditSegment> {
+        let attrs = unsafe { self.get_composition_data(GCS_COMPATTR) }.unwrap_or_default();
+
+        let mut segments: Vec<PreeditSegment> = Vec::new();
+        let mut boundary_before_char = 0;
+        // The attributes are per UTF-16 code unit, thus two for the characters outside of the BMP.
+        let mut code_unit = 0;
+
+        for chr in text.chars() {
+            let Some(&attr) = attrs.get(code_unit) else {
+                break;
+            };
+            code_unit += chr.len_utf16();
+
+            let style = match attr as u32 {
+                ATTR_INPUT | ATTR_CONVERTED | ATTR_INPUT_ERROR => PreeditStyle {
+                    underline: true,
+                    ..Default::default()
+                },
+                ATTR_TARGET_CONVERTED | ATTR_TARGET_NOTCONVERTED => PreeditStyle {
+                    underline: true,
+                    highlight: true,
+                    selected: true,
+                    ..Default::default()
+                },
+                _ => PreeditStyle::default(),
+            };
+
+            let end = boundary_before_char + chr.len_utf8();
+            match segments.last_mut() {
+                Some(last) if last.range.end == boundary_before_char && last.style == style => {
+                    last.range.end = end;
+                }
+                _ if style != PreeditStyle::default() => segments.push(PreeditSegment {
+                    range: boundary_before_char..end,
+                    style,
+                }),
+                _ => (),
+            }
+
+            boundary_before_char = end;
+        }
+
+        segments
+    }
+
     pub unsafe fn get_composed_text(&self) -> Option<String> {
         unsafe { self.get_composition_string(GCS_RESULTSTR) }
     }
@@ -83,8 +83,8 @@ use crate::{
         Fullscreen, SelectedCursor, WindowId,
     },
     window::{
-        CursorGrabMode, ImePurpose, ResizeDirection, Theme, UserAttentionType, WindowAttributes,
-        WindowButtons, WindowLevel,
+        CursorGrabMode, ImeHints, ImePurpose, ResizeDirection, Theme, UserAttentionType,
+        WindowAttributes, WindowButtons, WindowLevel,
     },
 };
 
@@ -146,6 +146,12 @@ impl Window {
 
     pub fn set_blur(&self, _blur: bool) {}
 
+    #[inline]
+    pub fn set_idle_inhibit(&self, _inhibit: bool) {}
+
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, _inhibit: bool) {}
+
     #[inline]
     pub fn set_visible(&self, visible: bool) {
         let window = self.window;
@@ -950,6 +956,12 @@ impl Window {
     #[inline]
     pub fn set_ime_purpose(&self, _purpose: ImePurpose) {}
 
+    #[inline]
+    pub fn set_ime_hints(&self, _hints: ImeHints) {}
+
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, _text: String, _cursor: usize, _anchor: usize) {}
+
     #[inline]
     pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
         let window = self.window;
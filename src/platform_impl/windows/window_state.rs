@@ -2,6 +2,7 @@ use crate::{
     dpi::{PhysicalPosition, PhysicalSize, Size},
     icon::Icon,
     keyboard::ModifiersState,
+    platform_impl::click_counter::ClickCounter,
     platform_impl::platform::{event_loop, util, Fullscreen, SelectedCursor},
     window::{Theme, WindowAttributes},
 };
@@ -28,6 +29,7 @@ use windows_sys::Win32::{
 /// Contains information about states and the window that the callback is going to use.
 pub(crate) struct WindowState {
     pub mouse: MouseProperties,
+    pub click_counter: ClickCounter,
 
     /// Used by `WM_GETMINMAXINFO`.
     pub min_size: Option<Size>,
@@ -151,6 +153,7 @@ impl WindowState {
                 cursor_flags: CursorFlags::empty(),
                 last_position: None,
             },
+            click_counter: ClickCounter::default(),
 
             min_size: attributes.min_inner_size,
             max_size: attributes.max_inner_size,
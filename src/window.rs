@@ -293,6 +293,7 @@ impl WindowBuilder {
     ///   the specifics of the Window Manager.
     /// - **X11:** The top left corner of the window, the window's "outer"
     ///   position.
+    /// - **Wayland:** The top left corner of a child window, relative to its parent.
     /// - **Others:** Ignored.
     #[inline]
     pub fn with_position<P: Into<Position>>(mut self, position: P) -> Self {
@@ -520,7 +521,12 @@ impl WindowBuilder {
     /// to the client area of its parent window. For more information, see
     /// <https://docs.microsoft.com/en-us/windows/win32/winmsg/window-features#child-windows>
     /// - **X11**: A child window is confined to the client area of its parent window.
-    /// - **Android / iOS / Wayland / Web:** Unsupported.
+    /// - **Wayland:** A child window is a `wl_subsurface` of its parent, placed relative to it with
+    ///   [`WindowBuilder::with_position`] and shown with the next commit of the parent. It's
+    ///   drawn independently of its parent, unless it's synchronized with
+    ///   `WindowExtWayland::set_subsurface_sync`. A parent which isn't a Wayland window fails
+    ///   the creation of the window.
+    /// - **Android / iOS / Web:** Unsupported.
     #[cfg(feature = "rwh_06")]
     #[inline]
     pub unsafe fn with_parent_window(
@@ -698,7 +704,9 @@ impl Window {
     ///   window's [safe area] in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport. _Note: this returns the
     ///    same value as [`Window::outer_position`]._
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **Wayland:** Returns the coordinates relative to the parent of a child window, and
+    ///   [`NotSupportedError`] for other windows.
+    /// - **Android:** Always returns [`NotSupportedError`].
     ///
     /// [safe area]: https://developer.apple.com/documentation/uikit/uiview/2891103-safeareainsets?language=objc
     #[inline]
@@ -721,7 +729,9 @@ impl Window {
     /// - **iOS:** Can only be called on the main thread. Returns the top left coordinates of the
     ///   window in the screen space coordinate system.
     /// - **Web:** Returns the top-left coordinates relative to the viewport.
-    /// - **Android / Wayland:** Always returns [`NotSupportedError`].
+    /// - **Wayland:** Returns the coordinates relative to the parent of a child window, and
+    ///   [`NotSupportedError`] for other windows.
+    /// - **Android:** Always returns [`NotSupportedError`].
     #[inline]
     pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
         self.window.maybe_wait_on_main(|w| w.outer_position())
@@ -751,7 +761,9 @@ impl Window {
     ///   window in the screen space coordinate system.
     /// - **Web:** Sets the top-left coordinates relative to the viewport. Doesn't account for CSS
     ///   [`transform`].
-    /// - **Android / Wayland:** Unsupported.
+    /// - **Wayland:** Sets the coordinates relative to the parent of a child window, applied with
+    ///   the next commit of the parent. Unsupported for other windows.
+    /// - **Android:** Unsupported.
     ///
     /// [`transform`]: https://developer.mozilla.org/en-US/docs/Web/CSS/transform
     #[inline]
@@ -959,6 +971,44 @@ impl Window {
         self.window.maybe_queue_on_main(move |w| w.set_blur(blur))
     }
 
+    /// Keep the screen from blanking and the system from going idle, such as while playing a
+    /// video.
+    ///
+    /// The inhibition ends when it's set to `false` or the window is dropped.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Android / iOS / macOS / Web / Windows / Orbital:** Unsupported.
+    /// - **Wayland:** Only works with the `zwp_idle_inhibit_manager_v1` protocol, and only while
+    ///   the window is visible.
+    /// - **X11:** Suspends the screen saver with the MIT-SCREEN-SAVER extension, even while the
+    ///   window is hidden.
+    #[inline]
+    pub fn set_idle_inhibit(&self, inhibit: bool) {
+        self.window
+            .maybe_queue_on_main(move |w| w.set_idle_inhibit(inhibit))
+    }
+
+    /// Send the keyboard shortcuts of the system to the window while it has the keyboard focus,
+    /// such as for a remote desktop client or a virtual machine.
+    ///
+    /// Whether the shortcuts are inhibited is reported with
+    /// [`WindowEvent::KeyboardShortcutsInhibited`], since the system may refuse or revoke the
+    /// inhibition. Disabling it with `false` doesn't send the event.
+    ///
+    /// [`WindowEvent::KeyboardShortcutsInhibited`]: crate::event::WindowEvent::KeyboardShortcutsInhibited
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **Android / iOS / macOS / Web / Windows / Orbital:** Unsupported.
+    /// - **Wayland:** Only works with the `zwp_keyboard_shortcuts_inhibit_manager_v1` protocol.
+    /// - **X11:** Actively grabs the keyboard while the window is focused.
+    #[inline]
+    pub fn set_keyboard_shortcuts_inhibit(&self, inhibit: bool) {
+        self.window
+            .maybe_queue_on_main(move |w| w.set_keyboard_shortcuts_inhibit(inhibit))
+    }
+
     /// Modifies the window's visibility.
     ///
     /// If `false`, this will hide the window. If `true`, this will show the window.
@@ -1176,13 +1226,16 @@ impl Window {
     ///
     /// ## Platform-specific
     ///
-    /// - **iOS / Android / Web / Wayland / macOS / Orbital:** Unsupported.
+    /// - **iOS / Android / Web / macOS / Orbital:** Unsupported.
     ///
     /// - **Windows:** Sets `ICON_SMALL`. The base size for a window icon is 16x16, but it's
     ///   recommended to account for screen scaling and pick a multiple of that, i.e. 32x32.
     ///
     /// - **X11:** Has no universal guidelines for icon sizes, so you're at the whims of the WM. That
     ///   said, it's usually in the same ballpark as on Windows.
+    ///
+    /// - **Wayland:** Requires the `xdg_toplevel_icon_v1` protocol, and is applied with the next
+    ///   redraw. Icons which aren't square are centered in a square.
     #[inline]
     pub fn set_window_icon(&self, window_icon: Option<Icon>) {
         self.window
@@ -1262,13 +1315,49 @@ impl Window {
     ///
     /// ## Platform-specific
     ///
-    /// - **iOS / Android / Web / Windows / X11 / macOS / Orbital:** Unsupported.
+    /// - **iOS / Android / Windows / macOS / Orbital:** Unsupported.
+    /// - **X11:** Only supported with the input methods on D-Bus, see `platform::dbus_ime`.
     #[inline]
     pub fn set_ime_purpose(&self, purpose: ImePurpose) {
         self.window
             .maybe_queue_on_main(move |w| w.set_ime_purpose(purpose))
     }
 
+    /// Sets the hints about the text expected from the IME using [`ImeHints`].
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Windows / macOS / Orbital:** Unsupported.
+    /// - **X11:** Only supported with the input methods on D-Bus, see `platform::dbus_ime`.
+    #[inline]
+    pub fn set_ime_hints(&self, hints: ImeHints) {
+        self.window
+            .maybe_queue_on_main(move |w| w.set_ime_hints(hints))
+    }
+
+    /// Sets the text around the cursor of the text input, so the IME can take it into account,
+    /// such as for the Korean and Thai input methods, or the autocorrection of the on-screen
+    /// keyboards.
+    ///
+    /// The `cursor` and `anchor` are the byte offsets in `text` of the cursor and of the other end
+    /// of the selection, which are equal without selection. They must lie on character
+    /// boundaries, otherwise the text is ignored. The text should be set again after each change,
+    /// including the ones from the [`Ime::Commit`] and [`Ime::DeleteSurrounding`] events.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Web / Windows / macOS / Orbital:** Unsupported.
+    /// - **Wayland:** The text is trimmed around the cursor to the limit of the protocol.
+    /// - **X11:** The text is only provided when the XIM server requests it.
+    ///
+    /// [`Ime::Commit`]: crate::event::Ime::Commit
+    /// [`Ime::DeleteSurrounding`]: crate::event::Ime::DeleteSurrounding
+    #[inline]
+    pub fn set_ime_surrounding_text(&self, text: String, cursor: usize, anchor: usize) {
+        self.window
+            .maybe_queue_on_main(move |w| w.set_ime_surrounding_text(text, cursor, anchor))
+    }
+
     /// Brings the window to the front and sets input focus. Has no effect if the window is
     /// already in focus, minimized, or not visible.
     ///
@@ -1759,7 +1848,10 @@ pub enum WindowLevel {
 ///
 /// ## Platform-specific
 ///
-/// - **iOS / Android / Web / Windows / X11 / macOS / Orbital:** Unsupported.
+/// - **iOS / Android / Windows / macOS / Orbital:** Unsupported.
+/// - **X11:** Only supported with the input methods on D-Bus, see `platform::dbus_ime`.
+/// - **Web:** Sets the `inputmode` of the canvas, which only picks the layout of the on-screen
+///   keyboards.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 #[non_exhaustive]
 pub enum ImePurpose {
@@ -1771,6 +1863,28 @@ pub enum ImePurpose {
     ///
     /// For example, that could alter OSK on Wayland to show extra buttons.
     Terminal,
+    /// The IME is used to input letters only.
+    Alpha,
+    /// The IME is used to input digits only, such as for a code.
+    Digits,
+    /// The IME is used to input a number, with its sign and decimal separator.
+    Number,
+    /// The IME is used to input a phone number.
+    Phone,
+    /// The IME is used to input a URL.
+    Url,
+    /// The IME is used to input an email address.
+    Email,
+    /// The IME is used to input the name of a person.
+    Name,
+    /// The IME is used to input a PIN, which is both digits and sensitive.
+    Pin,
+    /// The IME is used to input a date.
+    Date,
+    /// The IME is used to input a time.
+    Time,
+    /// The IME is used to input a date and a time.
+    DateTime,
 }
 
 impl Default for ImePurpose {
@@ -1779,10 +1893,48 @@ impl Default for ImePurpose {
     }
 }
 
+bitflags::bitflags! {
+    /// Hints about the text expected from the IME, for use in [`Window::set_ime_hints`].
+    ///
+    /// They complement the [`ImePurpose`], such as to disable the completion and spellcheck of
+    /// the on-screen keyboards on code fields.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **iOS / Android / Windows / macOS / Orbital:** Unsupported.
+    /// - **X11:** Only supported with the input methods on D-Bus, see `platform::dbus_ime`.
+    /// - **Web:** Only the spellcheck and the capitalization are supported.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ImeHints: u32 {
+        /// Suggest completions of the text.
+        const COMPLETION = 1 << 0;
+        /// Suggest corrections of the spelling.
+        const SPELLCHECK = 1 << 1;
+        /// Capitalize the first letter of the sentences.
+        const AUTO_CAPITALIZATION = 1 << 2;
+        /// Prefer lowercase letters.
+        const LOWERCASE = 1 << 3;
+        /// Prefer uppercase letters.
+        const UPPERCASE = 1 << 4;
+        /// Capitalize the first letter of the words.
+        const TITLECASE = 1 << 5;
+        /// Hide the characters as they are typed.
+        const HIDDEN_TEXT = 1 << 6;
+        /// Don't learn from the text or suggest it later.
+        const SENSITIVE_DATA = 1 << 7;
+        /// Only input latin characters.
+        const LATIN = 1 << 8;
+        /// The text can span multiple lines.
+        const MULTILINE = 1 << 9;
+    }
+}
+
 /// An opaque token used to activate the [`Window`].
 ///
 /// [`Window`]: crate::window::Window
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct ActivationToken {
     pub(crate) _token: String,
 }
@@ -0,0 +1,348 @@
+#![cfg(all(target_os = "linux", any(feature = "x11", feature = "wayland")))]
+
+use std::time::{Duration, Instant};
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::error::ExternalError;
+use winit::event::{DeviceId, DndAction, ElementState, Event, Ime, MouseButton, WindowEvent};
+use winit::event_loop::{EventLoop, EventLoopBuilder};
+use winit::keyboard::{Key, KeyCode, ModifiersState, PhysicalKey};
+use winit::platform::clipboard::{ClipboardContents, EventLoopWindowTargetExtClipboard};
+use winit::platform::drag_and_drop::{
+    DragSource, EventLoopWindowTargetExtDragAndDrop, WindowExtDragAndDrop,
+};
+use winit::platform::headless::{EventLoopBuilderExtHeadless, EventLoopWindowTargetExtHeadless};
+use winit::platform::keyboard_layout::EventLoopWindowTargetExtKeyboardLayout;
+use winit::platform::pump_events::EventLoopExtPumpEvents;
+use winit::platform::synthetic_input::{EventLoopWindowTargetExtSyntheticInput, SyntheticInput};
+#[cfg(not(feature = "x11"))]
+use winit::platform::wayland::EventLoopBuilderExtWayland as _;
+#[cfg(feature = "x11")]
+use winit::platform::x11::EventLoopBuilderExtX11 as _;
+use winit::window::{WindowBuilder, WindowId};
+
+/// Run a single loop iteration, returning the window events it has dispatched.
+fn pump_window_events(event_loop: &mut EventLoop<()>) -> Vec<(WindowId, WindowEvent)> {
+    let mut events = Vec::new();
+    event_loop.pump_events(Some(Duration::ZERO), |event, _| {
+        if let Event::WindowEvent {
+            window_id, event, ..
+        } = event
+        {
+            events.push((window_id, event));
+        }
+    });
+    events
+}
+
+// NOTE: Only a single event loop can be created per process, thus everything is tested at once.
+#[test]
+fn headless_event_loop() {
+    let mut event_loop = EventLoopBuilder::new()
+        .with_headless()
+        .with_any_thread(true)
+        .build()
+        .unwrap();
+    assert!(event_loop.is_headless());
+
+    let window = WindowBuilder::new()
+        .with_inner_size(PhysicalSize::new(200, 100))
+        .build(&event_loop)
+        .unwrap();
+    let id = window.id();
+
+    // A new window gets the focus and its first frame.
+    let events = pump_window_events(&mut event_loop);
+    assert_eq!(
+        events,
+        [
+            (id, WindowEvent::Focused(true)),
+            (id, WindowEvent::RedrawRequested)
+        ]
+    );
+    assert!(window.has_focus());
+    assert_eq!(window.inner_size(), PhysicalSize::new(200, 100));
+
+    // Geometry requests are applied immediately.
+    assert_eq!(
+        window.request_inner_size(PhysicalSize::new(300, 200)),
+        Some(PhysicalSize::new(300, 200))
+    );
+    window.set_outer_position(PhysicalPosition::new(10, 20));
+    let events = pump_window_events(&mut event_loop);
+    assert_eq!(
+        events,
+        [
+            (id, WindowEvent::Resized(PhysicalSize::new(300, 200))),
+            (id, WindowEvent::Moved(PhysicalPosition::new(10, 20))),
+        ]
+    );
+    assert_eq!(
+        window.outer_position().unwrap(),
+        PhysicalPosition::new(10, 20)
+    );
+
+    // Scale factor changes are negotiated with the application.
+    event_loop.set_headless_scale_factor(2.).unwrap();
+    let events = pump_window_events(&mut event_loop);
+    assert!(matches!(
+        events[0],
+        (window_id, WindowEvent::ScaleFactorChanged { scale_factor, .. })
+            if window_id == id && scale_factor == 2.
+    ));
+    assert_eq!(
+        events[1],
+        (id, WindowEvent::Resized(PhysicalSize::new(600, 400)))
+    );
+    assert_eq!(window.scale_factor(), 2.);
+
+    // Injected input is dispatched as is.
+    let device_id = unsafe { DeviceId::dummy() };
+    let cursor_moved = WindowEvent::CursorMoved {
+        device_id,
+        position: PhysicalPosition::new(5., 5.),
+    };
+    event_loop
+        .inject_window_event(id, cursor_moved.clone())
+        .unwrap();
+    assert_eq!(
+        pump_window_events(&mut event_loop),
+        [(id, cursor_moved.clone())]
+    );
+
+    // Events are stamped with the time they were injected at.
+    let injected_at = Instant::now();
+    event_loop.inject_window_event(id, cursor_moved).unwrap();
+    let mut timestamps = Vec::new();
+    event_loop.pump_events(Some(Duration::ZERO), |event, _| {
+        timestamps.extend(event.timestamp());
+    });
+    assert_eq!(timestamps.len(), 1);
+    assert!(timestamps[0] >= injected_at && timestamps[0] <= Instant::now());
+
+    // The focus moves to the new window.
+    let other_window = WindowBuilder::new().build(&event_loop).unwrap();
+    let other_id = other_window.id();
+    let events = pump_window_events(&mut event_loop);
+    assert_eq!(
+        events,
+        [
+            (id, WindowEvent::Focused(false)),
+            (other_id, WindowEvent::Focused(true)),
+            (other_id, WindowEvent::RedrawRequested),
+        ]
+    );
+    assert!(!window.has_focus());
+
+    event_loop
+        .inject_window_event(id, WindowEvent::Focused(true))
+        .unwrap();
+    let events = pump_window_events(&mut event_loop);
+    assert_eq!(
+        events,
+        [
+            (other_id, WindowEvent::Focused(false)),
+            (id, WindowEvent::Focused(true)),
+        ]
+    );
+
+    // Synthetic keys are translated with the keymap and update the modifiers.
+    let key = |key_code, state| SyntheticInput::KeyboardInput {
+        physical_key: PhysicalKey::Code(key_code),
+        state,
+        repeat: false,
+    };
+    event_loop
+        .inject_input(id, key(KeyCode::ShiftLeft, ElementState::Pressed))
+        .unwrap();
+    event_loop
+        .inject_input(id, key(KeyCode::KeyA, ElementState::Pressed))
+        .unwrap();
+    event_loop
+        .inject_input(id, key(KeyCode::ShiftLeft, ElementState::Released))
+        .unwrap();
+    let events = pump_window_events(&mut event_loop);
+    assert_eq!(events.len(), 5);
+    assert!(matches!(
+        &events[1],
+        (window_id, WindowEvent::ModifiersChanged(modifiers))
+            if *window_id == id && modifiers.state() == ModifiersState::SHIFT
+    ));
+    match &events[2] {
+        (window_id, WindowEvent::KeyboardInput { event, .. }) if *window_id == id => {
+            assert_eq!(event.physical_key, PhysicalKey::Code(KeyCode::KeyA));
+            assert_eq!(event.logical_key, Key::Character("A".into()));
+            assert_eq!(event.text.as_deref(), Some("A"));
+        }
+        event => panic!("unexpected event: {event:?}"),
+    }
+    assert!(matches!(
+        &events[4],
+        (window_id, WindowEvent::ModifiersChanged(modifiers))
+            if *window_id == id && modifiers.state().is_empty()
+    ));
+
+    // The layouts are those of the default keymap used for the synthetic keys.
+    let layouts = event_loop.keyboard_layouts();
+    let layout = event_loop.keyboard_layout().unwrap();
+    assert_eq!(layout.index, 0);
+    assert_eq!(Some(&layout.name), layouts.first());
+
+    event_loop
+        .inject_input(
+            id,
+            SyntheticInput::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+            },
+        )
+        .unwrap();
+    assert!(matches!(
+        pump_window_events(&mut event_loop)[..],
+        [(window_id, WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, click_count: 1, .. })]
+            if window_id == id
+    ));
+
+    // A quick second click is counted as a double click.
+    for state in [ElementState::Released, ElementState::Pressed] {
+        event_loop
+            .inject_input(
+                id,
+                SyntheticInput::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                },
+            )
+            .unwrap();
+    }
+    assert!(matches!(
+        pump_window_events(&mut event_loop)[..],
+        [
+            (
+                _,
+                WindowEvent::MouseInput {
+                    state: ElementState::Released,
+                    click_count: 1,
+                    ..
+                }
+            ),
+            (
+                _,
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    click_count: 2,
+                    ..
+                }
+            ),
+        ]
+    ));
+
+    drop(other_window);
+    assert_eq!(
+        pump_window_events(&mut event_loop),
+        [(other_id, WindowEvent::Destroyed)]
+    );
+    assert!(event_loop
+        .inject_window_event(other_id, WindowEvent::CloseRequested)
+        .is_err());
+    assert!(event_loop
+        .inject_input(other_id, SyntheticInput::Ime(Ime::Enabled))
+        .is_err());
+
+    check_clipboard(&event_loop);
+    check_drag_and_drop(&event_loop);
+}
+
+/// Check the clipboard and the primary selection, which are kept in memory.
+fn check_clipboard(event_loop: &EventLoop<()>) {
+    assert_eq!(
+        event_loop.clipboard_mime_types().unwrap(),
+        Vec::<String>::new()
+    );
+    assert_eq!(event_loop.clipboard_text().unwrap(), None);
+
+    let contents = ClipboardContents::from_text("Hello, world!")
+        .with_data("application/x-winit", vec![1, 2, 3]);
+    event_loop.set_clipboard(contents).unwrap();
+
+    assert_eq!(
+        event_loop.clipboard_text().unwrap().as_deref(),
+        Some("Hello, world!")
+    );
+    let mime_types = event_loop.clipboard_mime_types().unwrap();
+    assert!(mime_types.iter().any(|ty| ty == "text/plain;charset=utf-8"));
+    assert!(mime_types.iter().any(|ty| ty == "application/x-winit"));
+    assert_eq!(
+        event_loop.clipboard_data("application/x-winit").unwrap(),
+        Some(vec![1, 2, 3])
+    );
+    assert_eq!(event_loop.clipboard_data("image/png").unwrap(), None);
+
+    // The legacy Latin-1 text is decoded as well.
+    let contents = ClipboardContents::new().with_data("STRING", vec![0x63, 0x61, 0x66, 0xe9]);
+    event_loop.set_clipboard(contents).unwrap();
+    assert_eq!(
+        event_loop.clipboard_text().unwrap().as_deref(),
+        Some("café")
+    );
+
+    // The primary selection is independent of the clipboard.
+    event_loop
+        .set_primary_selection(ClipboardContents::from_text("selected"))
+        .unwrap();
+    assert_eq!(
+        event_loop.primary_selection_text().unwrap().as_deref(),
+        Some("selected")
+    );
+    assert_eq!(
+        event_loop.clipboard_text().unwrap().as_deref(),
+        Some("café")
+    );
+
+    // Empty contents clear the clipboard.
+    event_loop.set_clipboard(ClipboardContents::new()).unwrap();
+    assert_eq!(
+        event_loop.clipboard_mime_types().unwrap(),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        event_loop.primary_selection_data("text/plain").unwrap(),
+        Some(b"selected".to_vec())
+    );
+
+    event_loop
+        .set_primary_selection(ClipboardContents::new())
+        .unwrap();
+    assert_eq!(
+        event_loop.primary_selection_mime_types().unwrap(),
+        Vec::<String>::new()
+    );
+}
+
+/// Check the drag and drop, which has no other client to exchange the data with.
+fn check_drag_and_drop(event_loop: &EventLoop<()>) {
+    let source = DragSource::new()
+        .with_data("text/plain;charset=utf-8", "Hello, world!")
+        .with_provider("application/x-winit", || vec![1, 2, 3])
+        .with_data("text/plain;charset=utf-8", "Replaced")
+        .with_actions(&[DndAction::Move, DndAction::Copy]);
+    assert_eq!(
+        source.mime_types().collect::<Vec<_>>(),
+        ["text/plain;charset=utf-8", "application/x-winit"]
+    );
+    assert_eq!(source.actions(), [DndAction::Move, DndAction::Copy]);
+    assert_eq!(DragSource::new().actions(), [DndAction::Copy]);
+
+    let window = WindowBuilder::new().build(event_loop).unwrap();
+
+    // There's nothing to drag the data into.
+    assert!(matches!(
+        window.start_drag(source),
+        Err(ExternalError::NotSupported(_))
+    ));
+
+    // There's nothing dragged over the window either.
+    event_loop.set_drop_action(Some(DndAction::Copy));
+    assert!(event_loop.drop_mime_types().is_empty());
+    assert_eq!(event_loop.drop_data("text/plain").unwrap(), None);
+}
@@ -0,0 +1,107 @@
+#![cfg(all(target_os = "linux", any(feature = "x11", feature = "wayland")))]
+#![cfg(feature = "recording")]
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Event, WindowEvent};
+use winit::event_loop::EventLoopBuilder;
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::platform::headless::EventLoopBuilderExtHeadless;
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use winit::platform::recording::{EventLoopExtReplay, Recorder, Replay, ReplayTiming};
+use winit::platform::synthetic_input::{EventLoopWindowTargetExtSyntheticInput, SyntheticInput};
+#[cfg(not(feature = "x11"))]
+use winit::platform::wayland::EventLoopBuilderExtWayland as _;
+#[cfg(feature = "x11")]
+use winit::platform::x11::EventLoopBuilderExtX11 as _;
+use winit::window::WindowBuilder;
+
+#[test]
+fn record_and_replay() {
+    let mut event_loop = EventLoopBuilder::<u32>::with_user_event()
+        .with_headless()
+        .with_any_thread(true)
+        .build()
+        .unwrap();
+    let window = WindowBuilder::new()
+        .with_inner_size(PhysicalSize::new(200, 100))
+        .build(&event_loop)
+        .unwrap();
+
+    event_loop
+        .inject_input(
+            window.id(),
+            SyntheticInput::CursorMoved {
+                position: PhysicalPosition::new(5., 5.),
+            },
+        )
+        .unwrap();
+    event_loop
+        .inject_input(
+            window.id(),
+            SyntheticInput::KeyboardInput {
+                physical_key: PhysicalKey::Code(KeyCode::KeyA),
+                state: ElementState::Pressed,
+                repeat: false,
+            },
+        )
+        .unwrap();
+    event_loop.create_proxy().send_event(42).unwrap();
+
+    let mut recorder = Recorder::new(Vec::new());
+    let mut recorded = Vec::new();
+    for _ in 0..2 {
+        event_loop.pump_events(Some(Duration::ZERO), |event, _| {
+            recorder.record(&event).unwrap();
+            match event {
+                Event::WindowEvent {
+                    window_id, event, ..
+                } => recorded.push((window_id, event)),
+                Event::UserEvent(event) => assert_eq!(event, 42),
+                _ => (),
+            }
+        });
+    }
+    assert!(recorded.iter().any(|(_, event)| matches!(
+        event,
+        WindowEvent::KeyboardInput { event, .. } if event.text.as_deref() == Some("a")
+    )));
+
+    // Every event is stored on its own line.
+    let recording = recorder.into_inner();
+    let lines = recording.split(|&byte| byte == b'\n').count() - 1;
+
+    let mut replay = Replay::new(Cursor::new(recording), ReplayTiming::Immediate).unwrap();
+    let mut replayed = Vec::new();
+    let mut user_events = Vec::new();
+    let mut count = 0;
+    let status = event_loop
+        .pump_replay(&mut replay, Some(Duration::ZERO), |event, _| {
+            count += 1;
+            match event {
+                Event::WindowEvent {
+                    window_id, event, ..
+                } => replayed.push((window_id, event)),
+                Event::UserEvent(event) => user_events.push(event),
+                _ => (),
+            }
+        })
+        .unwrap();
+
+    assert!(matches!(status, PumpStatus::Exit(0)));
+    assert!(replay.is_finished());
+    assert_eq!(count, lines);
+    assert_eq!(user_events, [42]);
+    assert_eq!(recorded, replayed);
+}
+
+#[test]
+fn replay_timing_is_validated() {
+    for factor in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let replay = Replay::<_, ()>::new(Cursor::new(""), ReplayTiming::Accelerated(factor));
+        assert_eq!(replay.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+    assert!(Replay::<_, ()>::new(Cursor::new(""), ReplayTiming::Accelerated(2.0)).is_ok());
+}